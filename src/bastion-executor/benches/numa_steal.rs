@@ -0,0 +1,78 @@
+#![feature(test)]
+
+extern crate test;
+use bastion_executor::load_balancer::{SmpStats, Stats};
+use bastion_executor::placement::{CoreId, NumaNode};
+use bastion_executor::run_queue::Worker;
+use test::Bencher;
+
+const NODES: usize = 2;
+const CORES_PER_NODE: usize = 4;
+const TASKS: usize = 1_000;
+
+fn nodes() -> Vec<NumaNode> {
+    (0..NODES)
+        .map(|id| NumaNode {
+            id,
+            cores: (0..CORES_PER_NODE)
+                .map(|core| CoreId {
+                    id: id * CORES_PER_NODE + core,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+// Loads the first queue of each node, then lets the idle queues steal
+// from the others until all of them are empty.
+// Returns how many steals crossed nodes.
+fn drain(nodes: &[NumaNode], numa_aware: bool) -> usize {
+    let cores = NODES * CORES_PER_NODE;
+    let stats = Stats::new(cores);
+    let queues: Vec<Worker<usize>> = (0..cores).map(|_| Worker::new_fifo()).collect();
+    for node in nodes {
+        (0..TASKS).for_each(|i| queues[node.cores[0].id].push(i));
+    }
+
+    let mut cross_node_steals = 0;
+    while queues.iter().any(|queue| !queue.is_empty()) {
+        for thief in 0..cores {
+            for (core, queue) in queues.iter().enumerate() {
+                stats.store_load(core, queue.worker_run_queue_size());
+            }
+
+            if queues[thief].is_empty() {
+                let order = stats.steal_order(thief, if numa_aware { Some(nodes) } else { None });
+                for victim in order {
+                    if queues[victim]
+                        .stealer()
+                        .steal_batch(&queues[thief])
+                        .is_success()
+                    {
+                        if !nodes
+                            .iter()
+                            .any(|node| node.contains(thief) && node.contains(victim))
+                        {
+                            cross_node_steals += 1;
+                        }
+                        break;
+                    }
+                }
+            }
+            queues[thief].pop();
+        }
+    }
+    cross_node_steals
+}
+
+#[bench]
+fn flat_stealing(b: &mut Bencher) {
+    let nodes = nodes();
+    b.iter(|| drain(&nodes, false));
+}
+
+#[bench]
+fn numa_aware_stealing(b: &mut Bencher) {
+    let nodes = nodes();
+    b.iter(|| drain(&nodes, true));
+}
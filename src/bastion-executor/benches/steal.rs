@@ -0,0 +1,39 @@
+#![feature(test)]
+
+extern crate test;
+use bastion_executor::run_queue::Worker;
+use test::Bencher;
+
+const TASKS: usize = 1_000;
+
+fn drain_with<F>(steal: F)
+where
+    F: Fn(&Worker<usize>, &Worker<usize>),
+{
+    let victim = Worker::new_fifo();
+    let dest = Worker::new_fifo();
+    (0..TASKS).for_each(|i| victim.push(i));
+
+    while !victim.is_empty() {
+        steal(&victim, &dest);
+        while dest.pop().is_some() {}
+    }
+}
+
+#[bench]
+fn steal_batch_of_one(b: &mut Bencher) {
+    b.iter(|| {
+        drain_with(|victim, dest| {
+            let _ = victim.stealer().steal_batch_with_amount(dest, 1);
+        })
+    });
+}
+
+#[bench]
+fn steal_batch_of_half(b: &mut Bencher) {
+    b.iter(|| {
+        drain_with(|victim, dest| {
+            let _ = victim.stealer().steal_batch(dest);
+        })
+    });
+}
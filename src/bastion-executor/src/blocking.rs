@@ -4,15 +4,26 @@
 //! We spawn futures onto the pool with [spawn_blocking] method of global run queue or
 //! with corresponding [Worker]'s spawn method.
 
+#[cfg(feature = "metrics")]
+use crate::metrics;
+use crate::pool::PoolConfig;
+use crate::sleepers::Sleepers;
 use crate::thread_manager::{DynamicPoolManager, DynamicRunner};
+#[cfg(feature = "tokio-runtime")]
+use crate::tokio_runtime;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use lazy_static::lazy_static;
 use lightproc::lightproc::LightProc;
+use lightproc::proc_handle::ProcError;
 use lightproc::proc_stack::ProcStack;
 use lightproc::recoverable_handle::RecoverableHandle;
 use once_cell::sync::{Lazy, OnceCell};
+use std::error::Error;
+use std::fmt;
 use std::future::Future;
 use std::iter::Iterator;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use std::{env, thread};
@@ -24,25 +35,357 @@ const DEFAULT_LOW_WATERMARK: u64 = 2;
 
 const THREAD_RECV_TIMEOUT: Duration = Duration::from_millis(100);
 
+/// Default maximum amount of threads of the blocking pool.
+pub const DEFAULT_MAX_THREADS: usize = 512;
+
+/// Default time after which an unused thread of the blocking pool is reaped.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Spawns a blocking task.
 ///
 /// The task will be spawned onto a thread pool specifically dedicated to blocking tasks.
+///
+/// If the queue of the pool is full, this either blocks until there is room
+/// for the task, or returns a handle resolving to `None` without running the
+/// task, depending on the configured [`OverflowPolicy`]. Blocking blocks the
+/// calling thread, which is a worker of the executor when this is called from
+/// a process (like an actor), keeping it from running other processes meanwhile.
+///
+/// [`OverflowPolicy`]: enum.OverflowPolicy.html
 pub fn spawn_blocking<F, R>(future: F, stack: ProcStack) -> RecoverableHandle<R>
 where
     F: Future<Output = R> + Send + 'static,
     R: Send + 'static,
 {
+    #[cfg(feature = "tokio-runtime")]
+    let future = tokio_runtime::enter(future);
+    #[cfg(feature = "metrics")]
+    let future = metrics::track(future);
+
+    let has_room = reserve_slot();
     let (task, handle) = LightProc::recoverable(future, schedule, stack);
-    task.schedule();
+    if has_room {
+        push(task);
+    } else {
+        trace!("blocking pool: queue is full, rejecting task");
+        handle.cancel();
+    }
     handle
 }
 
+/// Runs a blocking closure onto the blocking pool, returning a future
+/// resolving to its output.
+///
+/// If the closure panics, the panic is resumed on the task awaiting the
+/// returned future. Dropping the future doesn't abort the closure, which
+/// still runs to completion with its output being discarded.
+///
+/// The closure is spawned right away, following the configured
+/// [`OverflowPolicy`] if the queue of the pool is full: the returned
+/// future panics if the closure was rejected. Use [`try_spawn`] to
+/// handle the rejection instead.
+///
+/// [`OverflowPolicy`]: enum.OverflowPolicy.html
+/// [`try_spawn`]: fn.try_spawn.html
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::blocking;
+/// use bastion_executor::run::run;
+/// use lightproc::proc_stack::ProcStack;
+///
+/// let sum = run(
+///     async {
+///         blocking::spawn(|| (1..=10).sum::<u32>()).await
+///     },
+///     ProcStack::default(),
+/// );
+/// assert_eq!(sum, 55);
+/// ```
+pub fn spawn<F, R>(f: F) -> impl Future<Output = R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    output(spawn_blocking(async move { f() }, ProcStack::default()))
+}
+
+/// Runs a blocking closure onto the blocking pool as [`spawn`] does,
+/// failing if the queue of the pool is full and the configured
+/// [`OverflowPolicy`] is to reject tasks.
+///
+/// [`spawn`]: fn.spawn.html
+/// [`OverflowPolicy`]: enum.OverflowPolicy.html
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::blocking;
+/// use bastion_executor::run::run;
+/// use lightproc::proc_stack::ProcStack;
+///
+/// let sum = run(
+///     async {
+///         match blocking::try_spawn(|| (1..=10).sum::<u32>()) {
+///             Ok(sum) => sum.await,
+///             Err(_) => 0,
+///         }
+///     },
+///     ProcStack::default(),
+/// );
+/// assert_eq!(sum, 55);
+/// ```
+pub fn try_spawn<F, R>(f: F) -> Result<impl Future<Output = R>, QueueFull>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    try_spawn_blocking(async move { f() }, ProcStack::default()).map(output)
+}
+
+/// Resolves to the output of a closure spawned by [`spawn`] or
+/// [`try_spawn`], resuming its panic if it panicked.
+///
+/// [`spawn`]: fn.spawn.html
+/// [`try_spawn`]: fn.try_spawn.html
+fn output<R>(handle: RecoverableHandle<R>) -> impl Future<Output = R> {
+    let join = handle.join();
+    async move {
+        match join.await {
+            Ok(output) => output,
+            Err(ProcError::Panicked { payload, .. }) => panic::resume_unwind(payload),
+            Err(ProcError::Cancelled) => panic!("the blocking pool rejected the closure"),
+        }
+    }
+}
+
+/// Spawns a blocking task, failing if the queue of the pool is full
+/// and the configured [`OverflowPolicy`] is to reject tasks.
+///
+/// [`OverflowPolicy`]: enum.OverflowPolicy.html
+pub fn try_spawn_blocking<F, R>(
+    future: F,
+    stack: ProcStack,
+) -> Result<RecoverableHandle<R>, QueueFull>
+where
+    F: Future<Output = R> + Send + 'static,
+    R: Send + 'static,
+{
+    if !reserve_slot() {
+        return Err(QueueFull);
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    let future = tokio_runtime::enter(future);
+    #[cfg(feature = "metrics")]
+    let future = metrics::track(future);
+
+    let (task, handle) = LightProc::recoverable(future, schedule, stack);
+    push(task);
+    Ok(handle)
+}
+
+///
+/// Configures the blocking pool.
+///
+/// This needs to be called before any task gets spawned onto the blocking pool;
+/// otherwise the pool is already running with the default configuration
+/// and the given configuration is returned back.
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::blocking::{self, BlockingConfig, OverflowPolicy};
+///
+/// let config = BlockingConfig::default()
+///     .with_max_threads(32)
+///     .with_queue_capacity(1_024)
+///     .with_overflow_policy(OverflowPolicy::Reject);
+///
+/// blocking::configure_blocking(config).expect("blocking pool is already running");
+/// ```
+pub fn configure_blocking(config: BlockingConfig) -> Result<(), BlockingConfig> {
+    CONFIG.set(config)
+}
+
+/// Configuration of the blocking pool.
+///
+/// By default the pool grows up to [`DEFAULT_MAX_THREADS`] threads, after which
+/// tasks are queued without limit until a thread is available. Threads unused
+/// for [`DEFAULT_IDLE_TIMEOUT`] are reaped.
+///
+/// [`DEFAULT_MAX_THREADS`]: constant.DEFAULT_MAX_THREADS.html
+/// [`DEFAULT_IDLE_TIMEOUT`]: constant.DEFAULT_IDLE_TIMEOUT.html
+#[derive(Debug, Clone)]
+pub struct BlockingConfig {
+    max_threads: usize,
+    idle_timeout: Duration,
+    queue_capacity: Option<usize>,
+    overflow_policy: OverflowPolicy,
+}
+
+/// What to do when a blocking task is spawned while the queue of the pool is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Blocks the caller until there is room in the queue.
+    ///
+    /// This blocks the caller's thread, which is a worker of the executor
+    /// when the task is spawned from a process (like an actor).
+    Block,
+    /// Rejects the task.
+    Reject,
+}
+
+/// Error returned by [`try_spawn_blocking`] when the
+/// queue of the blocking pool is full.
+///
+/// [`try_spawn_blocking`]: fn.try_spawn_blocking.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueFull;
+
+impl BlockingConfig {
+    /// Creates a new configuration with the default values.
+    pub fn new() -> Self {
+        BlockingConfig::default()
+    }
+
+    /// Sets the maximum amount of threads. Once reached, new tasks
+    /// are queued until a thread is available.
+    ///
+    /// As for [`PoolConfig::with_max_threads`], it is raised to one more
+    /// than the pool's static threads, so to 3 by default.
+    ///
+    /// Defaults to [`DEFAULT_MAX_THREADS`].
+    ///
+    /// [`PoolConfig::with_max_threads`]: ../pool/struct.PoolConfig.html#method.with_max_threads
+    /// [`DEFAULT_MAX_THREADS`]: constant.DEFAULT_MAX_THREADS.html
+    pub fn with_max_threads(mut self, max_threads: usize) -> Self {
+        self.max_threads = max_threads;
+        self
+    }
+
+    /// Sets how long a thread can stay unused before being reaped.
+    ///
+    /// Defaults to [`DEFAULT_IDLE_TIMEOUT`].
+    ///
+    /// [`DEFAULT_IDLE_TIMEOUT`]: constant.DEFAULT_IDLE_TIMEOUT.html
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Sets how many tasks can wait in the queue. It is clamped to at least one task.
+    ///
+    /// Defaults to no limit.
+    pub fn with_queue_capacity(mut self, queue_capacity: usize) -> Self {
+        self.queue_capacity = Some(queue_capacity.max(1));
+        self
+    }
+
+    /// Sets what to do when a task is spawned while the queue is full.
+    ///
+    /// Defaults to [`OverflowPolicy::Block`].
+    ///
+    /// [`OverflowPolicy::Block`]: enum.OverflowPolicy.html#variant.Block
+    pub fn with_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+
+    /// Returns the maximum amount of threads.
+    pub fn max_threads(&self) -> usize {
+        self.max_threads
+    }
+
+    /// Returns the idle timeout.
+    pub fn idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    /// Returns the queue capacity, if any was set.
+    pub fn queue_capacity(&self) -> Option<usize> {
+        self.queue_capacity
+    }
+
+    /// Returns the overflow policy.
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+}
+
+impl Default for BlockingConfig {
+    fn default() -> Self {
+        BlockingConfig {
+            max_threads: DEFAULT_MAX_THREADS,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            queue_capacity: None,
+            overflow_policy: OverflowPolicy::Block,
+        }
+    }
+}
+
+impl fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the queue of the blocking pool is full")
+    }
+}
+
+impl Error for QueueFull {}
+
+/// Reserves a slot in the queue for a new task, waiting until there is
+/// room if needed. Returns false if the task should be rejected instead.
+fn reserve_slot() -> bool {
+    let config = config();
+    let capacity = match config.queue_capacity {
+        Some(capacity) => capacity,
+        None => {
+            QUEUED.fetch_add(1, Ordering::SeqCst);
+            return true;
+        }
+    };
+
+    loop {
+        let reserved = QUEUED
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+                (queued < capacity).then_some(queued + 1)
+            })
+            .is_ok();
+        if reserved {
+            return true;
+        }
+        if config.overflow_policy == OverflowPolicy::Reject {
+            return false;
+        }
+
+        SUBMITTERS.wait_timeout(THREAD_RECV_TIMEOUT);
+    }
+}
+
+/// Pops a task from the queue with a timeout, waking up a submitter
+/// waiting for room in the queue.
+#[inline]
+fn recv_task() -> Option<LightProc> {
+    let task = POOL.receiver.recv_timeout(THREAD_RECV_TIMEOUT).ok();
+    if task.is_some() {
+        QUEUED.fetch_sub(1, Ordering::SeqCst);
+        if config().queue_capacity.is_some() {
+            SUBMITTERS.notify_one();
+        }
+    }
+    task
+}
+
+#[inline]
+fn config() -> &'static BlockingConfig {
+    CONFIG.get_or_init(BlockingConfig::default)
+}
+
 struct BlockingRunner {}
 
+static NEXT_THREAD_ID: AtomicUsize = AtomicUsize::new(0);
+
 impl DynamicRunner for BlockingRunner {
-    fn run_static(&self, park_timeout: Duration) -> ! {
+    fn run_static(&self, _id: usize, park_timeout: Duration) {
         loop {
-            while let Ok(task) = POOL.receiver.recv_timeout(THREAD_RECV_TIMEOUT) {
+            while let Some(task) = recv_task() {
                 trace!("static thread: running task");
                 task.run();
             }
@@ -51,9 +394,9 @@ impl DynamicRunner for BlockingRunner {
             thread::park_timeout(park_timeout);
         }
     }
-    fn run_dynamic(&self, parker: &dyn Fn()) -> ! {
+    fn run_dynamic(&self, _id: usize, parker: &dyn Fn() -> bool) {
         loop {
-            while let Ok(task) = POOL.receiver.recv_timeout(THREAD_RECV_TIMEOUT) {
+            while let Some(task) = recv_task() {
                 trace!("dynamic thread: running task");
                 task.run();
             }
@@ -61,15 +404,27 @@ impl DynamicRunner for BlockingRunner {
                 "dynamic thread: parking - {:?}",
                 std::thread::current().id()
             );
-            parker();
+            if !parker() {
+                break;
+            }
         }
     }
-    fn run_standalone(&self) {
-        while let Ok(task) = POOL.receiver.recv_timeout(THREAD_RECV_TIMEOUT) {
+    fn run_standalone(&self, _id: usize) {
+        while let Some(task) = recv_task() {
             task.run();
         }
         trace!("standalone thread: quitting.");
     }
+    fn queue_depth(&self) -> usize {
+        POOL.receiver.len()
+    }
+    fn sampled(&self, _queue_depth: usize) {}
+    fn thread_name(&self) -> &'static str {
+        "bastion-blocking"
+    }
+    fn next_thread_id(&self) -> usize {
+        NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed)
+    }
 }
 /// Pool interface between the scheduler and thread pool
 struct Pool {
@@ -79,11 +434,33 @@ struct Pool {
 
 static DYNAMIC_POOL_MANAGER: OnceCell<DynamicPoolManager> = OnceCell::new();
 
+/// Returns the manager of the pool's threads, if the pool is running.
+#[cfg(feature = "metrics")]
+pub(crate) fn manager() -> Option<&'static DynamicPoolManager> {
+    DYNAMIC_POOL_MANAGER.get()
+}
+
+static CONFIG: OnceCell<BlockingConfig> = OnceCell::new();
+
+/// Where callers wait for room in the queue.
+static SUBMITTERS: Lazy<Sleepers> = Lazy::new(Sleepers::new);
+
+/// How many tasks are in the queue, including the ones a slot was
+/// reserved for but which weren't pushed yet.
+static QUEUED: AtomicUsize = AtomicUsize::new(0);
+
 static POOL: Lazy<Pool> = Lazy::new(|| {
     let runner = Arc::new(BlockingRunner {});
+    let pool_config = PoolConfig::default()
+        .with_max_threads(config().max_threads)
+        .with_idle_timeout(config().idle_timeout);
 
     DYNAMIC_POOL_MANAGER
-        .set(DynamicPoolManager::new(*low_watermark() as usize, runner))
+        .set(DynamicPoolManager::new(
+            *low_watermark() as usize,
+            &pool_config,
+            runner,
+        ))
         .expect("couldn't create dynamic pool manager");
     DYNAMIC_POOL_MANAGER
         .get()
@@ -99,6 +476,13 @@ static POOL: Lazy<Pool> = Lazy::new(|| {
 /// based on the previous statistics without relying on
 /// if there is not a thread ready to accept the work or not.
 fn schedule(t: LightProc) {
+    // The task was woken up, so it has no slot reserved yet.
+    QUEUED.fetch_add(1, Ordering::SeqCst);
+    push(t);
+}
+
+/// Pushes a task a slot was reserved for in the queue.
+fn push(t: LightProc) {
     if let Err(err) = POOL.sender.try_send(t) {
         // We were not able to send to the channel without
         // blocking.
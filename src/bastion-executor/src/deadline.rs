@@ -0,0 +1,73 @@
+//!
+//! Deadlines of the processes spawned with [`spawn_with_deadline`].
+//!
+//! A process is cancelled (and its future dropped) once its deadline
+//! elapsed, which is noticed by a timer while the process waits at an
+//! await point. Since synchronous code can't be preempted, a process
+//! which is executing when its deadline elapses is only cancelled at
+//! its next await point: CPU-bound processes without await points can
+//! overrun their deadline for as long as they run.
+//!
+//! [`spawn_with_deadline`]: ../pool/fn.spawn_with_deadline.html
+
+use futures_timer::Delay;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The ways a process spawned with a deadline can be cancelled.
+pub enum DeadlineError {
+    /// The process was waiting at an await point when its
+    /// deadline elapsed, and was cancelled right away.
+    Cancelled,
+    /// The process was executing when its deadline elapsed and
+    /// didn't yield until then, so it was cancelled at its next
+    /// await point, overrunning its deadline by the given duration.
+    Overran(Duration),
+}
+
+/// Cancels `future` once `timeout` elapsed.
+pub(crate) fn with_deadline<F: Future>(future: F, timeout: Duration) -> Deadline<F> {
+    Deadline {
+        future,
+        delay: Delay::new(timeout),
+        deadline: Instant::now() + timeout,
+    }
+}
+
+pub(crate) struct Deadline<F> {
+    future: F,
+    // Wakes the process up once its deadline elapsed.
+    delay: Delay,
+    deadline: Instant,
+}
+
+impl<F: Future> Future for Deadline<F> {
+    type Output = Result<F::Output, DeadlineError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // SAFETY: the future is never moved out of `Deadline`.
+        let this = unsafe { self.get_unchecked_mut() };
+        if Pin::new(&mut this.delay).poll(cx).is_ready() {
+            return Poll::Ready(Err(DeadlineError::Cancelled));
+        }
+
+        // SAFETY: same as above.
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        match future.poll(cx) {
+            Poll::Ready(output) => Poll::Ready(Ok(output)),
+            Poll::Pending => {
+                // The process yields for the first time since its
+                // deadline elapsed.
+                let now = Instant::now();
+                if now >= this.deadline {
+                    Poll::Ready(Err(DeadlineError::Overran(now - this.deadline)))
+                } else {
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//!
+//! Scheduling and time of the pools run by the thread driving them.
+//!
+//! A pool configured with [`PoolConfig::with_deterministic_seed`]
+//! doesn't spawn any thread: its processes only run when a thread
+//! calls [`Pool::run_until_idle`] or [`Pool::advance`], one at a time
+//! and in an order picked from the seed. The same seed thus always
+//! gives the same interleaving, as long as the processes aren't woken
+//! up by other threads (like the ones of the blocking pool or of
+//! another timer than the pool's [`VirtualClock`]).
+//!
+//! The time of such a pool is virtual, and only passes when
+//! [`Pool::advance`] is called.
+//!
+//! [`PoolConfig::with_deterministic_seed`]: ../pool/struct.PoolConfig.html#method.with_deterministic_seed
+//! [`Pool::run_until_idle`]: ../pool/struct.Pool.html#method.run_until_idle
+//! [`Pool::advance`]: ../pool/struct.Pool.html#method.advance
+//! [`VirtualClock`]: struct.VirtualClock.html
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// Picks which of the ready processes runs next.
+#[derive(Debug)]
+pub(crate) struct Scheduler {
+    state: u64,
+}
+
+impl Scheduler {
+    pub(crate) fn new(seed: u64) -> Self {
+        Scheduler { state: seed }
+    }
+
+    /// Returns an index below `len`, which must not be zero.
+    pub(crate) fn pick(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+
+    /// Returns the next number drawn from the seed.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        // SplitMix64, which handles every seed (including zero).
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// A clock whose time only passes when its pool is advanced.
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::pool::{Pool, PoolConfig};
+/// use lightproc::prelude::*;
+/// use std::time::Duration;
+///
+/// let pool = Pool::start(&PoolConfig::default().with_deterministic_seed(42));
+/// let clock = pool.clock().unwrap();
+///
+/// let handle = pool.spawn(clock.sleep(Duration::from_secs(60)), ProcStack::default());
+/// pool.run_until_idle();
+/// assert!(clock.next_timer().is_some());
+///
+/// // A minute passes at once.
+/// pool.advance(Duration::from_secs(60));
+/// assert!(clock.next_timer().is_none());
+/// # drop(handle);
+/// ```
+#[derive(Debug)]
+pub struct VirtualClock {
+    start: Instant,
+    timers: Mutex<Timers>,
+}
+
+#[derive(Debug, Default)]
+struct Timers {
+    // How much virtual time passed since the clock was created.
+    elapsed: Duration,
+    // The wakers of the sleeps, by deadline and in creation order.
+    waiting: BTreeMap<(Duration, u64), Waker>,
+    next_id: u64,
+}
+
+/// A future completing once its [`VirtualClock`] reached a deadline.
+///
+/// [`VirtualClock`]: struct.VirtualClock.html
+#[derive(Debug)]
+pub struct Sleep {
+    clock: &'static VirtualClock,
+    deadline: Duration,
+    // Identifies the sleep's waker, once it was registered.
+    id: Option<u64>,
+}
+
+impl VirtualClock {
+    pub(crate) fn new() -> Self {
+        VirtualClock {
+            start: Instant::now(),
+            timers: Mutex::default(),
+        }
+    }
+
+    /// Returns the current virtual time, which starts from the time
+    /// the clock was created at.
+    pub fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    /// Returns how much virtual time passed since the clock was
+    /// created.
+    pub fn elapsed(&self) -> Duration {
+        self.timers.lock().unwrap().elapsed
+    }
+
+    /// Returns a future completing once `duration` of virtual time
+    /// passed.
+    pub fn sleep(&'static self, duration: Duration) -> Sleep {
+        Sleep {
+            clock: self,
+            deadline: self.elapsed() + duration,
+            id: None,
+        }
+    }
+
+    /// Returns when the earliest of the pending sleeps completes,
+    /// if any is pending.
+    pub fn next_timer(&self) -> Option<Instant> {
+        let timers = self.timers.lock().unwrap();
+        let (deadline, _) = timers.waiting.keys().next()?;
+        Some(self.start + *deadline)
+    }
+
+    /// Moves the clock forward up to `elapsed` (it never goes back),
+    /// waking up the sleeps which completed.
+    pub(crate) fn set_elapsed(&self, elapsed: Duration) {
+        let completed = {
+            let mut timers = self.timers.lock().unwrap();
+            let elapsed = timers.elapsed.max(elapsed);
+            timers.elapsed = elapsed;
+            let pending = timers.waiting.split_off(&(elapsed, u64::MAX));
+            mem::replace(&mut timers.waiting, pending)
+        };
+
+        // The processes are scheduled once the clock is unlocked.
+        for waker in completed.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// Returns how much virtual time will have passed when the
+    /// earliest of the pending sleeps completes.
+    pub(crate) fn next_deadline(&self) -> Option<Duration> {
+        let timers = self.timers.lock().unwrap();
+        timers.waiting.keys().next().map(|(deadline, _)| *deadline)
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let sleep = self.get_mut();
+        let mut timers = sleep.clock.timers.lock().unwrap();
+        if timers.elapsed >= sleep.deadline {
+            if let Some(id) = sleep.id.take() {
+                timers.waiting.remove(&(sleep.deadline, id));
+            }
+            return Poll::Ready(());
+        }
+
+        let id = match sleep.id {
+            Some(id) => id,
+            None => {
+                let id = timers.next_id;
+                timers.next_id += 1;
+                sleep.id = Some(id);
+                id
+            }
+        };
+        timers
+            .waiting
+            .insert((sleep.deadline, id), cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.clock
+                .timers
+                .lock()
+                .unwrap()
+                .waiting
+                .remove(&(self.deadline, id));
+        }
+    }
+}
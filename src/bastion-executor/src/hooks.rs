@@ -0,0 +1,183 @@
+//!
+//! Hooks observing how the pool schedules its processes.
+//!
+//! The hooks are called on the pool's worker threads (or on the
+//! thread scheduling a process) with minimal data about the event,
+//! and should thus return quickly. They are installed once for the
+//! whole process, and cost a single check when none were installed.
+//!
+//! The workers of the pool share one run queue per priority instead
+//! of owning queues they could steal processes from, so there is no
+//! steal event to observe.
+//!
+//! # Example
+//! ```rust
+//! use bastion_executor::hooks::{self, Hooks, TaskEvent};
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! static ENQUEUED: AtomicUsize = AtomicUsize::new(0);
+//!
+//! let hooks = Hooks::new().with_on_enqueue(|_event: &TaskEvent| {
+//!     ENQUEUED.fetch_add(1, Ordering::Relaxed);
+//! });
+//!
+//! hooks::install(hooks).expect("hooks are already installed");
+//! ```
+
+use crate::worker;
+use lightproc::lightproc::LightProc;
+use lightproc::proc_stack::Priority;
+use once_cell::sync::OnceCell;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+use std::time::Instant;
+
+type Hook<E> = Arc<dyn Fn(&E) + Send + Sync>;
+
+static HOOKS: OnceCell<Hooks> = OnceCell::new();
+
+/// An event about a process of the pool.
+#[derive(Debug, Clone, Copy)]
+pub struct TaskEvent {
+    /// Identifies the process, no other process of the program
+    /// getting the same identifier (see [`ProcStack::get_proc_id`]).
+    ///
+    /// [`ProcStack::get_proc_id`]: ../../lightproc/proc_stack/struct.ProcStack.html#method.get_proc_id
+    pub task_id: usize,
+    /// The worker thread the event happened on, if it happened on
+    /// one of the pool's threads.
+    pub worker_id: Option<usize>,
+    /// The priority of the process.
+    pub priority: Priority,
+    /// When the event happened.
+    pub timestamp: Instant,
+}
+
+/// An event about a worker thread of the pool.
+#[derive(Debug, Clone, Copy)]
+pub struct WorkerEvent {
+    /// The worker thread the event happened on.
+    pub worker_id: usize,
+    /// When the event happened.
+    pub timestamp: Instant,
+}
+
+/// The hooks called when the pool schedules processes.
+#[derive(Clone, Default)]
+pub struct Hooks {
+    on_enqueue: Option<Hook<TaskEvent>>,
+    on_dequeue: Option<Hook<TaskEvent>>,
+    on_park: Option<Hook<WorkerEvent>>,
+    on_unpark: Option<Hook<WorkerEvent>>,
+}
+
+impl Hooks {
+    /// Creates a new set of hooks, none of which is set.
+    pub fn new() -> Self {
+        Hooks::default()
+    }
+
+    /// Sets the hook called when a process is pushed onto the run
+    /// queue, which happens every time it is woken up.
+    pub fn with_on_enqueue<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&TaskEvent) + Send + Sync + 'static,
+    {
+        self.on_enqueue = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets the hook called when a worker pops a process from the
+    /// run queue, right before running it.
+    pub fn with_on_dequeue<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&TaskEvent) + Send + Sync + 'static,
+    {
+        self.on_dequeue = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets the hook called when a worker parks because the run
+    /// queue is empty.
+    pub fn with_on_park<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&WorkerEvent) + Send + Sync + 'static,
+    {
+        self.on_park = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets the hook called when a parked worker is woken up.
+    pub fn with_on_unpark<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&WorkerEvent) + Send + Sync + 'static,
+    {
+        self.on_unpark = Some(Arc::new(hook));
+        self
+    }
+}
+
+impl Debug for Hooks {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Hooks")
+            .field("on_enqueue", &self.on_enqueue.is_some())
+            .field("on_dequeue", &self.on_dequeue.is_some())
+            .field("on_park", &self.on_park.is_some())
+            .field("on_unpark", &self.on_unpark.is_some())
+            .finish()
+    }
+}
+
+///
+/// Installs the hooks for the whole process.
+///
+/// The hooks can only be installed once, the given hooks are returned
+/// back if some already were.
+pub fn install(hooks: Hooks) -> Result<(), Hooks> {
+    HOOKS.set(hooks)
+}
+
+fn task_event(proc: &LightProc) -> TaskEvent {
+    let stack = proc.stack();
+    TaskEvent {
+        task_id: stack.get_proc_id(),
+        worker_id: worker::current_worker_id(),
+        priority: stack.get_priority(),
+        timestamp: Instant::now(),
+    }
+}
+
+fn worker_event() -> Option<WorkerEvent> {
+    worker::current_worker_id().map(|worker_id| WorkerEvent {
+        worker_id,
+        timestamp: Instant::now(),
+    })
+}
+
+pub(crate) fn enqueued(proc: &LightProc) {
+    if let Some(hook) = HOOKS.get().and_then(|hooks| hooks.on_enqueue.as_ref()) {
+        hook(&task_event(proc));
+    }
+}
+
+pub(crate) fn dequeued(proc: &LightProc) {
+    if let Some(hook) = HOOKS.get().and_then(|hooks| hooks.on_dequeue.as_ref()) {
+        hook(&task_event(proc));
+    }
+}
+
+pub(crate) fn parked() {
+    if let Some(hook) = HOOKS.get().and_then(|hooks| hooks.on_park.as_ref()) {
+        if let Some(event) = worker_event() {
+            hook(&event);
+        }
+    }
+}
+
+pub(crate) fn unparked() {
+    if let Some(hook) = HOOKS.get().and_then(|hooks| hooks.on_unpark.as_ref()) {
+        if let Some(event) = worker_event() {
+            hook(&event);
+        }
+    }
+}
@@ -30,19 +30,29 @@
 #![warn(missing_debug_implementations)]
 
 pub mod blocking;
+pub mod deadline;
+pub mod deterministic;
+pub mod hooks;
 pub mod load_balancer;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod placement;
 pub mod pool;
 pub mod run;
 pub mod run_queue;
 pub mod sleepers;
 mod thread_manager;
+#[cfg(feature = "tokio-runtime")]
+pub mod tokio_runtime;
 pub mod worker;
 
 ///
 /// Prelude of Bastion Executor
 pub mod prelude {
     pub use crate::blocking::*;
+    pub use crate::deadline::*;
     pub use crate::pool::*;
     pub use crate::run::*;
+    // Both the pool and the blocking pool export a `spawn` function.
+    pub use crate::pool::spawn;
 }
@@ -2,7 +2,10 @@
 //! Module for gathering statistics about the run queues of the runtime
 //!
 //! Load balancer calculates sampled mean to provide average process execution amount
-//! to all runtime.
+//! to all runtime. The global pool feeds the loads while its threads run, and a
+//! snapshot of them can be taken with [`snapshot`].
+//!
+//! [`snapshot`]: fn.snapshot.html
 //!
 use crate::load_balancer;
 use crate::placement;
@@ -10,7 +13,7 @@ use arrayvec::ArrayVec;
 use fmt::{Debug, Formatter};
 use lazy_static::*;
 use once_cell::sync::Lazy;
-use placement::CoreId;
+use placement::{CoreId, NumaNode};
 use std::mem::MaybeUninit;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::RwLock;
@@ -105,6 +108,21 @@ pub fn update() {
     LOAD_BALANCER.update_load_mean()
 }
 
+/// Stores the number of processes waiting in the run queues of the
+/// global pool, as sampled by its manager, and updates the mean load.
+///
+/// The pool's threads share its run queues, so the processes are spread
+/// evenly across the loads of the cores.
+pub(crate) fn store_queue_depth(depth: usize) {
+    let cores = *core_count();
+    let stats = stats();
+    for core in 0..cores {
+        let share = depth / cores + usize::from(core < depth % cores);
+        stats.store_load(core, share);
+    }
+    update();
+}
+
 /// Maximum number of core supported by modern computers.
 const MAX_CORE: usize = 256;
 
@@ -118,6 +136,22 @@ pub struct Stats {
     smp_load: [AtomicUsize; MAX_CORE],
     mean_level: AtomicUsize,
     updating_mean: AtomicBool,
+    steals: AtomicUsize,
+    failed_steals: AtomicUsize,
+}
+
+///
+/// Snapshot of the statistics related to the run queues, taken by
+/// [`Stats::snapshot`].
+///
+/// [`Stats::snapshot`]: struct.Stats.html#method.snapshot
+#[derive(Debug, Clone)]
+pub struct LoadStats {
+    loads: ArrayVec<[usize; MAX_CORE]>,
+    mean: usize,
+    steals: usize,
+    failed_steals: usize,
+    sampling_interval: Duration,
 }
 
 impl fmt::Debug for Stats {
@@ -126,6 +160,8 @@ impl fmt::Debug for Stats {
             .field("smp_load", &&self.smp_load[..])
             .field("mean_level", &self.mean_level)
             .field("updating_mean", &self.updating_mean)
+            .field("steals", &self.steals)
+            .field("failed_steals", &self.failed_steals)
             .finish()
     }
 }
@@ -154,8 +190,125 @@ impl Stats {
             smp_load,
             mean_level: AtomicUsize::new(0),
             updating_mean: AtomicBool::new(false),
+            steals: AtomicUsize::new(0),
+            failed_steals: AtomicUsize::new(0),
         }
     }
+
+    /// Records an attempt to steal processes from another core's run
+    /// queue, which failed if it was empty.
+    ///
+    /// The threads of the pool share its run queues, so the pool never
+    /// steals and doesn't record anything. It's meant for schedulers
+    /// built on per-core [`run_queue`]s.
+    ///
+    /// [`run_queue`]: ../run_queue/index.html
+    pub fn record_steal(&self, stolen: bool) {
+        let counter = if stolen {
+            &self.steals
+        } else {
+            &self.failed_steals
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the queues which the queue of the given core should try
+    /// to steal processes from, from the most loaded to the least loaded.
+    ///
+    /// If NUMA nodes are given (see [`Placement::numa_nodes`]), the
+    /// queues of the thief's node come before the ones of the other
+    /// nodes. Otherwise, or if the thief isn't part of any node, the
+    /// queues are only ordered by load.
+    ///
+    /// The threads of the pool share its run queues, so the pool doesn't
+    /// steal processes and doesn't use this order yet. It's meant for
+    /// schedulers built on per-core [`run_queue`]s.
+    ///
+    /// [`run_queue`]: ../run_queue/index.html
+    /// [`Placement::numa_nodes`]: ../placement/struct.Placement.html#method.numa_nodes
+    pub fn steal_order(
+        &self,
+        thief: usize,
+        nodes: Option<&[NumaNode]>,
+    ) -> ArrayVec<[usize; MAX_CORE]> {
+        let mut order: ArrayVec<[usize; MAX_CORE]> = self
+            .get_sorted_load()
+            .into_iter()
+            .map(|(core, _)| core)
+            .filter(|core| *core != thief)
+            .collect();
+
+        if let Some(node) = nodes.and_then(|nodes| nodes.iter().find(|node| node.contains(thief))) {
+            // The sort is stable, so both groups stay ordered by load.
+            order.sort_by_key(|core| !node.contains(*core));
+        }
+        order
+    }
+
+    /// Copies the statistics without blocking the ones updating them.
+    ///
+    /// The values are read one by one, so they might not be consistent
+    /// with each other if they're updated in the meantime.
+    pub fn snapshot(&self) -> LoadStats {
+        let loads = self
+            .smp_load
+            .iter()
+            .map(|load| load.load(Ordering::Acquire))
+            .take_while(|load| *load != usize::MAX)
+            .collect();
+
+        LoadStats {
+            loads,
+            mean: self.mean(),
+            steals: self.steals.load(Ordering::Relaxed),
+            failed_steals: self.failed_steals.load(Ordering::Relaxed),
+            sampling_interval: MEAN_UPDATE_TRESHOLD,
+        }
+    }
+}
+
+impl LoadStats {
+    /// Returns the load of each core, indexed by its id.
+    ///
+    /// The threads of the pool share its run queues, so the processes
+    /// waiting in them when the pool was last sampled are spread evenly
+    /// across the cores.
+    pub fn loads(&self) -> &[usize] {
+        &self.loads
+    }
+
+    /// Returns the mean load of the cores, as last updated. This is how
+    /// many processes were waiting per core.
+    pub fn mean(&self) -> usize {
+        self.mean
+    }
+
+    /// Returns how many steals were recorded with [`Stats::record_steal`].
+    ///
+    /// The pool never steals, so this stays at zero unless a scheduler
+    /// built on per-core run queues records its steals.
+    ///
+    /// [`Stats::record_steal`]: struct.Stats.html#method.record_steal
+    pub fn steals(&self) -> usize {
+        self.steals
+    }
+
+    /// Returns how many failed steals were recorded with
+    /// [`Stats::record_steal`], because the victim's queue was empty.
+    ///
+    /// Like [`LoadStats::steals`], this stays at zero for the pool.
+    ///
+    /// [`Stats::record_steal`]: struct.Stats.html#method.record_steal
+    /// [`LoadStats::steals`]: #method.steals
+    pub fn failed_steals(&self) -> usize {
+        self.failed_steals
+    }
+
+    /// Returns the minimum interval between two updates of the mean
+    /// load, telling how stale it might be.
+    pub fn sampling_interval(&self) -> Duration {
+        self.sampling_interval
+    }
 }
 
 unsafe impl Sync for Stats {}
@@ -219,6 +372,23 @@ pub fn stats() -> &'static Stats {
     &*LOCKLESS_STATS
 }
 
+///
+/// Takes a snapshot of the runtime statistics
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::load_balancer;
+///
+/// let snapshot = load_balancer::snapshot();
+/// if snapshot.mean() > 64 {
+///     // Delay the low-priority work...
+/// }
+/// ```
+#[inline]
+pub fn snapshot() -> LoadStats {
+    stats().snapshot()
+}
+
 ///
 /// Retrieve core count for the runtime scheduling purposes
 #[inline]
@@ -0,0 +1,86 @@
+//!
+//! Runtime metrics of the executor.
+//!
+//! Counters are plain atomics updated inline when processes are
+//! spawned onto the pools and finish, while gauges are read from the
+//! pools when asked for. Enabled with the `metrics` feature.
+
+use crate::{blocking, pool};
+use lightproc::proc_stack::Priority;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+static SPAWNED_PROCS: AtomicU64 = AtomicU64::new(0);
+static COMPLETED_PROCS: AtomicU64 = AtomicU64::new(0);
+static PANICKED_PROCS: AtomicU64 = AtomicU64::new(0);
+
+/// Returns how many processes were spawned onto the pools.
+pub fn spawned_procs() -> u64 {
+    SPAWNED_PROCS.load(Ordering::Relaxed)
+}
+
+/// Returns how many processes spawned onto the pools ran to completion.
+pub fn completed_procs() -> u64 {
+    COMPLETED_PROCS.load(Ordering::Relaxed)
+}
+
+/// Returns how many processes spawned onto the pools panicked.
+pub fn panicked_procs() -> u64 {
+    PANICKED_PROCS.load(Ordering::Relaxed)
+}
+
+/// Returns how many worker threads of the pool are alive.
+pub fn workers() -> usize {
+    pool::manager().map_or(0, |manager| manager.live_threads())
+}
+
+/// Returns how many worker threads of the pool are parked.
+pub fn parked_workers() -> usize {
+    pool::manager().map_or(0, |manager| manager.parked_threads())
+}
+
+/// Returns how many processes are waiting to be run by the pool,
+/// for each priority.
+pub fn queue_depths() -> [(Priority, usize); 3] {
+    pool::queue_depths()
+}
+
+/// Returns how many threads of the blocking pool are alive.
+pub fn blocking_threads() -> usize {
+    blocking::manager().map_or(0, |manager| manager.live_threads())
+}
+
+/// Counts the process running `future` as spawned, and then as
+/// completed or panicked once it finishes.
+pub(crate) fn track<F: Future>(future: F) -> Tracked<F> {
+    SPAWNED_PROCS.fetch_add(1, Ordering::Relaxed);
+    Tracked { future }
+}
+
+pub(crate) struct Tracked<F> {
+    future: F,
+}
+
+impl<F: Future> Future for Tracked<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // SAFETY: the future is never moved out of `Tracked`.
+        let future = unsafe { self.map_unchecked_mut(|tracked| &mut tracked.future) };
+        match panic::catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(Poll::Ready(output)) => {
+                COMPLETED_PROCS.fetch_add(1, Ordering::Relaxed);
+                Poll::Ready(output)
+            }
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => {
+                PANICKED_PROCS.fetch_add(1, Ordering::Relaxed);
+                // The process still handles the panic itself.
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+}
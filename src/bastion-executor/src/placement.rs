@@ -22,6 +22,30 @@ pub fn set_for_current(core_id: CoreId) {
     set_for_current_helper(core_id);
 }
 
+/// This function tries to retrieve the NUMA nodes of the system,
+/// with the "cores" of each node which are active on this system.
+///
+/// The topology is only detected on Linux, so this function returns
+/// `None` on the other platforms.
+pub fn get_numa_nodes() -> Option<Vec<NumaNode>> {
+    let active = get_core_ids()?;
+    let nodes: Vec<NumaNode> = get_numa_nodes_helper()?
+        .into_iter()
+        .map(|mut node| {
+            node.cores
+                .retain(|core| active.iter().any(|active| active.id == core.id));
+            node
+        })
+        .filter(|node| !node.cores.is_empty())
+        .collect();
+
+    if nodes.is_empty() {
+        None
+    } else {
+        Some(nodes)
+    }
+}
+
 ///
 /// CoreID implementation to identify system cores.
 #[derive(Copy, Clone, Debug)]
@@ -30,6 +54,126 @@ pub struct CoreId {
     pub id: usize,
 }
 
+///
+/// NUMA node of the system, with the cores it's made of.
+#[derive(Clone, Debug)]
+pub struct NumaNode {
+    /// Used node ID
+    pub id: usize,
+    /// Cores of the node
+    pub cores: Vec<CoreId>,
+}
+
+impl NumaNode {
+    /// Returns whether the core with the given ID is part of the node.
+    pub fn contains(&self, core_id: usize) -> bool {
+        self.cores.iter().any(|core| core.id == core_id)
+    }
+}
+
+///
+/// Set of cores which the threads of a pool are pinned onto.
+///
+/// Threads are assigned to the cores in a round-robin fashion, so if there are
+/// more threads than allowed cores, some cores will be shared.
+/// When no cores are given, the threads are spread across all the cores the
+/// process is allowed to run on, and pinning is skipped on single core systems.
+///
+/// On platforms where pinning is unsupported, threads are left unpinned.
+///
+/// In NUMA-aware mode, the cores are grouped by NUMA node, so that the
+/// threads assigned one after the other share a node, and the node of each
+/// core can be looked up with [`numa_nodes`] to steal work from the same
+/// node first (see [`Stats::steal_order`]). The pool's threads share its
+/// run queues and don't steal from each other, so for the pool the mode
+/// only changes how the threads are pinned. If the topology can't be
+/// determined, or if there is a single node, the placement behaves as if
+/// the mode was disabled.
+///
+/// [`numa_nodes`]: #method.numa_nodes
+/// [`Stats::steal_order`]: ../load_balancer/struct.Stats.html#method.steal_order
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::placement::{CoreId, Placement};
+///
+/// let placement = Placement::new().with_cores(vec![CoreId { id: 0 }]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Placement {
+    cores: Option<Vec<CoreId>>,
+    numa_aware: bool,
+}
+
+impl Placement {
+    /// Creates a new placement using all the available cores.
+    pub fn new() -> Self {
+        Placement::default()
+    }
+
+    /// Restricts the placement to the given cores.
+    ///
+    /// An empty set of cores falls back to using all the available cores.
+    pub fn with_cores(mut self, cores: Vec<CoreId>) -> Self {
+        self.cores = if cores.is_empty() { None } else { Some(cores) };
+        self
+    }
+
+    /// Returns the cores given with [`with_cores`], if any.
+    ///
+    /// [`with_cores`]: #method.with_cores
+    pub fn cores(&self) -> Option<&[CoreId]> {
+        self.cores.as_deref()
+    }
+
+    /// Enables or disables the NUMA-aware mode.
+    ///
+    /// Defaults to disabled.
+    pub fn with_numa_aware(mut self, numa_aware: bool) -> Self {
+        self.numa_aware = numa_aware;
+        self
+    }
+
+    /// Returns whether the NUMA-aware mode is enabled.
+    pub fn numa_aware(&self) -> bool {
+        self.numa_aware
+    }
+
+    /// Returns the NUMA nodes of the cores of the placement, if the
+    /// NUMA-aware mode is enabled and the topology could be determined.
+    pub fn numa_nodes(&self) -> Option<Vec<NumaNode>> {
+        if !self.numa_aware {
+            return None;
+        }
+
+        let mut nodes = get_numa_nodes()?;
+        if let Some(cores) = &self.cores {
+            for node in &mut nodes {
+                node.cores
+                    .retain(|core| cores.iter().any(|given| given.id == core.id));
+            }
+            nodes.retain(|node| !node.cores.is_empty());
+        }
+
+        if nodes.is_empty() {
+            None
+        } else {
+            Some(nodes)
+        }
+    }
+
+    // The cores the threads are pinned onto in turn, if any were given or
+    // if they are grouped by NUMA node.
+    pub(crate) fn pinned_cores(&self) -> Option<Vec<CoreId>> {
+        match self.numa_nodes() {
+            Some(nodes) if nodes.len() > 1 => {
+                Some(nodes.into_iter().flat_map(|node| node.cores).collect())
+            }
+            _ => self.cores.clone(),
+        }
+    }
+}
+
 // Linux Section
 
 #[cfg(target_os = "linux")]
@@ -44,13 +188,22 @@ fn set_for_current_helper(core_id: CoreId) {
     linux::set_for_current(core_id);
 }
 
+#[cfg(target_os = "linux")]
+#[inline]
+fn get_numa_nodes_helper() -> Option<Vec<NumaNode>> {
+    linux::get_numa_nodes()
+}
+
 #[cfg(target_os = "linux")]
 mod linux {
+    use std::fs;
     use std::mem;
 
     use libc::{cpu_set_t, sched_getaffinity, sched_setaffinity, CPU_ISSET, CPU_SET, CPU_SETSIZE};
 
-    use super::CoreId;
+    use super::{CoreId, NumaNode};
+
+    const NODES_PATH: &str = "/sys/devices/system/node";
 
     pub fn get_core_ids() -> Option<Vec<CoreId>> {
         if let Some(full_set) = get_affinity_mask() {
@@ -85,6 +238,49 @@ mod linux {
         }
     }
 
+    pub fn get_numa_nodes() -> Option<Vec<NumaNode>> {
+        let mut nodes = Vec::new();
+
+        for entry in fs::read_dir(NODES_PATH).ok()? {
+            let entry = entry.ok()?;
+            let id = match entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("node"))
+                .and_then(|id| id.parse().ok())
+            {
+                Some(id) => id,
+                None => continue,
+            };
+
+            let cpulist = fs::read_to_string(entry.path().join("cpulist")).ok()?;
+            let cores = parse_cpulist(&cpulist)?
+                .into_iter()
+                .map(|id| CoreId { id })
+                .collect();
+            nodes.push(NumaNode { id, cores });
+        }
+
+        nodes.sort_by_key(|node| node.id);
+        Some(nodes)
+    }
+
+    // Parses a list of core ranges as written by sysfs, like `0-3,8,10-11`.
+    fn parse_cpulist(cpulist: &str) -> Option<Vec<usize>> {
+        let mut cores = Vec::new();
+
+        for range in cpulist.trim().split(',').filter(|range| !range.is_empty()) {
+            match range.split_once('-') {
+                Some((first, last)) => {
+                    cores.extend(first.parse::<usize>().ok()?..=last.parse().ok()?)
+                }
+                None => cores.push(range.parse().ok()?),
+            }
+        }
+
+        Some(cores)
+    }
+
     fn get_affinity_mask() -> Option<cpu_set_t> {
         let mut set = new_cpu_set();
 
@@ -123,6 +319,16 @@ mod linux {
             }
         }
 
+        #[test]
+        fn test_linux_parse_cpulist() {
+            assert_eq!(
+                parse_cpulist("0-3,8,10-11\n"),
+                Some(vec![0, 1, 2, 3, 8, 10, 11])
+            );
+            assert_eq!(parse_cpulist("\n"), Some(vec![]));
+            assert_eq!(parse_cpulist("0-a"), None);
+        }
+
         #[test]
         fn test_linux_get_core_ids() {
             match get_core_ids() {
@@ -387,6 +593,12 @@ fn get_core_ids_helper() -> Option<Vec<CoreId>> {
 #[inline]
 fn set_for_current_helper(core_id: CoreId) {}
 
+#[cfg(not(target_os = "linux"))]
+#[inline]
+fn get_numa_nodes_helper() -> Option<Vec<NumaNode>> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -412,4 +624,27 @@ mod tests {
 
         set_for_current(ids[0]);
     }
+
+    #[test]
+    fn test_placement_with_cores() {
+        let placement = Placement::new().with_cores(vec![CoreId { id: 0 }]);
+        assert_eq!(placement.cores().map(|cores| cores.len()), Some(1));
+
+        let placement = Placement::new().with_cores(vec![]);
+        assert!(placement.cores().is_none());
+    }
+
+    #[test]
+    fn test_placement_numa_aware() {
+        let placement = Placement::new();
+        assert!(!placement.numa_aware());
+        assert!(placement.numa_nodes().is_none());
+
+        let placement = Placement::new().with_numa_aware(true);
+        assert!(placement.numa_aware());
+        if let Some(nodes) = placement.numa_nodes() {
+            let cores = nodes.iter().map(|node| node.cores.len()).sum::<usize>();
+            assert_eq!(cores, num_cpus::get());
+        }
+    }
 }
@@ -4,19 +4,28 @@
 //! We spawn futures onto the pool with [spawn] method of global run queue or
 //! with corresponding [Worker]'s spawn method.
 
+use crate::deadline::{self, DeadlineError};
+use crate::deterministic::{Scheduler, VirtualClock};
+use crate::hooks;
+use crate::load_balancer;
+#[cfg(feature = "metrics")]
+use crate::metrics;
+use crate::placement::Placement;
 use crate::thread_manager::{DynamicPoolManager, DynamicRunner};
+#[cfg(feature = "tokio-runtime")]
+use crate::tokio_runtime;
 use crate::worker;
-use crossbeam_channel::{unbounded, Receiver, Sender};
+use crossbeam_channel::{unbounded, Receiver, Select, Sender};
 use lazy_static::lazy_static;
 use lightproc::lightproc::LightProc;
-use lightproc::proc_stack::ProcStack;
+use lightproc::proc_stack::{Priority, ProcStack};
 use lightproc::recoverable_handle::RecoverableHandle;
-use once_cell::sync::{Lazy, OnceCell};
+use once_cell::sync::OnceCell;
+use std::env;
 use std::future::Future;
 use std::iter::Iterator;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use std::{env, thread};
 use tracing::trace;
 
 ///
@@ -49,11 +58,58 @@ where
     F: Future<Output = T> + Send + 'static,
     T: Send + 'static,
 {
+    #[cfg(feature = "tokio-runtime")]
+    let future = tokio_runtime::enter(future);
+    #[cfg(feature = "metrics")]
+    let future = metrics::track(future);
+
     let (task, handle) = LightProc::recoverable(future, worker::schedule, stack);
     task.schedule();
     handle
 }
 
+///
+/// Spawn a process onto the executor from the global level, cancelling it
+/// if it didn't complete once `timeout` elapsed.
+///
+/// The handle resolves to the [`DeadlineError`] telling how the process
+/// was cancelled if it didn't complete on time. Processes are only
+/// cancelled at their await points, so CPU-bound processes without await
+/// points can overrun their deadline (see the [`deadline`] module).
+///
+/// [`DeadlineError`]: ../deadline/enum.DeadlineError.html
+/// [`deadline`]: ../deadline/index.html
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::prelude::*;
+/// use lightproc::prelude::*;
+/// use std::future;
+/// use std::time::Duration;
+///
+/// let stack = ProcStack::default();
+///
+/// let handle = spawn_with_deadline(
+///     future::pending::<()>(),
+///     Duration::from_millis(10),
+///     stack.clone(),
+/// );
+///
+/// let output = run(handle, stack.clone());
+/// assert_eq!(output, Some(Err(DeadlineError::Cancelled)));
+/// ```
+pub fn spawn_with_deadline<F, T>(
+    future: F,
+    timeout: Duration,
+    stack: ProcStack,
+) -> RecoverableHandle<Result<T, DeadlineError>>
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    spawn(deadline::with_deadline(future, timeout), stack)
+}
+
 /// Spawns a blocking task.
 ///
 /// The task will be spawned onto a thread pool specifically dedicated to blocking tasks.
@@ -62,6 +118,11 @@ where
     F: Future<Output = R> + Send + 'static,
     R: Send + 'static,
 {
+    #[cfg(feature = "tokio-runtime")]
+    let future = tokio_runtime::enter(future);
+    #[cfg(feature = "metrics")]
+    let future = metrics::track(future);
+
     let (task, handle) = LightProc::recoverable(future, schedule, stack);
     task.schedule();
     handle
@@ -71,13 +132,211 @@ where
 /// Acquire the static Pool reference
 #[inline]
 pub fn get() -> &'static Pool {
-    &*POOL
+    POOL.get_or_init(|| Pool::start(CONFIG.get_or_init(PoolConfig::default)))
+}
+
+/// Returns the static Pool reference, if it was already acquired.
+#[inline]
+pub fn try_get() -> Option<&'static Pool> {
+    POOL.get().copied()
+}
+
+///
+/// Configures the global pool.
+///
+/// This needs to be called before any process gets spawned onto the pool;
+/// otherwise the pool is already running with the default configuration
+/// and the given configuration is returned back.
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::pool::{self, PoolConfig};
+///
+/// let config = PoolConfig::default()
+///     .with_min_threads(2)
+///     .with_max_threads(4);
+///
+/// pool::configure(config).expect("pool is already running");
+/// ```
+pub fn configure(config: PoolConfig) -> Result<(), PoolConfig> {
+    CONFIG.set(config)
+}
+
+/// Configuration of the bounds and thresholds used to grow
+/// and shrink the number of threads of a pool.
+///
+/// Every 90 milliseconds the pool samples the mean number of tasks
+/// waiting per thread. If it stays above the high watermark during
+/// the whole sampling window, a thread is spawned up to the maximum
+/// amount of threads. If it stays at or below the low watermark, a
+/// thread is parked and retired down to the minimum amount of threads.
+///
+/// By default, the pool keeps one thread per core and doesn't limit
+/// the threads spawned to handle bursts.
+///
+/// The threads are pinned onto the cores given by the [`Placement`].
+///
+/// [`Placement`]: ../placement/struct.Placement.html
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    min_threads: Option<usize>,
+    max_threads: Option<usize>,
+    high_watermark: usize,
+    low_watermark: usize,
+    sampling_window: usize,
+    idle_timeout: Option<Duration>,
+    placement: Placement,
+    deterministic_seed: Option<u64>,
+}
+
+impl PoolConfig {
+    /// Creates a new configuration with the default values.
+    pub fn new() -> Self {
+        PoolConfig::default()
+    }
+
+    /// Sets the minimum amount of threads the pool shrinks to.
+    ///
+    /// It is raised to one more than the pool's static threads, whose
+    /// amount is set by the `BASTION_BLOCKING_THREADS` environment variable
+    /// and defaults to 2, and lowered to the maximum amount of threads.
+    ///
+    /// Defaults to the number of cores.
+    pub fn with_min_threads(mut self, min_threads: usize) -> Self {
+        self.min_threads = Some(min_threads);
+        self
+    }
+
+    /// Sets the maximum amount of threads the pool grows to.
+    ///
+    /// It is raised to one more than the pool's static threads, whose
+    /// amount is set by the `BASTION_BLOCKING_THREADS` environment variable
+    /// and defaults to 2, so the pool has 3 threads or more by default.
+    ///
+    /// Defaults to no limit.
+    pub fn with_max_threads(mut self, max_threads: usize) -> Self {
+        self.max_threads = Some(max_threads);
+        self
+    }
+
+    /// Sets the mean number of waiting tasks per thread above which
+    /// the pool grows.
+    ///
+    /// Defaults to `8`.
+    pub fn with_high_watermark(mut self, high_watermark: usize) -> Self {
+        self.high_watermark = high_watermark;
+        self
+    }
+
+    /// Sets the mean number of waiting tasks per thread at or below
+    /// which the pool shrinks.
+    ///
+    /// Defaults to `0`.
+    pub fn with_low_watermark(mut self, low_watermark: usize) -> Self {
+        self.low_watermark = low_watermark;
+        self
+    }
+
+    /// Sets how many consecutive samples need to cross a watermark
+    /// before the pool is resized. It is clamped to at least one sample.
+    ///
+    /// Defaults to `10`.
+    pub fn with_sampling_window(mut self, sampling_window: usize) -> Self {
+        self.sampling_window = sampling_window.max(1);
+        self
+    }
+
+    /// Sets how long a thread can stay parked before being retired,
+    /// down to the minimum amount of threads.
+    ///
+    /// Defaults to parking threads until new tasks come up.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Sets the cores which the threads are pinned onto.
+    ///
+    /// Defaults to all the available cores.
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Makes the pool run its processes on the thread driving it
+    /// instead of on threads of its own, in an order picked from
+    /// `seed`, and with a virtual clock (see the [`deterministic`]
+    /// module). The other settings are then ignored.
+    ///
+    /// Defaults to running the processes on the pool's threads.
+    ///
+    /// [`deterministic`]: ../deterministic/index.html
+    pub fn with_deterministic_seed(mut self, seed: u64) -> Self {
+        self.deterministic_seed = Some(seed);
+        self
+    }
+
+    /// Returns the minimum amount of threads, if any was set.
+    pub fn min_threads(&self) -> Option<usize> {
+        self.min_threads
+    }
+
+    /// Returns the maximum amount of threads, if any was set.
+    pub fn max_threads(&self) -> Option<usize> {
+        self.max_threads
+    }
+
+    /// Returns the high watermark.
+    pub fn high_watermark(&self) -> usize {
+        self.high_watermark
+    }
+
+    /// Returns the low watermark.
+    pub fn low_watermark(&self) -> usize {
+        self.low_watermark
+    }
+
+    /// Returns the sampling window.
+    pub fn sampling_window(&self) -> usize {
+        self.sampling_window
+    }
+
+    /// Returns the idle timeout, if any was set.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// Returns the placement of the threads.
+    pub fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    /// Returns the seed of the order the processes are run in, if the
+    /// pool is deterministic.
+    pub fn deterministic_seed(&self) -> Option<u64> {
+        self.deterministic_seed
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            min_threads: None,
+            max_threads: None,
+            high_watermark: 8,
+            low_watermark: 0,
+            sampling_window: 10,
+            idle_timeout: None,
+            placement: Placement::default(),
+            deterministic_seed: None,
+        }
+    }
 }
 
 impl Pool {
     ///
     /// Spawn a process (which contains future + process stack) onto the executor via [Pool] interface.
-    pub fn spawn<F, T>(&self, future: F, stack: ProcStack) -> RecoverableHandle<T>
+    pub fn spawn<F, T>(&'static self, future: F, stack: ProcStack) -> RecoverableHandle<T>
     where
         F: Future<Output = T> + Send + 'static,
         T: Send + 'static,
@@ -86,7 +345,12 @@ impl Pool {
         let _child_id = stack.get_pid() as u64;
         let _parent_id = worker::get_proc_stack(|t| t.get_pid() as u64).unwrap_or(0);
 
-        let (task, handle) = LightProc::recoverable(future, worker::schedule, stack);
+        #[cfg(feature = "tokio-runtime")]
+        let future = tokio_runtime::enter(future);
+        #[cfg(feature = "metrics")]
+        let future = metrics::track(future);
+
+        let (task, handle) = LightProc::recoverable(future, move |proc| self.schedule(proc), stack);
         task.schedule();
         handle
     }
@@ -97,13 +361,7 @@ impl Pool {
 /// based on the previous statistics without relying on
 /// if there is not a thread ready to accept the work or not.
 pub(crate) fn schedule(t: LightProc) {
-    if let Err(err) = POOL.sender.try_send(t) {
-        // We were not able to send to the channel without
-        // blocking.
-        POOL.sender.send(err.into_inner()).unwrap();
-    }
-    // Add up for every incoming scheduled task
-    DYNAMIC_POOL_MANAGER.get().unwrap().increment_frequency();
+    get().schedule(t)
 }
 
 ///
@@ -128,60 +386,362 @@ fn low_watermark() -> &'static u64 {
 const DEFAULT_LOW_WATERMARK: u64 = 2;
 
 /// Pool interface between the scheduler and thread pool
+///
+/// The run queue is split in one band per [Priority], processes of
+/// higher bands are always picked up before the ones of lower bands.
 #[derive(Debug)]
 pub struct Pool {
+    // Ordered from the highest priority to the lowest.
+    bands: [Band; 3],
+    manager: OnceCell<DynamicPoolManager>,
+    // Set if the pool is run by the thread driving it.
+    deterministic: Option<Deterministic>,
+    // Dropped once the pool is shut down, waking up its threads
+    // waiting on `closed`.
+    close: Mutex<Option<Sender<()>>>,
+    closed: Receiver<()>,
+}
+
+#[derive(Debug)]
+struct Deterministic {
+    scheduler: Mutex<Scheduler>,
+    clock: VirtualClock,
+}
+
+#[derive(Debug)]
+struct Band {
     sender: Sender<LightProc>,
     receiver: Receiver<LightProc>,
 }
 
-struct AsyncRunner {}
+impl Band {
+    fn new() -> Self {
+        let (sender, receiver) = unbounded();
+        Band { sender, receiver }
+    }
+}
+
+impl Pool {
+    ///
+    /// Starts a pool running its own threads, separate from the ones
+    /// of the global pool and of the other pools.
+    ///
+    /// The threads of a pool run until it is shut down with
+    /// [`shutdown`]. The pool itself is never freed.
+    ///
+    /// [`shutdown`]: #method.shutdown
+    ///
+    /// # Example
+    /// ```rust
+    /// use bastion_executor::pool::{Pool, PoolConfig};
+    /// use lightproc::prelude::*;
+    ///
+    /// let pool = Pool::start(&PoolConfig::default().with_max_threads(2));
+    /// let handle = pool.spawn(async { 42 }, ProcStack::default());
+    /// # let output = bastion_executor::run::run(handle, ProcStack::default());
+    /// # assert_eq!(output, Some(42));
+    /// ```
+    pub fn start(config: &PoolConfig) -> &'static Pool {
+        let deterministic = config.deterministic_seed.map(|seed| Deterministic {
+            scheduler: Mutex::new(Scheduler::new(seed)),
+            clock: VirtualClock::new(),
+        });
+        let (close, closed) = unbounded();
+        let pool: &'static Pool = Box::leak(Box::new(Pool {
+            bands: [Band::new(), Band::new(), Band::new()],
+            manager: OnceCell::new(),
+            deterministic,
+            close: Mutex::new(Some(close)),
+            closed,
+        }));
+        if pool.deterministic.is_some() {
+            return pool;
+        }
+
+        let runner = Arc::new(AsyncRunner { pool });
+
+        pool.manager
+            .set(DynamicPoolManager::new(
+                *low_watermark() as usize,
+                config,
+                runner,
+            ))
+            .expect("couldn't create dynamic pool manager");
+        pool.manager
+            .get()
+            .expect("couldn't get static pool manager")
+            .initialize();
+
+        pool
+    }
+
+    /// Shuts the pool down: its threads exit once they ran the
+    /// processes waiting in the pool, and the processes spawned onto it
+    /// afterwards are never run.
+    ///
+    /// It does nothing for deterministic pools, which have no threads.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bastion_executor::pool::{Pool, PoolConfig};
+    ///
+    /// let pool = Pool::start(&PoolConfig::default());
+    /// pool.shutdown();
+    /// ```
+    pub fn shutdown(&self) {
+        if let Some(manager) = self.manager.get() {
+            manager.shutdown();
+        }
+        self.close.lock().unwrap().take();
+    }
+
+    fn is_shut_down(&self) -> bool {
+        self.manager
+            .get()
+            .is_some_and(DynamicPoolManager::is_shut_down)
+    }
+
+    fn schedule(&self, t: LightProc) {
+        hooks::enqueued(&t);
+        let band = self.band(t.stack().get_priority());
+        if let Err(err) = band.sender.try_send(t) {
+            // We were not able to send to the channel without
+            // blocking.
+            band.sender.send(err.into_inner()).unwrap();
+        }
+        // Add up for every incoming scheduled task
+        if let Some(manager) = self.manager.get() {
+            manager.increment_frequency();
+        }
+    }
+
+    /// Returns whether the pool is run by the thread driving it, as
+    /// configured with [`PoolConfig::with_deterministic_seed`].
+    ///
+    /// [`PoolConfig::with_deterministic_seed`]: struct.PoolConfig.html#method.with_deterministic_seed
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic.is_some()
+    }
+
+    /// Returns the virtual clock of the pool, if it is deterministic.
+    pub fn clock(&self) -> Option<&VirtualClock> {
+        self.deterministic
+            .as_ref()
+            .map(|deterministic| &deterministic.clock)
+    }
+
+    /// Returns a number drawn from the seed of the pool, if it is
+    /// deterministic, so that what its processes pick at random can
+    /// be reproduced too.
+    pub fn seeded_random(&self) -> Option<u64> {
+        self.deterministic
+            .as_ref()
+            .map(|deterministic| deterministic.scheduler.lock().unwrap().next_u64())
+    }
+
+    /// Runs the processes of a deterministic pool on the current
+    /// thread, one at a time and in the order picked from its seed,
+    /// until none of them is ready to run. The virtual time doesn't
+    /// pass meanwhile.
+    ///
+    /// This method returns how many times processes were run. It does
+    /// nothing for pools run by their own threads.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bastion_executor::pool::{Pool, PoolConfig};
+    /// use lightproc::prelude::*;
+    ///
+    /// let pool = Pool::start(&PoolConfig::default().with_deterministic_seed(42));
+    /// let handle = pool.spawn(async { 42 }, ProcStack::default());
+    ///
+    /// assert_eq!(pool.run_until_idle(), 1);
+    /// # let output = bastion_executor::run::run(handle, ProcStack::default());
+    /// # assert_eq!(output, Some(42));
+    /// ```
+    pub fn run_until_idle(&self) -> usize {
+        let deterministic = match &self.deterministic {
+            Some(deterministic) => deterministic,
+            None => return 0,
+        };
+
+        let mut ready = Vec::new();
+        let mut ran = 0;
+        loop {
+            while let Some(task) = self.try_recv() {
+                ready.push(task);
+            }
+            if ready.is_empty() {
+                return ran;
+            }
+
+            let picked = deterministic.scheduler.lock().unwrap().pick(ready.len());
+            trace!("deterministic: running task");
+            run(ready.swap_remove(picked));
+            ran += 1;
+        }
+    }
+
+    /// Makes `duration` of virtual time pass for a deterministic
+    /// pool, running its processes until idle every time one of its
+    /// sleeps completes on the way (see [`run_until_idle`]).
+    ///
+    /// It does nothing for pools run by their own threads.
+    ///
+    /// [`run_until_idle`]: #method.run_until_idle
+    pub fn advance(&self, duration: Duration) {
+        let clock = match self.clock() {
+            Some(clock) => clock,
+            None => return,
+        };
+
+        let target = clock.elapsed() + duration;
+        self.run_until_idle();
+        while let Some(deadline) = clock.next_deadline().filter(|deadline| *deadline <= target) {
+            clock.set_elapsed(deadline);
+            self.run_until_idle();
+        }
+        clock.set_elapsed(target);
+        self.run_until_idle();
+    }
+
+    fn band(&self, priority: Priority) -> &Band {
+        match priority {
+            Priority::High => &self.bands[0],
+            Priority::Normal => &self.bands[1],
+            Priority::Low => &self.bands[2],
+        }
+    }
+
+    /// Pops the process with the highest priority without blocking.
+    fn try_recv(&self) -> Option<LightProc> {
+        self.bands
+            .iter()
+            .find_map(|band| band.receiver.try_recv().ok())
+    }
+
+    /// Blocks until a process is available and pops the one with the highest priority,
+    /// or returns `None` once there is none left and the pool is shut down.
+    fn recv(&self) -> Option<LightProc> {
+        loop {
+            if let Some(task) = self.try_recv() {
+                return Some(task);
+            }
+            if self.is_shut_down() {
+                return None;
+            }
+
+            hooks::parked();
+
+            let mut select = Select::new();
+            for band in self.bands.iter() {
+                select.recv(&band.receiver);
+            }
+            // Ready once the pool is shut down.
+            select.recv(&self.closed);
+            // Another thread might pop the process first, so only wait for one
+            // to be ready and pop it along with the priority order.
+            select.ready();
+            hooks::unparked();
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bands.iter().map(|band| band.receiver.len()).sum()
+    }
+}
+
+/// Returns the manager of the pool's threads, if the pool is running.
+#[cfg(feature = "metrics")]
+pub(crate) fn manager() -> Option<&'static DynamicPoolManager> {
+    POOL.get().and_then(|pool| pool.manager.get())
+}
+
+/// Returns how many processes are waiting in the band of each priority.
+#[cfg(feature = "metrics")]
+pub(crate) fn queue_depths() -> [(Priority, usize); 3] {
+    let priorities = [Priority::High, Priority::Normal, Priority::Low];
+    // Doesn't start the pool if it isn't running yet.
+    if manager().is_none() {
+        return priorities.map(|priority| (priority, 0));
+    }
+
+    priorities.map(|priority| (priority, get().band(priority).receiver.len()))
+}
+
+struct AsyncRunner {
+    pool: &'static Pool,
+}
+
+impl AsyncRunner {
+    /// Only the global pool feeds the load statistics.
+    fn feeds_stats(&self) -> bool {
+        try_get().is_some_and(|pool| std::ptr::eq(pool, self.pool))
+    }
+}
+
+fn run(task: LightProc) {
+    hooks::dequeued(&task);
+    task.run();
+}
 
 impl DynamicRunner for AsyncRunner {
-    fn run_static(&self, park_timeout: Duration) -> ! {
+    fn run_static(&self, id: usize, _park_timeout: Duration) {
+        worker::register(id);
         loop {
-            for task in &POOL.receiver {
+            while let Some(task) = self.pool.try_recv() {
                 trace!("static: running task");
-                task.run();
+                run(task);
             }
 
-            trace!("static: empty queue, parking with timeout");
-            thread::park_timeout(park_timeout);
+            trace!("static: empty queue, waiting for a task");
+            match self.pool.recv() {
+                Some(task) => run(task),
+                None => break,
+            }
         }
     }
-    fn run_dynamic(&self, parker: &dyn Fn()) -> ! {
+    fn run_dynamic(&self, id: usize, parker: &dyn Fn() -> bool) {
+        worker::register(id);
         loop {
-            while let Ok(task) = POOL.receiver.try_recv() {
+            while let Some(task) = self.pool.try_recv() {
                 trace!("dynamic thread: running task");
-                task.run();
+                run(task);
             }
             trace!(
                 "dynamic thread: parking - {:?}",
                 std::thread::current().id()
             );
-            parker();
+            hooks::parked();
+            if !parker() {
+                break;
+            }
+            hooks::unparked();
         }
     }
-    fn run_standalone(&self) {
-        while let Ok(task) = POOL.receiver.try_recv() {
-            task.run();
+    fn run_standalone(&self, id: usize) {
+        worker::register(id);
+        while let Some(task) = self.pool.try_recv() {
+            run(task);
         }
         trace!("standalone thread: quitting.");
     }
+    fn queue_depth(&self) -> usize {
+        self.pool.len()
+    }
+    fn sampled(&self, queue_depth: usize) {
+        if self.feeds_stats() {
+            load_balancer::store_queue_depth(queue_depth);
+        }
+    }
+    fn thread_name(&self) -> &'static str {
+        "bastion-worker"
+    }
+    fn next_thread_id(&self) -> usize {
+        worker::next_id()
+    }
 }
 
-static DYNAMIC_POOL_MANAGER: OnceCell<DynamicPoolManager> = OnceCell::new();
-
-static POOL: Lazy<Pool> = Lazy::new(|| {
-    let runner = Arc::new(AsyncRunner {});
-
-    DYNAMIC_POOL_MANAGER
-        .set(DynamicPoolManager::new(*low_watermark() as usize, runner))
-        .expect("couldn't create dynamic pool manager");
-    DYNAMIC_POOL_MANAGER
-        .get()
-        .expect("couldn't get static pool manager")
-        .initialize();
+static CONFIG: OnceCell<PoolConfig> = OnceCell::new();
 
-    let (sender, receiver) = unbounded();
-    Pool { sender, receiver }
-});
+static POOL: OnceCell<&'static Pool> = OnceCell::new();
@@ -2,6 +2,8 @@
 //! Blocking run of the async processes
 //!
 //!
+#[cfg(feature = "tokio-runtime")]
+use crate::tokio_runtime;
 use crate::worker;
 use crossbeam_utils::sync::Parker;
 use lightproc::proc_stack::ProcStack;
@@ -37,6 +39,9 @@ pub fn run<F, T>(future: F, stack: ProcStack) -> T
 where
     F: Future<Output = T>,
 {
+    #[cfg(feature = "tokio-runtime")]
+    let future = tokio_runtime::enter(future);
+
     unsafe {
         // A place on the stack where the result will be stored.
         let out = &mut UnsafeCell::new(None);
@@ -564,6 +564,20 @@ impl<T> Stealer<T> {
     /// How many tasks exactly will be stolen is not specified. That said, this method will try to
     /// steal around half of the tasks in the queue, but also not more than some constant limit.
     pub fn steal_batch(&self, dest: &Worker<T>) -> Steal<()> {
+        self.steal_batch_sized(dest, None)
+    }
+
+    ///
+    /// Steals a batch of tasks with the specified amount and pushes them into another worker.
+    /// The amount is clamped to at least one task and to the constant batch limit.
+    /// If the queue holds fewer tasks than requested, all of them are stolen.
+    pub fn steal_batch_with_amount(&self, dest: &Worker<T>, amount: usize) -> Steal<()> {
+        self.steal_batch_sized(dest, Some(amount))
+    }
+
+    /// Steals a batch of tasks with either the requested amount or
+    /// around half of the tasks in the queue if none was requested.
+    fn steal_batch_sized(&self, dest: &Worker<T>, amount: Option<usize>) -> Steal<()> {
         // Load the front index.
         let mut f = self.inner.front.load(Ordering::Acquire);
 
@@ -588,7 +602,11 @@ impl<T> Stealer<T> {
         }
 
         // Reserve capacity for the stolen batch.
-        let batch_size = cmp::min((len as usize + 1) / 2, MAX_BATCH);
+        let batch_size = match amount {
+            Some(amount) => cmp::min(cmp::max(amount, 1), len as usize),
+            None => (len as usize + 1) / 2,
+        };
+        let batch_size = cmp::min(batch_size, MAX_BATCH);
         dest.reserve(batch_size);
         let mut batch_size = batch_size as isize;
 
@@ -2,8 +2,9 @@
 //! Where workers went to parking while no workload is in their worker queue.
 //!
 //! If a workload received pool will wake them up.
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 /// The place where worker threads go to sleep.
 ///
@@ -20,6 +21,11 @@ pub struct Sleepers {
 
     /// Set to `true` if a notification came up while nobody was sleeping.
     notified: AtomicBool,
+
+    /// How many notifications were handed to sleeping threads but not yet consumed.
+    ///
+    /// Only modified while holding the `sleep` lock.
+    woken: AtomicUsize,
 }
 
 #[allow(clippy::mutex_atomic)]
@@ -30,6 +36,7 @@ impl Default for Sleepers {
             sleep: Mutex::new(0),
             wake: Condvar::new(),
             notified: AtomicBool::new(false),
+            woken: AtomicUsize::new(0),
         }
     }
 }
@@ -41,13 +48,94 @@ impl Sleepers {
         Self::default()
     }
 
+    /// Returns how many threads are currently sleeping.
+    ///
+    /// Threads that were notified but haven't woken up yet aren't counted.
+    pub fn parked(&self) -> usize {
+        *self.sleep.lock().unwrap()
+    }
+
+    /// Returns `true` if at least one thread is currently sleeping.
+    pub fn is_any_parked(&self) -> bool {
+        self.parked() > 0
+    }
+
+    /// Returns `true` if a notification came up while nobody was sleeping and
+    /// wasn't picked up yet.
+    ///
+    /// Combined with [`parked`], this allows to tell apart "every thread is busy"
+    /// from "a wakeup is queued but wasn't consumed yet".
+    ///
+    /// [`parked`]: #method.parked
+    pub fn is_notified(&self) -> bool {
+        self.notified.load(Ordering::SeqCst)
+    }
+
     /// Puts the current thread to sleep.
     pub fn wait(&self) {
         let mut sleep = self.sleep.lock().unwrap();
 
         if !self.notified.swap(false, Ordering::SeqCst) {
             *sleep += 1;
-            std::mem::drop(self.wake.wait(sleep).unwrap());
+
+            // Guard against spurious wakeups: only leave once a notification was consumed.
+            loop {
+                sleep = self.wake.wait(sleep).unwrap();
+
+                if self.consume_wakeup(&sleep) {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Puts the current thread to sleep for at most `dur`.
+    ///
+    /// Returns `true` if the thread was woken up by a notification and `false` if it timed out.
+    pub fn wait_timeout(&self, dur: Duration) -> bool {
+        let mut sleep = self.sleep.lock().unwrap();
+
+        if self.notified.swap(false, Ordering::SeqCst) {
+            return true;
+        }
+
+        *sleep += 1;
+        let deadline = Instant::now() + dur;
+
+        loop {
+            let now = Instant::now();
+            let timeout = if now < deadline {
+                deadline - now
+            } else {
+                Duration::from_secs(0)
+            };
+            sleep = self.wake.wait_timeout(sleep, timeout).unwrap().0;
+
+            // A notifier could have picked this thread right before the timeout expired,
+            // in which case it already removed it from the sleep count.
+            if self.consume_wakeup(&sleep) {
+                return true;
+            }
+
+            if Instant::now() >= deadline {
+                // Nobody accounted for this thread, remove it from the sleep count ourselves.
+                *sleep -= 1;
+                return false;
+            }
+        }
+    }
+
+    /// Consumes a pending wakeup handed out by a notifier, if any.
+    ///
+    /// Takes the `sleep` guard to make sure the lock is held.
+    fn consume_wakeup(&self, _sleep: &MutexGuard<'_, usize>) -> bool {
+        let woken = self.woken.load(Ordering::SeqCst);
+
+        if woken > 0 {
+            self.woken.store(woken - 1, Ordering::SeqCst);
+            true
+        } else {
+            false
         }
     }
 
@@ -58,10 +146,27 @@ impl Sleepers {
 
             if *sleep > 0 {
                 *sleep -= 1;
+                self.woken.fetch_add(1, Ordering::SeqCst);
                 self.wake.notify_one();
             } else {
                 self.notified.store(true, Ordering::SeqCst);
             }
         }
     }
+
+    /// Notifies all sleeping threads.
+    ///
+    /// If no threads are sleeping, the next thread that attempts to go to sleep
+    /// will pick up the notification immediately.
+    pub fn notify_all(&self) {
+        let mut sleep = self.sleep.lock().unwrap();
+
+        if *sleep > 0 {
+            self.woken.fetch_add(*sleep, Ordering::SeqCst);
+            *sleep = 0;
+            self.wake.notify_all();
+        } else {
+            self.notified.store(true, Ordering::SeqCst);
+        }
+    }
 }
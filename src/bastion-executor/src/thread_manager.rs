@@ -45,21 +45,21 @@
 //! Throughput hogs determined by a combination of job in / job out frequency and current scheduler task assignment frequency.
 //! Threshold of EMA difference is eluded by machine epsilon for floating point arithmetic errors.
 
+use crate::pool::PoolConfig;
+use crate::sleepers::Sleepers;
 use crate::{load_balancer, placement};
 use core::fmt;
-use crossbeam_queue::ArrayQueue;
 use fmt::{Debug, Formatter};
-use lazy_static::lazy_static;
 use lever::prelude::TTas;
 use placement::CoreId;
 use std::collections::VecDeque;
 use std::time::Duration;
 use std::{
     sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
     },
-    thread::{self, Thread},
+    thread,
 };
 use tracing::{debug, trace};
 
@@ -82,10 +82,6 @@ const SCALER_POLL_INTERVAL: u64 = 90;
 /// Smoothing factor is estimated with: 2 / (N + 1) where N is sample size.
 const EMA_COEFFICIENT: f64 = 2_f64 / (FREQUENCY_QUEUE_SIZE as f64 + 1_f64);
 
-lazy_static! {
-    static ref ROUND_ROBIN_PIN: Mutex<CoreId> = Mutex::new(CoreId { id: 0 });
-}
-
 /// The `DynamicRunner` is piloted by `DynamicPoolManager`.
 /// Upon request it needs to be able to provide runner routines for:
 /// * Static threads.
@@ -97,19 +93,54 @@ lazy_static! {
 ///
 /// Run static threads:
 ///
-/// run_static should never return, and park for park_timeout instead.
+/// run_static should park for park_timeout when it has no more tasks to process,
+/// and only return once the pool is shut down (see `DynamicPoolManager::is_shut_down`).
 ///
 /// Run dynamic threads:
-/// run_dynamic should never return, and call `parker()` when it has no more tasks to process.
+/// run_dynamic should call `parker()` when it has no more tasks to process.
 /// It will be unparked automatically by the `DynamicPoolManager` if needs be.
+/// run_dynamic should return once `parker()` returns `false`, which means the thread got retired.
 ///
 /// Run standalone threads:
 /// run_standalone should return once it has no more tasks to process.
 /// The `DynamicPoolManager` will spawn other standalone threads if needs be.
+///
+/// Each thread is given the identifier returned by `next_thread_id`, which
+/// is passed to the routine it runs and appended to its name.
 pub trait DynamicRunner {
-    fn run_static(&self, park_timeout: Duration) -> !;
-    fn run_dynamic(&self, parker: &dyn Fn()) -> !;
-    fn run_standalone(&self);
+    fn run_static(&self, id: usize, park_timeout: Duration);
+    fn run_dynamic(&self, id: usize, parker: &dyn Fn() -> bool);
+    fn run_standalone(&self, id: usize);
+    /// Number of tasks waiting to be processed by the threads.
+    fn queue_depth(&self) -> usize;
+    /// Called with the number of waiting tasks each time the pool is sampled.
+    fn sampled(&self, queue_depth: usize);
+    /// Name of the threads, which are named `{thread_name}-{id}`, without
+    /// the `bastion-` prefix of `thread_name` when that's too long for the
+    /// platform to keep the identifier.
+    fn thread_name(&self) -> &'static str;
+    /// Reserves the identifier of a thread about to be spawned.
+    fn next_thread_id(&self) -> usize;
+}
+
+/// Longest thread name the platform keeps whole, excluding the nul byte.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MAX_THREAD_NAME_LEN: usize = 15;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const MAX_THREAD_NAME_LEN: usize = 63;
+
+/// Names a thread after its identifier, dropping the `bastion-` prefix of
+/// its name if the platform would instead cut the identifier off.
+fn thread_name(name: &str, id: usize) -> String {
+    let full = format!("{}-{}", name, id);
+    if full.len() <= MAX_THREAD_NAME_LEN {
+        return full;
+    }
+
+    match name.strip_prefix("bastion-") {
+        Some(short) => format!("{}-{}", short, id),
+        None => full,
+    }
 }
 
 /// The `DynamicPoolManager` is responsible for
@@ -136,9 +167,16 @@ pub trait DynamicRunner {
 /// so the total number of Static threads + Dynamic threads
 /// is the number of available cores on the machine. (`num_cpus::get()`)
 ///
+/// Within the bounds given by the [`PoolConfig`], Dynamic threads are also
+/// spawned when the queue depth stays above the high watermark, and retired
+/// when it stays at or below the low watermark, for a whole sampling window.
+/// If an idle timeout is configured, they are also retired after staying parked that long.
+///
+/// [`PoolConfig`]: ../pool/struct.PoolConfig.html
+///
 /// ## Standalone threads:
 /// They are created when there aren't enough static and dynamic threads to process the expected load.
-/// They will be destroyed on idle.
+/// They will be destroyed on idle. They are never spawned above the maximum amount of threads.
 ///
 /// ## Spawn order:
 /// In order to handle a growing load, the pool manager will ask to:
@@ -153,7 +191,21 @@ pub trait DynamicRunner {
 pub struct DynamicPoolManager {
     static_threads: usize,
     dynamic_threads: usize,
-    parked_threads: ArrayQueue<Thread>,
+    min_threads: usize,
+    max_threads: usize,
+    high_watermark: usize,
+    low_watermark: usize,
+    sampling_window: usize,
+    idle_timeout: Option<Duration>,
+    live_threads: AtomicUsize,
+    live_dynamic_threads: AtomicUsize,
+    retiring_threads: AtomicUsize,
+    shut_down: AtomicBool,
+    high_pressure_samples: AtomicUsize,
+    low_pressure_samples: AtomicUsize,
+    sleepers: Sleepers,
+    pinned_cores: Option<Vec<CoreId>>,
+    next_core: AtomicUsize,
     runner: Arc<dyn DynamicRunner + Send + Sync>,
     last_frequency: AtomicU64,
     frequencies: TTas<VecDeque<u64>>,
@@ -164,8 +216,17 @@ impl Debug for DynamicPoolManager {
         fmt.debug_struct("DynamicPoolManager")
             .field("static_threads", &self.static_threads)
             .field("dynamic_threads", &self.dynamic_threads)
-            .field("parked_threads", &self.parked_threads.len())
-            .field("parked_threads", &self.parked_threads.len())
+            .field("min_threads", &self.min_threads)
+            .field("max_threads", &self.max_threads)
+            .field("high_watermark", &self.high_watermark)
+            .field("low_watermark", &self.low_watermark)
+            .field("sampling_window", &self.sampling_window)
+            .field("idle_timeout", &self.idle_timeout)
+            .field("live_threads", &self.live_threads)
+            .field("live_dynamic_threads", &self.live_dynamic_threads)
+            .field("retiring_threads", &self.retiring_threads)
+            .field("parked_threads", &self.sleepers.parked())
+            .field("pinned_cores", &self.pinned_cores)
             .field("last_frequency", &self.last_frequency)
             .field("frequencies", &self.frequencies.try_lock())
             .finish()
@@ -173,12 +234,45 @@ impl Debug for DynamicPoolManager {
 }
 
 impl DynamicPoolManager {
-    pub fn new(static_threads: usize, runner: Arc<dyn DynamicRunner + Send + Sync>) -> Self {
+    pub fn new(
+        static_threads: usize,
+        config: &PoolConfig,
+        runner: Arc<dyn DynamicRunner + Send + Sync>,
+    ) -> Self {
         let dynamic_threads = 1.max(num_cpus::get().checked_sub(static_threads).unwrap_or(0));
+        // Static threads are never retired, and at least one dynamic thread is required
+        // for the pool to make progress.
+        let max_threads = config
+            .max_threads()
+            .unwrap_or(usize::MAX)
+            .max(static_threads + 1);
+        let min_threads = config
+            .min_threads()
+            .unwrap_or(static_threads + dynamic_threads)
+            .max(static_threads + 1)
+            .min(max_threads);
+        let dynamic_threads = dynamic_threads
+            .max(min_threads - static_threads)
+            .min(max_threads - static_threads);
+
         Self {
             static_threads,
             dynamic_threads,
-            parked_threads: ArrayQueue::new(dynamic_threads),
+            min_threads,
+            max_threads,
+            high_watermark: config.high_watermark(),
+            low_watermark: config.low_watermark(),
+            sampling_window: config.sampling_window(),
+            idle_timeout: config.idle_timeout(),
+            live_threads: AtomicUsize::new(0),
+            live_dynamic_threads: AtomicUsize::new(0),
+            retiring_threads: AtomicUsize::new(0),
+            shut_down: AtomicBool::new(false),
+            high_pressure_samples: AtomicUsize::new(0),
+            low_pressure_samples: AtomicUsize::new(0),
+            sleepers: Sleepers::new(),
+            pinned_cores: config.placement().pinned_cores(),
+            next_core: AtomicUsize::new(0),
             runner,
             last_frequency: AtomicU64::new(0),
             frequencies: TTas::new(VecDeque::with_capacity(
@@ -187,6 +281,18 @@ impl DynamicPoolManager {
         }
     }
 
+    /// Returns how many threads of the pool are alive.
+    #[cfg(feature = "metrics")]
+    pub fn live_threads(&self) -> usize {
+        self.live_threads.load(Ordering::SeqCst)
+    }
+
+    /// Returns how many threads of the pool are parked.
+    #[cfg(feature = "metrics")]
+    pub fn parked_threads(&self) -> usize {
+        self.sleepers.parked()
+    }
+
     pub fn increment_frequency(&self) {
         self.last_frequency.fetch_add(1, Ordering::Acquire);
     }
@@ -198,11 +304,14 @@ impl DynamicPoolManager {
         trace!("setting up the static thread manager");
         (0..self.static_threads).for_each(|_| {
             let clone = Arc::clone(&self.runner);
+            let id = self.runner.next_thread_id();
+            self.live_threads.fetch_add(1, Ordering::SeqCst);
             thread::Builder::new()
-                .name("bastion-driver-static".to_string())
+                .name(thread_name(self.runner.thread_name(), id))
                 .spawn(move || {
-                    Self::affinity_pinner();
-                    clone.run_static(THREAD_PARK_TIMEOUT);
+                    self.affinity_pinner();
+                    clone.run_static(id, THREAD_PARK_TIMEOUT);
+                    self.live_threads.fetch_sub(1, Ordering::SeqCst);
                 })
                 .expect("couldn't spawn static thread");
         });
@@ -210,17 +319,7 @@ impl DynamicPoolManager {
         // Dynamic thread manager that will allow us to unpark threads
         // According to the needs
         trace!("setting up the dynamic thread manager");
-        (0..self.dynamic_threads).for_each(|_| {
-            let clone = Arc::clone(&self.runner);
-            thread::Builder::new()
-                .name("bastion-driver-dynamic".to_string())
-                .spawn(move || {
-                    Self::affinity_pinner();
-                    let parker = || self.park_thread();
-                    clone.run_dynamic(&parker);
-                })
-                .expect("cannot start dynamic thread");
-        });
+        (0..self.dynamic_threads).for_each(|_| self.spawn_dynamic_thread());
 
         // Pool manager to check frequency of task rates
         // and take action by scaling the pool accordingly.
@@ -229,7 +328,7 @@ impl DynamicPoolManager {
             .spawn(move || {
                 let poll_interval = Duration::from_millis(SCALER_POLL_INTERVAL);
                 trace!("setting up the pool manager");
-                loop {
+                while !self.is_shut_down() {
                     self.scale_pool();
                     thread::park_timeout(poll_interval);
                 }
@@ -237,6 +336,21 @@ impl DynamicPoolManager {
             .expect("thread pool manager cannot be started");
     }
 
+    /// Shuts the pool down: its threads exit once they have no more tasks
+    /// to process, and no thread is spawned anymore.
+    ///
+    /// The static threads are left to the `DynamicRunner` to stop, once
+    /// `is_shut_down` returns true.
+    pub fn shutdown(&self) {
+        self.shut_down.store(true, Ordering::SeqCst);
+        self.sleepers.notify_all();
+    }
+
+    /// Returns whether the pool was shut down.
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::SeqCst)
+    }
+
     /// Provision threads takes a number of threads that need to be made available.
     /// It will try to unpark threads from the dynamic pool, and spawn more threads if needs be.
     pub fn provision_threads(&'static self, n: usize) {
@@ -254,65 +368,167 @@ impl DynamicPoolManager {
 
     fn spawn_threads(&'static self, n: usize) {
         (0..n).for_each(|_| {
+            if !self.reserve_thread() {
+                trace!("maximum amount of threads reached, not spawning standalone thread");
+                return;
+            }
+
             let clone = Arc::clone(&self.runner);
+            let id = self.runner.next_thread_id();
             thread::Builder::new()
-                .name("bastion-blocking-driver-standalone".to_string())
+                .name(thread_name(self.runner.thread_name(), id))
                 .spawn(move || {
-                    Self::affinity_pinner();
-                    clone.run_standalone();
+                    self.affinity_pinner();
+                    clone.run_standalone(id);
+                    self.live_threads.fetch_sub(1, Ordering::SeqCst);
                 })
                 .unwrap();
         })
     }
 
-    /// Parks a thread until unpark_thread unparks it
-    pub fn park_thread(&self) {
-        let _ = self
-            .parked_threads
-            .push(std::thread::current())
-            .map(|_| {
-                trace!("parking thread {:?}", std::thread::current().id());
-                std::thread::park();
+    fn spawn_dynamic_thread(&'static self) {
+        if !self.reserve_thread() {
+            trace!("maximum amount of threads reached, not spawning dynamic thread");
+            return;
+        }
+        self.live_dynamic_threads.fetch_add(1, Ordering::SeqCst);
+
+        let clone = Arc::clone(&self.runner);
+        let id = self.runner.next_thread_id();
+        thread::Builder::new()
+            .name(thread_name(self.runner.thread_name(), id))
+            .spawn(move || {
+                self.affinity_pinner();
+                let parker = || self.park_thread();
+                clone.run_dynamic(id, &parker);
+
+                debug!("retired dynamic thread {:?}", std::thread::current().id());
+                self.live_threads.fetch_sub(1, Ordering::SeqCst);
             })
-            .map_err(|e| {
-                debug!(
-                    "couldn't park thread {:?} - {}",
-                    std::thread::current().id(),
-                    e
-                );
-            });
+            .expect("cannot start dynamic thread");
     }
 
-    /// Pops a thread from the parked_threads queue and unparks it.
+    /// Accounts for a new thread if it doesn't exceed the maximum amount of threads.
+    /// returns true on success.
+    fn reserve_thread(&self) -> bool {
+        let mut live = self.live_threads.load(Ordering::SeqCst);
+        loop {
+            if live >= self.max_threads || self.is_shut_down() {
+                return false;
+            }
+
+            match self.live_threads.compare_exchange(
+                live,
+                live + 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(current) => live = current,
+            }
+        }
+    }
+
+    /// Parks a thread until unpark_thread unparks it.
+    /// returns false if the thread got retired and should exit.
+    pub fn park_thread(&self) -> bool {
+        if self.is_shut_down() || self.take_retirement() {
+            return false;
+        }
+
+        trace!("parking thread {:?}", std::thread::current().id());
+        match self.idle_timeout {
+            Some(idle_timeout) => {
+                if !self.sleepers.wait_timeout(idle_timeout) && self.retire_idle() {
+                    trace!("idle for {:?}, retiring thread", idle_timeout);
+                    return false;
+                }
+            }
+            None => self.sleepers.wait(),
+        }
+
+        !self.is_shut_down() && !self.take_retirement()
+    }
+
+    /// Consumes a pending retirement request, if any.
+    fn take_retirement(&self) -> bool {
+        let mut retiring = self.retiring_threads.load(Ordering::SeqCst);
+        loop {
+            if retiring == 0 {
+                return false;
+            }
+
+            match self.retiring_threads.compare_exchange(
+                retiring,
+                retiring - 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => {
+                    self.live_dynamic_threads.fetch_sub(1, Ordering::SeqCst);
+                    return true;
+                }
+                Err(current) => retiring = current,
+            }
+        }
+    }
+
+    /// Retires the current idle thread, unless it would shrink the pool below its minimum.
+    fn retire_idle(&self) -> bool {
+        let mut live = self.live_dynamic_threads.load(Ordering::SeqCst);
+        loop {
+            let workers = self.static_threads
+                + live.saturating_sub(self.retiring_threads.load(Ordering::SeqCst));
+            if workers <= self.min_threads {
+                return false;
+            }
+
+            match self.live_dynamic_threads.compare_exchange(
+                live,
+                live - 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(current) => live = current,
+            }
+        }
+    }
+
+    /// Asks one of the dynamic threads to exit the next time it parks.
+    fn retire_thread(&self) {
+        self.retiring_threads.fetch_add(1, Ordering::SeqCst);
+        self.sleepers.notify_one();
+    }
+
+    /// Unparks one of the parked threads.
     /// returns true on success.
     fn unpark_thread(&self) -> bool {
-        if self.parked_threads.is_empty() {
+        if !self.sleepers.is_any_parked() {
             trace!("no parked threads");
             false
         } else {
-            trace!("parked_threads: len is {}", self.parked_threads.len());
-            self.parked_threads
-                .pop()
-                .map(|thread| {
-                    debug!("Executor: unpark_thread: unparking {:?}", thread.id());
-                    thread.unpark();
-                })
-                .map_err(|e| {
-                    debug!("Executor: unpark_thread: couldn't unpark thread - {}", e);
-                })
-                .is_ok()
+            trace!("parked_threads: len is {}", self.sleepers.parked());
+            self.sleepers.notify_one();
+            true
         }
     }
 
     ///
     /// Affinity pinner for blocking pool
-    /// Pinning isn't going to be enabled for single core systems.
+    /// Threads are pinned in a round-robin fashion onto the cores of the placement.
+    /// Pinning isn't going to be enabled for single core systems unless cores were given.
     #[inline]
-    fn affinity_pinner() {
-        if 1 != *load_balancer::core_count() {
-            let mut core = ROUND_ROBIN_PIN.lock().unwrap();
-            placement::set_for_current(*core);
-            core.id = (core.id + 1) % *load_balancer::core_count();
+    fn affinity_pinner(&self) {
+        let cores = match &self.pinned_cores {
+            Some(cores) => cores.as_slice(),
+            None if 1 != *load_balancer::core_count() => load_balancer::get_cores(),
+            None => return,
+        };
+
+        if !cores.is_empty() {
+            let index = self.next_core.fetch_add(1, Ordering::SeqCst) % cores.len();
+            placement::set_for_current(cores[index]);
         }
     }
 
@@ -403,5 +619,48 @@ impl DynamicPoolManager {
             trace!("unparking {} threads", DEFAULT_LOW_WATERMARK);
             self.provision_threads(DEFAULT_LOW_WATERMARK as usize);
         }
+
+        self.resize_pool();
+    }
+
+    /// Queue pressure based pool resizing
+    ///
+    /// Spawns a dynamic thread if the mean queue depth per thread stayed above the high
+    /// watermark, or retires one if it stayed at or below the low watermark,
+    /// during the whole sampling window. The number of threads is kept within the configured bounds.
+    fn resize_pool(&'static self) {
+        let workers = self.static_threads
+            + self
+                .live_dynamic_threads
+                .load(Ordering::SeqCst)
+                .saturating_sub(self.retiring_threads.load(Ordering::SeqCst));
+        let queue_depth = self.runner.queue_depth();
+        self.runner.sampled(queue_depth);
+        let mean_depth = queue_depth / workers.max(1);
+
+        if mean_depth > self.high_watermark {
+            self.low_pressure_samples.store(0, Ordering::SeqCst);
+            let samples = self.high_pressure_samples.fetch_add(1, Ordering::SeqCst) + 1;
+
+            if samples >= self.sampling_window {
+                self.high_pressure_samples.store(0, Ordering::SeqCst);
+                trace!("sustained queue pressure, spawning a dynamic thread");
+                self.spawn_dynamic_thread();
+            }
+        } else if mean_depth <= self.low_watermark {
+            self.high_pressure_samples.store(0, Ordering::SeqCst);
+            let samples = self.low_pressure_samples.fetch_add(1, Ordering::SeqCst) + 1;
+
+            if samples >= self.sampling_window {
+                self.low_pressure_samples.store(0, Ordering::SeqCst);
+                if workers > self.min_threads {
+                    trace!("sustained low queue pressure, retiring a dynamic thread");
+                    self.retire_thread();
+                }
+            }
+        } else {
+            self.high_pressure_samples.store(0, Ordering::SeqCst);
+            self.low_pressure_samples.store(0, Ordering::SeqCst);
+        }
     }
 }
@@ -0,0 +1,53 @@
+//!
+//! Compatibility with futures relying on the Tokio runtime.
+//!
+//! A Tokio runtime is started alongside the executor, and its context
+//! is entered whenever a process of the executor gets polled. This way
+//! timers, I/O resources and `tokio::spawn` are available to the
+//! processes without their having to be spawned onto the Tokio runtime.
+//! Enabled with the `tokio-runtime` feature.
+//!
+//! The runtime is built with all of its drivers enabled, so the I/O
+//! driver runs as soon as Tokio's `net` feature is enabled by the
+//! embedding application.
+
+use once_cell::sync::Lazy;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::runtime::{Builder, Handle, Runtime};
+
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    Builder::new_multi_thread()
+        .thread_name("bastion-tokio-driver")
+        .enable_all()
+        .build()
+        .expect("couldn't start the tokio runtime")
+});
+
+/// Returns a handle to the Tokio runtime driving timers and I/O
+/// resources for the processes of the executor.
+pub fn handle() -> &'static Handle {
+    RUNTIME.handle()
+}
+
+/// Makes `future` enter the context of the Tokio runtime whenever
+/// it gets polled.
+pub(crate) fn enter<F: Future>(future: F) -> Entered<F> {
+    Entered { future }
+}
+
+pub(crate) struct Entered<F> {
+    future: F,
+}
+
+impl<F: Future> Future for Entered<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let _guard = RUNTIME.enter();
+        // SAFETY: the future is never moved out of `Entered`.
+        let future = unsafe { self.map_unchecked_mut(|entered| &mut entered.future) };
+        future.poll(cx)
+    }
+}
@@ -9,6 +9,7 @@ use crate::pool;
 use lightproc::prelude::*;
 use std::cell::Cell;
 use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 /// The timeout we'll use when parking before an other Steal attempt
@@ -23,6 +24,48 @@ pub fn current() -> ProcStack {
 
 thread_local! {
     static STACK: Cell<*const ProcStack> = Cell::new(ptr::null_mut());
+    // The identifier of the pool's worker running on this thread, if any.
+    static WORKER_ID: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+static NEXT_WORKER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Reserves the identifier of a worker whose thread is about to be spawned.
+pub(crate) fn next_id() -> usize {
+    NEXT_WORKER_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Gives its identifier to the pool's worker running on the current thread.
+pub(crate) fn register(id: usize) {
+    WORKER_ID.with(|worker_id| worker_id.set(Some(id)));
+}
+
+///
+/// Returns the identifier of the pool's worker running on the current
+/// thread, which its thread is named after: `bastion-worker-{id}`, or
+/// `worker-{id}` on platforms keeping only 15 bytes of thread names, such
+/// as Linux. Debuggers and `thread::current().name()` show the same name.
+///
+/// Returns `None` when called outside of the pool's workers, which
+/// includes the threads of the blocking pool.
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::prelude::*;
+/// use bastion_executor::worker;
+/// use lightproc::proc_stack::ProcStack;
+///
+/// assert_eq!(worker::current_worker_id(), None);
+///
+/// let handle = spawn(async { worker::current_worker_id() }, ProcStack::default());
+/// let worker_id = run(handle, ProcStack::default()).unwrap();
+/// assert!(worker_id.is_some());
+/// ```
+pub fn current_worker_id() -> Option<usize> {
+    WORKER_ID
+        .try_with(|worker_id| worker_id.get())
+        .ok()
+        .flatten()
 }
 
 ///
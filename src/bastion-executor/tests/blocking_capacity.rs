@@ -0,0 +1,57 @@
+use bastion_executor::blocking::{self, BlockingConfig, OverflowPolicy};
+use bastion_executor::run::run;
+use futures::future::join_all;
+use lightproc::proc_stack::ProcStack;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const CAPACITY: usize = 4;
+const MAX_THREADS: usize = 3;
+
+#[test]
+fn concurrent_submitters_respect_capacity() {
+    let config = BlockingConfig::default()
+        .with_max_threads(MAX_THREADS)
+        .with_queue_capacity(CAPACITY)
+        .with_overflow_policy(OverflowPolicy::Reject);
+    blocking::configure_blocking(config).unwrap();
+
+    // No task completes until every submitter is done.
+    let released = Arc::new(AtomicBool::new(false));
+    let submitters = (0..8)
+        .map(|_| {
+            let released = released.clone();
+            thread::spawn(move || {
+                (0..50)
+                    .filter_map(|_| {
+                        let released = released.clone();
+                        blocking::try_spawn_blocking(
+                            async move {
+                                while !released.load(Ordering::SeqCst) {
+                                    thread::sleep(Duration::from_millis(1));
+                                }
+                            },
+                            ProcStack::default(),
+                        )
+                        .ok()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+    let handles = submitters
+        .into_iter()
+        .flat_map(|submitter| submitter.join().unwrap())
+        .collect::<Vec<_>>();
+
+    // The queue never held more than its capacity, and each thread
+    // took at most one task out of it.
+    assert!(!handles.is_empty());
+    assert!(handles.len() <= CAPACITY + MAX_THREADS, "{}", handles.len());
+
+    released.store(true, Ordering::SeqCst);
+    let outputs = run(join_all(handles), ProcStack::default());
+    assert!(outputs.iter().all(Option::is_some));
+}
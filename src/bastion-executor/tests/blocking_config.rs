@@ -0,0 +1,70 @@
+use bastion_executor::blocking::{self, BlockingConfig, OverflowPolicy, QueueFull};
+use bastion_executor::run::run;
+use futures::future::join_all;
+use lightproc::proc_stack::ProcStack;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn reject_when_queue_is_full() {
+    let config = BlockingConfig::default()
+        .with_max_threads(3)
+        .with_queue_capacity(1)
+        .with_overflow_policy(OverflowPolicy::Reject);
+    blocking::configure_blocking(config).unwrap();
+
+    let results = (0..20)
+        .map(|_| {
+            blocking::try_spawn_blocking(
+                async {
+                    thread::sleep(Duration::from_millis(300));
+                },
+                ProcStack::default(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert!(results
+        .iter()
+        .any(|res| res.as_ref().err() == Some(&QueueFull)));
+
+    let handles = results
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    assert!(!handles.is_empty());
+
+    let outputs = run(join_all(handles), ProcStack::default());
+    assert!(outputs.iter().all(Option::is_some));
+
+    // Rejected tasks spawned without `try_` resolve to `None`.
+    let rejected = (0..20)
+        .map(|_| {
+            blocking::spawn_blocking(
+                async {
+                    thread::sleep(Duration::from_millis(300));
+                },
+                ProcStack::default(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let outputs = run(join_all(rejected), ProcStack::default());
+    assert!(outputs.iter().any(Option::is_none));
+
+    // Rejected closures are reported instead of panicking.
+    let results = (0..20)
+        .map(|i| {
+            blocking::try_spawn(move || {
+                thread::sleep(Duration::from_millis(300));
+                i
+            })
+        })
+        .collect::<Vec<_>>();
+    assert!(results
+        .iter()
+        .any(|res| res.as_ref().err() == Some(&QueueFull)));
+
+    let spawned = results.into_iter().filter_map(Result::ok);
+    let outputs = run(join_all(spawned), ProcStack::default());
+    assert!(!outputs.is_empty());
+}
@@ -0,0 +1,76 @@
+use bastion_executor::deadline::DeadlineError;
+use bastion_executor::pool;
+use bastion_executor::run::run;
+use futures_timer::Delay;
+use lightproc::proc_stack::ProcStack;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// Sets the flag once dropped, telling that the future holding it was.
+struct DropGuard(Arc<AtomicBool>);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn completes_before_deadline() {
+    let handle = pool::spawn_with_deadline(
+        async {
+            Delay::new(Duration::from_millis(10)).await;
+            42
+        },
+        Duration::from_secs(5),
+        ProcStack::default(),
+    );
+
+    assert_eq!(run(handle, ProcStack::default()), Some(Ok(42)));
+}
+
+#[test]
+fn cancels_waiting_process() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let guard = DropGuard(dropped.clone());
+    let handle = pool::spawn_with_deadline(
+        async move {
+            let _guard = guard;
+            Delay::new(Duration::from_secs(60)).await;
+        },
+        Duration::from_millis(50),
+        ProcStack::default(),
+    );
+
+    let output = run(handle, ProcStack::default());
+    assert_eq!(output, Some(Err(DeadlineError::Cancelled)));
+    assert!(dropped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn cancels_overrunning_process_at_next_await() {
+    let yielded = Arc::new(AtomicBool::new(false));
+    let process_yielded = yielded.clone();
+    let handle = pool::spawn_with_deadline(
+        async move {
+            // Doesn't yield until after the deadline...
+            thread::sleep(Duration::from_millis(200));
+            process_yielded.store(true, Ordering::SeqCst);
+            Delay::new(Duration::from_millis(10)).await;
+            // ...and is cancelled there.
+            unreachable!();
+        },
+        Duration::from_millis(50),
+        ProcStack::default(),
+    );
+
+    match run(handle, ProcStack::default()) {
+        Some(Err(DeadlineError::Overran(overrun))) => {
+            assert!(overrun >= Duration::from_millis(100), "{:?}", overrun)
+        }
+        output => panic!("unexpected output: {:?}", output),
+    }
+    assert!(yielded.load(Ordering::SeqCst));
+}
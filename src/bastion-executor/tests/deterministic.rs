@@ -0,0 +1,68 @@
+use bastion_executor::pool::{Pool, PoolConfig};
+use lightproc::proc_stack::ProcStack;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn deterministic_pool(seed: u64) -> &'static Pool {
+    Pool::start(&PoolConfig::default().with_deterministic_seed(seed))
+}
+
+// Returns the order in which processes spawned at once ran.
+fn run_order(seed: u64) -> Vec<usize> {
+    let pool = deterministic_pool(seed);
+    let order: Arc<Mutex<Vec<usize>>> = Arc::default();
+    for i in 0..20 {
+        let order = order.clone();
+        pool.spawn(
+            async move { order.lock().unwrap().push(i) },
+            ProcStack::default(),
+        );
+    }
+
+    // Nothing runs until the pool is driven.
+    assert!(order.lock().unwrap().is_empty());
+    assert_eq!(pool.run_until_idle(), 20);
+
+    let order = order.lock().unwrap().clone();
+    order
+}
+
+#[test]
+fn seeded_order() {
+    assert_eq!(run_order(7), run_order(7));
+    assert_ne!(run_order(7), run_order(8));
+}
+
+#[test]
+fn virtual_clock() {
+    let pool = deterministic_pool(0);
+    let clock = pool.clock().unwrap();
+    let woken: Arc<Mutex<Vec<(u64, Duration)>>> = Arc::default();
+    for secs in [30, 10, 20] {
+        let woken = woken.clone();
+        pool.spawn(
+            async move {
+                clock.sleep(Duration::from_secs(secs)).await;
+                woken.lock().unwrap().push((secs, clock.elapsed()));
+            },
+            ProcStack::default(),
+        );
+    }
+
+    pool.run_until_idle();
+    pool.advance(Duration::from_secs(15));
+    assert_eq!(*woken.lock().unwrap(), vec![(10, Duration::from_secs(10))]);
+    assert_eq!(clock.elapsed(), Duration::from_secs(15));
+
+    // The sleeps complete at their own deadline on the way.
+    pool.advance(Duration::from_secs(60));
+    assert_eq!(
+        *woken.lock().unwrap(),
+        vec![
+            (10, Duration::from_secs(10)),
+            (20, Duration::from_secs(20)),
+            (30, Duration::from_secs(30)),
+        ]
+    );
+    assert_eq!(clock.next_timer(), None);
+}
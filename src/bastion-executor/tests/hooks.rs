@@ -0,0 +1,56 @@
+use bastion_executor::hooks::{self, Hooks, TaskEvent, WorkerEvent};
+use bastion_executor::pool;
+use bastion_executor::run::run;
+use lightproc::proc_stack::{Priority, ProcStack};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+type Events<E> = Arc<Mutex<Vec<E>>>;
+
+#[test]
+fn hooks() {
+    let enqueued: Events<TaskEvent> = Arc::default();
+    let dequeued: Events<TaskEvent> = Arc::default();
+    let parked: Events<WorkerEvent> = Arc::default();
+
+    let (on_enqueue, on_dequeue, on_park) = (enqueued.clone(), dequeued.clone(), parked.clone());
+    let hooks = Hooks::new()
+        .with_on_enqueue(move |event| on_enqueue.lock().unwrap().push(*event))
+        .with_on_dequeue(move |event| on_dequeue.lock().unwrap().push(*event))
+        .with_on_park(move |event| on_park.lock().unwrap().push(*event));
+    hooks::install(hooks).unwrap();
+    assert!(hooks::install(Hooks::new()).is_err());
+
+    let stack = ProcStack::default().with_priority(Priority::High);
+    let handle = pool::spawn(async { 42 }, stack);
+    assert_eq!(run(handle, ProcStack::default()), Some(42));
+    // Spawned once the first one completed.
+    let stack = ProcStack::default().with_priority(Priority::Low);
+    let handle = pool::spawn(async { 43 }, stack);
+    assert_eq!(run(handle, ProcStack::default()), Some(43));
+    // Lets the workers park once the run queue is empty.
+    thread::sleep(Duration::from_millis(100));
+
+    let enqueued = enqueued.lock().unwrap();
+    let dequeued = dequeued.lock().unwrap();
+    let scheduled = enqueued
+        .iter()
+        .find(|event| event.priority == Priority::High)
+        .expect("the process wasn't enqueued");
+    let ran = dequeued
+        .iter()
+        .find(|event| event.task_id == scheduled.task_id)
+        .expect("the process wasn't dequeued");
+    assert!(ran.worker_id.is_some());
+    assert!(ran.timestamp >= scheduled.timestamp);
+
+    // The identifiers of the completed processes aren't reused.
+    let next = enqueued
+        .iter()
+        .find(|event| event.priority == Priority::Low)
+        .expect("the second process wasn't enqueued");
+    assert_ne!(next.task_id, scheduled.task_id);
+
+    assert!(!parked.lock().unwrap().is_empty());
+}
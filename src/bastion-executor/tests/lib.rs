@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
     use bastion_executor::{placement, pool};
+    use futures::future::join_all;
+    use lightproc::proc_stack::{Priority, ProcStack};
 
     #[test]
     fn affinity_replacement() {
@@ -12,4 +14,25 @@ mod tests {
     fn pool_check() {
         pool::get();
     }
+
+    #[test]
+    fn spawn_with_priorities() {
+        let handles = [Priority::Low, Priority::Normal, Priority::High]
+            .iter()
+            .map(|priority| {
+                let stack = ProcStack::default().with_priority(*priority);
+                pool::spawn(async move { *priority }, stack)
+            })
+            .collect::<Vec<_>>();
+
+        let priorities = bastion_executor::run::run(join_all(handles), ProcStack::default());
+        assert_eq!(
+            priorities,
+            vec![
+                Some(Priority::Low),
+                Some(Priority::Normal),
+                Some(Priority::High)
+            ]
+        );
+    }
 }
@@ -0,0 +1,101 @@
+use bastion_executor::load_balancer::{self, SmpStats, Stats};
+use bastion_executor::placement::{CoreId, NumaNode};
+use bastion_executor::run_queue::Worker;
+
+#[test]
+fn snapshot() {
+    let stats = load_balancer::stats();
+    let cores = *load_balancer::core_count();
+    for core in 0..cores {
+        stats.store_load(core, 4);
+    }
+    stats.update_mean();
+
+    // A scheduler built on per-core run queues records its steals.
+    let (victim, thief) = (Worker::new_fifo(), Worker::new_fifo());
+    victim.push(0);
+    for _ in 0..3 {
+        stats.record_steal(victim.stealer().steal_batch(&thief).is_success());
+    }
+
+    let snapshot = load_balancer::snapshot();
+    assert_eq!(snapshot.loads(), vec![4; cores].as_slice());
+    assert_eq!(snapshot.mean(), 4);
+    assert_eq!(snapshot.steals(), 1);
+    assert_eq!(snapshot.failed_steals(), 2);
+    assert!(!snapshot.sampling_interval().is_zero());
+
+    // Taking it doesn't reset the counters.
+    assert_eq!(load_balancer::snapshot().steals(), 1);
+}
+
+#[test]
+fn steal_order() {
+    let stats = Stats::new(4);
+    for (core, load) in [1, 3, 2, 4].iter().enumerate() {
+        stats.store_load(core, *load);
+    }
+    let nodes = [
+        NumaNode {
+            id: 0,
+            cores: vec![CoreId { id: 0 }, CoreId { id: 1 }],
+        },
+        NumaNode {
+            id: 1,
+            cores: vec![CoreId { id: 2 }, CoreId { id: 3 }],
+        },
+    ];
+
+    assert_eq!(stats.steal_order(0, None).as_slice(), &[3, 1, 2]);
+    assert_eq!(stats.steal_order(0, Some(&nodes)).as_slice(), &[1, 3, 2]);
+    assert_eq!(stats.steal_order(2, Some(&nodes)).as_slice(), &[3, 1, 0]);
+}
+
+// Loads the first queue of each of two nodes of four cores, then lets
+// the idle queues steal from the others until all of them are empty.
+// Returns how many steals crossed nodes.
+fn drain(numa_aware: bool) -> usize {
+    let nodes: Vec<NumaNode> = (0..2)
+        .map(|id| NumaNode {
+            id,
+            cores: (0..4).map(|core| CoreId { id: id * 4 + core }).collect(),
+        })
+        .collect();
+    let stats = Stats::new(8);
+    let queues: Vec<Worker<usize>> = (0..8).map(|_| Worker::new_fifo()).collect();
+    for node in &nodes {
+        (0..100).for_each(|i| queues[node.cores[0].id].push(i));
+    }
+
+    let mut cross_node_steals = 0;
+    while queues.iter().any(|queue| !queue.is_empty()) {
+        for thief in 0..8 {
+            for (core, queue) in queues.iter().enumerate() {
+                stats.store_load(core, queue.worker_run_queue_size());
+            }
+
+            if queues[thief].is_empty() {
+                let nodes = if numa_aware { Some(&nodes[..]) } else { None };
+                for victim in stats.steal_order(thief, nodes) {
+                    if queues[victim]
+                        .stealer()
+                        .steal_batch(&queues[thief])
+                        .is_success()
+                    {
+                        if thief / 4 != victim / 4 {
+                            cross_node_steals += 1;
+                        }
+                        break;
+                    }
+                }
+            }
+            queues[thief].pop();
+        }
+    }
+    cross_node_steals
+}
+
+#[test]
+fn numa_aware_stealing() {
+    assert!(drain(true) < drain(false));
+}
@@ -0,0 +1,18 @@
+use bastion_executor::load_balancer;
+use bastion_executor::pool::spawn;
+use bastion_executor::run::run;
+use lightproc::proc_stack::ProcStack;
+
+#[test]
+fn pool_doesnt_steal() {
+    for i in 0..10 {
+        let handle = spawn(async move { i }, ProcStack::default());
+        assert_eq!(run(handle, ProcStack::default()), Some(i));
+    }
+
+    // The threads of the pool share its run queues, so taking processes
+    // from them isn't counted as stealing.
+    let snapshot = load_balancer::snapshot();
+    assert_eq!(snapshot.steals(), 0);
+    assert_eq!(snapshot.failed_steals(), 0);
+}
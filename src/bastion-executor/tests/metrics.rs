@@ -0,0 +1,27 @@
+#![cfg(feature = "metrics")]
+use bastion_executor::metrics;
+use bastion_executor::pool;
+use bastion_executor::run::run;
+use lightproc::proc_stack::ProcStack;
+
+#[test]
+fn count_procs() {
+    let spawned = metrics::spawned_procs();
+    let completed = metrics::completed_procs();
+    let panicked = metrics::panicked_procs();
+
+    let ok = pool::spawn(async { 42 }, ProcStack::default());
+    let failed = pool::spawn(async { panic!("test") }, ProcStack::default());
+    run(
+        async {
+            assert_eq!(ok.await, Some(42));
+            assert_eq!(failed.await, None);
+        },
+        ProcStack::default(),
+    );
+
+    assert_eq!(metrics::spawned_procs() - spawned, 2);
+    assert_eq!(metrics::completed_procs() - completed, 1);
+    assert_eq!(metrics::panicked_procs() - panicked, 1);
+    assert!(metrics::workers() > 0);
+}
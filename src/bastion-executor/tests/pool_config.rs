@@ -0,0 +1,29 @@
+use bastion_executor::placement::{CoreId, Placement};
+use bastion_executor::pool::{self, PoolConfig};
+use bastion_executor::prelude::spawn;
+use bastion_executor::run::run;
+use futures::future::join_all;
+use lightproc::proc_stack::ProcStack;
+
+#[test]
+fn bounded_pool() {
+    let config = PoolConfig::default()
+        .with_min_threads(1)
+        .with_max_threads(2)
+        .with_sampling_window(1)
+        .with_placement(Placement::new().with_cores(vec![CoreId { id: 0 }]));
+    pool::configure(config).unwrap();
+
+    let handles = (0..100)
+        .map(|i| spawn(async move { i * 2 }, ProcStack::default()))
+        .collect::<Vec<_>>();
+    let results = run(join_all(handles), ProcStack::default());
+
+    assert_eq!(
+        results.into_iter().map(Option::unwrap).sum::<usize>(),
+        9_900
+    );
+
+    // The pool is already running.
+    assert!(pool::configure(PoolConfig::default()).is_err());
+}
@@ -1,6 +1,8 @@
 use bastion_executor::blocking;
 use bastion_executor::run::run;
 use lightproc::proc_stack::ProcStack;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
@@ -21,3 +23,45 @@ fn test_run_blocking() {
 
     assert_eq!(42, output);
 }
+
+#[test]
+fn test_spawn_closure() {
+    let output = run(
+        async {
+            blocking::spawn(|| {
+                thread::sleep(Duration::from_millis(1));
+                42
+            })
+            .await
+        },
+        ProcStack::default(),
+    );
+
+    assert_eq!(42, output);
+}
+
+#[test]
+fn test_spawn_closure_panic() {
+    let output = panic::catch_unwind(AssertUnwindSafe(|| {
+        run(
+            blocking::spawn(|| -> u8 { panic!("blocking closure") }),
+            ProcStack::default(),
+        )
+    }));
+
+    let payload = output.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"blocking closure"));
+}
+
+#[test]
+fn test_spawn_closure_dropped() {
+    let (sender, receiver) = mpsc::channel();
+    let future = blocking::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        sender.send(42).unwrap();
+    });
+    drop(future);
+
+    let output = receiver.recv_timeout(Duration::from_secs(5));
+    assert_eq!(output, Ok(42));
+}
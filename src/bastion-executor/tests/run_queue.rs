@@ -0,0 +1,34 @@
+use bastion_executor::run_queue::{Steal, Worker};
+
+#[test]
+fn steal_batch_with_amount() {
+    let victim = Worker::new_fifo();
+    (0..10).for_each(|i| victim.push(i));
+
+    let dest = Worker::new_fifo();
+    let stealer = victim.stealer();
+
+    assert_eq!(
+        stealer.steal_batch_with_amount(&dest, 3),
+        Steal::Success(())
+    );
+    assert_eq!(dest.worker_run_queue_size(), 3);
+    assert_eq!(victim.worker_run_queue_size(), 7);
+
+    // Stealing zero tasks still steals one.
+    assert_eq!(
+        stealer.steal_batch_with_amount(&dest, 0),
+        Steal::Success(())
+    );
+    assert_eq!(dest.worker_run_queue_size(), 4);
+
+    // Stealing more than available only takes what is left.
+    assert_eq!(
+        stealer.steal_batch_with_amount(&dest, 100),
+        Steal::Success(())
+    );
+    assert_eq!(dest.worker_run_queue_size(), 10);
+    assert!(victim.is_empty());
+
+    assert_eq!(stealer.steal_batch_with_amount(&dest, 1), Steal::Empty);
+}
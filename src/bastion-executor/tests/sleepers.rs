@@ -0,0 +1,84 @@
+use bastion_executor::sleepers::Sleepers;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn notify_all_wakes_every_sleeper() {
+    let sleepers = Arc::new(Sleepers::new());
+
+    let handles = (0..4)
+        .map(|_| {
+            let sleepers = sleepers.clone();
+            thread::spawn(move || sleepers.wait())
+        })
+        .collect::<Vec<_>>();
+
+    // Give the threads some time to go to sleep.
+    thread::sleep(Duration::from_millis(100));
+    sleepers.notify_all();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn notify_all_without_sleepers() {
+    let sleepers = Sleepers::new();
+
+    sleepers.notify_all();
+    // The notification is picked up without blocking.
+    sleepers.wait();
+}
+
+#[test]
+fn wait_timeout_times_out() {
+    let sleepers = Sleepers::new();
+
+    assert!(!sleepers.wait_timeout(Duration::from_millis(10)));
+    // The timed out thread isn't accounted as sleeping anymore, so
+    // this notification is kept for the next thread going to sleep.
+    sleepers.notify_one();
+    assert!(sleepers.wait_timeout(Duration::from_millis(10)));
+}
+
+#[test]
+fn wait_timeout_notified() {
+    let sleepers = Arc::new(Sleepers::new());
+
+    let handle = {
+        let sleepers = sleepers.clone();
+        thread::spawn(move || sleepers.wait_timeout(Duration::from_secs(10)))
+    };
+
+    thread::sleep(Duration::from_millis(100));
+    sleepers.notify_one();
+
+    assert!(handle.join().unwrap());
+}
+
+#[test]
+fn parked_count() {
+    let sleepers = Arc::new(Sleepers::new());
+    assert_eq!(sleepers.parked(), 0);
+    assert!(!sleepers.is_any_parked());
+
+    let handle = {
+        let sleepers = sleepers.clone();
+        thread::spawn(move || sleepers.wait())
+    };
+
+    while !sleepers.is_any_parked() {
+        thread::yield_now();
+    }
+    assert_eq!(sleepers.parked(), 1);
+
+    sleepers.notify_one();
+    assert_eq!(sleepers.parked(), 0);
+    assert!(!sleepers.is_notified());
+    handle.join().unwrap();
+
+    sleepers.notify_one();
+    assert!(sleepers.is_notified());
+}
@@ -0,0 +1,57 @@
+use bastion_executor::blocking::spawn_blocking;
+use bastion_executor::pool::spawn;
+use bastion_executor::run::run;
+use bastion_executor::worker;
+use lightproc::proc_stack::ProcStack;
+use std::thread;
+
+// The name of the current thread, as returned by Rust and as seen by the
+// OS (and so debuggers and thread dumps).
+fn thread_names() -> (String, String) {
+    let name = thread::current().name().unwrap().to_string();
+    #[cfg(target_os = "linux")]
+    let os_name = std::fs::read_to_string("/proc/thread-self/comm")
+        .unwrap()
+        .trim_end()
+        .to_string();
+    #[cfg(not(target_os = "linux"))]
+    let os_name = name.clone();
+    (name, os_name)
+}
+
+// The name of the thread `id` of a runner named `bastion-{kind}`.
+fn expected_name(kind: &str, id: usize) -> String {
+    if cfg!(target_os = "linux") && format!("bastion-{}-{}", kind, id).len() > 15 {
+        format!("{}-{}", kind, id)
+    } else {
+        format!("bastion-{}-{}", kind, id)
+    }
+}
+
+#[test]
+fn worker_names() {
+    assert_eq!(worker::current_worker_id(), None);
+
+    let handle = spawn(
+        async { (worker::current_worker_id(), thread_names()) },
+        ProcStack::default(),
+    );
+    let (worker_id, (name, os_name)) = run(handle, ProcStack::default()).unwrap();
+    let worker_id = worker_id.expect("tasks run on a worker");
+    assert_eq!(name, expected_name("worker", worker_id));
+    assert_eq!(os_name, name);
+
+    let handle = spawn_blocking(
+        async { (worker::current_worker_id(), thread_names()) },
+        ProcStack::default(),
+    );
+    let (worker_id, (name, os_name)) = run(handle, ProcStack::default()).unwrap();
+    assert_eq!(worker_id, None);
+    let id = name
+        .rsplit('-')
+        .next()
+        .and_then(|id| id.parse().ok())
+        .unwrap_or_else(|| panic!("unexpected blocking thread name: {}", name));
+    assert_eq!(name, expected_name("blocking", id));
+    assert_eq!(os_name, name);
+}
@@ -155,7 +155,6 @@ async fn serve_prime_numbers(ctx: BastionContext) -> Result<(), ()> {
 mod client {
     use super::prime_number::Response;
     use bastion::prelude::*;
-    use lightproc::prelude::*;
     use rayon::prelude::*;
     use tracing::{error, info};
 
@@ -1,18 +1,25 @@
 use crate::broadcast::{Broadcast, Parent};
+use crate::child_ref::ChildRef;
 use crate::children::Children;
 use crate::children_ref::ChildrenRef;
 use crate::config::Config;
 use crate::context::{BastionContext, BastionId};
+use crate::dead_letters::{self, DeadLetter};
 use crate::envelope::Envelope;
+use crate::executor::{self, RecoverableHandle};
+use crate::lifecycle::LifecycleEvents;
 use crate::message::{BastionMessage, Message};
 use crate::path::BastionPathElement;
 use crate::supervisor::{Supervisor, SupervisorRef};
-use crate::system::SYSTEM;
+use crate::system;
 
+use bastion_executor::pool::{self, PoolConfig};
 use core::future::Future;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 use std::fmt::{self, Debug, Formatter};
+use std::thread;
+use std::time::{Duration, Instant};
 
 distributed_api! {
     use std::sync::Arc;
@@ -24,6 +31,11 @@ distributed_api! {
 /// start, stop and kill it and to create new supervisors and top-level
 /// children groups.
 ///
+/// Its functions act on the default runtime, or on the [`Runtime`]
+/// of the actor calling them.
+///
+/// [`Runtime`]: runtime/struct.Runtime.html
+///
 /// # Example
 ///
 /// ```rust
@@ -161,6 +173,19 @@ pub struct Bastion {
     _priv: (),
 }
 
+/// What was still outstanding once [`Bastion::stop_graceful`]
+/// returned.
+///
+/// [`Bastion::stop_graceful`]: struct.Bastion.html#method.stop_graceful
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StopReport {
+    tasks: usize,
+    messages: usize,
+}
+
+// How often a graceful stop checks whether the processes are done.
+const IN_FLIGHT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 impl Bastion {
     /// Initializes the system if it hasn't already been done, using
     /// the default [`Config`].
@@ -227,7 +252,90 @@ impl Bastion {
             std::panic::set_hook(Box::new(|_| ()));
         }
 
-        lazy_static::initialize(&SYSTEM);
+        system::global();
+    }
+
+    /// Initializes the system like [`Bastion::init`] does, but with a
+    /// deterministic default runtime: its actors are run one at a
+    /// time by the thread calling [`Bastion::run_until_idle`] or
+    /// [`Bastion::advance_time`], in an order picked from `seed`, and
+    /// its timers (like the ones of ask timeouts, restart backoffs
+    /// and heartbeats) follow a virtual clock which only moves
+    /// forward when [`Bastion::advance_time`] is called.
+    ///
+    /// This allows tests to check exact message orderings and to
+    /// trigger timeouts without waiting for real. The actors can be
+    /// defined as usual, but should wait using [`Delay`] instead of
+    /// other timers. [`Bastion::stop_graceful`] still waits for its
+    /// timeout in real time, and must thus not be used.
+    ///
+    /// [`Bastion::block_until_stopped`] runs the actors until the
+    /// system is stopped, and advances the virtual time whenever they
+    /// are all waiting for a timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the order in which the actors are run.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the default runtime's pool was already
+    /// configured (e.g. because `bastion_executor::pool::configure`
+    /// was called) or started.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::prelude::*;
+    /// use bastion::time::Delay;
+    /// use futures::FutureExt;
+    /// use std::time::Duration;
+    ///
+    /// Bastion::init_test(42);
+    /// Bastion::start();
+    ///
+    /// let children = Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| async move {
+    ///         msg! { ctx.recv().await?,
+    ///             msg: &'static str =!> {
+    ///                 // Takes a minute to answer...
+    ///                 Delay::new(Duration::from_secs(60)).await;
+    ///                 answer!(ctx, "pong").expect("Couldn't send the answer.");
+    ///             };
+    ///             _: _ => ();
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// Bastion::run_until_idle();
+    ///
+    /// let mut answer = children.elems()[0]
+    ///     .ask_anonymously("ping")
+    ///     .expect("Couldn't send the message.");
+    /// Bastion::run_until_idle();
+    /// assert!((&mut answer).now_or_never().is_none());
+    ///
+    /// // ...which happens once a minute of virtual time passed.
+    /// Bastion::advance_time(Duration::from_secs(60));
+    /// assert!(answer.now_or_never().is_some());
+    ///
+    /// Bastion::stop();
+    /// Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`Bastion::init`]: #method.init
+    /// [`Bastion::run_until_idle`]: #method.run_until_idle
+    /// [`Bastion::advance_time`]: #method.advance_time
+    /// [`Bastion::block_until_stopped`]: #method.block_until_stopped
+    /// [`Bastion::stop_graceful`]: #method.stop_graceful
+    /// [`Delay`]: time/struct.Delay.html
+    pub fn init_test(seed: u64) {
+        debug!("Bastion: Initializing deterministically (seed={}).", seed);
+        pool::configure(PoolConfig::default().with_deterministic_seed(seed))
+            .expect("The default runtime's pool was already configured.");
+
+        Bastion::init()
     }
 
     /// Creates a new [`Supervisor`], passes it through the specified
@@ -279,9 +387,10 @@ impl Bastion {
 
         debug!("Bastion: Deploying Supervisor({}).", supervisor.id());
         let msg = BastionMessage::deploy_supervisor(supervisor);
-        let envelope = Envelope::new(msg, SYSTEM.path().clone(), SYSTEM.sender().clone());
+        let system = system::current();
+        let envelope = Envelope::new(msg, system.path().clone(), system.sender().clone());
         trace!("Bastion: Sending envelope: {:?}", envelope);
-        SYSTEM.sender().unbounded_send(envelope).map_err(|_| ())?;
+        system.sender().unbounded_send(envelope).map_err(|_| ())?;
 
         Ok(supervisor_ref)
     }
@@ -337,7 +446,7 @@ impl Bastion {
         C: FnOnce(Children) -> Children,
     {
         debug!("Bastion: Creating children group.");
-        SYSTEM.supervisor().children(init)
+        system::current().supervisor().children(init)
     }
 
     /// Creates a new [`Children`] which will have the given closure
@@ -382,6 +491,57 @@ impl Bastion {
     {
         Bastion::children(|ch| ch.with_redundancy(1).with_exec(action))
     }
+
+    /// Spawns a one-shot task running the given future, without
+    /// defining an actor, and returns a handle to await its output.
+    ///
+    /// The task isn't part of any supervision tree: it doesn't
+    /// receive messages, isn't stopped or killed along with the
+    /// system or the actor that spawned it, and isn't restarted if it
+    /// panics.
+    ///
+    /// A panic of the task is caught, so it doesn't take down the
+    /// thread running it. Awaiting the handle then returns `None`,
+    /// while awaiting [`RecoverableHandle::join`] returns the panic
+    /// as a [`ProcError`].
+    ///
+    /// Called by the actors of a [`Runtime`], the task is run by the
+    /// runtime's threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `future` - The future which gets executed by the task.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// # Bastion::start();
+    /// #
+    /// let handle = Bastion::spawn_task(async { 6 * 7 });
+    /// assert_eq!(run!(handle), Some(42));
+    ///
+    /// let handle = Bastion::spawn_task(async { panic!("failed") });
+    /// let error = run!(handle.join()).unwrap_err();
+    /// assert_eq!(error.panic_message(), Some("failed"));
+    /// #
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`RecoverableHandle::join`]: executor/struct.RecoverableHandle.html#method.join
+    /// [`ProcError`]: executor/enum.ProcError.html
+    /// [`Runtime`]: runtime/struct.Runtime.html
+    pub fn spawn_task<F, T>(future: F) -> RecoverableHandle<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        executor::spawn(future)
+    }
+
     distributed_api! {
         // FIXME!
         #[allow(missing_docs)]
@@ -441,16 +601,148 @@ impl Bastion {
     /// ```
     pub fn broadcast<M: Message>(msg: M) -> Result<(), M> {
         debug!("Bastion: Broadcasting message: {:?}", msg);
+        let system = system::current();
+        if !system.intake().accepts() {
+            return Err(msg);
+        }
+
         let msg = BastionMessage::broadcast(msg);
         let envelope = Envelope::from_dead_letters(msg);
         trace!("Bastion: Sending envelope: {:?}", envelope);
         // FIXME: panics?
-        SYSTEM
+        system
             .sender()
             .unbounded_send(envelope)
             .map_err(|err| err.into_inner().into_msg().unwrap())
     }
 
+    /// Registers the callback called with every message that
+    /// couldn't be delivered to (or wasn't handled by) its
+    /// recipient, replacing the previously registered dead letters
+    /// handler.
+    ///
+    /// No dead letter is captured until a handler is registered.
+    /// The callback is called on the thread that reported the dead
+    /// letter, and thus shouldn't block.
+    ///
+    /// # Arguments
+    ///
+    /// * `handler` - The callback called with each [`DeadLetter`].
+    ///
+    /// [`DeadLetter`]: dead_letters/struct.DeadLetter.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::on_dead_letter(|letter: DeadLetter| {
+    ///     println!("{:?}: {:?}", letter.reason, letter.envelope);
+    /// });
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn on_dead_letter<C>(handler: C)
+    where
+        C: Fn(DeadLetter) + Send + Sync + 'static,
+    {
+        debug!("Bastion: Registering dead letters handler.");
+        dead_letters::set_handler(handler);
+    }
+
+    /// Registers the child which will be told every message that
+    /// couldn't be delivered to (or wasn't handled by) its
+    /// recipient, as a [`DeadLetter`], replacing the previously
+    /// registered dead letters handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `child_ref` - The child the dead letters are told to.
+    ///
+    /// [`DeadLetter`]: dead_letters/struct.DeadLetter.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// let children_ref = Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             loop {
+    ///                 msg! { ctx.recv().await?,
+    ///                     letter: DeadLetter => {
+    ///                         // Inspect or replay the message...
+    ///                     };
+    ///                     _: _ => ();
+    ///                 }
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// Bastion::forward_dead_letters(children_ref.elems()[0].clone());
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn forward_dead_letters(child_ref: ChildRef) {
+        Bastion::on_dead_letter(move |letter| {
+            // The dead letters that can't be forwarded are dropped
+            // instead of being reported again.
+            if let Err(letter) = child_ref.try_tell(letter) {
+                debug!("Bastion: Dropping dead letter: {:?}", letter.into_inner());
+            }
+        });
+    }
+
+    /// Returns a receiver of the [`LifecycleEvent`]s of the whole
+    /// supervision tree, as they happen from now on: its children
+    /// starting, getting restarted and stopping, as well as its
+    /// supervisors escalating and failing.
+    ///
+    /// The receiver never slows down the supervision: if it doesn't
+    /// receive the events fast enough, the oldest of them are dropped
+    /// (see [`LifecycleEvents`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// use futures::StreamExt;
+    ///
+    /// # Bastion::init();
+    /// let mut events = Bastion::lifecycle_events();
+    ///
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| async move {
+    ///         ctx.recv().await?;
+    ///         Ok(())
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// Bastion::start();
+    /// let event = run!(events.next()).unwrap();
+    /// assert_eq!(event.kind, LifecycleEventKind::Started);
+    /// #
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`LifecycleEvent`]: lifecycle/struct.LifecycleEvent.html
+    /// [`LifecycleEvents`]: lifecycle/struct.LifecycleEvents.html
+    pub fn lifecycle_events() -> LifecycleEvents {
+        debug!("Bastion: Subscribing to lifecycle events.");
+        system::current().lifecycle().subscribe()
+    }
+
     /// Sends a message to the system to tell it to start
     /// handling messages and running children.
     ///
@@ -477,12 +769,15 @@ impl Bastion {
         let envelope = Envelope::from_dead_letters(msg);
         trace!("Bastion: Sending envelope: {:?}", envelope);
         // FIXME: Err(Error)
-        SYSTEM.sender().unbounded_send(envelope).ok();
+        system::current().sender().unbounded_send(envelope).ok();
     }
 
     /// Sends a message to the system to tell it to stop
     /// every running children groups and supervisors.
     ///
+    /// See [`Bastion::stop_graceful`] to let them finish handling the
+    /// messages they received first.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -506,7 +801,7 @@ impl Bastion {
         let envelope = Envelope::from_dead_letters(msg);
         trace!("Bastion: Sending envelope: {:?}", envelope);
         // FIXME: Err(Error)
-        SYSTEM.sender().unbounded_send(envelope).ok();
+        system::current().sender().unbounded_send(envelope).ok();
     }
 
     /// Sends a message to the system to tell it to kill every
@@ -534,16 +829,95 @@ impl Bastion {
         let envelope = Envelope::from_dead_letters(msg);
         trace!("Bastion: Sending envelope: {:?}", envelope);
         // FIXME: Err(Error)
-        SYSTEM.sender().unbounded_send(envelope).ok();
+        let system = system::current();
+        system.sender().unbounded_send(envelope).ok();
 
-        let handle = SYSTEM.handle();
-        let system = crate::executor::run(async { handle.lock().await.take() });
-        if let Some(system) = system {
+        let handle = system.handle();
+        let handle = crate::executor::run(async { handle.lock().await.take() });
+        if let Some(handle) = handle {
             debug!("Bastion: Cancelling system handle.");
-            system.cancel();
+            handle.cancel();
+        }
+
+        system.notify_stopped();
+    }
+
+    /// Stops the system gracefully, blocking the current thread until
+    /// the work in progress is done or `timeout` elapsed, and returns
+    /// what was still outstanding by then.
+    ///
+    /// The messages sent from outside of the system's actors (with
+    /// [`Bastion::broadcast`], [`ChildrenRef::broadcast`] or the
+    /// anonymous functions of [`ChildRef`]) are refused from then on,
+    /// while the actors keep exchanging messages. Every element is
+    /// stopped after handling the messages waiting in its mailbox,
+    /// as if its group was set to [`Children::with_drain_on_stop`],
+    /// and the processes running onto the system's threads (e.g. the
+    /// tasks spawned with [`Bastion::spawn_task`]) are waited for.
+    ///
+    /// If the work isn't done before `timeout` elapsed, the system is
+    /// killed as [`Bastion::kill`] does. The worker threads are then
+    /// parked, as they are once the system stopped.
+    ///
+    /// This must not be called by an actor or a task of the system,
+    /// which would wait for itself until the timeout elapsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - How long the work in progress can take at most.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// Bastion::init();
+    ///
+    /// // Use bastion, spawn children and supervisors...
+    ///
+    /// Bastion::start();
+    ///
+    /// let report = Bastion::stop_graceful(Duration::from_secs(5));
+    /// if !report.is_complete() {
+    ///     eprintln!("{} messages were dropped.", report.outstanding_messages());
+    /// }
+    /// ```
+    ///
+    /// [`Bastion::broadcast`]: #method.broadcast
+    /// [`ChildrenRef::broadcast`]: children_ref/struct.ChildrenRef.html#method.broadcast
+    /// [`ChildRef`]: child_ref/struct.ChildRef.html
+    /// [`Children::with_drain_on_stop`]: children/struct.Children.html#method.with_drain_on_stop
+    /// [`Bastion::spawn_task`]: #method.spawn_task
+    /// [`Bastion::kill`]: #method.kill
+    pub fn stop_graceful(timeout: Duration) -> StopReport {
+        debug!("Bastion: Stopping gracefully (timeout={:?}).", timeout);
+        let system = system::current();
+        let deadline = Instant::now() + timeout;
+        system.start_draining(deadline);
+        Bastion::stop();
+
+        // The processes outliving the system, like the tasks spawned
+        // by its actors, are waited for too.
+        if system.wait_until_stopped_or(deadline) {
+            while system.in_flight() > 0 && Instant::now() < deadline {
+                thread::sleep(IN_FLIGHT_POLL_INTERVAL);
+            }
         }
 
-        SYSTEM.notify_stopped();
+        let report = StopReport {
+            tasks: system.in_flight(),
+            messages: system.pending_messages(),
+        };
+        if !report.is_complete() {
+            warn!(
+                "Bastion: Timed out while stopping gracefully, killing: {:?}",
+                report
+            );
+            Bastion::kill();
+        }
+
+        report
     }
 
     /// Blocks the current thread until the system is stopped
@@ -573,7 +947,55 @@ impl Bastion {
     /// [`Bastion::kill()`]: #method.kill
     pub fn block_until_stopped() {
         debug!("Bastion: Blocking until system is stopped.");
-        SYSTEM.wait_until_stopped();
+        system::current().wait_until_stopped();
+    }
+
+    /// Runs the actors of a deterministic system (see
+    /// [`Bastion::init_test`]) on the current thread until none of
+    /// them has anything left to do without the virtual time passing.
+    ///
+    /// This method returns how many times actors were run, and does
+    /// nothing if the system isn't deterministic. It must not be called
+    /// by an actor.
+    ///
+    /// [`Bastion::init_test`]: #method.init_test
+    pub fn run_until_idle() -> usize {
+        trace!("Bastion: Running until idle.");
+        system::current().pool().run_until_idle()
+    }
+
+    /// Makes `duration` of virtual time pass for a deterministic system
+    /// (see [`Bastion::init_test`]), running its actors until idle
+    /// every time one of its timers elapses on the way.
+    ///
+    /// This method does nothing if the system isn't deterministic. It
+    /// must not be called by an actor.
+    ///
+    /// [`Bastion::init_test`]: #method.init_test
+    pub fn advance_time(duration: Duration) {
+        debug!("Bastion: Advancing time by {:?}.", duration);
+        system::current().pool().advance(duration);
+    }
+}
+
+impl StopReport {
+    /// Returns whether all the work in progress was done before the
+    /// timeout elapsed.
+    pub fn is_complete(&self) -> bool {
+        self.tasks == 0 && self.messages == 0
+    }
+
+    /// Returns how many processes (actors, their groups and
+    /// supervisors, or tasks) were still running when the timeout
+    /// elapsed.
+    pub fn outstanding_tasks(&self) -> usize {
+        self.tasks
+    }
+
+    /// Returns how many messages were still waiting in the mailboxes
+    /// of the actors when the timeout elapsed.
+    pub fn outstanding_messages(&self) -> usize {
+        self.messages
     }
 }
 
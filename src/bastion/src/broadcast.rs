@@ -4,7 +4,7 @@ use crate::envelope::Envelope;
 use crate::message::BastionMessage;
 use crate::path::{BastionPath, BastionPathElement};
 use crate::supervisor::SupervisorRef;
-use crate::system::SYSTEM;
+use crate::system;
 use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use futures::prelude::*;
 use fxhash::FxHashMap;
@@ -40,6 +40,14 @@ impl Parent {
     pub(super) fn is_system(&self) -> bool {
         matches!(self, Parent::System)
     }
+
+    pub(crate) fn span_id(&self) -> Option<tracing::Id> {
+        match self {
+            Parent::None | Parent::System => None,
+            Parent::Supervisor(sv_ref) => sv_ref.span().id(),
+            Parent::Children(ch_ref) => ch_ref.span().id(),
+        }
+    }
 }
 
 impl Broadcast {
@@ -147,6 +155,11 @@ impl Broadcast {
         self.clear_children();
     }
 
+    // Returns the next envelope that was already received, if any.
+    pub(crate) fn try_next(&mut self) -> Option<Envelope> {
+        self.recver.try_recv().ok()
+    }
+
     pub(crate) fn stopped(&mut self) {
         self.stop_children();
 
@@ -230,7 +243,7 @@ impl Parent {
         match self {
             // FIXME
             Parent::None => unimplemented!(),
-            Parent::System => SYSTEM
+            Parent::System => system::current()
                 .sender()
                 .unbounded_send(env)
                 .map_err(|err| err.into_inner()),
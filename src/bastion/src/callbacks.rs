@@ -1,6 +1,10 @@
 use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 
+type AsyncCallback = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub(crate) enum CallbackType {
@@ -50,6 +54,10 @@ pub struct Callbacks {
     before_restart: Option<Arc<dyn Fn() + Send + Sync>>,
     after_restart: Option<Arc<dyn Fn() + Send + Sync>>,
     after_stop: Option<Arc<dyn Fn() + Send + Sync>>,
+    before_start_async: Option<AsyncCallback>,
+    before_restart_async: Option<AsyncCallback>,
+    after_restart_async: Option<AsyncCallback>,
+    after_stop_async: Option<AsyncCallback>,
 }
 
 impl Callbacks {
@@ -152,6 +160,52 @@ impl Callbacks {
         self
     }
 
+    /// Sets an asynchronous method that will get called in the same
+    /// cases as the one set with [`with_before_start`], returning a future
+    /// that will be awaited before the element's future starts executing.
+    ///
+    /// If both are defined, the method set with [`with_before_start`] gets
+    /// called first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     let callbacks = Callbacks::new()
+    ///         .with_before_start_async(|| async {
+    ///             // Open connections, register to a service...
+    ///         });
+    ///
+    ///     children
+    ///         .with_callbacks(callbacks)
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`with_before_start`]: #method.with_before_start
+    pub fn with_before_start_async<C, F>(mut self, before_start: C) -> Self
+    where
+        C: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let before_start: AsyncCallback = Arc::new(move || Box::pin(before_start()));
+        self.before_start_async = Some(before_start);
+        self
+    }
+
     /// Sets the method that will get called before the [`Supervisor`]
     /// or [`Children`] is reset if:
     /// - the supervisor of the supervised element using this callback
@@ -213,6 +267,52 @@ impl Callbacks {
         self
     }
 
+    /// Sets an asynchronous method that will get called in the same
+    /// cases as the one set with [`with_before_restart`], returning a future
+    /// that will be awaited before the element is reset.
+    ///
+    /// If both are defined, the method set with [`with_before_restart`] gets
+    /// called first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     let callbacks = Callbacks::new()
+    ///         .with_before_restart_async(|| async {
+    ///             // Flush buffered work, close connections...
+    ///         });
+    ///
+    ///     children
+    ///         .with_callbacks(callbacks)
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`with_before_restart`]: #method.with_before_restart
+    pub fn with_before_restart_async<C, F>(mut self, before_restart: C) -> Self
+    where
+        C: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let before_restart: AsyncCallback = Arc::new(move || Box::pin(before_restart()));
+        self.before_restart_async = Some(before_restart);
+        self
+    }
+
     /// Sets the method that will get called before the [`Supervisor`]
     /// or [`Children`] is launched if:
     /// - the supervisor of the supervised element using this callback
@@ -274,6 +374,52 @@ impl Callbacks {
         self
     }
 
+    /// Sets an asynchronous method that will get called in the same
+    /// cases as the one set with [`with_after_restart`], returning a future
+    /// that will be awaited before the element's future starts executing again.
+    ///
+    /// If both are defined, the method set with [`with_after_restart`] gets
+    /// called first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     let callbacks = Callbacks::new()
+    ///         .with_after_restart_async(|| async {
+    ///             // Reopen connections, restore the cached state...
+    ///         });
+    ///
+    ///     children
+    ///         .with_callbacks(callbacks)
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`with_after_restart`]: #method.with_after_restart
+    pub fn with_after_restart_async<C, F>(mut self, after_restart: C) -> Self
+    where
+        C: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let after_restart: AsyncCallback = Arc::new(move || Box::pin(after_restart()));
+        self.after_restart_async = Some(after_restart);
+        self
+    }
+
     /// Sets the method that will get called after the [`Supervisor`]
     /// or [`Children`] is stopped or killed if:
     /// - the supervisor of the supervised element using this callback
@@ -337,7 +483,54 @@ impl Callbacks {
         self
     }
 
-    /// Returns whether a callback was defined using [`with_before_start`].
+    /// Sets an asynchronous method that will get called in the same
+    /// cases as the one set with [`with_after_stop`], returning a future
+    /// that will be awaited before the element is torn down.
+    ///
+    /// If both are defined, the method set with [`with_after_stop`] gets
+    /// called first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     let callbacks = Callbacks::new()
+    ///         .with_after_stop_async(|| async {
+    ///             // Close connections, deregister from a service...
+    ///         });
+    ///
+    ///     children
+    ///         .with_callbacks(callbacks)
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`with_after_stop`]: #method.with_after_stop
+    pub fn with_after_stop_async<C, F>(mut self, after_stop: C) -> Self
+    where
+        C: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let after_stop: AsyncCallback = Arc::new(move || Box::pin(after_stop()));
+        self.after_stop_async = Some(after_stop);
+        self
+    }
+
+    /// Returns whether a callback was defined using [`with_before_start`]
+    /// or [`with_before_start_async`].
     ///
     /// # Example
     ///
@@ -351,11 +544,13 @@ impl Callbacks {
     /// ```
     ///
     /// [`with_before_start`]: #method.with_before_start
+    /// [`with_before_start_async`]: #method.with_before_start_async
     pub fn has_before_start(&self) -> bool {
-        self.before_start.is_some()
+        self.before_start.is_some() || self.before_start_async.is_some()
     }
 
-    /// Returns whether a callback was defined using [`with_before_restart`].
+    /// Returns whether a callback was defined using [`with_before_restart`]
+    /// or [`with_before_restart_async`].
     ///
     /// # Example
     ///
@@ -369,11 +564,13 @@ impl Callbacks {
     /// ```
     ///
     /// [`with_before_restart`]: #method.with_before_restart
+    /// [`with_before_restart_async`]: #method.with_before_restart_async
     pub fn has_before_restart(&self) -> bool {
-        self.before_restart.is_some()
+        self.before_restart.is_some() || self.before_restart_async.is_some()
     }
 
-    /// Returns whether a callback was defined using [`with_after_restart`].
+    /// Returns whether a callback was defined using [`with_after_restart`]
+    /// or [`with_after_restart_async`].
     ///
     /// # Example
     ///
@@ -387,11 +584,13 @@ impl Callbacks {
     /// ```
     ///
     /// [`with_after_restart`]: #method.with_after_restart
+    /// [`with_after_restart_async`]: #method.with_after_restart_async
     pub fn has_after_restart(&self) -> bool {
-        self.after_restart.is_some()
+        self.after_restart.is_some() || self.after_restart_async.is_some()
     }
 
-    /// Returns whether a callback was defined using [`with_after_stop`].
+    /// Returns whether a callback was defined using [`with_after_stop`]
+    /// or [`with_after_stop_async`].
     ///
     /// # Example
     ///
@@ -405,36 +604,57 @@ impl Callbacks {
     /// ```
     ///
     /// [`with_after_stop`]: #method.with_after_stop
+    /// [`with_after_stop_async`]: #method.with_after_stop_async
     pub fn has_after_stop(&self) -> bool {
-        self.after_stop.is_some()
+        self.after_stop.is_some() || self.after_stop_async.is_some()
     }
 
-    pub(crate) fn before_start(&self) {
+    pub(crate) async fn before_start(&self) {
         if let Some(before_start) = &self.before_start {
             before_start()
         }
+
+        if let Some(before_start) = &self.before_start_async {
+            before_start().await
+        }
     }
 
-    pub(crate) fn before_restart(&self) {
+    pub(crate) async fn before_restart(&self) {
+        if !self.has_before_restart() {
+            return self.after_stop().await;
+        }
+
         if let Some(before_restart) = &self.before_restart {
             before_restart()
-        } else {
-            self.after_stop()
+        }
+
+        if let Some(before_restart) = &self.before_restart_async {
+            before_restart().await
         }
     }
 
-    pub(crate) fn after_restart(&self) {
+    pub(crate) async fn after_restart(&self) {
+        if !self.has_after_restart() {
+            return self.before_start().await;
+        }
+
         if let Some(after_restart) = &self.after_restart {
             after_restart()
-        } else {
-            self.before_start()
+        }
+
+        if let Some(after_restart) = &self.after_restart_async {
+            after_restart().await
         }
     }
 
-    pub(crate) fn after_stop(&self) {
+    pub(crate) async fn after_stop(&self) {
         if let Some(after_stop) = &self.after_stop {
             after_stop()
         }
+
+        if let Some(after_stop) = &self.after_stop_async {
+            after_stop().await
+        }
     }
 }
 
@@ -445,6 +665,10 @@ impl Debug for Callbacks {
             .field("before_restart", &self.before_start.is_some())
             .field("after_restart", &self.before_start.is_some())
             .field("after_stop", &self.before_start.is_some())
+            .field("before_start_async", &self.before_start_async.is_some())
+            .field("before_restart_async", &self.before_restart_async.is_some())
+            .field("after_restart_async", &self.after_restart_async.is_some())
+            .field("after_stop_async", &self.after_stop_async.is_some())
             .finish()
     }
 }
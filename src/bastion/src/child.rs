@@ -3,15 +3,21 @@
 use crate::broadcast::Broadcast;
 use crate::callbacks::{CallbackType, Callbacks};
 use crate::child_ref::ChildRef;
+use crate::children_ref::ChildrenRef;
 use crate::context::{BastionContext, BastionId, ContextState};
+use crate::dead_letters::{self, DeadLetterReason};
+use crate::dispatcher::LeaveReason;
 use crate::envelope::Envelope;
-use crate::message::BastionMessage;
+use crate::lifecycle::LifecycleEventKind;
+use crate::local::Locals;
+use crate::message::{BastionMessage, PanicPayload};
 #[cfg(feature = "scaling")]
 use crate::resizer::ActorGroupStats;
-use crate::system::SYSTEM;
+use crate::system;
+use crate::time::Delay;
 use anyhow::Result as AnyResult;
 
-use bastion_executor::pool;
+use futures::channel::oneshot;
 use futures::pending;
 use futures::poll;
 use futures::prelude::*;
@@ -19,13 +25,25 @@ use lightproc::prelude::*;
 use lightproc::proc_state::EmptyProcState;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
-use tracing::{debug, error, trace, warn};
+use std::time::Duration;
+use tracing::{debug, error, info_span, trace, warn, Instrument, Span};
 
 pub(crate) struct Init(pub(crate) Box<dyn Fn(BastionContext) -> Exec + Send>);
-pub(crate) struct Exec(pub(crate) Pin<Box<dyn Future<Output = Result<(), ()>> + Send>>);
+pub(crate) struct Exec {
+    future: Pin<Box<dyn Future<Output = Result<(), ()>> + Send>>,
+    // What the future panicked with, kept until the process' panic
+    // handler tells the supervisor about it.
+    panic: Arc<Mutex<Option<PanicPayload>>>,
+    // The storage entered while the future is polled.
+    locals: Arc<Locals>,
+    // The context of the future replacing this one if the child's
+    // behavior gets hot-swapped.
+    ctx: BastionContext,
+}
 
 #[derive(Debug)]
 pub(crate) struct Child {
@@ -47,6 +65,21 @@ pub(crate) struct Child {
     // A shortcut for accessing to this actor by others.
     child_ref: ChildRef,
     started: bool,
+    // How long the child keeps processing the messages of its
+    // mailbox once asked to stop, if it should drain it at all.
+    drain_timeout: Option<Duration>,
+    // Elapses when the child should stop draining its mailbox,
+    // set once the child was asked to stop.
+    draining: Option<Delay>,
+    // The span covering the child's lifecycle, which is only
+    // recorded with the `tracing-spans` feature.
+    span: Span,
+    // The closure that the child's behavior will be hot-swapped to
+    // once its future is done with the message it is handling.
+    swap: Option<Init>,
+    // Told once the child started, if its supervisor waits for it to
+    // restart the next elements.
+    on_started: Option<oneshot::Sender<()>>,
 }
 
 impl Init {
@@ -56,10 +89,18 @@ impl Init {
         F: Future<Output = Result<(), ()>> + Send + 'static,
     {
         let init = Box::new(move |ctx: BastionContext| {
+            let locals = ctx.locals().clone();
+            let renewed = ctx.renew();
             let fut = init(ctx);
-            let exec = Box::pin(fut);
-
-            Exec(exec)
+            let future = Box::pin(fut);
+            let panic = Arc::default();
+
+            Exec {
+                future,
+                panic,
+                locals,
+                ctx: renewed,
+            }
         });
 
         Init(init)
@@ -73,10 +114,23 @@ impl Child {
         bcast: Broadcast,
         state: Arc<Pin<Box<ContextState>>>,
         child_ref: ChildRef,
+        drain_timeout: Option<Duration>,
     ) -> Self {
         debug!("Child({}): Initializing.", bcast.id());
         let pre_start_msgs = Vec::new();
         let started = false;
+        let draining = None;
+        let swap = None;
+        let span = if cfg!(feature = "tracing-spans") {
+            info_span!(
+                parent: bcast.parent().span_id(),
+                "child",
+                path = %bcast.path(),
+                id = %bcast.id(),
+            )
+        } else {
+            Span::none()
+        };
 
         Child {
             bcast,
@@ -86,9 +140,19 @@ impl Child {
             pre_start_msgs,
             child_ref,
             started,
+            drain_timeout,
+            draining,
+            span,
+            swap,
+            on_started: None,
         }
     }
 
+    pub(crate) fn with_on_started(mut self, on_started: Option<oneshot::Sender<()>>) -> Self {
+        self.on_started = on_started;
+        self
+    }
+
     fn stack(&self) -> ProcStack {
         trace!("Child({}): Creating ProcStack.", self.id());
         let id = self.bcast.id().clone();
@@ -99,19 +163,23 @@ impl Child {
 
         let parent_inner = self.bcast.parent().clone().into_children();
         let child_ref_inner = self.child_ref.clone();
+        let panic = self.exec.panic.clone();
+        let state = self.state.clone();
 
         // FIXME: with_pid
         ProcStack::default().with_after_panic(move |_state: &mut EmptyProcState| {
             warn!("Child({}): Panicked.", id);
 
-            if let Some(parent) = &parent_inner {
-                let used_dispatchers = parent.dispatchers();
-                let global_dispatcher = SYSTEM.dispatcher();
-                global_dispatcher.remove(used_dispatchers, &child_ref_inner);
-            }
+            leave_dispatchers(
+                parent_inner.as_ref(),
+                &child_ref_inner,
+                &state,
+                LeaveReason::Faulted,
+            );
 
             let id = id.clone();
-            let msg = BastionMessage::restart_required(id, parent.id().clone());
+            let panic = panic.lock().unwrap().take();
+            let msg = BastionMessage::restart_required(id, parent.id().clone(), panic);
             let env = Envelope::new(msg, path.clone(), sender.clone());
             // TODO: handle errors
             parent.send(env).ok();
@@ -122,26 +190,110 @@ impl Child {
         self.bcast.id()
     }
 
-    fn stopped(&mut self) {
+    fn stopped(&mut self, reason: LeaveReason) {
         debug!("Child({}): Stopped.", self.id());
-        self.remove_from_dispatchers();
+        #[cfg(feature = "tracing-spans")]
+        tracing::info!("child stopped");
+        system::current()
+            .lifecycle()
+            .emit(self.bcast.path(), LifecycleEventKind::Stopped);
+        self.remove_from_dispatchers(reason);
+        // The child won't get restarted.
+        self.state.clear_persistent_state();
         self.bcast.stopped();
     }
 
     fn faulted(&mut self) {
         debug!("Child({}): Faulted.", self.id());
-        self.remove_from_dispatchers();
+        self.remove_from_dispatchers(LeaveReason::Faulted);
 
         let parent = self.bcast.parent().clone().into_children().unwrap();
         let path = self.bcast.path().clone();
         let sender = self.bcast.sender().clone();
 
-        let msg = BastionMessage::restart_required(self.id().clone(), parent.id().clone());
+        let msg = BastionMessage::restart_required(self.id().clone(), parent.id().clone(), None);
         let env = Envelope::new(msg, path, sender);
         // TODO: handle errors
         parent.send(env).ok();
     }
 
+    async fn stop(&mut self) -> Result<(), ()> {
+        // The callback is awaited before the parent is told
+        // that the child stopped.
+        self.callbacks.after_stop().await;
+        self.stopped(LeaveReason::Stopped);
+
+        #[cfg(feature = "scaling")]
+        self.cleanup_actors_stats().await;
+
+        Err(())
+    }
+
+    fn start_draining(&mut self, timeout: Duration) {
+        if self.draining.is_none() {
+            debug!(
+                "Child({}): Draining its mailbox before stopping (timeout={:?}).",
+                self.id(),
+                timeout
+            );
+            self.draining = Some(Delay::new(timeout));
+        }
+    }
+
+    // Returns whether the child is draining its mailbox and either
+    // handled all of its messages or ran out of time to do so.
+    async fn drained(&mut self) -> bool {
+        let draining = match &mut self.draining {
+            Some(draining) => draining,
+            None => return false,
+        };
+
+        // The future is done with the last message it retrieved once
+        // it tries to retrieve another one from the empty mailbox.
+        if self.state.mailbox_size() == 0 && self.state.is_waiting() {
+            debug!("Child({}): Drained its mailbox.", self.bcast.id());
+            return true;
+        }
+
+        if poll!(draining).is_ready() {
+            warn!(
+                "Child({}): Timed out while draining its mailbox.",
+                self.bcast.id()
+            );
+            return true;
+        }
+
+        false
+    }
+
+    // Drops the messages that are still in the mailbox, which
+    // resolves the `Answer` of every un-answered ask to an error
+    // instead of leaving its asker waiting forever.
+    fn reject_pending_messages(&mut self) {
+        while let Some(msg) = self.state.pop_message() {
+            debug!(
+                "Child({}): Rejecting a message that wasn't handled: {:?}",
+                self.id(),
+                msg
+            );
+        }
+    }
+
+    // Replaces the child's future by the one of the closure it is
+    // hot-swapped to, dropping the previous future along with the
+    // state it captured.
+    fn hot_swap(&mut self) {
+        if let Some(init) = self.swap.take() {
+            debug!("Child({}): Hot-swapping its behavior.", self.id());
+            let mut exec = (init.0)(self.exec.ctx.renew());
+            // What the new future panics with is still reported to
+            // the supervisor.
+            exec.panic = self.exec.panic.clone();
+            self.exec = exec;
+            self.state.swap_init(init);
+        }
+    }
+
     async fn handle(&mut self, env: Envelope) -> Result<(), ()> {
         match env {
             Envelope {
@@ -152,24 +304,22 @@ impl Child {
                 msg: BastionMessage::Stop,
                 ..
             } => {
-                self.stopped();
-
-                #[cfg(feature = "scaling")]
-                self.cleanup_actors_stats().await;
-
-                self.callbacks.after_stop();
-                return Err(());
+                if let Some(timeout) = system::current().drain_timeout(self.drain_timeout) {
+                    self.start_draining(timeout);
+                } else {
+                    return self.stop().await;
+                }
             }
             Envelope {
                 msg: BastionMessage::Kill,
                 ..
             } => {
-                self.stopped();
+                self.stopped(LeaveReason::Killed);
 
                 #[cfg(feature = "scaling")]
                 self.cleanup_actors_stats().await;
 
-                self.callbacks.before_restart();
+                self.callbacks.before_restart().await;
                 return Err(());
             }
             // FIXME
@@ -185,7 +335,7 @@ impl Child {
             Envelope {
                 msg: BastionMessage::ApplyCallback(callback_type),
                 ..
-            } => self.apply_callback(callback_type),
+            } => self.apply_callback(callback_type).await,
             // FIXME
             Envelope {
                 msg: BastionMessage::SuperviseWith(_),
@@ -198,9 +348,37 @@ impl Child {
             Envelope {
                 msg: BastionMessage::Message(msg),
                 sign,
+                high_priority,
+                reserved,
             } => {
-                debug!("Child({}): Received a message: {:?}", self.id(), msg);
-                self.state.push_message(msg, sign);
+                if self.draining.is_some() {
+                    if let (true, Some(mailbox)) = (reserved, self.state.mailbox()) {
+                        mailbox.release();
+                    }
+
+                    // Dropping the message resolves its `Answer`
+                    // to an error if it was asked.
+                    debug!(
+                        "Child({}): Rejecting a message while draining: {:?}",
+                        self.id(),
+                        msg
+                    );
+                } else {
+                    debug!("Child({}): Received a message: {:?}", self.id(), msg);
+                    #[cfg(feature = "tracing-spans")]
+                    tracing::debug!(message_type = msg.type_name(), "message received");
+                    // The message is counted even if its sender didn't
+                    // reserve room for it (e.g. if it was broadcasted),
+                    // since room is released once it is received.
+                    if let (false, Some(mailbox)) = (reserved, self.state.mailbox()) {
+                        mailbox.force_reserve();
+                    }
+                    if high_priority {
+                        self.state.push_priority_message(msg, sign);
+                    } else {
+                        self.state.push_message(msg, sign);
+                    }
+                }
             }
             Envelope {
                 msg: BastionMessage::RestartRequired { .. },
@@ -222,6 +400,10 @@ impl Child {
                 msg: BastionMessage::DropChild { .. },
                 ..
             } => unreachable!(),
+            Envelope {
+                msg: BastionMessage::TearDownChild { .. },
+                ..
+            } => unreachable!(),
             Envelope {
                 msg: BastionMessage::SetState { state },
                 ..
@@ -243,6 +425,20 @@ impl Child {
                 msg: BastionMessage::Heartbeat,
                 ..
             } => unreachable!(),
+            // Only wakes the child up, so that it retrieves the
+            // messages it received while paused.
+            Envelope {
+                msg: BastionMessage::Resume,
+                ..
+            } => (),
+            Envelope {
+                msg: BastionMessage::HotSwap(init),
+                ..
+            } => {
+                debug!("Child({}): Waiting to hot-swap its behavior.", self.id());
+                self.state.start_swapping();
+                self.swap = Some(init);
+            }
         }
 
         Ok(())
@@ -255,7 +451,12 @@ impl Child {
             BastionMessage::Start
         );
         debug!("Child({}): Starting.", self.id());
-        self.callbacks.before_start();
+        #[cfg(feature = "tracing-spans")]
+        tracing::info!("child started");
+        system::current()
+            .lifecycle()
+            .emit(self.bcast.path(), LifecycleEventKind::Started);
+        self.callbacks.before_start().await;
         self.started = true;
 
         let msgs = self.pre_start_msgs.drain(..).collect::<Vec<_>>();
@@ -272,15 +473,19 @@ impl Child {
             }
         }
 
+        if let Some(on_started) = self.on_started.take() {
+            on_started.send(()).ok();
+        }
+
         Ok(())
     }
 
-    fn apply_callback(&mut self, callback_type: CallbackType) {
+    async fn apply_callback(&mut self, callback_type: CallbackType) {
         match callback_type {
-            CallbackType::BeforeStart => self.callbacks.before_start(),
-            CallbackType::BeforeRestart => self.callbacks.before_restart(),
-            CallbackType::AfterRestart => self.callbacks.after_restart(),
-            CallbackType::AfterStop => self.callbacks.after_stop(),
+            CallbackType::BeforeStart => self.callbacks.before_start().await,
+            CallbackType::BeforeRestart => self.callbacks.before_restart().await,
+            CallbackType::AfterRestart => self.callbacks.after_restart().await,
+            CallbackType::AfterStop => self.callbacks.after_stop().await,
         }
     }
 
@@ -305,6 +510,7 @@ impl Child {
             error!("couldn't add actor to the registry: {}", e);
             return;
         };
+        system::current().register_mailbox(&self.state);
 
         loop {
             #[cfg(feature = "scaling")]
@@ -362,28 +568,64 @@ impl Child {
                 continue;
             }
 
+            if self.state.can_swap() {
+                self.hot_swap();
+            }
+
             match poll!(&mut self.exec) {
                 Poll::Ready(Ok(())) => {
                     debug!(
                         "Child({}): The future finished executing successfully.",
                         self.id()
                     );
-                    return self.stopped();
+                    if self.draining.is_some() {
+                        self.reject_pending_messages();
+                        self.stop().await.ok();
+                        return;
+                    }
+
+                    return self.stopped(LeaveReason::Stopped);
                 }
                 Poll::Ready(Err(())) => {
                     warn!("Child({}): The future returned an error.", self.id());
+                    if self.draining.is_some() {
+                        self.reject_pending_messages();
+                    }
+
                     return self.faulted();
                 }
                 Poll::Pending => (),
             }
 
+            // The future just got done with its last message, and
+            // the messages it was waiting for are already there.
+            if self.state.can_swap() {
+                continue;
+            }
+
+            if self.drained().await {
+                self.reject_pending_messages();
+                self.stop().await.ok();
+                return;
+            }
+
             pending!();
         }
     }
 
     pub(crate) fn launch(self) -> RecoverableHandle<()> {
         let stack = self.stack();
-        pool::spawn(self.run(), stack)
+        // The span is entered whenever the child is polled, whichever
+        // worker thread polls it.
+        let span = self.span.clone();
+        // Allows `ChildRef::stop_and_wait_timeout` to kill the
+        // child even if it is stuck in its callbacks.
+        let termination = self.child_ref.termination().cloned();
+        let (run, abort) = future::abortable(self.run());
+        if let Some(termination) = termination {
+            termination.set_abort(abort);
+        }
+        system::spawn(run.map(drop).instrument(span), stack)
     }
 
     /// Adds the actor into each registry declared in the parent node.
@@ -392,7 +634,7 @@ impl Child {
             let child_ref = self.child_ref.clone();
             let used_dispatchers = parent.dispatchers();
 
-            let global_dispatcher = SYSTEM.dispatcher();
+            let global_dispatcher = system::current().dispatcher();
             // FIXME: Pass the module name explicitly?
             let module_name = module_path!().to_string();
             global_dispatcher.register(used_dispatchers, &child_ref, module_name)?;
@@ -400,15 +642,26 @@ impl Child {
         Ok(())
     }
 
-    /// Cleanup the actor's record from each declared dispatcher.
-    fn remove_from_dispatchers(&self) {
-        if let Some(parent) = self.bcast.parent().clone().into_children() {
-            let child_ref = self.child_ref.clone();
-            let used_dispatchers = parent.dispatchers();
+    // Reports the messages the child won't receive anymore.
+    fn report_dead_letters(&mut self) {
+        let path = self.bcast.path().clone();
+        for msg in self.state.take_messages() {
+            dead_letters::report(msg, DeadLetterReason::Stopped, Some(path.clone()));
+        }
 
-            let global_dispatcher = SYSTEM.dispatcher();
-            global_dispatcher.remove(used_dispatchers, &child_ref);
+        let mut pending = std::mem::take(&mut self.pre_start_msgs);
+        while let Some(env) = self.bcast.try_next() {
+            pending.push(env);
         }
+        for env in pending {
+            dead_letters::report_envelope(env, DeadLetterReason::Stopped, Some(path.clone()));
+        }
+    }
+
+    /// Cleanup the actor's record from each declared dispatcher.
+    fn remove_from_dispatchers(&self, reason: LeaveReason) {
+        let parent = self.bcast.parent().clone().into_children();
+        leave_dispatchers(parent.as_ref(), &self.child_ref, &self.state, reason);
     }
 
     #[cfg(feature = "scaling")]
@@ -420,11 +673,85 @@ impl Child {
     }
 }
 
+impl Drop for Child {
+    fn drop(&mut self) {
+        // Wakes up the actors waiting for the child's mailbox
+        // to have room, since it won't have any anymore.
+        if let Some(mailbox) = self.child_ref.mailbox() {
+            mailbox.close();
+        }
+
+        if dead_letters::is_enabled() {
+            self.report_dead_letters();
+        }
+
+        // Its restarted incarnation starts from new values.
+        self.state.clear_locals();
+
+        if let Some(termination) = self.child_ref.termination().cloned() {
+            // A killed child didn't get to tell its parent that it
+            // stopped.
+            if termination.is_killed() {
+                self.stopped(LeaveReason::Killed);
+            }
+            termination.notify_stopped();
+        }
+
+        // A child cancelled by its group didn't get to leave its
+        // dispatchers, nor did a panicked one yet.
+        let reason = if self.exec.panic.lock().unwrap().is_some() {
+            LeaveReason::Faulted
+        } else {
+            LeaveReason::Killed
+        };
+        self.remove_from_dispatchers(reason);
+    }
+}
+
+// Removes the child from the dispatchers of its group and the ones it
+// subscribed to, unless it already left them.
+fn leave_dispatchers(
+    parent: Option<&ChildrenRef>,
+    child_ref: &ChildRef,
+    state: &ContextState,
+    reason: LeaveReason,
+) {
+    if let Some(parent) = parent {
+        if !state.leave_dispatchers() {
+            return;
+        }
+
+        let global_dispatcher = system::current().dispatcher();
+        global_dispatcher.remove(parent.dispatchers(), child_ref, reason);
+        global_dispatcher.remove(&state.take_subscriptions(), child_ref, reason);
+    }
+}
+
 impl Future for Exec {
     type Output = Result<(), ()>;
 
     fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
-        Pin::new(&mut self.get_mut().0).poll(ctx)
+        let exec = self.get_mut();
+        let _entered = exec.locals.enter();
+        let future = &mut exec.future;
+        match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(ctx))) {
+            Ok(poll) => poll,
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("Box<dyn Any>")
+                    .to_string();
+                #[cfg(feature = "tracing-spans")]
+                error!(panic = %message, "child panicked");
+
+                // The child's process still handles the panic itself,
+                // while the payload is kept for the supervisor.
+                *exec.panic.lock().unwrap() = Some(payload);
+                panic::resume_unwind(Box::new(message))
+            }
+        }
     }
 }
 
@@ -1,14 +1,26 @@
 //!
 //! Allows users to communicate with Child through the mailboxes.
 use crate::broadcast::Sender;
-use crate::context::BastionId;
+use crate::child::Init;
+use crate::context::{BastionContext, BastionId};
 use crate::envelope::{Envelope, RefAddr};
+use crate::errors::{SendError, StopError};
+use crate::mailbox::BoundedMailbox;
 use crate::message::{Answer, BastionMessage, Message};
 use crate::path::BastionPath;
+use crate::system::Intake;
+use crate::time::Delay;
+use crossbeam_queue::SegQueue;
+use futures::future::{self, AbortHandle, Either};
 use std::cmp::{Eq, PartialEq};
 use std::fmt::Debug;
+use std::future::Future;
 use std::hash::{Hash, Hasher};
-use std::sync::Arc;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 use tracing::{debug, trace};
 
 #[derive(Debug, Clone)]
@@ -23,6 +35,34 @@ pub struct ChildRef {
     // use `ChildRef::new_internal` to set it to false, for internal use children,
     // such as the heartbeat children for example
     is_public: bool,
+    // The child's mailbox, if it is bounded.
+    mailbox: Option<Arc<BoundedMailbox>>,
+    // Tells when the child stopped, if it was launched by a
+    // children group.
+    termination: Option<Arc<Termination>>,
+    // How long asking the child waits for its answer by default.
+    ask_timeout: Option<Duration>,
+    // Whether the child's system accepts anonymous messages, if it
+    // was launched by a children group.
+    intake: Option<&'static Intake>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Termination {
+    // Whether the child was dropped, once its run loop exited
+    // and its callbacks were called.
+    stopped: AtomicBool,
+    // Whether the child was force-killed, in which case it
+    // couldn't tell its parent that it stopped.
+    killed: AtomicBool,
+    // The `ChildRef`s waiting for the child to stop.
+    waiters: SegQueue<Waker>,
+    // Aborts the child's run loop, set once it was launched.
+    abort: Mutex<Option<AbortHandle>>,
+}
+
+struct Stopped {
+    termination: Option<Arc<Termination>>,
 }
 
 impl ChildRef {
@@ -38,6 +78,10 @@ impl ChildRef {
             name,
             path,
             is_public: false,
+            mailbox: None,
+            termination: None,
+            ask_timeout: None,
+            intake: None,
         }
     }
 
@@ -53,9 +97,33 @@ impl ChildRef {
             name,
             path,
             is_public: true,
+            mailbox: None,
+            termination: None,
+            ask_timeout: None,
+            intake: None,
         }
     }
 
+    pub(crate) fn with_mailbox(mut self, mailbox: Option<Arc<BoundedMailbox>>) -> Self {
+        self.mailbox = mailbox;
+        self
+    }
+
+    pub(crate) fn with_termination(mut self, termination: Option<Arc<Termination>>) -> Self {
+        self.termination = termination;
+        self
+    }
+
+    pub(crate) fn with_ask_timeout(mut self, ask_timeout: Option<Duration>) -> Self {
+        self.ask_timeout = ask_timeout;
+        self
+    }
+
+    pub(crate) fn with_intake(mut self, intake: &'static Intake) -> Self {
+        self.intake = Some(intake);
+        self
+    }
+
     /// Returns the identifier of the children group element this
     /// `ChildRef` is referencing.
     ///
@@ -172,10 +240,67 @@ impl ChildRef {
     /// ```
     pub fn tell_anonymously<M: Message>(&self, msg: M) -> Result<(), M> {
         debug!("ChildRef({}): Telling message: {:?}", self.id(), msg);
+        if !self.accepts_anonymous() {
+            return Err(msg);
+        }
+
         let msg = BastionMessage::tell(msg);
         let env = Envelope::from_dead_letters(msg);
         // FIXME: panics?
-        self.send(env).map_err(|env| env.into_msg().unwrap())
+        self.send_message(env)
+            .map_err(|env| env.into_msg().unwrap())
+    }
+
+    /// Sends a message to the child this `ChildRef` is referencing,
+    /// which will receive it before the other messages waiting in its
+    /// mailbox. Messages sent this way are received in the order they
+    /// were sent, like the ones sent with [`tell_anonymously`].
+    ///
+    /// This is useful for control messages (e.g. asking the child to
+    /// checkpoint its state) that shouldn't wait for the child to
+    /// go through a backlog of ordinary messages.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)`
+    /// otherwise.
+    ///
+    /// # Argument
+    ///
+    /// * `msg` - The message to send.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// # let children_ref = Bastion::children(|children| children).unwrap();
+    /// # let child_ref = &children_ref.elems()[0];
+    /// child_ref.tell_anonymously("Some work").expect("Couldn't send the message.");
+    /// // This one will be received first...
+    /// child_ref.tell_priority_anonymously("Checkpoint now").expect("Couldn't send the message.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`tell_anonymously`]: #method.tell_anonymously
+    pub fn tell_priority_anonymously<M: Message>(&self, msg: M) -> Result<(), M> {
+        debug!(
+            "ChildRef({}): Telling high priority message: {:?}",
+            self.id(),
+            msg
+        );
+        if !self.accepts_anonymous() {
+            return Err(msg);
+        }
+
+        let msg = BastionMessage::tell(msg);
+        let env = Envelope::from_dead_letters(msg).with_high_priority();
+        // FIXME: panics?
+        self.send_message(env)
+            .map_err(|env| env.into_msg().unwrap())
     }
 
     /// Sends a message to the child this `ChildRef` is referencing,
@@ -184,7 +309,8 @@ impl ChildRef {
     /// there is no way for receiver to identify message sender
     ///
     /// This method returns [`Answer`](../message/struct.Answer.html) if it succeeded, or `Err(msg)`
-    /// otherwise.
+    /// otherwise. The answer fails once the default ask timeout of
+    /// the child's group elapsed, if it has one.
     ///
     /// # Argument
     ///
@@ -258,10 +384,16 @@ impl ChildRef {
     /// [`Answer`]: message/struct.Answer.html
     pub fn ask_anonymously<M: Message>(&self, msg: M) -> Result<Answer, M> {
         debug!("ChildRef({}): Asking message: {:?}", self.id(), msg);
+        if !self.accepts_anonymous() {
+            return Err(msg);
+        }
+
         let (msg, answer) = BastionMessage::ask(msg);
+        let answer = answer.with_timeout(self.ask_timeout);
         let env = Envelope::from_dead_letters(msg);
         // FIXME: panics?
-        self.send(env).map_err(|env| env.into_msg().unwrap())?;
+        self.send_message(env)
+            .map_err(|env| env.into_msg().unwrap())?;
 
         Ok(answer)
     }
@@ -340,9 +472,182 @@ impl ChildRef {
         self.send(env).map_err(|_| ())
     }
 
+    /// Sends a message to the child this `ChildRef` is referencing
+    /// to tell it to replace the future it is executing by the one
+    /// returned by `init`, while keeping its mailbox, identity,
+    /// dispatchers and persistent state.
+    ///
+    /// The swap only happens between messages: the child's future
+    /// isn't given any message anymore, and gets replaced once it
+    /// tries to receive one. The new future is then given the
+    /// messages that were received meanwhile. The child keeps
+    /// executing the new future when it gets restarted, so if it
+    /// panics or returns an error, its supervisor restarts it
+    /// with it as usual.
+    ///
+    /// Note that the previous future is dropped when the swap
+    /// happens, along with everything it borrowed or captured.
+    ///
+    /// This method returns `true` if the message was sent, or
+    /// `false` if the child already stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure taking a [`BastionContext`] and returning
+    ///   the [`Future`] that the child will execute from now on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// # let children_ref = Bastion::children(|children| children).unwrap();
+    /// # let child_ref = &children_ref.elems()[0];
+    /// child_ref.hot_swap(|ctx: BastionContext| {
+    ///     async move {
+    ///         loop {
+    ///             let msg: SignedMessage = ctx.recv().await?;
+    ///             // Handle the message the new way...
+    ///         }
+    ///     }
+    /// });
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`BastionContext`]: context/struct.BastionContext.html
+    /// [`Future`]: https://doc.rust-lang.org/std/future/trait.Future.html
+    pub fn hot_swap<I, F>(&self, init: I) -> bool
+    where
+        I: Fn(BastionContext) -> F + Send + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        debug!("ChildRef({}): Hot-swapping.", self.id());
+        let msg = BastionMessage::hot_swap(Init::new(init));
+        let env = Envelope::from_dead_letters(msg);
+        self.send(env).is_ok()
+    }
+
+    /// Sends a message to the child this `ChildRef` is referencing
+    /// to tell it to stop its execution, returning a future which
+    /// resolves once it actually stopped, that is once its
+    /// execution was dropped and its `after_stop` callback was
+    /// called.
+    ///
+    /// The future resolves with `StopError::Unreachable` if the
+    /// child was already dead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// # Bastion::start();
+    /// #
+    /// let children_ref = Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             loop {
+    ///                 ctx.recv().await?;
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// let child_ref = &children_ref.elems()[0];
+    /// run!(child_ref.stop_and_wait()).expect("Couldn't stop the child.");
+    /// #
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn stop_and_wait(&self) -> impl Future<Output = Result<(), StopError>> {
+        let stopped = self.stopped();
+        let sent = self.stop();
+        async move {
+            sent.map_err(|_| StopError::Unreachable)?;
+            stopped.await;
+            Ok(())
+        }
+    }
+
+    /// Sends a message to the child this `ChildRef` is referencing
+    /// to tell it to stop its execution, returning a future which
+    /// resolves once it actually stopped, like
+    /// [`stop_and_wait`] does.
+    ///
+    /// If the child didn't stop after `timeout`, it is killed
+    /// without waiting for its execution or callbacks to finish
+    /// and the future resolves with `StopError::Timeout`.
+    ///
+    /// [`stop_and_wait`]: #method.stop_and_wait
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # Bastion::init();
+    /// # Bastion::start();
+    /// #
+    /// let children_ref = Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             loop {
+    ///                 ctx.recv().await?;
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// let child_ref = &children_ref.elems()[0];
+    /// let stopped = child_ref.stop_and_wait_timeout(Duration::from_secs(1));
+    /// run!(stopped).expect("Couldn't stop the child on time.");
+    /// #
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn stop_and_wait_timeout(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<(), StopError>> {
+        let id = self.id.clone();
+        let termination = self.termination.clone();
+        let stopped = self.stop_and_wait();
+        async move {
+            let delay = Delay::new(timeout);
+            match future::select(Box::pin(stopped), delay).await {
+                Either::Left((stopped, _)) => stopped,
+                Either::Right(_) => {
+                    debug!("ChildRef({}): Killing after {:?}.", id, timeout);
+                    if let Some(termination) = termination {
+                        termination.kill();
+                    }
+
+                    Err(StopError::Timeout(timeout))
+                }
+            }
+        }
+    }
+
+    // Resolves once the child stopped, or right away if it
+    // can't tell it.
+    fn stopped(&self) -> Stopped {
+        Stopped {
+            termination: self.termination.clone(),
+        }
+    }
+
     /// Returns [`RefAddr`] for the child
     pub fn addr(&self) -> RefAddr {
         RefAddr::new(self.path.clone(), self.sender.clone())
+            .with_mailbox(self.mailbox.clone())
+            .with_ask_timeout(self.ask_timeout)
     }
 
     pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
@@ -352,6 +657,58 @@ impl ChildRef {
             .map_err(|err| err.into_inner())
     }
 
+    // Tells a message, giving it back along with why it couldn't
+    // be delivered.
+    pub(crate) fn try_tell<M: Message>(&self, msg: M) -> Result<(), SendError<M>> {
+        let msg = BastionMessage::tell(msg);
+        let env = Envelope::from_dead_letters(msg);
+        self.addr()
+            .try_send(env)
+            .map_err(|err| err.map(|env| env.into_msg().unwrap()))
+    }
+
+    // Tells a message as `try_tell` does, but waits for the
+    // child's mailbox to have room for it if it is full.
+    pub(crate) async fn tell_when_room<M: Message>(&self, msg: M) -> Result<(), SendError<M>> {
+        let msg = BastionMessage::tell(msg);
+        let env = Envelope::from_dead_letters(msg);
+        self.addr()
+            .send_when_room(env)
+            .await
+            .map_err(|err| err.map(|env| env.into_msg().unwrap()))
+    }
+
+    // Anonymous messages are refused once the child's system is
+    // stopping gracefully, unless they are sent by its processes.
+    fn accepts_anonymous(&self) -> bool {
+        self.intake.is_none_or(Intake::accepts)
+    }
+
+    // Sends a user message, giving it back if the child's
+    // mailbox is full.
+    pub(crate) fn send_message(&self, env: Envelope) -> Result<(), Envelope> {
+        trace!("ChildRef({}): Sending message: {:?}", self.id(), env);
+        self.addr().try_send(env).map_err(|err| err.into_inner())
+    }
+
+    // Whether the child stopped, or at least can't receive messages
+    // anymore.
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.sender.is_closed()
+            || self
+                .termination
+                .as_ref()
+                .is_some_and(|termination| termination.stopped.load(Ordering::SeqCst))
+    }
+
+    pub(crate) fn mailbox(&self) -> Option<&Arc<BoundedMailbox>> {
+        self.mailbox.as_ref()
+    }
+
+    pub(crate) fn termination(&self) -> Option<&Arc<Termination>> {
+        self.termination.as_ref()
+    }
+
     pub(crate) fn sender(&self) -> &Sender {
         &self.sender
     }
@@ -380,3 +737,56 @@ impl Hash for ChildRef {
         self.id.hash(state);
     }
 }
+
+impl Termination {
+    pub(crate) fn set_abort(&self, abort: AbortHandle) {
+        if let Ok(mut handle) = self.abort.lock() {
+            *handle = Some(abort);
+        }
+    }
+
+    pub(crate) fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    fn kill(&self) {
+        self.killed.store(true, Ordering::SeqCst);
+        if let Ok(handle) = self.abort.lock() {
+            if let Some(abort) = &*handle {
+                abort.abort();
+            }
+        }
+    }
+
+    pub(crate) fn notify_stopped(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        while let Ok(waker) = self.waiters.pop() {
+            waker.wake();
+        }
+    }
+}
+
+impl Future for Stopped {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        let termination = match &self.termination {
+            Some(termination) => termination,
+            None => return Poll::Ready(()),
+        };
+
+        if termination.stopped.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+
+        termination.waiters.push(ctx.waker().clone());
+
+        // The child might have stopped before the waker was
+        // registered.
+        if termination.stopped.load(Ordering::SeqCst) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
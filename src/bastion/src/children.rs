@@ -3,33 +3,46 @@
 use crate::broadcast::{Broadcast, Parent, Sender};
 use crate::callbacks::{CallbackType, Callbacks};
 use crate::child::{Child, Init};
-use crate::child_ref::ChildRef;
+use crate::child_ref::{ChildRef, Termination};
 use crate::children_ref::ChildrenRef;
 use crate::context::{BastionContext, BastionId, ContextState};
 use crate::dispatcher::Dispatcher;
 use crate::envelope::Envelope;
-use crate::message::BastionMessage;
+use crate::mailbox::{BoundedMailbox, MailboxPolicy};
+use crate::message::{BastionMessage, PanicPayload};
+#[cfg(feature = "metrics")]
+use crate::metrics::{self, GroupMetrics};
 use crate::path::BastionPathElement;
 #[cfg(feature = "scaling")]
 use crate::resizer::{ActorGroupStats, OptimalSizeExploringResizer, ScalingRule};
-use crate::system::SYSTEM;
+use crate::system;
+use crate::time::Delay;
 use anyhow::Result as AnyResult;
 
-use bastion_executor::pool;
+use futures::channel::oneshot;
 use futures::pending;
 use futures::poll;
 use futures::prelude::*;
 use futures::stream::FuturesOrdered;
-use futures_timer::Delay;
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use lightproc::prelude::*;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::task::Poll;
 use std::time::Duration;
-use tracing::{debug, trace, warn};
+use tracing::{debug, info_span, trace, warn, Instrument, Span};
+
+// How long an element keeps draining its mailbox by default when
+// asked to stop, if the group was set to drain them.
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The outcomes of a health check.
+const CHECKING: u8 = 0;
+const HEALTHY: u8 = 1;
+const UNHEALTHY: u8 = 2;
 
 #[derive(Debug)]
 /// A children group that will contain a defined number of
@@ -101,11 +114,78 @@ pub struct Children {
     // Defines how often do heartbeat checks. By default checks will
     // be done each 60 seconds.
     hearbeat_tick: Duration,
+    // The check run against each element of the group on every
+    // heartbeat, if any.
+    health_check: Option<HealthCheck>,
+    // The outcome of the last health check run against each
+    // element of the group, along with the check's handle.
+    health_checks: FxHashMap<BastionId, (Arc<AtomicU8>, RecoverableHandle<()>)>,
+    // The elements whose restart was requested but which
+    // weren't restarted yet.
+    restarting: FxHashSet<BastionId>,
     // Special kind for actors that not going to be visible for others
     // parts of the cluster, but required for extra behaviour for the
     // Children instance. For example for heartsbeat checks, collecting
     // stats, etc.
     helper_actors: FxHashMap<BastionId, (Sender, RecoverableHandle<()>)>,
+    // How long the elements keep processing the messages left in
+    // their mailbox when stopped. By default, they don't.
+    drain_timeout: Option<Duration>,
+    // How many messages can wait in the mailbox of each element
+    // of the group. By default, there is no limit.
+    mailbox_capacity: Option<usize>,
+    // What happens when a message is sent to an element whose
+    // mailbox is full.
+    mailbox_policy: MailboxPolicy,
+    // How long asking an element of the group waits for its
+    // answer by default. By default, it waits forever.
+    ask_timeout: Option<Duration>,
+    // The bounded mailboxes of the currently launched elements
+    // of the group.
+    mailboxes: FxHashMap<BastionId, Arc<BoundedMailbox>>,
+    // Tells when the currently launched elements of the group
+    // stopped.
+    terminations: FxHashMap<BastionId, Arc<Termination>>,
+    #[cfg(feature = "metrics")]
+    // The message throughput and restart counts of the group.
+    metrics: Arc<GroupMetrics>,
+    // The span covering the group's lifecycle, which is only
+    // recorded with the `tracing-spans` feature.
+    span: Span,
+    // Whether the group is paused or stopped, shared with its
+    // `ChildrenRef`s and the contexts of its elements.
+    status: Arc<GroupStatus>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct GroupStatus {
+    // Whether the elements of the group stopped retrieving
+    // messages from their mailboxes.
+    paused: AtomicBool,
+    stopped: AtomicBool,
+}
+
+type HealthFuture = Pin<Box<dyn Future<Output = Result<(), ()>> + Send>>;
+
+pub(crate) struct HealthCheck(Box<dyn Fn(ChildRef) -> HealthFuture + Send>);
+
+impl GroupStatus {
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    // Returns whether the group was paused before.
+    pub(crate) fn set_paused(&self, paused: bool) -> bool {
+        self.paused.swap(paused, Ordering::SeqCst)
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    fn set_stopped(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
 }
 
 impl Children {
@@ -122,7 +202,29 @@ impl Children {
         #[cfg(feature = "scaling")]
         let resizer = Box::new(OptimalSizeExploringResizer::default());
         let hearbeat_tick = Duration::from_secs(60);
+        let health_check = None;
+        let health_checks = FxHashMap::default();
+        let restarting = FxHashSet::default();
         let helper_actors = FxHashMap::default();
+        let drain_timeout = None;
+        let mailbox_capacity = None;
+        let mailbox_policy = MailboxPolicy::default();
+        let ask_timeout = None;
+        let mailboxes = FxHashMap::default();
+        let terminations = FxHashMap::default();
+        #[cfg(feature = "metrics")]
+        let metrics = Arc::default();
+        let span = if cfg!(feature = "tracing-spans") {
+            info_span!(
+                parent: bcast.parent().span_id(),
+                "children",
+                path = %bcast.path(),
+                name = tracing::field::Empty,
+            )
+        } else {
+            Span::none()
+        };
+        let status = Arc::default();
 
         Children {
             bcast,
@@ -137,7 +239,20 @@ impl Children {
             #[cfg(feature = "scaling")]
             resizer,
             hearbeat_tick,
+            health_check,
+            health_checks,
+            restarting,
             helper_actors,
+            drain_timeout,
+            mailbox_capacity,
+            mailbox_policy,
+            ask_timeout,
+            mailboxes,
+            terminations,
+            #[cfg(feature = "metrics")]
+            metrics,
+            span,
+            status,
         }
     }
 
@@ -204,7 +319,11 @@ impl Children {
         for (id, (sender, _)) in &self.launched {
             trace!("Children({}): Creating new ChildRef({}).", self.id(), id);
             // TODO: clone or ref?
-            let child = ChildRef::new(id.clone(), sender.clone(), self.name(), path.clone());
+            let child = ChildRef::new(id.clone(), sender.clone(), self.name(), path.clone())
+                .with_mailbox(self.mailboxes.get(id).cloned())
+                .with_termination(self.terminations.get(id).cloned())
+                .with_ask_timeout(self.ask_timeout)
+                .with_intake(system::current().intake());
             children.push(child);
         }
 
@@ -214,12 +333,18 @@ impl Children {
             .map(|dispatcher| dispatcher.dispatcher_type())
             .collect();
 
-        ChildrenRef::new(id, sender, path, children, dispatchers)
+        let span = self.span.clone();
+
+        let status = self.status.clone();
+        ChildrenRef::new(id, sender, path, children, dispatchers, span, status)
+            .with_intake(system::current().intake())
     }
 
     /// Sets the name of this children group.
     pub fn with_name(mut self, name: impl Into<String>) -> Self {
-        self.name = Some(name.into());
+        let name = name.into();
+        self.span.record("name", name.as_str());
+        self.name = Some(name);
         self
     }
 
@@ -483,6 +608,308 @@ impl Children {
         self
     }
 
+    /// Sets the health check run against each element of this
+    /// children group on every heartbeat (see [`with_heartbeat_tick`]).
+    ///
+    /// The check runs as its own task, so that it can detect an
+    /// element which is alive but stuck, for example by asking it
+    /// a message. If it returns `Err(())`, or doesn't return before
+    /// the next heartbeat, the element is considered faulted: it is
+    /// cancelled and its supervisor applies its restart strategy.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - The closure taking a [`ChildRef`] referencing an element and returning the future checking it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_heartbeat_tick(Duration::from_secs(5))
+    ///         .with_health_check(|child: ChildRef| async move {
+    ///             // The element is healthy as long as it answers.
+    ///             let answer = child.ask_anonymously("ping").map_err(|_| ())?;
+    ///             answer.await.map(|_| ())
+    ///         })
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 loop {
+    ///                     msg! { ctx.recv().await?,
+    ///                         _ping: &'static str =!> {
+    ///                             answer!(ctx, "pong").unwrap();
+    ///                         };
+    ///                         _: _ => ();
+    ///                     }
+    ///                 }
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`with_heartbeat_tick`]: #method.with_heartbeat_tick
+    /// [`ChildRef`]: children/struct.ChildRef.html
+    pub fn with_health_check<C, F>(mut self, check: C) -> Self
+    where
+        C: Fn(ChildRef) -> F + Send + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        trace!("Children({}): Setting health check.", self.id());
+        let check = Box::new(move |child: ChildRef| -> HealthFuture { Box::pin(check(child)) });
+        self.health_check = Some(HealthCheck(check));
+        self
+    }
+
+    /// Makes the elements of this children group drain their mailbox
+    /// when they are stopped, either with [`ChildRef::stop`] or
+    /// because their supervisor is stopping.
+    ///
+    /// A draining element doesn't accept new messages anymore but
+    /// keeps processing the ones it already received until its
+    /// mailbox is empty or the drain timeout elapses (five seconds
+    /// by default, see [`with_drain_timeout`]). The messages that were
+    /// asked but weren't answered by then get their [`Answer`]
+    /// resolved to an error.
+    ///
+    /// By default, elements stop without draining their mailbox.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_drain_on_stop()
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`ChildRef::stop`]: children/struct.ChildRef.html#method.stop
+    /// [`with_drain_timeout`]: #method.with_drain_timeout
+    /// [`Answer`]: message/struct.Answer.html
+    pub fn with_drain_on_stop(mut self) -> Self {
+        trace!("Children({}): Enabling mailbox draining.", self.id());
+        if self.drain_timeout.is_none() {
+            self.drain_timeout = Some(DEFAULT_DRAIN_TIMEOUT);
+        }
+
+        self
+    }
+
+    /// Makes the elements of this children group drain their mailbox
+    /// for at most `timeout` when they are stopped (see
+    /// [`with_drain_on_stop`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - How long an element keeps draining its mailbox.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_drain_timeout(Duration::from_secs(1))
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`with_drain_on_stop`]: #method.with_drain_on_stop
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        trace!(
+            "Children({}): Setting drain timeout: {:?}",
+            self.id(),
+            timeout
+        );
+        self.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Bounds the mailbox of each element of this children group,
+    /// so that at most `capacity` messages can wait to be received
+    /// by an element.
+    ///
+    /// What happens when a message is sent to an element whose
+    /// mailbox is full depends on the group's [`MailboxPolicy`]
+    /// (see [`with_mailbox_policy`]), but asking it always fails
+    /// instead of waiting. Messages broadcasted to the group are
+    /// never rejected but still count toward the capacity.
+    ///
+    /// By default, mailboxes are unbounded.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - How many messages can wait in the mailbox of an element.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_mailbox_capacity(100)
+    ///         .with_mailbox_policy(MailboxPolicy::Backpressure)
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`MailboxPolicy`]: mailbox/enum.MailboxPolicy.html
+    /// [`with_mailbox_policy`]: #method.with_mailbox_policy
+    pub fn with_mailbox_capacity(mut self, capacity: usize) -> Self {
+        trace!(
+            "Children({}): Setting mailbox capacity: {}",
+            self.id(),
+            capacity
+        );
+        self.mailbox_capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the policy applied when a message is sent to an element
+    /// of this children group whose mailbox is full (see
+    /// [`with_mailbox_capacity`]).
+    ///
+    /// By default, the message is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy applied when an element's mailbox is full.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_mailbox_capacity(100)
+    ///         .with_mailbox_policy(MailboxPolicy::Backpressure)
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`with_mailbox_capacity`]: #method.with_mailbox_capacity
+    pub fn with_mailbox_policy(mut self, policy: MailboxPolicy) -> Self {
+        trace!(
+            "Children({}): Setting mailbox policy: {:?}",
+            self.id(),
+            policy
+        );
+        self.mailbox_policy = policy;
+        self
+    }
+
+    /// Sets how long asking an element of this children group waits
+    /// for its answer by default, after which the [`Answer`] resolves
+    /// to `Err(())`. The timeout starts once the message is sent,
+    /// whether or not the element started handling it.
+    ///
+    /// A timeout given to [`BastionContext::ask_timeout`] overrides
+    /// this one. By default, asking an element waits forever.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - How long asking an element waits for its answer.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_ask_timeout(Duration::from_secs(5))
+    ///         .with_exec(|ctx| {
+    ///             async move {
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`Answer`]: ../message/struct.Answer.html
+    /// [`BastionContext::ask_timeout`]: ../context/struct.BastionContext.html#method.ask_timeout
+    pub fn with_ask_timeout(mut self, timeout: Duration) -> Self {
+        trace!(
+            "Children({}): Setting ask timeout: {:?}",
+            self.id(),
+            timeout
+        );
+        self.ask_timeout = Some(timeout);
+        self
+    }
+
+    fn new_mailbox(&self) -> Option<Arc<BoundedMailbox>> {
+        self.mailbox_capacity
+            .map(|capacity| Arc::new(BoundedMailbox::new(capacity, self.mailbox_policy)))
+    }
+
     /// Returns executable code for the actor that will trigger heartbeat
     fn get_heartbeat_fut(&self) -> Init {
         let interval = self.hearbeat_tick;
@@ -504,6 +931,8 @@ impl Children {
     }
 
     async fn disable_helper_actors(&mut self) {
+        self.cancel_health_checks();
+
         let mut children = FuturesOrdered::new();
         for (_, (_, launched)) in self.helper_actors.drain() {
             launched.cancel();
@@ -540,17 +969,23 @@ impl Children {
 
     fn stopped(&mut self) {
         debug!("Children({}): Stopped.", self.id());
+        #[cfg(feature = "metrics")]
+        metrics::unregister_group(self.id());
         if let Err(e) = self.remove_dispatchers() {
             warn!("couldn't remove all dispatchers from the registry: {}", e);
         };
+        self.status.set_stopped();
         self.bcast.stopped();
     }
 
     fn faulted(&mut self) {
         debug!("Children({}): Faulted.", self.id());
+        #[cfg(feature = "metrics")]
+        metrics::unregister_group(self.id());
         if let Err(e) = self.remove_dispatchers() {
             warn!("couldn't remove all dispatchers from the registry: {}", e);
         };
+        self.status.set_stopped();
         self.bcast.faulted();
     }
 
@@ -561,9 +996,35 @@ impl Children {
         Err(())
     }
 
+    async fn drain(&mut self) {
+        debug!("Children({}): Draining.", self.id());
+        self.bcast.stop_children();
+
+        // Each element stops by itself once it drained its mailbox
+        // or the drain timeout elapsed.
+        let mut children = FuturesOrdered::new();
+        for (_, (_, launched)) in self.launched.drain() {
+            children.push_back(launched);
+        }
+
+        let id = self.id();
+        children
+            .for_each_concurrent(None, |_| async {
+                trace!("Children({}): Unknown child drained.", id);
+            })
+            .await;
+    }
+
     async fn stop_children(&mut self) -> Result<(), ()> {
         self.disable_helper_actors().await;
-        self.kill().await;
+        if system::current()
+            .drain_timeout(self.drain_timeout)
+            .is_some()
+        {
+            self.drain().await;
+        } else {
+            self.kill().await;
+        }
         self.stopped();
         Err(())
     }
@@ -595,28 +1056,69 @@ impl Children {
         Ok(())
     }
 
-    fn request_restarting_child(&mut self, id: &BastionId, parent_id: &BastionId) {
-        if parent_id == self.bcast.id() && self.launched.contains_key(id) {
+    fn request_restarting_child(
+        &mut self,
+        id: &BastionId,
+        parent_id: &BastionId,
+        panic: Option<PanicPayload>,
+    ) {
+        // An element can both panic and fail its health check.
+        if parent_id == self.bcast.id()
+            && self.launched.contains_key(id)
+            && self.restarting.insert(id.clone())
+        {
+            // The element might still be running if it failed its
+            // health check.
+            if let Some((_, launched)) = self.launched.get(id) {
+                launched.cancel();
+            }
+
             let parent_id = self.bcast.id().clone();
-            let msg = BastionMessage::restart_required(id.clone(), parent_id);
+            let msg = BastionMessage::restart_required(id.clone(), parent_id, panic);
             let env = Envelope::new(msg, self.bcast.path().clone(), self.bcast.sender().clone());
             self.bcast.send_parent(env).ok();
         }
     }
 
-    fn restart_child(&mut self, old_id: &BastionId, old_state: Arc<Pin<Box<ContextState>>>) {
+    fn restart_child(
+        &mut self,
+        old_id: &BastionId,
+        old_state: &ContextState,
+        started: Option<oneshot::Sender<()>>,
+    ) {
+        self.cancel_health_check(old_id);
+        self.restarting.remove(old_id);
+
         let parent = Parent::children(self.as_ref());
         let bcast = Broadcast::new(parent, BastionPathElement::Child(old_id.clone()));
 
         let id = bcast.id().clone();
         let sender = bcast.sender().clone();
         let path = bcast.path().clone();
-        let child_ref = ChildRef::new(id.clone(), sender.clone(), self.name(), path);
+        let mailbox = self.new_mailbox();
+        let termination = Arc::new(Termination::default());
+        let child_ref = ChildRef::new(id.clone(), sender.clone(), self.name(), path)
+            .with_mailbox(mailbox.clone())
+            .with_termination(Some(termination.clone()))
+            .with_ask_timeout(self.ask_timeout)
+            .with_intake(system::current().intake());
+        if let Some(mailbox) = &mailbox {
+            self.mailboxes.insert(id.clone(), mailbox.clone());
+        }
+        self.terminations.insert(id.clone(), termination);
 
         let children = self.as_ref();
         let supervisor = self.bcast.parent().clone().into_supervisor();
 
-        let state = Arc::new(Box::pin(ContextState::new()));
+        let mut state = ContextState::new();
+        state.set_mailbox(mailbox);
+        state.set_group_status(self.status.clone());
+        state.restore_persistent_state(old_state);
+        #[cfg(feature = "metrics")]
+        state.set_group_metrics(self.metrics.clone());
+        #[cfg(feature = "scaling")]
+        self.init_data_for_scaling(&mut state);
+        let state = Arc::new(Box::pin(state));
 
         let ctx = BastionContext::new(
             id.clone(),
@@ -625,14 +1127,10 @@ impl Children {
             supervisor,
             state.clone(),
         );
-        let exec = (self.init.0)(ctx);
+        let exec = state.exec(&self.init, ctx);
 
         self.bcast.register(&bcast);
 
-        let msg = BastionMessage::set_state(old_state);
-        let env = Envelope::new(msg, self.bcast.path().clone(), self.bcast.sender().clone());
-        self.bcast.send_child(&id, env);
-
         let msg = BastionMessage::apply_callback(CallbackType::AfterRestart);
         let env = Envelope::new(msg, self.bcast.path().clone(), self.bcast.sender().clone());
         self.bcast.send_child(&id, env);
@@ -642,8 +1140,11 @@ impl Children {
         self.bcast.send_child(&id, env);
 
         debug!("Children({}): Restarting Child({}).", self.id(), bcast.id());
+        #[cfg(feature = "metrics")]
+        self.metrics.record_restart();
         let callbacks = self.callbacks.clone();
-        let child = Child::new(exec, callbacks, bcast, state, child_ref);
+        let child = Child::new(exec, callbacks, bcast, state, child_ref, self.drain_timeout)
+            .with_on_started(started);
         debug!(
             "Children({}): Launching faulted Child({}).",
             self.id(),
@@ -654,6 +1155,23 @@ impl Children {
         self.launched.insert(id, (sender, launched));
     }
 
+    // Stops a child that its supervisor is about to restore, without
+    // stopping it for good so that its persistent state survives.
+    async fn tear_down_child(&mut self, id: &BastionId, done: oneshot::Sender<()>) {
+        // The child already stopped if it's being restarted.
+        if !self.restarting.contains(id) {
+            if let Some((_, launched)) = self.launched.remove(id) {
+                debug!("Children({}): Tearing down Child({}).", self.id(), id);
+                self.restarting.insert(id.clone());
+                launched.cancel();
+                launched.await;
+                self.callbacks.before_restart().await;
+            }
+        }
+
+        done.send(()).ok();
+    }
+
     fn drop_child(&mut self, id: &BastionId) {
         debug!(
             "Children({}): Dropping Child({:?}): reached restart limits.",
@@ -661,6 +1179,10 @@ impl Children {
             id,
         );
         self.launched.remove_entry(id);
+        self.mailboxes.remove(id);
+        self.terminations.remove(id);
+        self.cancel_health_check(id);
+        self.restarting.remove(id);
 
         #[cfg(feature = "scaling")]
         self.update_actors_count_stats();
@@ -715,9 +1237,14 @@ impl Children {
                 self.bcast.send_children(envelope);
             }
             Envelope {
-                msg: BastionMessage::RestartRequired { id, parent_id },
+                msg:
+                    BastionMessage::RestartRequired {
+                        id,
+                        parent_id,
+                        panic,
+                    },
                 ..
-            } => self.request_restarting_child(&id, &parent_id),
+            } => self.request_restarting_child(&id, &parent_id, panic),
             Envelope {
                 msg: BastionMessage::FinishedChild { .. },
                 ..
@@ -727,13 +1254,17 @@ impl Children {
                 ..
             } => unreachable!(),
             Envelope {
-                msg: BastionMessage::RestoreChild { id, state },
+                msg: BastionMessage::RestoreChild { id, state, started },
                 ..
-            } => self.restart_child(&id, state),
+            } => self.restart_child(&id, &state, started),
             Envelope {
                 msg: BastionMessage::DropChild { id },
                 ..
             } => self.drop_child(&id),
+            Envelope {
+                msg: BastionMessage::TearDownChild { id, done },
+                ..
+            } => self.tear_down_child(&id, done).await,
             Envelope {
                 msg: BastionMessage::SetState { .. },
                 ..
@@ -749,7 +1280,18 @@ impl Children {
             Envelope {
                 msg: BastionMessage::Heartbeat,
                 ..
-            } => {}
+            } => self.check_health(),
+            Envelope {
+                msg: BastionMessage::Resume,
+                ..
+            } => {
+                debug!("Children({}): Resuming elements.", self.id());
+                self.bcast.send_children(envelope);
+            }
+            Envelope {
+                msg: BastionMessage::HotSwap(_),
+                ..
+            } => unreachable!(),
         }
 
         Ok(())
@@ -880,13 +1422,26 @@ impl Children {
         let id = bcast.id().clone();
         let sender = bcast.sender().clone();
         let path = bcast.path().clone();
-        let child_ref = ChildRef::new(id.clone(), sender.clone(), name, path);
+        let mailbox = self.new_mailbox();
+        let termination = Arc::new(Termination::default());
+        let child_ref = ChildRef::new(id.clone(), sender.clone(), name, path)
+            .with_mailbox(mailbox.clone())
+            .with_termination(Some(termination.clone()))
+            .with_ask_timeout(self.ask_timeout)
+            .with_intake(system::current().intake());
+        if let Some(mailbox) = &mailbox {
+            self.mailboxes.insert(id.clone(), mailbox.clone());
+        }
+        self.terminations.insert(id.clone(), termination);
 
         let children = self.as_ref();
         let supervisor = self.bcast.parent().clone().into_supervisor();
 
-        #[allow(unused_mut)]
         let mut state = ContextState::new();
+        state.set_mailbox(mailbox);
+        state.set_group_status(self.status.clone());
+        #[cfg(feature = "metrics")]
+        state.set_group_metrics(self.metrics.clone());
         #[cfg(feature = "scaling")]
         self.init_data_for_scaling(&mut state);
 
@@ -918,13 +1473,75 @@ impl Children {
             bcast.id()
         );
         let callbacks = self.callbacks.clone();
-        let child = Child::new(exec, callbacks, bcast, state, child_ref);
+        let child = Child::new(exec, callbacks, bcast, state, child_ref, self.drain_timeout);
         debug!("Children({}): Launching Child({}).", self.id(), child.id());
         let id = child.id().clone();
         let launched = child.launch();
         self.launched.insert(id, (sender, launched));
     }
 
+    fn check_health(&mut self) {
+        let health_check = match &self.health_check {
+            Some(health_check) => health_check,
+            None => return,
+        };
+
+        let children = self.as_ref();
+        let mut unhealthy = Vec::new();
+        for child in children.elems() {
+            let id = child.id();
+            if self.restarting.contains(id) {
+                continue;
+            }
+
+            // A check that is still running by the next heartbeat
+            // means that the element is stuck.
+            if let Some((outcome, _)) = self.health_checks.get(id) {
+                if outcome.load(Ordering::SeqCst) != HEALTHY {
+                    unhealthy.push(id.clone());
+                    continue;
+                }
+            }
+
+            let outcome = Arc::new(AtomicU8::new(CHECKING));
+            let check = (health_check.0)(child.clone());
+            let check_outcome = outcome.clone();
+            let handle = system::spawn(
+                async move {
+                    let healthy = check.await.is_ok();
+                    let res = if healthy { HEALTHY } else { UNHEALTHY };
+                    check_outcome.store(res, Ordering::SeqCst);
+                },
+                ProcStack::default(),
+            );
+            self.health_checks.insert(id.clone(), (outcome, handle));
+        }
+
+        let parent_id = self.bcast.id().clone();
+        for id in unhealthy {
+            warn!(
+                "Children({}): Child({}) failed its health check.",
+                self.id(),
+                id
+            );
+            self.cancel_health_check(&id);
+            self.request_restarting_child(&id, &parent_id, None);
+        }
+    }
+
+    // Cancels the health check of the element, in case it hung.
+    fn cancel_health_check(&mut self, id: &BastionId) {
+        if let Some((_, handle)) = self.health_checks.remove(id) {
+            handle.cancel();
+        }
+    }
+
+    fn cancel_health_checks(&mut self) {
+        for (_, (_, handle)) in self.health_checks.drain() {
+            handle.cancel();
+        }
+    }
+
     pub(crate) fn launch_heartbeat(&mut self) {
         let name = self.name();
         let parent = Parent::children(self.as_ref());
@@ -952,7 +1569,7 @@ impl Children {
             bcast.id()
         );
         let callbacks = self.callbacks.clone();
-        let child = Child::new(exec, callbacks, bcast, state, child_ref);
+        let child = Child::new(exec, callbacks, bcast, state, child_ref, None);
         debug!(
             "Children({}): Launching HeartbeatChild({}).",
             self.id(),
@@ -965,6 +1582,8 @@ impl Children {
 
     pub(crate) fn launch_elems(&mut self) {
         debug!("Children({}): Launching elements.", self.id());
+        #[cfg(feature = "metrics")]
+        metrics::register_group(self.id(), self.name(), self.metrics.clone());
         for _ in 0..self.redundancy {
             self.launch_child();
         }
@@ -972,15 +1591,41 @@ impl Children {
         self.launch_heartbeat();
     }
 
+    // Gives the group, which was killed by its supervisor, a new
+    // identity and launches its elements again with a fresh state.
+    pub(crate) fn revive(&mut self, bcast: Broadcast) {
+        debug!(
+            "Children({}): Reviving as Children({}).",
+            self.id(),
+            bcast.id()
+        );
+        self.bcast = bcast;
+        self.launched.clear();
+        self.helper_actors.clear();
+        self.cancel_health_checks();
+        self.restarting.clear();
+        self.mailboxes.clear();
+        self.terminations.clear();
+        self.pre_start_msgs.clear();
+        self.started = false;
+        self.status = Arc::default();
+
+        if let Err(e) = self.register_dispatchers() {
+            warn!("couldn't register all dispatchers into the registry: {}", e);
+        };
+        self.launch_elems();
+    }
+
     pub(crate) fn launch(self) -> RecoverableHandle<Self> {
         debug!("Children({}): Launching.", self.id());
         let stack = self.stack();
-        pool::spawn(self.run(), stack)
+        let span = self.span.clone();
+        system::spawn(self.run().instrument(span), stack)
     }
 
     /// Registers all declared local dispatchers in the global dispatcher.
     pub(crate) fn register_dispatchers(&self) -> AnyResult<()> {
-        let global_dispatcher = SYSTEM.dispatcher();
+        let global_dispatcher = system::current().dispatcher();
 
         for dispatcher in self.dispatchers.iter() {
             global_dispatcher.register_dispatcher(dispatcher)?;
@@ -990,7 +1635,7 @@ impl Children {
 
     /// Removes all declared local dispatchers from the global dispatcher.
     pub(crate) fn remove_dispatchers(&self) -> AnyResult<()> {
-        let global_dispatcher = SYSTEM.dispatcher();
+        let global_dispatcher = system::current().dispatcher();
 
         for dispatcher in self.dispatchers.iter() {
             global_dispatcher.remove_dispatcher(dispatcher)?;
@@ -998,3 +1643,9 @@ impl Children {
         Ok(())
     }
 }
+
+impl Debug for HealthCheck {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("HealthCheck").finish()
+    }
+}
@@ -2,16 +2,20 @@
 //! Allows users to communicate with children through the mailboxes.
 use crate::broadcast::Sender;
 use crate::child_ref::ChildRef;
+use crate::children::GroupStatus;
 use crate::context::BastionId;
 use crate::dispatcher::DispatcherType;
 use crate::envelope::Envelope;
+use crate::errors::{PauseError, RequestError};
 use crate::message::{BastionMessage, Message};
 use crate::path::BastionPath;
-use crate::system::SYSTEM;
+use crate::system::{self, Intake};
 use std::cmp::{Eq, PartialEq};
 use std::fmt::Debug;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tracing::{debug, trace};
+use tracing::{debug, trace, Span};
 
 #[derive(Debug, Clone)]
 /// A "reference" to a children group, allowing to communicate
@@ -22,6 +26,14 @@ pub struct ChildrenRef {
     path: Arc<BastionPath>,
     children: Vec<ChildRef>,
     dispatchers: Vec<DispatcherType>,
+    // The span covering the children group's lifecycle.
+    span: Span,
+    // Whether the children group is paused or stopped.
+    status: Arc<GroupStatus>,
+    // Whether the group's system accepts anonymous messages.
+    intake: Option<&'static Intake>,
+    // The index of the element the next request starts looking from.
+    next_request: Arc<AtomicUsize>,
 }
 
 impl ChildrenRef {
@@ -31,6 +43,8 @@ impl ChildrenRef {
         path: Arc<BastionPath>,
         children: Vec<ChildRef>,
         dispatchers: Vec<DispatcherType>,
+        span: Span,
+        status: Arc<GroupStatus>,
     ) -> Self {
         ChildrenRef {
             id,
@@ -38,9 +52,22 @@ impl ChildrenRef {
             path,
             children,
             dispatchers,
+            span,
+            status,
+            intake: None,
+            next_request: Arc::default(),
         }
     }
 
+    pub(crate) fn with_intake(mut self, intake: &'static Intake) -> Self {
+        self.intake = Some(intake);
+        self
+    }
+
+    pub(crate) fn span(&self) -> &Span {
+        &self.span
+    }
+
     /// Returns the identifier of the children group this `ChildrenRef`
     /// is referencing.
     ///
@@ -173,12 +200,105 @@ impl ChildrenRef {
             self.id(),
             msg
         );
+        // Refused once the group's system is stopping gracefully,
+        // unless it is broadcasted by its processes.
+        if !self.intake.is_none_or(Intake::accepts) {
+            return Err(msg);
+        }
+
         let msg = BastionMessage::broadcast(msg);
         let env = Envelope::from_dead_letters(msg);
         // FIXME: panics?
         self.send(env).map_err(|err| err.into_msg().unwrap())
     }
 
+    /// Asks a message to one of the elements of the children group
+    /// this `ChildrenRef` is referencing, and returns a [`Future`]
+    /// resolving to its answer, downcasted to `Resp`.
+    ///
+    /// This is a typed alternative to asking the message to one of
+    /// the [`elems`] and matching the [`Answer`] with [`msg!`].
+    ///
+    /// The requests are sent to the elements in turn, skipping the ones
+    /// which stopped or can't receive the request.
+    ///
+    /// The returned future resolves to `Err(RequestError::NoActor)` if
+    /// no element could receive the request, to
+    /// `Err(RequestError::Dropped)` if the element didn't answer and to
+    /// `Err(RequestError::TypeMismatch)` if it answered with something
+    /// else than a `Resp`.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The message to ask.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     # Bastion::start();
+    /// let children_ref = Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             msg! { ctx.recv().await?,
+    ///                 n: u64 =!> {
+    ///                     answer!(ctx, n * 2).expect("Couldn't answer.");
+    ///                 };
+    ///                 _: _ => ();
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// let doubled: Result<u64, RequestError> = run!(children_ref.request(21u64));
+    /// assert_eq!(doubled, Ok(42));
+    ///     #
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Future`]: https://doc.rust-lang.org/std/future/trait.Future.html
+    /// [`elems`]: #method.elems
+    /// [`Answer`]: ../message/struct.Answer.html
+    /// [`msg!`]: ../macro.msg.html
+    pub fn request<Req: Message, Resp: Message>(
+        &self,
+        req: Req,
+    ) -> impl Future<Output = Result<Resp, RequestError>> {
+        debug!("ChildrenRef({}): Requesting: {:?}", self.id(), req);
+        let elems = self.elems();
+        let start = self.next_request.fetch_add(1, Ordering::Relaxed);
+        let mut req = req;
+        let mut answer = Err(RequestError::NoActor);
+        for offset in 0..elems.len() {
+            let child = &elems[(start + offset) % elems.len()];
+            if child.is_stopped() {
+                continue;
+            }
+            match child.ask_anonymously(req) {
+                Ok(sent) => {
+                    answer = Ok(sent);
+                    break;
+                }
+                Err(msg) => {
+                    trace!("ChildrenRef({}): Skipping {}.", self.id(), child.id());
+                    req = msg;
+                }
+            }
+        }
+
+        async move {
+            let (msg, _) = answer?.await.map_err(|_| RequestError::Dropped)?.extract();
+            msg.downcast().map_err(|_| RequestError::TypeMismatch)
+        }
+    }
+
     /// Sends a message to the children group this `ChildrenRef`
     /// is referencing to tell it to stop all of its running
     /// elements.
@@ -235,10 +355,115 @@ impl ChildrenRef {
         self.send(env).map_err(|_| ())
     }
 
+    /// Pauses the children group this `ChildrenRef` is referencing.
+    ///
+    /// While paused, its elements stop retrieving messages from
+    /// their mailboxes but keep receiving them, as long as bounded
+    /// mailboxes have room, and retrieve them once the group is
+    /// [`resume`]d. They still run though, so that they keep being
+    /// supervised.
+    ///
+    /// Pausing a group that is already paused does nothing.
+    ///
+    /// This method returns `()` if it succeeded, or
+    /// `Err(PauseError::Stopped)` if the group already stopped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// # let children_ref = Bastion::children(|children| children).unwrap();
+    /// children_ref.pause().expect("The group was stopped.");
+    /// assert!(children_ref.is_paused());
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`resume`]: #method.resume
+    pub fn pause(&self) -> Result<(), PauseError> {
+        debug!("ChildrenRef({}): Pausing.", self.id());
+        if self.status.is_stopped() {
+            return Err(PauseError::Stopped);
+        }
+
+        self.status.set_paused(true);
+        Ok(())
+    }
+
+    /// Resumes the children group this `ChildrenRef` is referencing,
+    /// after it was [`pause`]d, making its elements retrieve the
+    /// messages they received in the meantime.
+    ///
+    /// Resuming a group that isn't paused does nothing.
+    ///
+    /// This method returns `()` if it succeeded, or
+    /// `Err(PauseError::Stopped)` if the group already stopped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// # let children_ref = Bastion::children(|children| children).unwrap();
+    /// children_ref.pause().expect("The group was stopped.");
+    /// children_ref.resume().expect("The group was stopped.");
+    /// assert!(!children_ref.is_paused());
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`pause`]: #method.pause
+    pub fn resume(&self) -> Result<(), PauseError> {
+        debug!("ChildrenRef({}): Resuming.", self.id());
+        if self.status.is_stopped() {
+            return Err(PauseError::Stopped);
+        }
+
+        if self.status.set_paused(false) {
+            // The elements need to be woken up to retrieve the
+            // messages they received while paused.
+            let msg = BastionMessage::resume();
+            let env = Envelope::from_dead_letters(msg);
+            self.send(env).map_err(|_| PauseError::Stopped)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the children group this `ChildrenRef` is
+    /// referencing is paused.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// # let children_ref = Bastion::children(|children| children).unwrap();
+    /// assert!(!children_ref.is_paused());
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn is_paused(&self) -> bool {
+        self.status.is_paused()
+    }
+
     pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
         trace!("ChildrenRef({}): Sending message: {:?}", self.id(), env);
         self.sender.unbounded_send(env).or_else(|err| {
-            SYSTEM
+            system::current()
                 .dead_letters()
                 .sender
                 .unbounded_send(err.into_inner())
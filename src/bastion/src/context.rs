@@ -2,26 +2,43 @@
 //! A context allows a child's future to access its received
 //! messages, parent and supervisor.
 
+use crate::child::{Exec, Init};
 use crate::child_ref::ChildRef;
+use crate::children::GroupStatus;
 use crate::children_ref::ChildrenRef;
-use crate::dispatcher::{BroadcastTarget, DispatcherType, NotificationType};
+use crate::dead_letters::{self, DeadLetterReason};
+use crate::dispatcher::{BroadcastTarget, Delivery, DispatcherType, NotificationType};
 use crate::envelope::{Envelope, RefAddr, SignedMessage};
+use crate::local::Locals;
+use crate::mailbox::BoundedMailbox;
 use crate::message::{Answer, BastionMessage, Message, Msg};
+#[cfg(feature = "metrics")]
+use crate::metrics::GroupMetrics;
 use crate::supervisor::SupervisorRef;
-use crate::{prelude::ReceiveError, system::SYSTEM};
+use crate::time::Delay;
+use crate::{
+    prelude::{AskError, DispatchError, ReceiveError, SendError},
+    system,
+};
 
+use bastion_executor::pool;
 use crossbeam_queue::SegQueue;
+use futures::future;
 use futures::pending;
 use futures::FutureExt;
-use futures_timer::Delay;
 #[cfg(feature = "scaling")]
 use lever::table::lotable::LOTable;
+use std::any::Any;
 use std::fmt::{self, Display, Formatter};
+use std::future::Future;
 use std::pin::Pin;
 #[cfg(feature = "scaling")]
 use std::sync::atomic::AtomicU64;
-use std::{sync::Arc, time::Duration};
-use tracing::{debug, trace};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Duration;
+use tracing::{debug, trace, warn};
 use uuid::Uuid;
 
 /// Identifier for a root supervisor and dead-letters children.
@@ -112,9 +129,51 @@ pub struct BastionContext {
     state: Arc<Pin<Box<ContextState>>>,
 }
 
+#[derive(Debug)]
+/// What [`BastionContext::recv_or`] was ready with first.
+///
+/// [`BastionContext::recv_or`]: struct.BastionContext.html#method.recv_or
+pub enum RecvOr<T> {
+    /// A message was received by the element.
+    Message(SignedMessage),
+    /// The future completed, with this output.
+    Completed(T),
+}
+
+// The state that a child persists across its restarts.
+type PersistentState = Mutex<Option<Box<dyn Any + Send>>>;
+
 #[derive(Debug)]
 pub(crate) struct ContextState {
     messages: SegQueue<SignedMessage>,
+    // Messages that will be received before the ones in `messages`.
+    priority_messages: SegQueue<SignedMessage>,
+    // Whether the last attempt to retrieve a message found
+    // the mailbox empty.
+    waiting: AtomicBool,
+    // The child's mailbox, if it is bounded.
+    mailbox: Option<Arc<BoundedMailbox>>,
+    // Whether the child's group is paused.
+    group_status: Arc<GroupStatus>,
+    // The state the child persisted, shared by all its
+    // incarnations.
+    persistent_state: Arc<PersistentState>,
+    // The dispatchers the child subscribed to, which it must be
+    // removed from once it stops.
+    subscriptions: Mutex<Vec<DispatcherType>>,
+    // Whether the child was removed from its dispatchers, which
+    // happens once whichever way it stops.
+    left_dispatchers: AtomicBool,
+    // The child's actor-local storage.
+    locals: Arc<Locals>,
+    // Whether the child's behavior is about to be hot-swapped,
+    // which holds its messages back until it is.
+    swapping: AtomicBool,
+    // The closure the child's behavior was hot-swapped to, shared
+    // by all its incarnations.
+    swapped_init: Arc<Mutex<Option<Init>>>,
+    #[cfg(feature = "metrics")]
+    group_metrics: Arc<GroupMetrics>,
     #[cfg(feature = "scaling")]
     stats: Arc<AtomicU64>,
     #[cfg(feature = "scaling")]
@@ -123,7 +182,22 @@ pub(crate) struct ContextState {
 
 impl BastionId {
     pub(crate) fn new() -> Self {
-        let uuid = Uuid::new_v4();
+        // The ids of a deterministic runtime are drawn from its seed,
+        // as the order of what is keyed by id depends on them.
+        let pool = system::try_current()
+            .map(|system| system.pool())
+            .or_else(pool::try_get);
+        let seeded = pool.and_then(|pool| Some((pool.seeded_random()?, pool.seeded_random()?)));
+        let uuid = match seeded {
+            Some((high, low)) => {
+                let bytes = ((u128::from(high) << 64) | u128::from(low)).to_be_bytes();
+                uuid::Builder::from_bytes(bytes)
+                    .set_variant(uuid::Variant::RFC4122)
+                    .set_version(uuid::Version::Random)
+                    .build()
+            }
+            None => Uuid::new_v4(),
+        };
 
         BastionId(uuid)
     }
@@ -147,6 +221,18 @@ impl BastionContext {
         }
     }
 
+    // Creates a context for the future that replaces the one using
+    // this context, once the child's behavior gets hot-swapped.
+    pub(crate) fn renew(&self) -> Self {
+        BastionContext::new(
+            self.id.clone(),
+            self.child.clone(),
+            self.children.clone(),
+            self.supervisor.clone(),
+            self.state.clone(),
+        )
+    }
+
     /// Returns a [`ChildRef`] referencing the children group's
     /// element that is linked to this `BastionContext`.
     ///
@@ -371,6 +457,131 @@ impl BastionContext {
         }
     }
 
+    /// Retrieves asynchronously up to `max` of the messages received by
+    /// the element this `BastionContext` is linked to, and waits (always
+    /// asynchronously) for one if none has been received yet.
+    ///
+    /// Once at least one message was received, it doesn't wait for more
+    /// to fill the batch: with 3 messages queued and a `max` of 100, the
+    /// 3 messages are returned right away. The messages are returned in
+    /// the order [`recv`] would have returned them, so priority messages
+    /// come first.
+    ///
+    /// If you don't need to wait until at least one message
+    /// can be retrieved, use [`try_recv_batch`] instead.
+    ///
+    /// This method returns an empty batch right away if `max` is `0`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             // This will block until a message has been received...
+    ///             let batch: Vec<SignedMessage> = ctx.recv_batch(100).await;
+    ///             // ...and `batch` will contain between 1 and 100 messages.
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`recv`]: #method.recv
+    /// [`try_recv_batch`]: #method.try_recv_batch
+    pub async fn recv_batch(&self, max: usize) -> Vec<SignedMessage> {
+        debug!(
+            "BastionContext({}): Waiting to receive up to {} messages.",
+            self.id, max
+        );
+        let mut batch = Vec::new();
+        if max == 0 {
+            return batch;
+        }
+
+        loop {
+            self.pop_messages(&mut batch, max);
+            if !batch.is_empty() {
+                return batch;
+            }
+            pending!();
+        }
+    }
+
+    /// Retrieves asynchronously up to `max` of the messages received by
+    /// the element this `BastionContext` is linked to, without waiting
+    /// for any.
+    ///
+    /// If you want to wait until at least one message
+    /// can be retrieved, use [`recv_batch`] instead.
+    ///
+    /// This method returns the messages that were available, in the
+    /// order [`try_recv`] would have returned them, which is an empty
+    /// batch if none were.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             let batch: Vec<SignedMessage> = ctx.try_recv_batch(100).await;
+    ///             // `batch` will contain the messages received by the
+    ///             // element, up to 100 of them, or be empty.
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`recv_batch`]: #method.recv_batch
+    /// [`try_recv`]: #method.try_recv
+    pub async fn try_recv_batch(&self, max: usize) -> Vec<SignedMessage> {
+        // We want to let a tick pass
+        // otherwise guard will never contain anything.
+        Delay::new(Duration::from_millis(0)).await;
+
+        trace!(
+            "BastionContext({}): Trying to receive up to {} messages.",
+            self.id,
+            max
+        );
+        let mut batch = Vec::new();
+        self.pop_messages(&mut batch, max);
+        batch
+    }
+
+    // Moves the available messages into `batch` until it holds `max` of them.
+    fn pop_messages(&self, batch: &mut Vec<SignedMessage>, max: usize) {
+        while batch.len() < max {
+            match self.state.pop_message() {
+                Some(msg) => {
+                    trace!("BastionContext({}): Received message: {:?}", self.id, msg);
+                    batch.push(msg);
+                }
+                None => break,
+            }
+        }
+    }
+
     /// Retrieves asynchronously a message received by the element
     /// this `BastionContext` is linked to and waits until `timeout` (always
     /// asynchronously) for one if none has been received yet.
@@ -432,6 +643,79 @@ impl BastionContext {
         }
     }
 
+    /// Retrieves asynchronously a message received by the element
+    /// this `BastionContext` is linked to, or the output of `fut`,
+    /// whichever is ready first.
+    ///
+    /// `fut` is polled before the mailbox, so that it can't get
+    /// starved by a steady flow of messages. A message is only taken
+    /// out of the mailbox if it is returned: when `fut` completes
+    /// first, the messages that were received stay queued for the
+    /// next call to [`recv`].
+    ///
+    /// This method returns [`RecvOr::Message`] if a message was
+    /// received, or [`RecvOr::Completed`] with the output of `fut`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use bastion::time::Delay;
+    /// # use std::time::Duration;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             let tick = Delay::new(Duration::from_millis(100));
+    ///             match ctx.recv_or(tick).await {
+    ///                 RecvOr::Message(msg) => {
+    ///                     // A message was received before the tick...
+    ///                 }
+    ///                 RecvOr::Completed(()) => {
+    ///                     // The tick happened first, and the messages
+    ///                     // received meanwhile are still queued...
+    ///                 }
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`recv`]: #method.recv
+    /// [`RecvOr::Message`]: enum.RecvOr.html#variant.Message
+    /// [`RecvOr::Completed`]: enum.RecvOr.html#variant.Completed
+    pub async fn recv_or<F: Future>(&self, fut: F) -> RecvOr<F::Output> {
+        debug!(
+            "BastionContext({}): Waiting to receive message or a future's output.",
+            self.id
+        );
+        futures::pin_mut!(fut);
+        future::poll_fn(|cx| {
+            if let Poll::Ready(output) = fut.as_mut().poll(cx) {
+                trace!("BastionContext({}): The future completed first.", self.id);
+                return Poll::Ready(RecvOr::Completed(output));
+            }
+
+            match self.state.pop_message() {
+                Some(msg) => {
+                    trace!("BastionContext({}): Received message: {:?}", self.id, msg);
+                    Poll::Ready(RecvOr::Message(msg))
+                }
+                // The child is woken up by the next message.
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
+
     /// Returns [`RefAddr`] of the current `BastionContext`
     ///
     /// # Example
@@ -459,10 +743,161 @@ impl BastionContext {
     ///
     /// [`RefAddr`]: /prelude/struct.Answer.html
     pub fn signature(&self) -> RefAddr {
-        RefAddr::new(
-            self.current().path().clone(),
-            self.current().sender().clone(),
-        )
+        self.current().addr()
+    }
+
+    /// Reports a message that this context's child received but
+    /// didn't handle as a dead letter, along with its sender's
+    /// signature.
+    ///
+    /// This is meant to be called in the default case of [`msg!`],
+    /// and does nothing unless a dead letters handler was registered
+    /// (e.g. with [`Bastion::on_dead_letter`]).
+    ///
+    /// [`msg!`]: ../macro.msg.html
+    /// [`Bastion::on_dead_letter`]: ../struct.Bastion.html#method.on_dead_letter
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             loop {
+    ///                 msg! { ctx.recv().await?,
+    ///                     msg: &'static str => {
+    ///                         // Handle the message...
+    ///                     };
+    ///                     msg: _ => ctx.unhandled(msg, signature!());
+    ///                 }
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn unhandled(&self, msg: Msg, sign: RefAddr) {
+        debug!("BastionContext({}): Unhandled message: {:?}", self.id, msg);
+        let recipient = Some(self.current().path().clone());
+        let msg = SignedMessage::new(msg, sign);
+        dead_letters::report(msg, DeadLetterReason::Unhandled, recipient);
+    }
+
+    /// Calls `f` with this context's child's actor-local value of type
+    /// `T`, creating it first if the child didn't access it yet.
+    ///
+    /// This is the same value as the one [`local::with`] gives when
+    /// called by the child, which doesn't need the context.
+    ///
+    /// [`local::with`]: ../local/fn.with.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if the child is already accessing its value of
+    /// type `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             loop {
+    ///                 ctx.recv().await?;
+    ///                 let received = ctx.with_local(|received: &mut u64| {
+    ///                     *received += 1;
+    ///                     *received
+    ///                 });
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn with_local<T, F, R>(&self, f: F) -> R
+    where
+        T: Default + Send + 'static,
+        F: FnOnce(&mut T) -> R,
+    {
+        self.state.locals.with(f)
+    }
+
+    pub(crate) fn locals(&self) -> &Arc<Locals> {
+        &self.state.locals
+    }
+
+    /// Persists a state that survives the restarts of this context's
+    /// child, replacing the one that was persisted before.
+    ///
+    /// The state is kept by the child's supervisor, so that a child
+    /// restarted after a panic can get it back with
+    /// [`take_persistent_state`]. It is dropped when the child stops
+    /// for good: once it stopped, it got killed or its supervisor
+    /// doesn't restart it anymore.
+    ///
+    /// [`take_persistent_state`]: #method.take_persistent_state
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             // Continues where the previous incarnation stopped.
+    ///             let mut seq = ctx.take_persistent_state::<u64>().unwrap_or(0);
+    ///             loop {
+    ///                 ctx.recv().await?;
+    ///                 seq += 1;
+    ///                 ctx.set_persistent_state(seq);
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn set_persistent_state<T: Send + 'static>(&self, state: T) {
+        trace!("BastionContext({}): Persisting state.", self.id);
+        *self.state.persistent_state.lock().unwrap() = Some(Box::new(state));
+    }
+
+    /// Takes the state that this context's child or one of its
+    /// previous incarnations persisted with [`set_persistent_state`],
+    /// if it is of type `T`.
+    ///
+    /// A state of another type is left persisted.
+    ///
+    /// [`set_persistent_state`]: #method.set_persistent_state
+    pub fn take_persistent_state<T: Send + 'static>(&self) -> Option<T> {
+        trace!("BastionContext({}): Taking persisted state.", self.id);
+        let mut persistent_state = self.state.persistent_state.lock().unwrap();
+        match persistent_state.take()?.downcast() {
+            Ok(state) => Some(*state),
+            Err(state) => {
+                *persistent_state = Some(state);
+                None
+            }
+        }
     }
 
     /// Sends a message to the specified [`RefAddr`]
@@ -509,62 +944,168 @@ impl BastionContext {
         let msg = BastionMessage::tell(msg);
         let env = Envelope::new_with_sign(msg, self.signature());
         // FIXME: panics?
-        to.sender()
-            .unbounded_send(env)
+        to.try_send(env)
             .map_err(|err| err.into_inner().into_msg().unwrap())
     }
 
-    /// Sends a message from behalf of current context to the addr,
-    /// allowing to addr owner answer.
+    /// Sends a message to the specified [`RefAddr`], waiting for
+    /// its mailbox to have room for it if it is full and its
+    /// [`MailboxPolicy`] is `Backpressure`.
     ///
-    /// This method returns [`Answer`] if it succeeded, or `Err(msg)`
-    /// otherwise.
+    /// This method returns `()` if it succeeded, or a
+    /// [`SendError`] containing the message otherwise.
     ///
-    /// # Argument
+    /// # Arguments
     ///
-    /// * `msg` - The message to send.
+    /// * `to` – the [`RefAddr`] to send the message to
+    /// * `msg` – The actual message to send
     ///
     /// # Example
     ///
-    /// ```
+    /// ```rust
     /// # use bastion::prelude::*;
     /// #
-    /// # fn main() {
-    ///     # Bastion::init();
-    /// // The message that will be "asked"...
-    /// const ASK_MSG: &'static str = "A message containing data (ask).";
-    /// // The message the will be "answered"...
-    /// const ANSWER_MSG: &'static str = "A message containing data (answer).";
-    ///
-    ///     # let children_ref =
-    /// // Create a new child...
+    /// # Bastion::init();
+    /// #
     /// Bastion::children(|children| {
     ///     children.with_exec(|ctx: BastionContext| {
     ///         async move {
-    ///             // ...which will receive the message asked...
-    ///             msg! { ctx.recv().await?,
-    ///                 msg: &'static str =!> {
-    ///                     assert_eq!(msg, ASK_MSG);
-    ///                     // Handle the message...
-    ///
-    ///                     // ...and eventually answer to it...
-    ///                     answer!(ctx, ANSWER_MSG);
-    ///                 };
-    ///                 // This won't happen because this example
-    ///                 // only "asks" a `&'static str`...
-    ///                 _: _ => ();
-    ///             }
-    ///
+    ///             let smsg: SignedMessage = ctx.recv().await?;
+    ///             let sender_addr = smsg.signature();
+    ///             // Wait for the sender to have room for the reply...
+    ///             ctx.tell_async(&sender_addr, "Ack")
+    ///                 .await
+    ///                 .expect("Unable to acknowledge");
     ///             Ok(())
     ///         }
     ///     })
     /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
     ///
-    ///     # Bastion::children(|children| {
-    ///         # children.with_exec(move |ctx: BastionContext| {
-    ///             # let child_ref = children_ref.elems()[0].clone();
-    ///             # async move {
-    /// // Later, the message is "asked" to the child...
+    /// [`RefAddr`]: ../prelude/struct.RefAddr.html
+    /// [`MailboxPolicy`]: ../mailbox/enum.MailboxPolicy.html
+    /// [`SendError`]: ../errors/enum.SendError.html
+    pub async fn tell_async<M: Message>(&self, to: &RefAddr, msg: M) -> Result<(), SendError<M>> {
+        debug!(
+            "{:?}: Telling message: {:?} to: {:?}",
+            self.current().path(),
+            msg,
+            to.path()
+        );
+        let msg = BastionMessage::tell(msg);
+        let env = Envelope::new_with_sign(msg, self.signature());
+        // FIXME: panics?
+        to.send(env)
+            .await
+            .map_err(|err| err.map(|env| env.into_msg().unwrap()))
+    }
+
+    /// Sends a message to the specified [`RefAddr`], which will
+    /// receive it before the other messages waiting in its mailbox
+    /// (see [`ChildRef::tell_priority_anonymously`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `to` – the [`RefAddr`] to send the message to
+    /// * `msg` – The actual message to send
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             let smsg: SignedMessage = ctx.recv().await?;
+    ///             let sender_addr = smsg.signature();
+    ///             // Ask the sender to handle this before its other messages...
+    ///             ctx.tell_priority(&sender_addr, "Checkpoint now")
+    ///                 .expect("Unable to send the message");
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`RefAddr`]: ../prelude/struct.RefAddr.html
+    /// [`ChildRef::tell_priority_anonymously`]: ../children/struct.ChildRef.html#method.tell_priority_anonymously
+    pub fn tell_priority<M: Message>(&self, to: &RefAddr, msg: M) -> Result<(), M> {
+        debug!(
+            "{:?}: Telling high priority message: {:?} to: {:?}",
+            self.current().path(),
+            msg,
+            to.path()
+        );
+        let msg = BastionMessage::tell(msg);
+        let env = Envelope::new_with_sign(msg, self.signature()).with_high_priority();
+        // FIXME: panics?
+        to.try_send(env)
+            .map_err(|err| err.into_inner().into_msg().unwrap())
+    }
+
+    /// Sends a message from behalf of current context to the addr,
+    /// allowing to addr owner answer.
+    ///
+    /// This method returns [`Answer`] if it succeeded, or `Err(msg)`
+    /// otherwise. The answer fails once the default ask timeout of
+    /// the addr owner's children group elapsed, if it has one.
+    ///
+    /// # Argument
+    ///
+    /// * `msg` - The message to send.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    /// // The message that will be "asked"...
+    /// const ASK_MSG: &'static str = "A message containing data (ask).";
+    /// // The message the will be "answered"...
+    /// const ANSWER_MSG: &'static str = "A message containing data (answer).";
+    ///
+    ///     # let children_ref =
+    /// // Create a new child...
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             // ...which will receive the message asked...
+    ///             msg! { ctx.recv().await?,
+    ///                 msg: &'static str =!> {
+    ///                     assert_eq!(msg, ASK_MSG);
+    ///                     // Handle the message...
+    ///
+    ///                     // ...and eventually answer to it...
+    ///                     answer!(ctx, ANSWER_MSG);
+    ///                 };
+    ///                 // This won't happen because this example
+    ///                 // only "asks" a `&'static str`...
+    ///                 _: _ => ();
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///
+    ///     # Bastion::children(|children| {
+    ///         # children.with_exec(move |ctx: BastionContext| {
+    ///             # let child_ref = children_ref.elems()[0].clone();
+    ///             # async move {
+    /// // Later, the message is "asked" to the child...
     /// let answer: Answer = ctx.ask(&child_ref.addr(), ASK_MSG).expect("Couldn't send the message.");
     ///
     /// // ...and the child's answer is received...
@@ -598,15 +1139,92 @@ impl BastionContext {
             to
         );
         let (msg, answer) = BastionMessage::ask(msg);
+        let answer = answer.with_timeout(to.ask_timeout());
         let env = Envelope::new_with_sign(msg, self.signature());
         // FIXME: panics?
-        to.sender()
-            .unbounded_send(env)
+        to.try_send(env)
             .map_err(|err| err.into_inner().into_msg().unwrap())?;
 
         Ok(answer)
     }
 
+    /// Sends a message from behalf of current context to the addr,
+    /// allowing to addr owner answer, and waits until `timeout`
+    /// (always asynchronously) for the answer.
+    ///
+    /// This method returns the answer as a [`SignedMessage`] if it
+    /// succeeded, `Err(AskError::Timeout)` if no answer was received
+    /// on time, `Err(AskError::Unreachable)` if the addr owner was
+    /// already dead, `Err(AskError::MailboxFull)` if its mailbox
+    /// was full or `Err(AskError::Dropped)` if it dropped the
+    /// message without answering it.
+    ///
+    /// An answer received after the timeout is dropped. The timeout
+    /// overrides the default ask timeout of the addr owner's children
+    /// group.
+    ///
+    /// # Arguments
+    ///
+    /// * `to` – the [`RefAddr`] to send the message to
+    /// * `msg` – The actual message to send
+    /// * `timeout` – How long to wait for the answer
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             let smsg: SignedMessage = ctx.recv().await?;
+    ///             let timeout = Duration::from_millis(100);
+    ///             match ctx.ask_timeout(smsg.signature(), "Still there?", timeout).await {
+    ///                 Ok(answer) => { /* Handle the answer... */ }
+    ///                 Err(AskError::Timeout(_)) => { /* No answer on time... */ }
+    ///                 Err(_) => { /* The sender is gone... */ }
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`SignedMessage`]: ../prelude/struct.SignedMessage.html
+    /// [`RefAddr`]: ../prelude/struct.RefAddr.html
+    pub async fn ask_timeout<M: Message>(
+        &self,
+        to: &RefAddr,
+        msg: M,
+        timeout: Duration,
+    ) -> Result<SignedMessage, AskError> {
+        debug!(
+            "{:?}: Asking message: {:?} to: {:?}",
+            self.current().path(),
+            msg,
+            to
+        );
+        let (msg, answer) = BastionMessage::ask(msg);
+        let env = Envelope::new_with_sign(msg, self.signature());
+        to.try_send(env).map_err(|err| match err {
+            SendError::MailboxFull(_) => AskError::MailboxFull,
+            SendError::Unreachable(_) => AskError::Unreachable,
+        })?;
+
+        futures::select! {
+            answer = answer.fuse() => answer.map_err(|_| AskError::Dropped),
+            _ = Delay::new(timeout).fuse() => Err(AskError::Timeout(timeout)),
+        }
+    }
+
     /// Sends the notification to each declared dispatcher of the actor.
     ///
     /// # Argument
@@ -616,11 +1234,171 @@ impl BastionContext {
     /// * `notification_type` - The type of the notification to send.
     ///
     pub fn notify(&self, dispatchers: &[DispatcherType], notification_type: NotificationType) {
-        let global_dispatcher = SYSTEM.dispatcher();
+        let global_dispatcher = system::current().dispatcher();
         let from_actor = self.current();
         global_dispatcher.notify(from_actor, dispatchers, notification_type);
     }
 
+    /// Registers this context's child in the given dispatcher,
+    /// returning `false` if no such dispatcher exists or the child
+    /// was already registered in it.
+    ///
+    /// Unlike the dispatchers declared with
+    /// [`Children::with_dispatcher`], which all the elements of a
+    /// group are registered in, this lets a child join a dispatcher
+    /// once it is ready to receive its messages. The child is removed
+    /// from it once it stops, and its restarted incarnations aren't
+    /// registered in it.
+    ///
+    /// [`Children::with_dispatcher`]: ../children/struct.Children.html#method.with_dispatcher
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             let ready = DispatcherType::Named("ready".to_string());
+    ///             // Warms up...
+    ///             ctx.subscribe(ready.clone());
+    ///
+    ///             loop {
+    ///                 // Handles the dispatched messages...
+    ///                 ctx.recv().await?;
+    ///                 # break;
+    ///             }
+    ///
+    ///             // Starts draining...
+    ///             ctx.unsubscribe(ready);
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn subscribe(&self, dispatcher: DispatcherType) -> bool {
+        debug!(
+            "BastionContext({}): Subscribing to {:?}.",
+            self.id, dispatcher
+        );
+        let global_dispatcher = system::current().dispatcher();
+        let module_name = module_path!().to_string();
+        match global_dispatcher.subscribe(&dispatcher, self.current(), module_name) {
+            Ok(subscribed) => {
+                if subscribed {
+                    self.state.subscriptions.lock().unwrap().push(dispatcher);
+                }
+
+                subscribed
+            }
+            Err(err) => {
+                warn!(
+                    "BastionContext({}): Couldn't subscribe to {:?}: {}",
+                    self.id, dispatcher, err
+                );
+                false
+            }
+        }
+    }
+
+    /// Removes this context's child from the given dispatcher,
+    /// whether it subscribed to it or its group declared it,
+    /// returning `false` if it wasn't registered in it.
+    ///
+    /// The messages which are being dispatched to the child when it
+    /// unsubscribes are sent before this returns, and none is sent
+    /// to it by the dispatcher afterwards.
+    pub fn unsubscribe(&self, dispatcher: DispatcherType) -> bool {
+        debug!(
+            "BastionContext({}): Unsubscribing from {:?}.",
+            self.id, dispatcher
+        );
+        self.state
+            .subscriptions
+            .lock()
+            .unwrap()
+            .retain(|subscription| *subscription != dispatcher);
+
+        let global_dispatcher = system::current().dispatcher();
+        global_dispatcher.unsubscribe(&dispatcher, self.current())
+    }
+
+    /// Makes this context's child receive the membership changes of
+    /// the given dispatcher as [`MembershipEvent`]s, returning `false`
+    /// if no such dispatcher exists or the child already watches it.
+    ///
+    /// The child first receives a `MemberJoined` event for each of
+    /// the dispatcher's current members, and then the events of the
+    /// actors joining and leaving it, in the order they happened.
+    /// Only the public actors, which the messages are dispatched to,
+    /// are watched.
+    ///
+    /// [`MembershipEvent`]: ../dispatcher/enum.MembershipEvent.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             ctx.watch_members(DispatcherType::Named("workers".to_string()));
+    ///
+    ///             loop {
+    ///                 msg! { ctx.recv().await?,
+    ///                     event: MembershipEvent => {
+    ///                         match event {
+    ///                             MembershipEvent::MemberJoined { member, .. } => {
+    ///                                 // Gives it some shards...
+    ///                             }
+    ///                             MembershipEvent::MemberLeft { member, reason, .. } => {
+    ///                                 // Moves its shards to the others...
+    ///                             }
+    ///                         }
+    ///                     };
+    ///                     _: _ => ();
+    ///                 }
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn watch_members(&self, dispatcher: DispatcherType) -> bool {
+        debug!(
+            "BastionContext({}): Watching the members of {:?}.",
+            self.id, dispatcher
+        );
+        let global_dispatcher = system::current().dispatcher();
+        global_dispatcher.watch(&dispatcher, self.current())
+    }
+
+    /// Makes this context's child stop receiving the membership
+    /// changes of the given dispatcher, returning `false` if it
+    /// didn't watch it.
+    pub fn unwatch_members(&self, dispatcher: DispatcherType) -> bool {
+        debug!(
+            "BastionContext({}): Unwatching the members of {:?}.",
+            self.id, dispatcher
+        );
+        let global_dispatcher = system::current().dispatcher();
+        global_dispatcher.unwatch(&dispatcher, self.current())
+    }
+
     /// Sends the broadcasted message to the target group(s).
     ///
     /// # Argument
@@ -636,15 +1414,251 @@ impl BastionContext {
             sign: self.signature(),
         });
 
-        let global_dispatcher = SYSTEM.dispatcher();
+        let global_dispatcher = system::current().dispatcher();
         global_dispatcher.broadcast_message(target, &msg);
     }
+
+    /// Sends the message to the group with the given name, as
+    /// [`broadcast_message`] does with [`BroadcastTarget::Group`],
+    /// but returns `Err(DispatchError::NoAvailableRecipient)` instead
+    /// of reporting it as a dead letter if no live actor of the group
+    /// could receive it.
+    ///
+    /// With the default round-robin handler, the message is sent to the
+    /// next live actor of the group, skipping the dead ones and the ones
+    /// whose mailbox is full. If the mailboxes of all the live actors
+    /// are full, `Err(DispatchError::MailboxFull)` is returned instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The name of the group's dispatcher.
+    /// * `message` - The broadcasted message.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| async move {
+    ///         if let Err(DispatchError::NoAvailableRecipient) = ctx.send_to_group("workers", "job") {
+    ///             // No worker is alive...
+    ///         }
+    ///         Ok(())
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`broadcast_message`]: #method.broadcast_message
+    /// [`BroadcastTarget::Group`]: ../dispatcher/enum.BroadcastTarget.html#variant.Group
+    pub fn send_to_group<M: Message>(
+        &self,
+        group: impl Into<String>,
+        message: M,
+    ) -> Result<(), DispatchError> {
+        let msg = Arc::new(SignedMessage {
+            msg: Msg::broadcast(message),
+            sign: self.signature(),
+        });
+
+        let global_dispatcher = system::current().dispatcher();
+        global_dispatcher.dispatch(group.into(), &msg)
+    }
+
+    /// Sends the broadcasted message to the members of the target
+    /// group(s) for which the predicate returns `true`, whatever the
+    /// handlers of their dispatchers are, and returns how many
+    /// members it was sent to.
+    ///
+    /// The message is shared by its recipients instead of being
+    /// cloned for each of them. If no member matched, the message is
+    /// reported as a dead letter.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Defines the groups whose members are filtered.
+    /// * `message` - The broadcasted message.
+    /// * `predicate` - Tells whether the message should be sent to
+    ///   a member.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// # Bastion::children(|children| {
+    /// #     children.with_exec(|ctx: BastionContext| async move {
+    /// // Only notifies the members handling the partitions 0 to 3.
+    /// let target = BroadcastTarget::Group("partitions".to_string());
+    /// ctx.broadcast_filtered(target, "reload", |member: &ChildRef| {
+    ///     member.name().parse::<u32>().is_ok_and(|partition| partition < 4)
+    /// });
+    /// #         Ok(())
+    /// #     })
+    /// # }).unwrap();
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn broadcast_filtered<M, P>(
+        &self,
+        target: BroadcastTarget,
+        message: M,
+        predicate: P,
+    ) -> usize
+    where
+        M: Message,
+        P: Fn(&ChildRef) -> bool,
+    {
+        let msg = Arc::new(SignedMessage {
+            msg: Msg::broadcast(message),
+            sign: self.signature(),
+        });
+
+        let global_dispatcher = system::current().dispatcher();
+        global_dispatcher.broadcast_filtered(target, &msg, predicate)
+    }
+
+    /// Sends the message to every member of the group with the given
+    /// name, whatever the handler of its dispatcher is, and returns
+    /// what happened to it for each of them.
+    ///
+    /// Unlike with [`broadcast_message`], the members whose mailbox was
+    /// full or who were dead aren't reported as dead letters, so the
+    /// caller can decide whether to send it again to them.
+    ///
+    /// The message is shared by its recipients instead of being
+    /// cloned for each of them, which only happens when a recipient
+    /// needs to own it.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The name of the group's dispatcher.
+    /// * `message` - The message to send.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| async move {
+    ///         for (member, delivery) in ctx.tell_everyone("workers", "reload") {
+    ///             if delivery == Delivery::MailboxFull {
+    ///                 // `member` didn't get the new configuration...
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`broadcast_message`]: #method.broadcast_message
+    pub fn tell_everyone<M: Message>(
+        &self,
+        group: impl Into<String>,
+        message: M,
+    ) -> Vec<(ChildRef, Delivery)> {
+        let msg = Arc::new(SignedMessage {
+            msg: Msg::broadcast(message),
+            sign: self.signature(),
+        });
+
+        let global_dispatcher = system::current().dispatcher();
+        global_dispatcher
+            .members(group.into())
+            .into_iter()
+            .map(|member| {
+                let delivery = Delivery::of(&member.try_tell(msg.clone()));
+                (member, delivery)
+            })
+            .collect()
+    }
+
+    /// Sends the message to every member of the group with the given
+    /// name as [`tell_everyone`] does, but waits for the full mailboxes
+    /// to have room for it (whatever their [`MailboxPolicy`] is) for
+    /// up to `timeout`.
+    ///
+    /// The members whose mailbox was still full once `timeout` passed
+    /// are returned with [`Delivery::MailboxFull`].
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The name of the group's dispatcher.
+    /// * `message` - The message to send.
+    /// * `timeout` - How long to wait for the full mailboxes.
+    ///
+    /// [`tell_everyone`]: #method.tell_everyone
+    /// [`MailboxPolicy`]: ../mailbox/enum.MailboxPolicy.html
+    /// [`Delivery::MailboxFull`]: ../dispatcher/enum.Delivery.html#variant.MailboxFull
+    pub async fn tell_everyone_timeout<M: Message>(
+        &self,
+        group: impl Into<String>,
+        message: M,
+        timeout: Duration,
+    ) -> Vec<(ChildRef, Delivery)> {
+        let msg = Arc::new(SignedMessage {
+            msg: Msg::broadcast(message),
+            sign: self.signature(),
+        });
+
+        let global_dispatcher = system::current().dispatcher();
+        let deliveries = global_dispatcher
+            .members(group.into())
+            .into_iter()
+            .map(|member| {
+                let msg = msg.clone();
+                async move {
+                    let delivery = match member.try_tell(msg) {
+                        Err(SendError::MailboxFull(msg)) => {
+                            let send = member.tell_when_room(msg).map(|res| Delivery::of(&res));
+                            let timeout = Delay::new(timeout).map(|()| Delivery::MailboxFull);
+                            future::select(Box::pin(send), timeout)
+                                .await
+                                .factor_first()
+                                .0
+                        }
+                        res => Delivery::of(&res),
+                    };
+                    (member, delivery)
+                }
+            });
+
+        future::join_all(deliveries).await
+    }
 }
 
 impl ContextState {
     pub(crate) fn new() -> Self {
         ContextState {
             messages: SegQueue::new(),
+            priority_messages: SegQueue::new(),
+            waiting: AtomicBool::new(false),
+            mailbox: None,
+            group_status: Arc::default(),
+            persistent_state: Arc::default(),
+            subscriptions: Mutex::default(),
+            left_dispatchers: AtomicBool::new(false),
+            locals: Arc::default(),
+            swapping: AtomicBool::new(false),
+            swapped_init: Arc::default(),
+            #[cfg(feature = "metrics")]
+            group_metrics: Arc::default(),
             #[cfg(feature = "scaling")]
             stats: Arc::new(AtomicU64::new(0)),
             #[cfg(feature = "scaling")]
@@ -672,17 +1686,134 @@ impl ContextState {
         self.actor_stats.clone()
     }
 
+    #[cfg(feature = "metrics")]
+    pub(crate) fn set_group_metrics(&mut self, group_metrics: Arc<GroupMetrics>) {
+        self.group_metrics = group_metrics;
+    }
+
+    pub(crate) fn set_mailbox(&mut self, mailbox: Option<Arc<BoundedMailbox>>) {
+        self.mailbox = mailbox;
+    }
+
+    pub(crate) fn set_group_status(&mut self, group_status: Arc<GroupStatus>) {
+        self.group_status = group_status;
+    }
+
+    pub(crate) fn mailbox(&self) -> Option<&Arc<BoundedMailbox>> {
+        self.mailbox.as_ref()
+    }
+
+    // Makes the child share the state persisted by its previous
+    // incarnations.
+    pub(crate) fn restore_persistent_state(&mut self, restored: &ContextState) {
+        self.persistent_state = restored.persistent_state.clone();
+        self.swapped_init = restored.swapped_init.clone();
+    }
+
+    pub(crate) fn clear_persistent_state(&self) {
+        self.persistent_state.lock().unwrap().take();
+    }
+
+    pub(crate) fn clear_locals(&self) {
+        self.locals.clear();
+    }
+
+    pub(crate) fn take_subscriptions(&self) -> Vec<DispatcherType> {
+        std::mem::take(&mut *self.subscriptions.lock().unwrap())
+    }
+
+    // Returns whether the child has to be removed from its
+    // dispatchers, which is only the case the first time.
+    pub(crate) fn leave_dispatchers(&self) -> bool {
+        !self.left_dispatchers.swap(true, Ordering::SeqCst)
+    }
+
+    // Holds the messages back until the child's behavior gets
+    // hot-swapped.
+    pub(crate) fn start_swapping(&self) {
+        self.swapping.store(true, Ordering::SeqCst);
+    }
+
+    // Returns whether the child's future is done with the last
+    // message it retrieved, and can get swapped.
+    pub(crate) fn can_swap(&self) -> bool {
+        self.swapping.load(Ordering::SeqCst) && self.is_waiting()
+    }
+
+    // Makes the child use `init` from now on, including once it
+    // gets restarted, and lets it retrieve its messages again.
+    pub(crate) fn swap_init(&self, init: Init) {
+        *self.swapped_init.lock().unwrap() = Some(init);
+        self.swapping.store(false, Ordering::SeqCst);
+    }
+
+    // Creates the future that a restarted child executes, which is
+    // the one it was hot-swapped to if it was.
+    pub(crate) fn exec(&self, init: &Init, ctx: BastionContext) -> Exec {
+        match &*self.swapped_init.lock().unwrap() {
+            Some(swapped) => (swapped.0)(ctx),
+            None => (init.0)(ctx),
+        }
+    }
+
     pub(crate) fn push_message(&self, msg: Msg, sign: RefAddr) {
+        #[cfg(feature = "metrics")]
+        self.group_metrics.record_message();
         self.messages.push(SignedMessage::new(msg, sign))
     }
 
+    pub(crate) fn push_priority_message(&self, msg: Msg, sign: RefAddr) {
+        #[cfg(feature = "metrics")]
+        self.group_metrics.record_message();
+        self.priority_messages.push(SignedMessage::new(msg, sign))
+    }
+
+    // Takes the messages that weren't received yet, even if the
+    // group is paused.
+    pub(crate) fn take_messages(&self) -> Vec<SignedMessage> {
+        let mut messages = Vec::new();
+        while let Ok(msg) = self
+            .priority_messages
+            .pop()
+            .or_else(|_| self.messages.pop())
+        {
+            messages.push(msg);
+        }
+        messages
+    }
+
     pub(crate) fn pop_message(&self) -> Option<SignedMessage> {
-        self.messages.pop().ok()
+        // Messages keep waiting in the mailbox while the group
+        // is paused.
+        if self.group_status.is_paused() {
+            return None;
+        }
+
+        // The child's future isn't given any message anymore once
+        // its behavior is about to be hot-swapped.
+        if self.swapping.load(Ordering::SeqCst) {
+            self.waiting.store(true, Ordering::SeqCst);
+            return None;
+        }
+
+        let msg = self
+            .priority_messages
+            .pop()
+            .or_else(|_| self.messages.pop())
+            .ok();
+        self.waiting.store(msg.is_none(), Ordering::SeqCst);
+        if let (Some(_), Some(mailbox)) = (&msg, &self.mailbox) {
+            mailbox.release();
+        }
+        msg
+    }
+
+    pub(crate) fn is_waiting(&self) -> bool {
+        self.waiting.load(Ordering::SeqCst)
     }
 
-    #[cfg(feature = "scaling")]
     pub(crate) fn mailbox_size(&self) -> u32 {
-        self.messages.len() as _
+        (self.priority_messages.len() + self.messages.len()) as _
     }
 }
 
@@ -805,6 +1936,79 @@ mod context_tests {
         assert!(children.broadcast("test recv timeout").is_ok());
     }
 
+    #[test]
+    fn test_priority_mailbox() {
+        let state = ContextState::new();
+        state.push_message(Msg::tell(1), RefAddr::dead_letters());
+        state.push_priority_message(Msg::tell(2), RefAddr::dead_letters());
+        state.push_message(Msg::tell(3), RefAddr::dead_letters());
+        state.push_priority_message(Msg::tell(4), RefAddr::dead_letters());
+
+        let received = std::iter::from_fn(|| state.pop_message())
+            .map(|smsg| smsg.extract().0.downcast::<i32>().unwrap())
+            .collect::<Vec<_>>();
+
+        // High priority messages first, each level in FIFO order.
+        assert_eq!(received, vec![2, 4, 1, 3]);
+    }
+
+    #[cfg(feature = "scaling")]
+    #[test]
+    fn test_restarted_child_shares_group_stats() {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        let runtime = Runtime::builder().with_deterministic_seed(0).build();
+        runtime.start();
+
+        let states: Arc<Mutex<Vec<Arc<Pin<Box<ContextState>>>>>> = Arc::default();
+        let failed = Arc::new(AtomicBool::new(false));
+        let exec_states = states.clone();
+        runtime
+            .supervisor(move |sp| {
+                sp.children(move |children| {
+                    children
+                        .with_heartbeat_tick(Duration::from_secs(1))
+                        // Fails once, so the child gets restarted.
+                        .with_health_check(move |_child: ChildRef| {
+                            let failed = failed.clone();
+                            async move {
+                                if failed.swap(true, Ordering::SeqCst) {
+                                    Ok(())
+                                } else {
+                                    Err(())
+                                }
+                            }
+                        })
+                        .with_exec(move |ctx: BastionContext| {
+                            exec_states.lock().unwrap().push(ctx.state.clone());
+                            async move {
+                                loop {
+                                    ctx.recv().await?;
+                                }
+                            }
+                        })
+                })
+            })
+            .expect("Couldn't create the supervisor.");
+        runtime.run_until_idle();
+        for _ in 0..3 {
+            runtime.advance_time(Duration::from_secs(1));
+        }
+
+        let states = states.lock().unwrap();
+        assert_eq!(states.len(), 2);
+        assert!(Arc::ptr_eq(&states[1].stats(), &states[0].stats()));
+        assert!(Arc::ptr_eq(
+            &states[1].actor_stats(),
+            &states[0].actor_stats()
+        ));
+        drop(states);
+
+        runtime.shutdown();
+    }
+
     fn run_test<T>(test: T) -> ()
     where
         T: FnOnce() -> () + panic::UnwindSafe,
@@ -0,0 +1,125 @@
+//!
+//! A process-wide sink for the messages that couldn't be delivered
+//! to (or weren't handled by) their recipient, allowing to inspect
+//! or replay them.
+//!
+//! Nothing is captured until a handler is registered with
+//! [`Bastion::on_dead_letter`] or [`Bastion::forward_dead_letters`].
+//! Note that the messages given back to their sender (e.g. by
+//! [`ChildRef::tell_anonymously`]) aren't dead letters.
+//!
+//! [`Bastion::on_dead_letter`]: ../struct.Bastion.html#method.on_dead_letter
+//! [`Bastion::forward_dead_letters`]: ../struct.Bastion.html#method.forward_dead_letters
+//! [`ChildRef::tell_anonymously`]: ../child_ref/struct.ChildRef.html#method.tell_anonymously
+use crate::envelope::{Envelope, SignedMessage};
+use crate::errors::SendError;
+use crate::message::BastionMessage;
+use crate::path::BastionPath;
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+type Handler = Arc<dyn Fn(DeadLetter) + Send + Sync>;
+
+lazy_static! {
+    static ref HANDLER: RwLock<Option<Handler>> = RwLock::new(None);
+}
+
+// Whether a handler was registered, checked before doing any work
+// to report a dead letter.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Why a message ended up in the dead letters.
+pub enum DeadLetterReason {
+    /// There was no live recipient to deliver the message to.
+    NoRecipient,
+    /// The recipient's mailbox was full.
+    MailboxFull,
+    /// The recipient stopped (or was restarted) before receiving
+    /// the message.
+    Stopped,
+    /// The recipient received the message but didn't handle it
+    /// (see [`BastionContext::unhandled`]).
+    ///
+    /// [`BastionContext::unhandled`]: ../context/struct.BastionContext.html#method.unhandled
+    Unhandled,
+}
+
+#[derive(Debug)]
+/// A message that couldn't be delivered to (or wasn't handled by)
+/// its recipient.
+pub struct DeadLetter {
+    /// The message, along with its sender's signature.
+    ///
+    /// The messages that couldn't be delivered by a dispatcher
+    /// are the `Arc<SignedMessage>` its children would have
+    /// received.
+    pub envelope: SignedMessage,
+    /// Why the message ended up in the dead letters.
+    pub reason: DeadLetterReason,
+    /// The path of the message's intended recipient, if known.
+    pub recipient: Option<Arc<BastionPath>>,
+    /// When the message ended up in the dead letters.
+    pub timestamp: SystemTime,
+}
+
+impl DeadLetterReason {
+    pub(crate) fn from_send_error<M>(err: &SendError<M>) -> Self {
+        match err {
+            SendError::MailboxFull(_) => DeadLetterReason::MailboxFull,
+            SendError::Unreachable(_) => DeadLetterReason::NoRecipient,
+        }
+    }
+}
+
+pub(crate) fn set_handler<C>(handler: C)
+where
+    C: Fn(DeadLetter) + Send + Sync + 'static,
+{
+    if let Ok(mut current) = HANDLER.write() {
+        *current = Some(Arc::new(handler));
+        ENABLED.store(true, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub(crate) fn report(
+    envelope: SignedMessage,
+    reason: DeadLetterReason,
+    recipient: Option<Arc<BastionPath>>,
+) {
+    if !is_enabled() {
+        return;
+    }
+
+    // The handler is called without holding the lock, since it
+    // could end up reporting another dead letter.
+    let handler = match HANDLER.read() {
+        Ok(handler) => handler.clone(),
+        Err(_) => return,
+    };
+    if let Some(handler) = handler {
+        handler(DeadLetter {
+            envelope,
+            reason,
+            recipient,
+            timestamp: SystemTime::now(),
+        });
+    }
+}
+
+// Reports the user message `env` contains, if any.
+pub(crate) fn report_envelope(
+    env: Envelope,
+    reason: DeadLetterReason,
+    recipient: Option<Arc<BastionPath>>,
+) {
+    if let BastionMessage::Message(msg) = env.msg {
+        report(SignedMessage::new(msg, env.sign), reason, recipient);
+    }
+}
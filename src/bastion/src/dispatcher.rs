@@ -3,16 +3,22 @@
 //! group of actors through the dispatchers that holds information about
 //! actors grouped together.
 use crate::child_ref::ChildRef;
-use crate::envelope::SignedMessage;
+use crate::context::BastionId;
+use crate::dead_letters::{self, DeadLetterReason};
+use crate::envelope::{RefAddr, SignedMessage};
+use crate::errors::{DispatchError, SendError};
+use crate::message::Msg;
 use anyhow::Result as AnyResult;
+use fxhash::FxHashMap;
 use lever::prelude::*;
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug};
 use std::hash::{Hash, Hasher};
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
-    Arc,
+    Arc, Mutex, RwLock,
 };
-use tracing::{debug, trace, warn};
+use tracing::{debug, error, trace, warn};
 
 /// Type alias for the concurrency hashmap. Each key-value pair stores
 /// the Bastion identifier as the key and the module name as the value.
@@ -30,6 +36,72 @@ pub enum NotificationType {
     Remove,
 }
 
+#[derive(Debug, Clone)]
+/// The membership changes of a dispatcher, which the actors that
+/// watch it with [`BastionContext::watch_members`] receive.
+///
+/// The changes of a dispatcher are received in the order they
+/// happened, so a member always leaves after it joined.
+///
+/// [`BastionContext::watch_members`]: ../context/struct.BastionContext.html#method.watch_members
+pub enum MembershipEvent {
+    /// An actor joined the dispatcher, either because its group
+    /// declared it or because it subscribed to it.
+    MemberJoined {
+        /// The dispatcher the actor joined.
+        dispatcher: DispatcherType,
+        /// The address of the actor.
+        member: RefAddr,
+    },
+    /// An actor left the dispatcher. A restarted actor joins it
+    /// again afterwards.
+    MemberLeft {
+        /// The dispatcher the actor left.
+        dispatcher: DispatcherType,
+        /// The address of the actor.
+        member: RefAddr,
+        /// Why the actor left.
+        reason: LeaveReason,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Why an actor left a dispatcher.
+pub enum LeaveReason {
+    /// The actor stopped or finished its execution.
+    Stopped,
+    /// The actor was killed.
+    Killed,
+    /// The actor returned an error or panicked.
+    Faulted,
+    /// The actor unsubscribed from the dispatcher.
+    Unsubscribed,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// What happened to a message sent to a member of a group by
+/// [`BastionContext::tell_everyone`].
+///
+/// [`BastionContext::tell_everyone`]: ../context/struct.BastionContext.html#method.tell_everyone
+pub enum Delivery {
+    /// The message was added to the member's mailbox.
+    Delivered,
+    /// The member's mailbox was full, so it didn't get the message.
+    MailboxFull,
+    /// The member was dead.
+    Unreachable,
+}
+
+impl Delivery {
+    pub(crate) fn of<M>(res: &Result<(), SendError<M>>) -> Self {
+        match res {
+            Ok(()) => Delivery::Delivered,
+            Err(SendError::MailboxFull(_)) => Delivery::MailboxFull,
+            Err(SendError::Unreachable(_)) => Delivery::Unreachable,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Defines types of the notifications handled by the dispatcher
 /// when the group of actors is changing.
@@ -64,6 +136,11 @@ pub enum DispatcherType {
 pub type DefaultDispatcherHandler = RoundRobinHandler;
 
 /// Dispatcher that will do simple round-robin distribution
+///
+/// The children found dead when sending them a message are removed
+/// from the rotation, and the message is sent to the next one. They
+/// join it again once restarted. The children whose mailbox is full are
+/// skipped as well, but stay in the rotation.
 #[derive(Default, Debug)]
 pub struct RoundRobinHandler {
     index: AtomicUsize,
@@ -80,27 +157,482 @@ impl DispatcherHandler for RoundRobinHandler {
     }
     // Each child in turn will receive a message.
     fn broadcast_message(&self, entries: &DispatcherMap, message: &Arc<SignedMessage>) {
-        let entries = entries
+        match self.deliver(entries, message) {
+            Ok(()) => (),
+            Err(Some(full)) => dead_letter(message, DeadLetterReason::MailboxFull, Some(&full)),
+            Err(None) => dead_letter(message, DeadLetterReason::NoRecipient, None),
+        }
+    }
+
+    fn dispatch(
+        &self,
+        entries: &DispatcherMap,
+        message: &Arc<SignedMessage>,
+    ) -> Result<(), DispatchError> {
+        self.deliver(entries, message).map_err(|full| match full {
+            Some(_) => DispatchError::MailboxFull,
+            None => DispatchError::NoAvailableRecipient,
+        })
+    }
+}
+
+impl RoundRobinHandler {
+    // The next live child whose mailbox has room will receive the
+    // message. If there is none, the last child found with a full
+    // mailbox is returned.
+    fn deliver(
+        &self,
+        entries: &DispatcherMap,
+        message: &Arc<SignedMessage>,
+    ) -> Result<(), Option<ChildRef>> {
+        let children = entries
             .iter()
             .filter(|entry| entry.0.is_public())
+            .map(|entry| entry.0)
             .collect::<Vec<_>>();
 
-        if entries.is_empty() {
+        if children.is_empty() {
             debug!("no public children to broadcast message to");
-            return;
+            return Err(None);
         }
-        let current_index = self.index.load(Ordering::SeqCst) % entries.len();
+        let start_index = self.index.load(Ordering::SeqCst);
+        let mut full = None;
 
-        if let Some(entry) = entries.get(current_index) {
-            warn!(
+        for offset in 0..children.len() {
+            let current_index = (start_index + offset) % children.len();
+            let child = &children[current_index];
+            trace!(
                 "sending message to child {}/{} - {}",
                 current_index + 1,
-                entries.len(),
-                entry.0.path()
+                children.len(),
+                child.path()
             );
-            entry.0.tell_anonymously(message.clone()).unwrap();
+            match child.try_tell(message.clone()) {
+                Ok(()) => (),
+                Err(SendError::Unreachable(_)) => {
+                    debug!("child {} is dead, removing it", child.path());
+                    // Its restarted incarnation, which has the same
+                    // identifier, might have joined the group since.
+                    let dead = entries
+                        .iter()
+                        .any(|(entry, _)| &entry == child && entry.sender().is_closed());
+                    if dead {
+                        let _ = entries.remove(child);
+                    }
+                    continue;
+                }
+                Err(SendError::MailboxFull(_)) => {
+                    debug!("child {} has a full mailbox, skipping it", child.path());
+                    full = Some(child.clone());
+                    continue;
+                }
+            }
+
             self.index.store(current_index + 1, Ordering::SeqCst);
+            return Ok(());
+        }
+
+        if full.is_none() {
+            debug!("no live children to broadcast message to");
+        }
+        Err(full)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// Defines what a [`ConsistentHashHandler`] does when the child
+/// a message is routed to is dead.
+///
+/// The default policy is `Fail`.
+///
+/// [`ConsistentHashHandler`]: struct.ConsistentHashHandler.html
+pub enum DeadChildPolicy {
+    /// Don't deliver the message and log an error.
+    #[default]
+    Fail,
+    /// Deliver the message to the next live child on the hash ring.
+    Rehash,
+}
+
+type KeyExtractor = Box<dyn Fn(&Msg) -> Option<u64> + Send + Sync + 'static>;
+
+/// Dispatcher handler that does sticky routing: the messages with the
+/// same routing key are always delivered to the same child, as long as
+/// the group doesn't change.
+///
+/// The children are placed on a hash ring, each of them appearing
+/// [`with_virtual_nodes`] times, and a message is delivered to the first
+/// child found on the ring after its key. When a child joins or leaves the
+/// group, only the keys that it takes over or that were routed to it move
+/// to another child.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// # Bastion::init();
+/// #
+/// #[derive(Debug)]
+/// struct SessionMessage {
+///     session: u64,
+///     data: String,
+/// }
+///
+/// let handler = ConsistentHashHandler::new(|msg: &Msg| {
+///     msg.downcast_ref::<SessionMessage>().map(|msg| msg.session)
+/// })
+/// .with_dead_child_policy(DeadChildPolicy::Rehash);
+///
+/// Bastion::children(|children| {
+///     children.with_dispatcher(
+///         Dispatcher::with_type(DispatcherType::Named("sessions".to_string()))
+///             .with_handler(Box::new(handler)),
+///     )
+/// }).expect("Couldn't create the children group.");
+/// #
+/// # Bastion::start();
+/// # Bastion::stop();
+/// # Bastion::block_until_stopped();
+/// ```
+///
+/// [`with_virtual_nodes`]: #method.with_virtual_nodes
+pub struct ConsistentHashHandler {
+    key_extractor: KeyExtractor,
+    virtual_nodes: usize,
+    dead_child_policy: DeadChildPolicy,
+    ring: RwLock<BTreeMap<u64, ChildRef>>,
+}
+
+impl ConsistentHashHandler {
+    /// The default number of times each child appears on the hash ring.
+    pub const DEFAULT_VIRTUAL_NODES: usize = 64;
+
+    /// Creates a handler routing messages with the key returned
+    /// by `key_extractor`. The messages for which it returns `None`
+    /// aren't delivered.
+    pub fn new<K, F>(key_extractor: F) -> Self
+    where
+        K: Hash,
+        F: Fn(&Msg) -> Option<K> + Send + Sync + 'static,
+    {
+        let key_extractor = Box::new(move |msg: &Msg| key_extractor(msg).map(|key| hash(&key)));
+
+        ConsistentHashHandler {
+            key_extractor,
+            virtual_nodes: Self::DEFAULT_VIRTUAL_NODES,
+            dead_child_policy: DeadChildPolicy::default(),
+            ring: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Sets how many times each child appears on the hash ring. More
+    /// virtual nodes spread the keys more evenly between the children.
+    pub fn with_virtual_nodes(mut self, virtual_nodes: usize) -> Self {
+        trace!("Setting consistent hash virtual nodes: {}", virtual_nodes);
+        self.virtual_nodes = virtual_nodes.max(1);
+        self
+    }
+
+    /// Sets what happens when a message is routed to a dead child.
+    pub fn with_dead_child_policy(mut self, dead_child_policy: DeadChildPolicy) -> Self {
+        trace!(
+            "Setting consistent hash dead child policy: {:?}",
+            dead_child_policy
+        );
+        self.dead_child_policy = dead_child_policy;
+        self
+    }
+
+    fn nodes(&self, id: &BastionId) -> impl Iterator<Item = u64> + '_ {
+        let id = id.clone();
+        (0..self.virtual_nodes).map(move |replica| hash(&(&id, replica)))
+    }
+
+    fn add_child(&self, child: &ChildRef) {
+        let mut ring = self.ring.write().unwrap();
+        for node in self.nodes(child.id()) {
+            ring.insert(node, child.clone());
+        }
+    }
+
+    fn remove_child(&self, child: &ChildRef) {
+        let mut ring = self.ring.write().unwrap();
+        for node in self.nodes(child.id()) {
+            if ring.get(&node) == Some(child) {
+                ring.remove(&node);
+            }
+        }
+    }
+}
+
+impl DispatcherHandler for ConsistentHashHandler {
+    // Places joining children on the ring and removes the leaving ones.
+    fn notify(
+        &self,
+        from_child: &ChildRef,
+        _entries: &DispatcherMap,
+        notification_type: NotificationType,
+    ) {
+        if !from_child.is_public() {
+            return;
+        }
+
+        match notification_type {
+            NotificationType::Register => self.add_child(from_child),
+            NotificationType::Remove => self.remove_child(from_child),
+        }
+    }
+
+    // The child owning the message's key will receive it.
+    fn broadcast_message(&self, _entries: &DispatcherMap, message: &Arc<SignedMessage>) {
+        let key = match (self.key_extractor)(&message.msg) {
+            Some(key) => key,
+            None => {
+                warn!("no routing key for message {:?}", message.msg);
+                return;
+            }
+        };
+
+        let ring = self.ring.read().unwrap();
+        let mut dead_children: Vec<&ChildRef> = Vec::new();
+        for child in ring
+            .range(key..)
+            .chain(ring.range(..key))
+            .map(|node| node.1)
+        {
+            if dead_children.contains(&child) {
+                continue;
+            }
+
+            match child.try_tell(message.clone()) {
+                Ok(()) => {
+                    debug!("sending message with key {} to child {}", key, child.path());
+                    return;
+                }
+                Err(SendError::Unreachable(_))
+                    if self.dead_child_policy == DeadChildPolicy::Rehash =>
+                {
+                    debug!("child {} is dead, rehashing key {}", child.path(), key);
+                    dead_children.push(child);
+                }
+                Err(err) => {
+                    error!(
+                        "couldn't send message with key {} to child {}",
+                        key,
+                        child.path()
+                    );
+                    let reason = DeadLetterReason::from_send_error(&err);
+                    dead_letter(message, reason, Some(child));
+                    return;
+                }
+            }
+        }
+
+        debug!("no live children to send message with key {} to", key);
+        dead_letter(message, DeadLetterReason::NoRecipient, None);
+    }
+}
+
+impl Debug for ConsistentHashHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConsistentHashHandler")
+            .field("virtual_nodes", &self.virtual_nodes)
+            .field("dead_child_policy", &self.dead_child_policy)
+            .finish()
+    }
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    fxhash::hash64(value)
+}
+
+type WeightFn = Arc<dyn Fn(&ChildRef) -> usize + Send + Sync + 'static>;
+
+#[derive(Debug)]
+struct WeightedChild {
+    child: ChildRef,
+    weight: usize,
+    // How far the child is from its share of the messages.
+    current: i64,
+}
+
+/// Dispatcher handler that does smooth weighted round-robin: each
+/// child receives a share of the messages proportional to its weight,
+/// and the messages it receives are spread evenly between the ones
+/// the other children receive.
+///
+/// The weight of a child is returned by the closure given to
+/// [`with_weights`] when it joins the group (`1` by default), and can
+/// be changed afterwards with [`set_weight`], without resetting the
+/// rotation. A child with a weight of `0` doesn't receive messages.
+///
+/// The clones of a handler share its children and their weights, so
+/// a clone can be kept to change them once the handler was given
+/// to a dispatcher.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// # Bastion::init();
+/// #
+/// let handler = WeightedRoundRobinHandler::new().with_weights(|child: &ChildRef| {
+///     // The children named "large" receive three times as many messages.
+///     if child.name() == "large" {
+///         3
+///     } else {
+///         1
+///     }
+/// });
+///
+/// Bastion::children(|children| {
+///     children.with_name("large").with_dispatcher(
+///         Dispatcher::with_type(DispatcherType::Named("workers".to_string()))
+///             .with_handler(Box::new(handler.clone())),
+///     )
+/// }).expect("Couldn't create the children group.");
+/// #
+/// # Bastion::start();
+/// # Bastion::stop();
+/// # Bastion::block_until_stopped();
+/// ```
+///
+/// [`with_weights`]: #method.with_weights
+/// [`set_weight`]: #method.set_weight
+#[derive(Clone)]
+pub struct WeightedRoundRobinHandler {
+    weight_fn: WeightFn,
+    children: Arc<Mutex<Vec<WeightedChild>>>,
+}
+
+impl WeightedRoundRobinHandler {
+    /// Creates a handler giving a weight of `1` to every child.
+    pub fn new() -> Self {
+        WeightedRoundRobinHandler {
+            weight_fn: Arc::new(|_| 1),
+            children: Arc::default(),
+        }
+    }
+
+    /// Sets the closure returning the weight of a child when it
+    /// joins the group.
+    pub fn with_weights<F>(mut self, weight_fn: F) -> Self
+    where
+        F: Fn(&ChildRef) -> usize + Send + Sync + 'static,
+    {
+        trace!("Setting weighted round-robin weights.");
+        self.weight_fn = Arc::new(weight_fn);
+        self
+    }
+
+    /// Changes the weight of a child of the group, returning whether
+    /// it was part of it.
+    ///
+    /// The other children keep their place in the rotation.
+    pub fn set_weight(&self, child: &ChildRef, weight: usize) -> bool {
+        let mut children = self.children.lock().unwrap();
+        match children.iter_mut().find(|entry| &entry.child == child) {
+            Some(entry) => {
+                debug!("setting weight of child {} to {}", child.path(), weight);
+                entry.weight = weight;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the weight of a child of the group, if it is part of it.
+    pub fn weight(&self, child: &ChildRef) -> Option<usize> {
+        let children = self.children.lock().unwrap();
+        children
+            .iter()
+            .find(|entry| &entry.child == child)
+            .map(|entry| entry.weight)
+    }
+
+    // Picks the child with the largest current weight, after
+    // increasing every current weight by its weight.
+    fn next_child(&self) -> Option<ChildRef> {
+        let mut children = self.children.lock().unwrap();
+        let mut total = 0;
+        let mut selected: Option<&mut WeightedChild> = None;
+        for entry in children.iter_mut().filter(|entry| entry.weight > 0) {
+            entry.current += entry.weight as i64;
+            total += entry.weight as i64;
+            match &selected {
+                Some(best) if best.current >= entry.current => (),
+                _ => selected = Some(entry),
+            }
+        }
+
+        selected.map(|entry| {
+            entry.current -= total;
+            entry.child.clone()
+        })
+    }
+}
+
+impl Default for WeightedRoundRobinHandler {
+    fn default() -> Self {
+        WeightedRoundRobinHandler::new()
+    }
+}
+
+impl DispatcherHandler for WeightedRoundRobinHandler {
+    // Adds joining children with their weight and removes the leaving ones.
+    fn notify(
+        &self,
+        from_child: &ChildRef,
+        _entries: &DispatcherMap,
+        notification_type: NotificationType,
+    ) {
+        if !from_child.is_public() {
+            return;
+        }
+
+        let mut children = self.children.lock().unwrap();
+        match notification_type {
+            NotificationType::Register => {
+                if children.iter().all(|entry| &entry.child != from_child) {
+                    let weight = (self.weight_fn)(from_child);
+                    children.push(WeightedChild {
+                        child: from_child.clone(),
+                        weight,
+                        current: 0,
+                    });
+                }
+            }
+            NotificationType::Remove => children.retain(|entry| &entry.child != from_child),
+        }
+    }
+
+    // Each child in turn will receive a message, as often as its weight allows.
+    fn broadcast_message(&self, _entries: &DispatcherMap, message: &Arc<SignedMessage>) {
+        let child = match self.next_child() {
+            Some(child) => child,
+            None => {
+                debug!("no weighted children to broadcast message to");
+                dead_letter(message, DeadLetterReason::NoRecipient, None);
+                return;
+            }
         };
+
+        debug!("sending message to child {}", child.path());
+        if let Err(err) = child.try_tell(message.clone()) {
+            error!("couldn't send message to child {}", child.path());
+            let reason = DeadLetterReason::from_send_error(&err);
+            dead_letter(message, reason, Some(&child));
+        }
+    }
+}
+
+impl Debug for WeightedRoundRobinHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeightedRoundRobinHandler")
+            .field("children", &self.children)
+            .finish()
     }
 }
 /// Generic trait which any custom dispatcher handler must implement for
@@ -115,6 +647,21 @@ pub trait DispatcherHandler {
     );
     /// Broadcasts the message to actors in according to the implemented behaviour.
     fn broadcast_message(&self, entries: &DispatcherMap, message: &Arc<SignedMessage>);
+    /// Sends the message to actors in according to the implemented
+    /// behaviour, returning an error if no actor could receive it
+    /// instead of reporting it as a dead letter.
+    ///
+    /// Defaults to [`broadcast_message`], which never fails.
+    ///
+    /// [`broadcast_message`]: #tymethod.broadcast_message
+    fn dispatch(
+        &self,
+        entries: &DispatcherMap,
+        message: &Arc<SignedMessage>,
+    ) -> Result<(), DispatchError> {
+        self.broadcast_message(entries, message);
+        Ok(())
+    }
 }
 
 /// A generic implementation of the Bastion dispatcher
@@ -132,6 +679,40 @@ pub struct Dispatcher {
     /// Special field that stores information about all
     /// registered actors in the group.
     actors: DispatcherMap,
+    /// Held while messages are dispatched, so that an actor
+    /// can't get removed while a message is being sent to it.
+    dispatching: RwLock<()>,
+    /// The public actors of the group and the actors watching them,
+    /// locked while the membership changes are sent so they are
+    /// received in order.
+    membership: Mutex<Membership>,
+}
+
+#[derive(Debug, Default)]
+struct Membership {
+    members: FxHashMap<BastionId, ChildRef>,
+    watchers: Vec<ChildRef>,
+}
+
+impl Membership {
+    // Sends the event to every watcher, forgetting the dead ones.
+    fn tell_watchers(&mut self, event: MembershipEvent) {
+        self.watchers
+            .retain(|watcher| match watcher.try_tell(event.clone()) {
+                Ok(()) => true,
+                Err(SendError::Unreachable(_)) => {
+                    debug!("watcher {} is dead, removing it", watcher.path());
+                    false
+                }
+                Err(SendError::MailboxFull(_)) => {
+                    warn!(
+                        "couldn't send membership event to watcher {}",
+                        watcher.path()
+                    );
+                    true
+                }
+            });
+    }
 }
 
 impl Dispatcher {
@@ -162,6 +743,8 @@ impl Dispatcher {
             dispatcher_type,
             handler: Box::new(DefaultDispatcherHandler::default()),
             actors: Default::default(),
+            dispatching: RwLock::default(),
+            membership: Mutex::default(),
         }
     }
 
@@ -180,19 +763,95 @@ impl Dispatcher {
 
     /// Appends the information about actor to the dispatcher.
     pub(crate) fn register(&self, key: &ChildRef, module_name: String) -> AnyResult<()> {
+        let mut membership = self.membership.lock().unwrap();
         self.actors.insert(key.to_owned(), module_name)?;
         self.handler
             .notify(key, &self.actors, NotificationType::Register);
+
+        if key.is_public() && !membership.members.contains_key(key.id()) {
+            membership.members.insert(key.id().clone(), key.clone());
+            membership.tell_watchers(MembershipEvent::MemberJoined {
+                dispatcher: self.dispatcher_type(),
+                member: key.addr(),
+            });
+        }
         Ok(())
     }
 
+    /// Returns the public actors of the group.
+    pub(crate) fn members(&self) -> Vec<ChildRef> {
+        self.actors
+            .iter()
+            .map(|(child, _)| child)
+            .filter(ChildRef::is_public)
+            .collect()
+    }
+
+    /// Returns whether the actor is registered in the dispatcher.
+    pub(crate) fn contains(&self, key: &ChildRef) -> bool {
+        self.actors.contains_key(key)
+    }
+
     /// Removes and then returns the record from the registry by the given key.
     /// Returns `None` when the record wasn't found by the given key.
-    pub(crate) fn remove(&self, key: &ChildRef) {
+    ///
+    /// Waits for the messages being dispatched to be sent, so
+    /// that none is sent to the actor once it was removed.
+    pub(crate) fn remove(&self, key: &ChildRef, reason: LeaveReason) {
+        let _dispatching = self.dispatching.write().unwrap();
+        let mut membership = self.membership.lock().unwrap();
         if self.actors.remove(key).is_ok() {
             self.handler
                 .notify(key, &self.actors, NotificationType::Remove);
         }
+
+        // The actor might have been pruned from the registry
+        // already, without leaving the group.
+        if membership.members.remove(key.id()).is_some() {
+            membership.tell_watchers(MembershipEvent::MemberLeft {
+                dispatcher: self.dispatcher_type(),
+                member: key.addr(),
+                reason,
+            });
+        }
+    }
+
+    /// Makes the actor receive the membership changes of the
+    /// dispatcher, starting with a `MemberJoined` event for each of
+    /// its current members. Returns `false` if it already did.
+    pub(crate) fn watch(&self, watcher: &ChildRef) -> bool {
+        let mut membership = self.membership.lock().unwrap();
+        // A restarted watcher has the identifier of its dead incarnation.
+        membership
+            .watchers
+            .retain(|entry| !entry.sender().is_closed());
+        if membership.watchers.contains(watcher) {
+            return false;
+        }
+
+        for member in membership.members.values() {
+            let event = MembershipEvent::MemberJoined {
+                dispatcher: self.dispatcher_type(),
+                member: member.addr(),
+            };
+            if watcher.try_tell(event).is_err() {
+                warn!(
+                    "couldn't send membership event to watcher {}",
+                    watcher.path()
+                );
+            }
+        }
+        membership.watchers.push(watcher.clone());
+        true
+    }
+
+    /// Stops sending the membership changes to the actor, returning
+    /// whether it was receiving them.
+    pub(crate) fn unwatch(&self, watcher: &ChildRef) -> bool {
+        let mut membership = self.membership.lock().unwrap();
+        let watchers = membership.watchers.len();
+        membership.watchers.retain(|entry| entry != watcher);
+        membership.watchers.len() != watchers
     }
 
     /// Forwards the message to the handler for processing.
@@ -205,8 +864,44 @@ impl Dispatcher {
     /// The logic of who and how should receive the message relies onto
     /// the handler implementation.
     pub fn broadcast_message(&self, message: &Arc<SignedMessage>) {
+        let _dispatching = self.dispatching.read().unwrap();
         self.handler.broadcast_message(&self.actors, &message);
     }
+
+    /// Sends the message to the group of actors as
+    /// [`broadcast_message`] does, but returns an error if the
+    /// handler couldn't deliver it to any of them.
+    ///
+    /// [`broadcast_message`]: #method.broadcast_message
+    pub fn dispatch(&self, message: &Arc<SignedMessage>) -> Result<(), DispatchError> {
+        let _dispatching = self.dispatching.read().unwrap();
+        self.handler.dispatch(&self.actors, message)
+    }
+
+    /// Sends the message to every public actor of the group for
+    /// which the predicate returns `true`, bypassing the handler,
+    /// and returns how many of them it was sent to.
+    pub(crate) fn broadcast_filtered<P>(&self, message: &Arc<SignedMessage>, predicate: P) -> usize
+    where
+        P: Fn(&ChildRef) -> bool,
+    {
+        let _dispatching = self.dispatching.read().unwrap();
+        let mut recipients = 0;
+        for (child, _) in self.actors.iter() {
+            if !child.is_public() || !predicate(&child) {
+                continue;
+            }
+
+            recipients += 1;
+            if let Err(err) = child.try_tell(message.clone()) {
+                error!("couldn't send message to child {}", child.path());
+                let reason = DeadLetterReason::from_send_error(&err);
+                dead_letter(message, reason, Some(&child));
+            }
+        }
+
+        recipients
+    }
 }
 
 impl Debug for Dispatcher {
@@ -235,6 +930,8 @@ impl Default for Dispatcher {
             dispatcher_type: DispatcherType::default(),
             handler: Box::new(DefaultDispatcherHandler::default()),
             actors: LOTable::new(),
+            dispatching: RwLock::default(),
+            membership: Mutex::default(),
         }
     }
 }
@@ -300,15 +997,72 @@ impl GlobalDispatcher {
         Ok(())
     }
 
+    /// Registers the actor in the dispatcher if it exists and the
+    /// actor wasn't registered in it yet, returning whether it was.
+    pub(crate) fn subscribe(
+        &self,
+        dispatcher_type: &DispatcherType,
+        child_ref: &ChildRef,
+        module_name: String,
+    ) -> AnyResult<bool> {
+        match self.dispatchers.get(dispatcher_type) {
+            Some(dispatcher) if !dispatcher.contains(child_ref) => {
+                dispatcher.register(child_ref, module_name)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Removes the actor from the dispatcher if it was registered
+    /// in it, returning whether it was.
+    pub(crate) fn unsubscribe(
+        &self,
+        dispatcher_type: &DispatcherType,
+        child_ref: &ChildRef,
+    ) -> bool {
+        match self.dispatchers.get(dispatcher_type) {
+            Some(dispatcher) if dispatcher.contains(child_ref) => {
+                dispatcher.remove(child_ref, LeaveReason::Unsubscribed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Makes the actor watch the membership of the dispatcher,
+    /// returning `false` if it doesn't exist or the actor already
+    /// watches it.
+    pub(crate) fn watch(&self, dispatcher_type: &DispatcherType, watcher: &ChildRef) -> bool {
+        match self.dispatchers.get(dispatcher_type) {
+            Some(dispatcher) => dispatcher.watch(watcher),
+            None => false,
+        }
+    }
+
+    /// Makes the actor stop watching the membership of the
+    /// dispatcher, returning whether it watched it.
+    pub(crate) fn unwatch(&self, dispatcher_type: &DispatcherType, watcher: &ChildRef) -> bool {
+        match self.dispatchers.get(dispatcher_type) {
+            Some(dispatcher) => dispatcher.unwatch(watcher),
+            None => false,
+        }
+    }
+
     /// Removes and then returns the record from the registry by the given key.
     /// Returns `None` when the record wasn't found by the given key.
-    pub(crate) fn remove(&self, dispatchers: &[DispatcherType], child_ref: &ChildRef) {
+    pub(crate) fn remove(
+        &self,
+        dispatchers: &[DispatcherType],
+        child_ref: &ChildRef,
+        reason: LeaveReason,
+    ) {
         dispatchers
             .iter()
             .filter(|key| self.dispatchers.contains_key(*key))
             .for_each(|key| {
                 if let Some(dispatcher) = self.dispatchers.get(key) {
-                    dispatcher.remove(child_ref)
+                    dispatcher.remove(child_ref, reason)
                 }
             })
     }
@@ -351,18 +1105,73 @@ impl GlobalDispatcher {
                 Some(dispatcher) => {
                     dispatcher.broadcast_message(&message.clone());
                 }
-                // TODO: Put the message into the dead queue
                 None => {
                     let name = dispatcher_type.name();
                     warn!(
                         "The message can't be delivered to the group with the '{}' name.",
                         name
                     );
+                    dead_letter(message, DeadLetterReason::NoRecipient, None);
                 }
             }
         }
     }
 
+    /// Sends the message to the group with the given name, returning
+    /// an error if no actor of the group could receive it.
+    pub(crate) fn dispatch(
+        &self,
+        name: String,
+        message: &Arc<SignedMessage>,
+    ) -> Result<(), DispatchError> {
+        match self.dispatchers.get(&name.into()) {
+            Some(dispatcher) => dispatcher.dispatch(message),
+            None => Err(DispatchError::NoAvailableRecipient),
+        }
+    }
+
+    /// Returns the public actors of the group with the given name.
+    pub(crate) fn members(&self, name: String) -> Vec<ChildRef> {
+        match self.dispatchers.get(&name.into()) {
+            Some(dispatcher) => dispatcher.members(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Sends the message to the actors of the targeted groups for
+    /// which the predicate returns `true`, returning how many of them
+    /// it was sent to.
+    ///
+    /// The message is reported as a dead letter if none matched.
+    pub(crate) fn broadcast_filtered<P>(
+        &self,
+        target: BroadcastTarget,
+        message: &Arc<SignedMessage>,
+        predicate: P,
+    ) -> usize
+    where
+        P: Fn(&ChildRef) -> bool,
+    {
+        let recipients = match target {
+            BroadcastTarget::All => self
+                .dispatchers
+                .iter()
+                .map(|pair| pair.1.broadcast_filtered(message, &predicate))
+                .sum(),
+            BroadcastTarget::Group(name) => match self.dispatchers.get(&name.into()) {
+                Some(dispatcher) => dispatcher.broadcast_filtered(message, &predicate),
+                None => 0,
+            },
+        };
+
+        if recipients == 0 {
+            debug!("no children matched the filtered broadcast");
+            dead_letter(message, DeadLetterReason::NoRecipient, None);
+        }
+
+        recipients
+    }
+
     /// Adds dispatcher to the global registry.
     pub(crate) fn register_dispatcher(&self, dispatcher: &Arc<Box<Dispatcher>>) -> AnyResult<()> {
         let dispatcher_type = dispatcher.dispatcher_type();
@@ -388,12 +1197,27 @@ impl GlobalDispatcher {
     }
 }
 
+// Reports a message that a dispatcher couldn't deliver to
+// `recipient` as a dead letter.
+fn dead_letter(
+    message: &Arc<SignedMessage>,
+    reason: DeadLetterReason,
+    recipient: Option<&ChildRef>,
+) {
+    if dead_letters::is_enabled() {
+        let msg = Msg::tell(message.clone());
+        let envelope = SignedMessage::new(msg, message.signature().clone());
+        let recipient = recipient.map(|child| child.path().clone());
+        dead_letters::report(envelope, reason, recipient);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::child_ref::ChildRef;
     use crate::context::BastionId;
     use crate::dispatcher::*;
-    use crate::envelope::{RefAddr, SignedMessage};
+    use crate::envelope::{Envelope, RefAddr, SignedMessage};
     use crate::message::Msg;
     use crate::path::BastionPath;
     use futures::channel::mpsc;
@@ -486,7 +1310,7 @@ mod tests {
             .unwrap();
         assert_eq!(instance.actors.contains_key(&child_ref), true);
 
-        instance.remove(&child_ref);
+        instance.remove(&child_ref, LeaveReason::Stopped);
         assert_eq!(instance.actors.contains_key(&child_ref), false);
     }
 
@@ -523,6 +1347,252 @@ mod tests {
         assert_eq!(handler_was_called, true);
     }
 
+    #[test]
+    fn test_local_dispatcher_broadcast_filtered() {
+        let instance = Dispatcher::default();
+        let mut receivers = (0..4)
+            .map(|partition| {
+                let (sender, receiver) = mpsc::unbounded();
+                let path = Arc::new(BastionPath::root());
+                let child_ref =
+                    ChildRef::new(BastionId::new(), sender, partition.to_string(), path);
+                instance
+                    .register(&child_ref, "my::test::module".to_string())
+                    .unwrap();
+                receiver
+            })
+            .collect::<Vec<_>>();
+
+        let recipients = instance.broadcast_filtered(&session_message(0), |child| {
+            child.name().parse::<u32>().unwrap() % 2 == 0
+        });
+        assert_eq!(recipients, 2);
+        let received = receivers
+            .iter_mut()
+            .map(|receiver| receiver.try_recv().is_ok())
+            .collect::<Vec<_>>();
+        assert_eq!(received, vec![true, false, true, false]);
+
+        let recipients = instance.broadcast_filtered(&session_message(0), |_| false);
+        assert_eq!(recipients, 0);
+    }
+
+    fn session_message(session: u64) -> Arc<SignedMessage> {
+        let (sender, _) = mpsc::unbounded();
+        let path = Arc::new(BastionPath::root());
+        Arc::new(SignedMessage::new(
+            Msg::broadcast(session),
+            RefAddr::new(path, sender),
+        ))
+    }
+
+    fn consistent_hash_group(
+        handler: &ConsistentHashHandler,
+        size: usize,
+    ) -> Vec<(ChildRef, mpsc::UnboundedReceiver<Envelope>)> {
+        let entries = DispatcherMap::new();
+        (0..size)
+            .map(|_| {
+                let (sender, receiver) = mpsc::unbounded();
+                let path = Arc::new(BastionPath::root());
+                let child_ref = ChildRef::new(BastionId::new(), sender, "test".to_string(), path);
+                handler.notify(&child_ref, &entries, NotificationType::Register);
+                (child_ref, receiver)
+            })
+            .collect()
+    }
+
+    // Returns the index of the child that received the message, if any.
+    fn receiver_of(group: &mut [(ChildRef, mpsc::UnboundedReceiver<Envelope>)]) -> Option<usize> {
+        group
+            .iter_mut()
+            .position(|(_, receiver)| receiver.try_recv().is_ok())
+    }
+
+    fn session_key_handler() -> ConsistentHashHandler {
+        ConsistentHashHandler::new(|msg: &Msg| msg.downcast_ref::<u64>().map(|session| *session))
+    }
+
+    #[test]
+    fn test_consistent_hash_sticky_routing() {
+        let handler = session_key_handler();
+        let mut group = consistent_hash_group(&handler, 4);
+        let entries = DispatcherMap::new();
+
+        for session in 0..32 {
+            handler.broadcast_message(&entries, &session_message(session));
+            let first = receiver_of(&mut group).expect("message wasn't delivered");
+
+            handler.broadcast_message(&entries, &session_message(session));
+            assert_eq!(receiver_of(&mut group), Some(first));
+        }
+    }
+
+    #[test]
+    fn test_consistent_hash_minimal_rebalancing() {
+        let handler = session_key_handler();
+        let mut group = consistent_hash_group(&handler, 4);
+        let entries = DispatcherMap::new();
+
+        let mut owners = Vec::new();
+        for session in 0..64 {
+            handler.broadcast_message(&entries, &session_message(session));
+            owners.push(receiver_of(&mut group).unwrap());
+        }
+
+        let leaving = group[0].0.clone();
+        handler.notify(&leaving, &entries, NotificationType::Remove);
+
+        for (session, owner) in owners.into_iter().enumerate() {
+            handler.broadcast_message(&entries, &session_message(session as u64));
+            let new_owner = receiver_of(&mut group).unwrap();
+            // Only the keys of the child that left are moved.
+            if owner != 0 {
+                assert_eq!(new_owner, owner);
+            } else {
+                assert_ne!(new_owner, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_consistent_hash_dead_child_policies() {
+        for policy in [DeadChildPolicy::Fail, DeadChildPolicy::Rehash].iter() {
+            let handler = session_key_handler().with_dead_child_policy(*policy);
+            let mut group = consistent_hash_group(&handler, 2);
+            let entries = DispatcherMap::new();
+
+            handler.broadcast_message(&entries, &session_message(42));
+            let owner = receiver_of(&mut group).unwrap();
+            // The child died but wasn't removed from the group yet.
+            group[owner].1.close();
+
+            handler.broadcast_message(&entries, &session_message(42));
+            let rehashed = group
+                .iter_mut()
+                .enumerate()
+                .any(|(index, (_, receiver))| index != owner && receiver.try_recv().is_ok());
+            assert_eq!(rehashed, *policy == DeadChildPolicy::Rehash);
+        }
+    }
+
+    #[test]
+    fn test_round_robin_prunes_dead_children() {
+        let handler = RoundRobinHandler::default();
+        let entries = DispatcherMap::new();
+        let member = |id: BastionId| {
+            let (sender, receiver) = mpsc::unbounded();
+            let path = Arc::new(BastionPath::root());
+            let child_ref = ChildRef::new(id, sender, "test".to_string(), path);
+            entries
+                .insert(child_ref.clone(), "test".to_string())
+                .unwrap();
+            (child_ref, receiver)
+        };
+        let mut group = (0..3).map(|_| member(BastionId::new())).collect::<Vec<_>>();
+        // The child died but wasn't removed from the group yet.
+        group[1].1.close();
+
+        for _ in 0..4 {
+            assert_eq!(handler.dispatch(&entries, &session_message(0)), Ok(()));
+        }
+        assert!(!entries.contains_key(&group[1].0));
+        let received = group
+            .iter_mut()
+            .map(|(_, receiver)| std::iter::from_fn(|| receiver.try_recv().ok()).count())
+            .collect::<Vec<_>>();
+        assert_eq!(received[1], 0);
+        assert_eq!(received.iter().sum::<usize>(), 4);
+
+        // Its restarted incarnation rejoins the rotation.
+        let (_, mut restarted) = member(group[1].0.id().clone());
+        for _ in 0..3 {
+            assert_eq!(handler.dispatch(&entries, &session_message(0)), Ok(()));
+        }
+        assert!(restarted.try_recv().is_ok());
+
+        restarted.close();
+        group[0].1.close();
+        group[2].1.close();
+        assert_eq!(
+            handler.dispatch(&entries, &session_message(0)),
+            Err(DispatchError::NoAvailableRecipient)
+        );
+        assert_eq!(entries.len(), 0);
+    }
+
+    fn weighted_group(
+        handler: &WeightedRoundRobinHandler,
+        names: &[&str],
+    ) -> Vec<(ChildRef, mpsc::UnboundedReceiver<Envelope>)> {
+        let entries = DispatcherMap::new();
+        names
+            .iter()
+            .map(|name| {
+                let (sender, receiver) = mpsc::unbounded();
+                let path = Arc::new(BastionPath::root());
+                let child_ref = ChildRef::new(BastionId::new(), sender, name.to_string(), path);
+                handler.notify(&child_ref, &entries, NotificationType::Register);
+                (child_ref, receiver)
+            })
+            .collect()
+    }
+
+    fn weighted_receivers(
+        handler: &WeightedRoundRobinHandler,
+        group: &mut [(ChildRef, mpsc::UnboundedReceiver<Envelope>)],
+        count: usize,
+    ) -> Vec<usize> {
+        let entries = DispatcherMap::new();
+        (0..count)
+            .map(|_| {
+                handler.broadcast_message(&entries, &session_message(0));
+                receiver_of(group).expect("message wasn't delivered")
+            })
+            .collect()
+    }
+
+    fn weight_by_name() -> WeightedRoundRobinHandler {
+        WeightedRoundRobinHandler::new()
+            .with_weights(|child: &ChildRef| child.name().parse().unwrap())
+    }
+
+    #[test]
+    fn test_weighted_round_robin_smooth_distribution() {
+        let handler = weight_by_name();
+        let mut group = weighted_group(&handler, &["5", "1", "1"]);
+
+        let receivers = weighted_receivers(&handler, &mut group, 14);
+        // The heaviest child doesn't receive all of its messages in a row.
+        assert_eq!(receivers, vec![0, 0, 1, 0, 2, 0, 0, 0, 0, 1, 0, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_weighted_round_robin_weight_change() {
+        let handler = weight_by_name();
+        let mut group = weighted_group(&handler, &["1", "1", "0"]);
+
+        let receivers = weighted_receivers(&handler, &mut group, 4);
+        assert_eq!(receivers, vec![0, 1, 0, 1]);
+
+        assert!(handler.set_weight(&group[2].0, 2));
+        assert_eq!(handler.weight(&group[2].0), Some(2));
+        let receivers = weighted_receivers(&handler, &mut group, 8);
+        for (index, count) in [2, 2, 4].iter().enumerate() {
+            let received = receivers
+                .iter()
+                .filter(|receiver| **receiver == index)
+                .count();
+            assert_eq!(received, *count);
+        }
+
+        let leaving = group[2].0.clone();
+        handler.notify(&leaving, &DispatcherMap::new(), NotificationType::Remove);
+        assert!(!handler.set_weight(&leaving, 1));
+        let receivers = weighted_receivers(&handler, &mut group, 4);
+        assert!(receivers.iter().all(|receiver| *receiver != 2));
+    }
+
     #[test]
     fn test_global_dispatcher_add_local_dispatcher() {
         let dispatcher_type = DispatcherType::Named("test".to_string());
@@ -615,7 +1685,7 @@ mod tests {
             .unwrap();
         assert_eq!(local_dispatcher.actors.contains_key(&child_ref), true);
 
-        global_dispatcher.remove(&actor_groups, &child_ref);
+        global_dispatcher.remove(&actor_groups, &child_ref, LeaveReason::Stopped);
         assert_eq!(local_dispatcher.actors.contains_key(&child_ref), false);
     }
 
@@ -1,24 +1,443 @@
 //!
 //! Cluster formation and distributed actor instantiation
+//!
+//! Note that the cluster transport is provided by artillery and that
+//! its traffic, including the payloads sent with
+//! [`DistributedContext::tell`], isn't encrypted. Clusters shouldn't
+//! span networks that aren't trusted.
+//!
+//! Messages implementing [`RemoteMessage`] can be sent to other members
+//! with [`DistributedContext::tell_remote`]: they are encoded by a
+//! [`Codec`] ([`JsonCodec`] by default) along with their type's tag, and
+//! decoded back with [`ClusterMessage::decode`] by the receiving member.
+//!
+//! Those messages are fire and forget unless they are sent with
+//! [`DistributedContext::tell_at_least_once`], which retransmits them
+//! until the receiving member acknowledges them (see [`AtLeastOnce`]).
 use crate::children_ref::ChildrenRef;
 use crate::context::*;
 use crate::message::Message;
+use crate::system;
 use crate::Bastion;
 
 use crate::message::Msg;
+use crate::time::{self, Delay};
 
 use artillery_core::cluster::ap::*;
 use artillery_core::epidemic::prelude::*;
-use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use core::future::Future;
+use crossbeam_queue::SegQueue;
 use futures::future;
+use fxhash::{FxHashMap, FxHashSet};
 use tracing::*;
 
 use lever::table::lotable::*;
 
 use uuid::Uuid;
 
+///
+/// A message that can be sent to other members of the cluster.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// struct Job {
+///     id: u64,
+/// }
+///
+/// impl RemoteMessage for Job {
+///     const TAG: &'static str = "my_app::Job";
+/// }
+/// ```
+pub trait RemoteMessage: Message + Serialize + DeserializeOwned {
+    /// The tag identifying the message's type on every member of
+    /// the cluster, which needs to be unique within the application.
+    ///
+    /// Unlike a `TypeId`, it doesn't change between the binaries
+    /// that the members run.
+    const TAG: &'static str;
+}
+
+///
+/// Encodes and decodes the messages sent to other members of the cluster.
+pub trait Codec {
+    ///
+    /// Encodes a message into a payload.
+    fn encode<T: Serialize>(&self, value: &T) -> Result<String, CodecError>;
+
+    ///
+    /// Decodes a message from a payload.
+    fn decode<T: DeserializeOwned>(&self, payload: &str) -> Result<T, CodecError>;
+}
+
+///
+/// The default [`Codec`], which encodes messages as JSON.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<String, CodecError> {
+        serde_json::to_string(value).map_err(|err| CodecError(err.to_string()))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, payload: &str) -> Result<T, CodecError> {
+        serde_json::from_str(payload).map_err(|err| CodecError(err.to_string()))
+    }
+}
+
+///
+/// The error returned by a [`Codec`] that couldn't encode or decode a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodecError(pub String);
+
+impl Display for CodecError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.write_str(&self.0)
+    }
+}
+
+///
+/// The error returned when a [`ClusterMessage`] can't be decoded
+/// into a [`RemoteMessage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    ///
+    /// The message wasn't sent with [`DistributedContext::tell_remote`].
+    NotRemote,
+    ///
+    /// The message is of another type, identified by its tag.
+    TagMismatch(String),
+    ///
+    /// The message has the expected tag but its payload couldn't be decoded.
+    Malformed(CodecError),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            DecodeError::NotRemote => fmt.write_str("not a remote message"),
+            DecodeError::TagMismatch(tag) => write!(fmt, "remote message tagged {}", tag),
+            DecodeError::Malformed(err) => write!(fmt, "malformed remote message: {}", err),
+        }
+    }
+}
+
+///
+/// The error returned by [`DistributedContext::tell_at_least_once`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryError {
+    ///
+    /// The message couldn't be encoded.
+    Codec(CodecError),
+    ///
+    /// The receiving member didn't acknowledge the message, which was
+    /// sent as many times as the retry budget allowed.
+    DeliveryFailed {
+        ///
+        /// How many times the message was sent.
+        attempts: usize,
+    },
+}
+
+impl Display for DeliveryError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            DeliveryError::Codec(err) => write!(fmt, "couldn't encode message: {}", err),
+            DeliveryError::DeliveryFailed { attempts } => {
+                write!(fmt, "message not acknowledged after {} attempts", attempts)
+            }
+        }
+    }
+}
+
+impl From<CodecError> for DeliveryError {
+    fn from(err: CodecError) -> Self {
+        DeliveryError::Codec(err)
+    }
+}
+
+///
+/// How a message sent with [`DistributedContext::tell_at_least_once`]
+/// is retransmitted until the receiving member acknowledges it.
+///
+/// The message is sent again whenever the acknowledgement timeout
+/// elapses, which doubles after each attempt up to the maximum
+/// backoff. Once the retries are exhausted, sending it fails with
+/// [`DeliveryError::DeliveryFailed`].
+///
+/// Since a retransmitted message might already have been received,
+/// the receiving member drops the ones whose id it recently received.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// use std::time::Duration;
+///
+/// let delivery = AtLeastOnce::new()
+///     .with_ack_timeout(Duration::from_millis(200))
+///     .with_retries(10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AtLeastOnce {
+    ack_timeout: Duration,
+    max_backoff: Duration,
+    retries: usize,
+}
+
+impl AtLeastOnce {
+    ///
+    /// Creates the default policy, which waits 500ms for the first
+    /// acknowledgement, up to 10s, and retransmits 5 times.
+    pub fn new() -> Self {
+        AtLeastOnce::default()
+    }
+
+    ///
+    /// Sets how long the first attempt waits for its acknowledgement.
+    pub fn with_ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = ack_timeout;
+        self
+    }
+
+    ///
+    /// Sets how long an attempt waits for its acknowledgement at most.
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    ///
+    /// Sets how many times the message is retransmitted at most.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+}
+
+impl Default for AtLeastOnce {
+    fn default() -> Self {
+        AtLeastOnce {
+            ack_timeout: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            retries: 5,
+        }
+    }
+}
+
+///
+/// A remote message that couldn't be decoded, which is sent to the
+/// dead letters.
+#[derive(Debug)]
+pub struct UndecodedMessage {
+    ///
+    /// The member that sent the message.
+    pub member: Uuid,
+    ///
+    /// The tag of the message's type.
+    pub tag: String,
+    ///
+    /// Why the message couldn't be decoded.
+    pub error: DecodeError,
+}
+
+// What is sent over the wire: the tag of the message's type and
+// the message encoded by the codec.
+#[derive(Serialize, Deserialize)]
+struct RemotePayload {
+    tag: String,
+    data: String,
+}
+
+// What is sent over the wire by `tell_at_least_once`: the payload
+// along with its id, and its acknowledgement.
+#[derive(Serialize, Deserialize)]
+enum DeliveryFrame {
+    Message { id: Uuid, payload: String },
+    Ack { id: Uuid },
+}
+
+// How many message ids a member remembers to drop the duplicates.
+const DEDUP_CAPACITY: usize = 4096;
+// How often a message waiting for its acknowledgement checks the
+// cluster's events.
+const ACK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// The ids of the messages that were recently received at least once,
+// forgetting the least recently received ones.
+#[derive(Debug)]
+struct Delivered {
+    capacity: usize,
+    last: u64,
+    ids: FxHashMap<Uuid, u64>,
+    order: BTreeMap<u64, Uuid>,
+}
+
+impl Delivered {
+    fn new(capacity: usize) -> Self {
+        Delivered {
+            capacity,
+            last: 0,
+            ids: FxHashMap::default(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    // Returns whether the message wasn't received before.
+    fn insert(&mut self, id: Uuid) -> bool {
+        self.last += 1;
+        let first = match self.ids.insert(id, self.last) {
+            Some(previous) => {
+                self.order.remove(&previous);
+                false
+            }
+            None => true,
+        };
+        self.order.insert(self.last, id);
+
+        if self.ids.len() > self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.ids.remove(&oldest);
+            }
+        }
+
+        first
+    }
+}
+
+// What the messages sent at least once and their acknowledgements
+// go through, which is the cluster.
+trait Transport {
+    // Sends a payload to the member, without waiting for it to be
+    // received.
+    fn send_payload(&self, to: Uuid, payload: String);
+    // Handles the payloads received from the other members meanwhile.
+    fn receive_payloads(&self);
+}
+
+// The messages sent at least once which weren't acknowledged yet,
+// and the ones recently received.
+#[derive(Debug)]
+struct Deliveries {
+    unacked: Mutex<FxHashSet<Uuid>>,
+    delivered: Mutex<Delivered>,
+}
+
+impl Deliveries {
+    fn new(capacity: usize) -> Self {
+        Deliveries {
+            unacked: Mutex::default(),
+            delivered: Mutex::new(Delivered::new(capacity)),
+        }
+    }
+
+    // Sends the frame of the message `id` until it's acknowledged or
+    // the retries of `delivery` are exhausted.
+    async fn send<T: Transport>(
+        &self,
+        transport: &T,
+        to: Uuid,
+        id: Uuid,
+        frame: String,
+        delivery: &AtLeastOnce,
+    ) -> Result<(), DeliveryError> {
+        // FIXME: panics
+        self.unacked.lock().unwrap().insert(id);
+        let mut ack_timeout = delivery.ack_timeout;
+        for attempt in 1..=delivery.retries + 1 {
+            debug!("Sending remote message {} (attempt {})", id, attempt);
+            transport.send_payload(to, frame.clone());
+            if self.acknowledged(transport, &id, ack_timeout).await {
+                return Ok(());
+            }
+
+            ack_timeout = (ack_timeout * 2).min(delivery.max_backoff);
+        }
+
+        warn!("Remote message {} wasn't acknowledged by {}", id, to);
+        self.unacked.lock().unwrap().remove(&id);
+        Err(DeliveryError::DeliveryFailed {
+            attempts: delivery.retries + 1,
+        })
+    }
+
+    // Waits for the acknowledgement of a message until `timeout`
+    // elapsed, and returns whether it was acknowledged.
+    async fn acknowledged<T: Transport>(&self, transport: &T, id: &Uuid, timeout: Duration) -> bool {
+        let deadline = time::now() + timeout;
+        loop {
+            transport.receive_payloads();
+            // FIXME: panics
+            if !self.unacked.lock().unwrap().contains(id) {
+                return true;
+            }
+
+            if time::now() >= deadline {
+                return false;
+            }
+
+            Delay::new(ACK_POLL_INTERVAL).await;
+        }
+    }
+
+    // Handles a payload received from a member, and returns the
+    // message to queue unless it's an acknowledgement or a duplicate.
+    fn receive<T: Transport>(
+        &self,
+        transport: &T,
+        member: Uuid,
+        payload: String,
+    ) -> Option<ClusterMessage> {
+        match serde_json::from_str(&payload) {
+            Ok(DeliveryFrame::Ack { id }) => {
+                debug!("Remote message {} acknowledged by {}", id, member);
+                // FIXME: panics
+                self.unacked.lock().unwrap().remove(&id);
+                None
+            }
+            Ok(DeliveryFrame::Message { id, payload }) => {
+                // Acknowledged again if it was a retransmission, since
+                // the previous acknowledgement might have been lost.
+                if let Ok(ack) = serde_json::to_string(&DeliveryFrame::Ack { id }) {
+                    transport.send_payload(member, ack);
+                }
+
+                // FIXME: panics
+                if self.delivered.lock().unwrap().insert(id) {
+                    Some(ClusterMessage::new(Msg::tell(payload), member))
+                } else {
+                    debug!("Dropping duplicate remote message {} from {}", id, member);
+                    None
+                }
+            }
+            Err(_) => Some(ClusterMessage::new(Msg::tell(payload), member)),
+        }
+    }
+}
+
+fn encode_remote<M, C>(msg: &M, codec: &C) -> Result<String, CodecError>
+where
+    M: RemoteMessage,
+    C: Codec,
+{
+    let payload = RemotePayload {
+        tag: M::TAG.to_string(),
+        data: codec.encode(msg)?,
+    };
+    serde_json::to_string(&payload).map_err(|err| CodecError(err.to_string()))
+}
+
 ///
 /// Cluster message that is sent and delivered among members
 #[derive(Debug)]
@@ -39,6 +458,66 @@ impl ClusterMessage {
     pub fn extract(self) -> Msg {
         self.msg
     }
+
+    ///
+    /// Returns the member that sent the message.
+    pub fn member(&self) -> Uuid {
+        self.member
+    }
+
+    ///
+    /// Returns the tag of the message's type, if it was sent with
+    /// [`DistributedContext::tell_remote`].
+    pub fn tag(&self) -> Option<String> {
+        self.remote_payload().map(|payload| payload.tag)
+    }
+
+    ///
+    /// Decodes a message sent with [`DistributedContext::tell_remote`],
+    /// using the [`JsonCodec`].
+    ///
+    /// If the message has the tag of `M` but can't be decoded, an
+    /// [`UndecodedMessage`] is sent to the dead letters.
+    pub fn decode<M: RemoteMessage>(&self) -> Result<M, DecodeError> {
+        self.decode_with(&JsonCodec)
+    }
+
+    ///
+    /// Decodes a message sent with [`DistributedContext::tell_remote_with`],
+    /// using the same codec.
+    ///
+    /// If the message has the tag of `M` but can't be decoded, an
+    /// [`UndecodedMessage`] is sent to the dead letters.
+    pub fn decode_with<M, C>(&self, codec: &C) -> Result<M, DecodeError>
+    where
+        M: RemoteMessage,
+        C: Codec,
+    {
+        let payload = self.remote_payload().ok_or(DecodeError::NotRemote)?;
+        if payload.tag != M::TAG {
+            return Err(DecodeError::TagMismatch(payload.tag));
+        }
+
+        codec.decode(&payload.data).map_err(|err| {
+            let error = DecodeError::Malformed(err);
+            warn!(
+                "Couldn't decode message tagged {} from {}: {}",
+                payload.tag, self.member, error
+            );
+            let undecoded = UndecodedMessage {
+                member: self.member,
+                tag: payload.tag,
+                error: error.clone(),
+            };
+            system::current().dead_letters().broadcast(undecoded).ok();
+            error
+        })
+    }
+
+    fn remote_payload(&self) -> Option<RemotePayload> {
+        let payload = self.msg.tell_ref::<String>()?;
+        serde_json::from_str(payload).ok()
+    }
 }
 
 ///
@@ -49,6 +528,9 @@ pub struct DistributedContext {
     me: Uuid,
     members: LOTable<Uuid, ArtilleryMember>,
     cluster: Arc<Cluster>,
+    // The messages received from the cluster but not by the actor yet.
+    inbox: SegQueue<ClusterMessage>,
+    deliveries: Deliveries,
 }
 
 impl DistributedContext {
@@ -60,6 +542,8 @@ impl DistributedContext {
             me,
             members: LOTable::new(),
             cluster,
+            inbox: SegQueue::new(),
+            deliveries: Deliveries::new(DEDUP_CAPACITY),
         }
     }
 
@@ -102,6 +586,85 @@ impl DistributedContext {
         Ok(())
     }
 
+    ///
+    /// Send a fire and forget style message to a destined cluster member,
+    /// encoding it with the [`JsonCodec`].
+    ///
+    /// The receiving member decodes it with [`ClusterMessage::decode`].
+    pub fn tell_remote<M: RemoteMessage>(&self, to: &Uuid, msg: &M) -> Result<(), CodecError> {
+        self.tell_remote_with(to, msg, &JsonCodec)
+    }
+
+    ///
+    /// Send a fire and forget style message to a destined cluster member,
+    /// encoding it with the given codec.
+    ///
+    /// The receiving member decodes it with [`ClusterMessage::decode_with`].
+    pub fn tell_remote_with<M, C>(&self, to: &Uuid, msg: &M, codec: &C) -> Result<(), CodecError>
+    where
+        M: RemoteMessage,
+        C: Codec,
+    {
+        let payload = encode_remote(msg, codec)?;
+        debug!("Sending remote message tagged {}", M::TAG);
+        self.cluster.send_payload(*to, payload);
+        Ok(())
+    }
+
+    ///
+    /// Sends a message to a destined cluster member until it
+    /// acknowledges it, encoding it with the [`JsonCodec`].
+    ///
+    /// The returned future resolves once the message was acknowledged,
+    /// which the receiving member does once it queued the message for
+    /// [`recv`], or to [`DeliveryError::DeliveryFailed`] once the retries
+    /// of `delivery` are exhausted. The message might then have been
+    /// received anyway, if its acknowledgements were lost.
+    ///
+    /// A retransmitted message is received only once, as long as the
+    /// receiving member still remembers its id.
+    ///
+    /// [`recv`]: #method.recv
+    pub async fn tell_at_least_once<M: RemoteMessage>(
+        &self,
+        to: &Uuid,
+        msg: &M,
+        delivery: &AtLeastOnce,
+    ) -> Result<(), DeliveryError> {
+        self.tell_at_least_once_with(to, msg, &JsonCodec, delivery)
+            .await
+    }
+
+    ///
+    /// Sends a message to a destined cluster member until it
+    /// acknowledges it, encoding it with the given codec (see
+    /// [`tell_at_least_once`]).
+    ///
+    /// [`tell_at_least_once`]: #method.tell_at_least_once
+    pub async fn tell_at_least_once_with<M, C>(
+        &self,
+        to: &Uuid,
+        msg: &M,
+        codec: &C,
+        delivery: &AtLeastOnce,
+    ) -> Result<(), DeliveryError>
+    where
+        M: RemoteMessage,
+        C: Codec,
+    {
+        let id = Uuid::new_v4();
+        let frame = DeliveryFrame::Message {
+            id,
+            payload: encode_remote(msg, codec)?,
+        };
+        let frame = serde_json::to_string(&frame).map_err(|err| CodecError(err.to_string()))?;
+
+        debug!("Sending remote message tagged {} ({})", M::TAG, id);
+        self.deliveries
+            .send(self, *to, id, frame, delivery)
+            .await
+    }
+
     ///
     /// Channel that aggregates incoming cluster events to this node.
     pub async fn recv(&self) -> Result<ClusterMessage, ()> {
@@ -110,26 +673,53 @@ impl DistributedContext {
             self.me
         );
         loop {
-            for (members, event) in self.cluster.events.try_iter() {
-                warn!(event = format!("{:?}", event).as_str(), "Cluster event");
-                if let ArtilleryMemberEvent::Payload(member, msg) = event {
-                    return Ok(ClusterMessage::new(Msg::tell(msg), member.host_key()));
-                }
+            if let Ok(msg) = self.inbox.pop() {
+                return Ok(msg);
+            }
+
+            self.receive_events();
+        }
+    }
 
-                members.iter().for_each(|m| match m.state() {
-                    ArtilleryMemberState::Alive => {
-                        let _ = self.members.insert(m.host_key(), m.clone());
-                    }
-                    ArtilleryMemberState::Down => {
-                        let _ = self.members.remove(&m.host_key());
-                    }
-                    _ => {}
-                });
+    // Handles the cluster's events, queuing the messages received
+    // from the other members in the inbox.
+    fn receive_events(&self) {
+        for (members, event) in self.cluster.events.try_iter() {
+            warn!(event = format!("{:?}", event).as_str(), "Cluster event");
+            if let ArtilleryMemberEvent::Payload(member, msg) = event {
+                self.receive_payload(member.host_key(), msg);
+                continue;
             }
+
+            members.iter().for_each(|m| match m.state() {
+                ArtilleryMemberState::Alive => {
+                    let _ = self.members.insert(m.host_key(), m.clone());
+                }
+                ArtilleryMemberState::Down => {
+                    let _ = self.members.remove(&m.host_key());
+                }
+                _ => {}
+            });
+        }
+    }
+
+    fn receive_payload(&self, member: Uuid, payload: String) {
+        if let Some(msg) = self.deliveries.receive(self, member, payload) {
+            self.inbox.push(msg);
         }
     }
 }
 
+impl Transport for DistributedContext {
+    fn send_payload(&self, to: Uuid, payload: String) {
+        self.cluster.send_payload(to, payload);
+    }
+
+    fn receive_payloads(&self) {
+        self.receive_events();
+    }
+}
+
 ///
 /// Creates distributed cluster actor
 pub(crate) fn cluster_actor<I, F>(
@@ -168,3 +758,259 @@ where
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::Runtime;
+    use std::time::Instant;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Job {
+        id: u64,
+        name: String,
+    }
+
+    impl RemoteMessage for Job {
+        const TAG: &'static str = "tests::Job";
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Other(u8);
+
+    impl RemoteMessage for Other {
+        const TAG: &'static str = "tests::Other";
+    }
+
+    fn received(payload: String) -> ClusterMessage {
+        ClusterMessage::new(Msg::tell(payload), Uuid::new_v4())
+    }
+
+    // A transport recording the payloads sent through it, whose
+    // receiving member acknowledges the message it was sent once it
+    // got it `acked_on` times.
+    struct FakeTransport {
+        deliveries: Arc<Deliveries>,
+        acked_on: Option<usize>,
+        sent: Mutex<Vec<(Uuid, Instant, String)>>,
+    }
+
+    impl FakeTransport {
+        fn new(deliveries: Arc<Deliveries>, acked_on: Option<usize>) -> Self {
+            FakeTransport {
+                deliveries,
+                acked_on,
+                sent: Mutex::default(),
+            }
+        }
+
+        fn sent(&self) -> Vec<(Uuid, Instant, String)> {
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    impl Transport for FakeTransport {
+        fn send_payload(&self, to: Uuid, payload: String) {
+            self.sent.lock().unwrap().push((to, time::now(), payload));
+        }
+
+        fn receive_payloads(&self) {
+            let sent = self.sent();
+            if self.acked_on != Some(sent.len()) {
+                return;
+            }
+            if let Some((to, _, frame)) = sent.last() {
+                let ack = match serde_json::from_str(frame).unwrap() {
+                    DeliveryFrame::Message { id, .. } => DeliveryFrame::Ack { id },
+                    DeliveryFrame::Ack { .. } => panic!("unexpected ack"),
+                };
+                let ack = serde_json::to_string(&ack).unwrap();
+                assert!(self.deliveries.receive(self, *to, ack).is_none());
+            }
+        }
+    }
+
+    // Sends a message at least once through a transport acknowledging
+    // it on its `acked_on`th attempt, and returns the result along
+    // with the transport.
+    fn send(
+        acked_on: Option<usize>,
+        delivery: AtLeastOnce,
+    ) -> (Result<(), DeliveryError>, Arc<FakeTransport>) {
+        let runtime = Runtime::builder().with_deterministic_seed(0).build();
+        runtime.start();
+
+        let deliveries = Arc::new(Deliveries::new(DEDUP_CAPACITY));
+        let transport = Arc::new(FakeTransport::new(deliveries.clone(), acked_on));
+        let result = Arc::new(Mutex::new(None));
+        let (task_transport, task_result) = (transport.clone(), result.clone());
+        runtime.spawn_task(async move {
+            let id = Uuid::new_v4();
+            let frame = DeliveryFrame::Message {
+                id,
+                payload: encode_remote(&Other(7), &JsonCodec).unwrap(),
+            };
+            let frame = serde_json::to_string(&frame).unwrap();
+            let res = deliveries
+                .send(&*task_transport, Uuid::new_v4(), id, frame, &delivery)
+                .await;
+            assert!(deliveries.unacked.lock().unwrap().is_empty());
+            *task_result.lock().unwrap() = Some(res);
+        });
+
+        runtime.run_until_idle();
+        while result.lock().unwrap().is_none() {
+            runtime.advance_time(ACK_POLL_INTERVAL);
+        }
+        runtime.shutdown();
+
+        let res = result.lock().unwrap().take().unwrap();
+        (res, transport)
+    }
+
+    // How long each attempt waited before the next one.
+    fn backoffs(transport: &FakeTransport) -> Vec<Duration> {
+        let sent = transport.sent();
+        sent.windows(2)
+            .map(|attempts| attempts[1].1 - attempts[0].1)
+            .collect()
+    }
+
+    #[test]
+    fn test_retransmits_with_backoff() {
+        let delivery = AtLeastOnce::new()
+            .with_ack_timeout(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(300))
+            .with_retries(3);
+        let (res, transport) = send(None, delivery);
+
+        assert_eq!(res, Err(DeliveryError::DeliveryFailed { attempts: 4 }));
+        assert_eq!(
+            backoffs(&transport),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(300),
+            ]
+        );
+        // The same message was retransmitted.
+        let sent = transport.sent();
+        assert!(sent.iter().all(|(to, _, frame)| (to, frame) == (&sent[0].0, &sent[0].2)));
+    }
+
+    #[test]
+    fn test_stops_retransmitting_once_acknowledged() {
+        let delivery = AtLeastOnce::new()
+            .with_ack_timeout(Duration::from_millis(100))
+            .with_retries(3);
+        let (res, transport) = send(Some(2), delivery);
+
+        assert_eq!(res, Ok(()));
+        assert_eq!(backoffs(&transport), vec![Duration::from_millis(100)]);
+    }
+
+    #[test]
+    fn test_receives_duplicates_once() {
+        let deliveries = Arc::new(Deliveries::new(DEDUP_CAPACITY));
+        let transport = FakeTransport::new(deliveries.clone(), None);
+        let (member, id) = (Uuid::new_v4(), Uuid::new_v4());
+        let frame = DeliveryFrame::Message {
+            id,
+            payload: encode_remote(&Other(7), &JsonCodec).unwrap(),
+        };
+        let frame = serde_json::to_string(&frame).unwrap();
+
+        let msg = deliveries.receive(&transport, member, frame.clone());
+        assert_eq!(msg.unwrap().decode::<Other>(), Ok(Other(7)));
+        assert!(deliveries.receive(&transport, member, frame).is_none());
+
+        // The duplicate was acknowledged again, in case the first
+        // acknowledgement was lost.
+        let ack = serde_json::to_string(&DeliveryFrame::Ack { id }).unwrap();
+        let sent: Vec<_> = transport
+            .sent()
+            .into_iter()
+            .map(|(to, _, payload)| (to, payload))
+            .collect();
+        assert_eq!(sent, vec![(member, ack.clone()), (member, ack)]);
+
+        // Messages sent with `tell_remote` aren't acknowledged.
+        let payload = encode_remote(&Other(8), &JsonCodec).unwrap();
+        let msg = deliveries.receive(&transport, member, payload);
+        assert_eq!(msg.unwrap().decode::<Other>(), Ok(Other(8)));
+        assert_eq!(transport.sent().len(), 2);
+    }
+
+    #[test]
+    fn test_remote_message_roundtrip() {
+        let job = Job {
+            id: 42,
+            name: "job".to_string(),
+        };
+        let msg = received(encode_remote(&job, &JsonCodec).unwrap());
+
+        assert_eq!(msg.tag(), Some("tests::Job".to_string()));
+        assert_eq!(msg.decode::<Job>(), Ok(job));
+        assert_eq!(
+            msg.decode::<Other>(),
+            Err(DecodeError::TagMismatch("tests::Job".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_delivered_drops_duplicates() {
+        let mut delivered = Delivered::new(2);
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(delivered.insert(first));
+        assert!(delivered.insert(second));
+        assert!(!delivered.insert(first));
+
+        // The least recently received id is forgotten.
+        assert!(delivered.insert(third));
+        assert!(!delivered.insert(first));
+        assert!(delivered.insert(second));
+        assert_eq!(delivered.ids.len(), 2);
+        assert_eq!(delivered.order.len(), 2);
+    }
+
+    #[test]
+    fn test_delivery_frame_roundtrip() {
+        let id = Uuid::new_v4();
+        let payload = encode_remote(&Other(7), &JsonCodec).unwrap();
+        let frame = DeliveryFrame::Message {
+            id,
+            payload: payload.clone(),
+        };
+
+        match serde_json::from_str(&serde_json::to_string(&frame).unwrap()) {
+            Ok(DeliveryFrame::Message {
+                id: got,
+                payload: data,
+            }) => {
+                assert_eq!(got, id);
+                assert_eq!(received(data).decode::<Other>(), Ok(Other(7)));
+            }
+            _ => panic!("unexpected frame"),
+        }
+        // Remote messages aren't mistaken for frames.
+        assert!(serde_json::from_str::<DeliveryFrame>(&payload).is_err());
+    }
+
+    #[test]
+    fn test_remote_message_decode_errors() {
+        let msg = received("not remote".to_string());
+        assert_eq!(msg.tag(), None);
+        assert_eq!(msg.decode::<Job>(), Err(DecodeError::NotRemote));
+
+        let payload = RemotePayload {
+            tag: Job::TAG.to_string(),
+            data: "{\"id\": \"oops\"}".to_string(),
+        };
+        let msg = received(serde_json::to_string(&payload).unwrap());
+        match msg.decode::<Job>() {
+            Err(DecodeError::Malformed(_)) => (),
+            res => panic!("unexpected decoding result: {:?}", res),
+        }
+    }
+}
@@ -3,15 +3,24 @@
 //! and instruct Bastion how to send messages back to them
 
 use crate::broadcast::Sender;
+use crate::errors::SendError;
+use crate::mailbox::BoundedMailbox;
 use crate::message::{BastionMessage, Message, Msg};
 use crate::path::BastionPath;
-use crate::system::SYSTEM;
+use crate::system;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub(crate) struct Envelope {
     pub(crate) msg: BastionMessage,
     pub(crate) sign: RefAddr,
+    // Whether the message should be received before the
+    // other messages waiting in the recipient's mailbox.
+    pub(crate) high_priority: bool,
+    // Whether room was reserved for the message in the
+    // recipient's bounded mailbox.
+    pub(crate) reserved: bool,
 }
 
 #[derive(Debug)]
@@ -110,17 +119,41 @@ impl SignedMessage {
 pub struct RefAddr {
     path: Arc<BastionPath>,
     sender: Sender,
+    // The bounded mailbox of the recipient, if it has one.
+    mailbox: Option<Arc<BoundedMailbox>>,
+    // How long asking the recipient waits for its answer by
+    // default.
+    ask_timeout: Option<Duration>,
 }
 
 impl RefAddr {
     pub(crate) fn new(path: Arc<BastionPath>, sender: Sender) -> Self {
-        RefAddr { path, sender }
+        RefAddr {
+            path,
+            sender,
+            mailbox: None,
+            ask_timeout: None,
+        }
+    }
+
+    pub(crate) fn with_mailbox(mut self, mailbox: Option<Arc<BoundedMailbox>>) -> Self {
+        self.mailbox = mailbox;
+        self
+    }
+
+    pub(crate) fn with_ask_timeout(mut self, ask_timeout: Option<Duration>) -> Self {
+        self.ask_timeout = ask_timeout;
+        self
+    }
+
+    pub(crate) fn ask_timeout(&self) -> Option<Duration> {
+        self.ask_timeout
     }
 
     pub(crate) fn dead_letters() -> Self {
         Self::new(
-            SYSTEM.dead_letters().path().clone(),
-            SYSTEM.dead_letters().sender().clone(),
+            system::current().dead_letters().path().clone(),
+            system::current().dead_letters().sender().clone(),
         )
     }
 
@@ -208,8 +241,34 @@ impl RefAddr {
         &self.path
     }
 
-    pub(crate) fn sender(&self) -> &Sender {
-        &self.sender
+    // Sends a user message, giving it back if the recipient's
+    // mailbox is full.
+    pub(crate) fn try_send(&self, env: Envelope) -> Result<(), SendError<Envelope>> {
+        match &self.mailbox {
+            Some(mailbox) => mailbox.try_send(&self.sender, env),
+            None => self
+                .sender
+                .unbounded_send(env)
+                .map_err(|err| SendError::Unreachable(err.into_inner())),
+        }
+    }
+
+    // Sends a user message, waiting for the recipient's mailbox
+    // to have room for it if it applies backpressure.
+    pub(crate) async fn send(&self, env: Envelope) -> Result<(), SendError<Envelope>> {
+        match &self.mailbox {
+            Some(mailbox) => mailbox.send(&self.sender, env).await,
+            None => self.try_send(env),
+        }
+    }
+
+    // Sends a user message, waiting for the recipient's mailbox
+    // to have room for it whatever its policy is.
+    pub(crate) async fn send_when_room(&self, env: Envelope) -> Result<(), SendError<Envelope>> {
+        match &self.mailbox {
+            Some(mailbox) => mailbox.send_when_room(&self.sender, env).await,
+            None => self.try_send(env),
+        }
     }
 }
 
@@ -218,24 +277,41 @@ impl Envelope {
         Envelope {
             msg,
             sign: RefAddr::new(path, sender),
+            high_priority: false,
+            reserved: false,
         }
     }
 
     pub(crate) fn new_with_sign(msg: BastionMessage, sign: RefAddr) -> Self {
-        Envelope { msg, sign }
+        Envelope {
+            msg,
+            sign,
+            high_priority: false,
+            reserved: false,
+        }
     }
 
     pub(crate) fn from_dead_letters(msg: BastionMessage) -> Self {
         Envelope {
             msg,
             sign: RefAddr::dead_letters(),
+            high_priority: false,
+            reserved: false,
         }
     }
 
+    pub(crate) fn with_high_priority(mut self) -> Self {
+        self.high_priority = true;
+        self
+    }
+
     pub(crate) fn try_clone(&self) -> Option<Self> {
         self.msg.try_clone().map(|msg| Envelope {
             msg,
             sign: self.sign.clone(),
+            high_priority: self.high_priority,
+            // The clones are delivered to other recipients.
+            reserved: false,
         })
     }
 
@@ -2,6 +2,12 @@
 //! Describes the error types that may happen within bastion.
 //! Given Bastion has a let it crash strategy, most error aren't noticeable.
 //! A ReceiveError may however be raised when calling try_recv() or try_recv_timeout()
+//! and a RequestError when calling ChildrenRef::request()
+//! or an AskError when calling ask_timeout()
+//! and a SendError when calling tell_async()
+//! and a PauseError when calling ChildrenRef::pause() or ChildrenRef::resume()
+//! and a StopError when calling ChildRef::stop_and_wait()
+//! and a DispatchError when calling BastionContext::send_to_group()
 //! More errors may happen in the future.
 
 use std::time::Duration;
@@ -15,3 +21,84 @@ pub enum ReceiveError {
     /// Generic error. Not used yet
     Other,
 }
+
+#[derive(Debug, PartialEq, Eq)]
+/// These errors happen
+/// when BastionContext::ask_timeout() is invoked
+pub enum AskError {
+    /// The recipient didn't answer on time
+    Timeout(Duration),
+    /// The recipient was already dead when the message was sent
+    Unreachable,
+    /// The recipient's mailbox was full when the message was sent
+    MailboxFull,
+    /// The recipient dropped the message without answering it
+    Dropped,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// These errors happen
+/// when BastionContext::tell_async() is invoked,
+/// giving the message back to its sender
+pub enum SendError<M> {
+    /// The recipient's mailbox was full
+    MailboxFull(M),
+    /// The recipient was dead
+    Unreachable(M),
+}
+
+impl<M> SendError<M> {
+    /// Returns the message that couldn't be sent
+    pub fn into_inner(self) -> M {
+        match self {
+            SendError::MailboxFull(msg) | SendError::Unreachable(msg) => msg,
+        }
+    }
+
+    pub(crate) fn map<N>(self, f: impl FnOnce(M) -> N) -> SendError<N> {
+        match self {
+            SendError::MailboxFull(msg) => SendError::MailboxFull(f(msg)),
+            SendError::Unreachable(msg) => SendError::Unreachable(f(msg)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// These errors happen
+/// when ChildrenRef::request() is invoked
+pub enum RequestError {
+    /// The children group has no element able to receive the request
+    NoActor,
+    /// The element stopped or dropped the request without answering it
+    Dropped,
+    /// The element answered with a message of another type than the expected one
+    TypeMismatch,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// These errors happen
+/// when ChildrenRef::pause() or ChildrenRef::resume() are invoked
+pub enum PauseError {
+    /// The children group already stopped
+    Stopped,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// These errors happen
+/// when ChildRef::stop_and_wait() or ChildRef::stop_and_wait_timeout() are invoked
+pub enum StopError {
+    /// The child didn't stop on time, and was killed
+    Timeout(Duration),
+    /// The child was already dead when asked to stop
+    Unreachable,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// These errors happen
+/// when BastionContext::send_to_group() is invoked
+pub enum DispatchError {
+    /// The group had no live element to deliver the message to
+    NoAvailableRecipient,
+    /// The mailboxes of all the live elements of the group were full
+    MailboxFull,
+}
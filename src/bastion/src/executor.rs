@@ -1,7 +1,11 @@
 //! A module that exposes the functions used under the hoods from `bastion`s macros: `spawn!`, `run!`
 //! and `blocking!`.
+#[cfg(feature = "tokio-runtime")]
+pub use bastion_executor::tokio_runtime::handle as tokio_handle;
+pub use bastion_executor::worker::current_worker_id;
+pub use lightproc::proc_handle::ProcError;
 pub use lightproc::proc_stack::ProcStack;
-use lightproc::recoverable_handle::RecoverableHandle;
+pub use lightproc::recoverable_handle::RecoverableHandle;
 use std::future::Future;
 
 /// Spawns a blocking task, which will run on the blocking thread pool,
@@ -55,6 +59,15 @@ where
 
 /// Spawn a given future onto the executor from the global level.
 ///
+/// Called by the actors of a [`Runtime`], the future is spawned onto
+/// the runtime's threads instead.
+///
+/// The future isn't supervised: it is neither restarted nor stopped
+/// along with the actor that spawned it. See [`Bastion::spawn_task`].
+///
+/// [`Runtime`]: ../runtime/struct.Runtime.html
+/// [`Bastion::spawn_task`]: ../struct.Bastion.html#method.spawn_task
+///
 /// # Example
 /// ```
 /// # use bastion::prelude::*;
@@ -69,5 +82,9 @@ where
     F: Future<Output = T> + Send + 'static,
     T: Send + 'static,
 {
-    bastion_executor::pool::spawn(future, lightproc::proc_stack::ProcStack::default())
+    let stack = lightproc::proc_stack::ProcStack::default();
+    match crate::system::try_current() {
+        Some(system) => system.spawn(future, stack),
+        None => bastion_executor::pool::spawn(future, stack),
+    }
 }
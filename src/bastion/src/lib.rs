@@ -58,7 +58,7 @@
 // Doc generation experimental features
 #![cfg_attr(feature = "docs", feature(doc_cfg))]
 
-pub use self::bastion::Bastion;
+pub use self::bastion::{Bastion, StopReport};
 pub use self::callbacks::Callbacks;
 pub use self::config::Config;
 
@@ -76,16 +76,24 @@ pub mod child_ref;
 pub mod children;
 pub mod children_ref;
 pub mod context;
+pub mod dead_letters;
 pub mod dispatcher;
 pub mod envelope;
 pub mod executor;
 #[cfg(not(target_os = "windows"))]
 pub mod io;
+pub mod lifecycle;
+pub mod local;
+pub mod mailbox;
 pub mod message;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod path;
 #[cfg(feature = "scaling")]
 pub mod resizer;
+pub mod runtime;
 pub mod supervisor;
+pub mod time;
 
 pub mod errors;
 
@@ -97,29 +105,37 @@ distributed_api! {
 ///
 /// Prelude of Bastion
 pub mod prelude {
-    pub use crate::bastion::Bastion;
+    pub use crate::bastion::{Bastion, StopReport};
     pub use crate::callbacks::Callbacks;
     pub use crate::child_ref::ChildRef;
     pub use crate::children::Children;
     pub use crate::children_ref::ChildrenRef;
     pub use crate::config::Config;
-    pub use crate::context::{BastionContext, BastionId, NIL_ID};
+    pub use crate::context::{BastionContext, BastionId, RecvOr, NIL_ID};
+    pub use crate::dead_letters::{DeadLetter, DeadLetterReason};
     pub use crate::dispatcher::{
-        BroadcastTarget, DefaultDispatcherHandler, Dispatcher, DispatcherHandler, DispatcherMap,
-        DispatcherType, NotificationType,
+        BroadcastTarget, ConsistentHashHandler, DeadChildPolicy, DefaultDispatcherHandler,
+        Delivery, Dispatcher, DispatcherHandler, DispatcherMap, DispatcherType, LeaveReason,
+        MembershipEvent, NotificationType, WeightedRoundRobinHandler,
     };
     pub use crate::envelope::{RefAddr, SignedMessage};
     pub use crate::errors::*;
+    pub use crate::executor::{ProcError, RecoverableHandle};
     #[cfg(not(target_os = "windows"))]
     pub use crate::io::*;
+    pub use crate::lifecycle::{
+        LifecycleEvent, LifecycleEventKind, LifecycleEvents, RestartReason,
+    };
+    pub use crate::mailbox::MailboxPolicy;
     pub use crate::message::{Answer, AnswerSender, Message, Msg};
     pub use crate::msg;
     pub use crate::path::{BastionPath, BastionPathElement};
     #[cfg(feature = "scaling")]
     pub use crate::resizer::{OptimalSizeExploringResizer, UpperBound, UpscaleStrategy};
+    pub use crate::runtime::{Runtime, RuntimeBuilder};
     pub use crate::supervisor::{
-        ActorRestartStrategy, RestartPolicy, RestartStrategy, SupervisionStrategy, Supervisor,
-        SupervisorRef,
+        ActorRestartStrategy, EscalationPolicy, FailureInfo, JitterKind, RestartDelay,
+        RestartPolicy, RestartStrategy, SupervisionStrategy, Supervisor, SupervisorRef,
     };
     pub use crate::{answer, blocking, children, run, spawn, supervisor};
 
@@ -0,0 +1,193 @@
+//!
+//! Events about the elements of a supervision tree starting,
+//! restarting, stopping and failing.
+//!
+//! The events of a whole runtime can be received with
+//! [`Bastion::lifecycle_events`], instead of setting callbacks on each
+//! children group (see [`Callbacks`]).
+//!
+//! [`Bastion::lifecycle_events`]: ../struct.Bastion.html#method.lifecycle_events
+//! [`Callbacks`]: ../struct.Callbacks.html
+
+use crate::path::BastionPath;
+use crate::supervisor::FailureInfo;
+use futures::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+use std::time::SystemTime;
+
+// How many events a receiver keeps until they are received.
+const CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone)]
+/// Something that happened to an element of the supervision tree.
+pub struct LifecycleEvent {
+    /// The path of the element it happened to.
+    pub path: Arc<BastionPath>,
+    /// What happened to it.
+    pub kind: LifecycleEventKind,
+    /// When it happened.
+    pub timestamp: SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// What happened to an element of the supervision tree.
+pub enum LifecycleEventKind {
+    /// The child started, which it also does once restarted.
+    Started,
+    /// The supervisor of the element restarted it.
+    Restarted {
+        /// How many times the element was restarted, including this
+        /// time.
+        restart_count: usize,
+        /// Why the element was restarted.
+        reason: RestartReason,
+    },
+    /// The child stopped without failing and won't get restarted.
+    Stopped,
+    /// The supervisor failed, either because it escalated or because
+    /// its own supervisor made it fail.
+    Failed,
+    /// An element of the supervisor ran out of restarts, so the
+    /// supervisor escalated the failure to its own supervisor.
+    Escalated,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Why an element of the supervision tree was restarted.
+///
+/// When the supervision strategy restarts more than the failed
+/// element, the elements restarted alongside it get the failed
+/// element's reason.
+pub enum RestartReason {
+    /// The element returned an error, failed its health check or
+    /// failed after escalating.
+    Faulted,
+    /// The element panicked, with the message it panicked with if
+    /// it panicked with a string.
+    Panicked(Option<String>),
+}
+
+/// The lifecycle events of a runtime, received in the order they
+/// happened in (see [`Bastion::lifecycle_events`]).
+///
+/// The events are kept until they are received, up to 1024 of them
+/// after which the oldest ones are dropped, so that a slow receiver
+/// never slows down the supervision of the runtime.
+///
+/// It can be used as a [`Stream`], or polled with [`try_recv`].
+///
+/// [`Bastion::lifecycle_events`]: ../struct.Bastion.html#method.lifecycle_events
+/// [`Stream`]: https://docs.rs/futures/0.3/futures/stream/trait.Stream.html
+/// [`try_recv`]: #method.try_recv
+#[derive(Debug)]
+pub struct LifecycleEvents {
+    queue: Arc<Queue>,
+}
+
+#[derive(Debug)]
+struct Queue {
+    events: Mutex<VecDeque<LifecycleEvent>>,
+    dropped: AtomicUsize,
+    waker: Mutex<Option<Waker>>,
+}
+
+// The receivers of the lifecycle events of a system.
+#[derive(Debug, Default)]
+pub(crate) struct Lifecycle {
+    queues: Mutex<Vec<Weak<Queue>>>,
+}
+
+impl RestartReason {
+    pub(crate) fn of(failure: &FailureInfo) -> Self {
+        if failure.panicked() {
+            RestartReason::Panicked(failure.panic_message().map(String::from))
+        } else {
+            RestartReason::Faulted
+        }
+    }
+}
+
+impl LifecycleEvents {
+    /// Returns the oldest event that wasn't received yet, if any.
+    pub fn try_recv(&self) -> Option<LifecycleEvent> {
+        self.queue.events.lock().unwrap().pop_front()
+    }
+
+    /// Returns how many events were dropped because they weren't
+    /// received while the capacity was reached.
+    pub fn dropped(&self) -> usize {
+        self.queue.dropped.load(Ordering::SeqCst)
+    }
+}
+
+impl Stream for LifecycleEvents {
+    type Item = LifecycleEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        if let Some(event) = self.try_recv() {
+            return Poll::Ready(Some(event));
+        }
+
+        *self.queue.waker.lock().unwrap() = Some(cx.waker().clone());
+        // An event might have been pushed before the waker was set.
+        match self.try_recv() {
+            Some(event) => Poll::Ready(Some(event)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl Queue {
+    fn push(&self, event: LifecycleEvent) {
+        {
+            let mut events = self.events.lock().unwrap();
+            if events.len() == CAPACITY {
+                events.pop_front();
+                self.dropped.fetch_add(1, Ordering::SeqCst);
+            }
+            events.push_back(event);
+        }
+
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Lifecycle {
+    pub(crate) fn subscribe(&self) -> LifecycleEvents {
+        let queue = Arc::new(Queue {
+            events: Mutex::new(VecDeque::new()),
+            dropped: AtomicUsize::new(0),
+            waker: Mutex::default(),
+        });
+        self.queues.lock().unwrap().push(Arc::downgrade(&queue));
+
+        LifecycleEvents { queue }
+    }
+
+    // Sends the event to every receiver, forgetting the dropped ones.
+    pub(crate) fn emit(&self, path: &Arc<BastionPath>, kind: LifecycleEventKind) {
+        let mut queues = self.queues.lock().unwrap();
+        if queues.is_empty() {
+            return;
+        }
+
+        let event = LifecycleEvent {
+            path: path.clone(),
+            kind,
+            timestamp: SystemTime::now(),
+        };
+        queues.retain(|queue| match queue.upgrade() {
+            Some(queue) => {
+                queue.push(event.clone());
+                true
+            }
+            None => false,
+        });
+    }
+}
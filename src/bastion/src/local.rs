@@ -0,0 +1,172 @@
+//!
+//! Storage local to the actors, as `thread_local!` is to threads.
+//!
+//! Each actor lazily gets its own value of each type it accesses,
+//! which it keeps whichever worker thread runs it after an await
+//! point. The values are dropped once the actor stops or restarts,
+//! its restarted incarnation starting from new values.
+//!
+//! The values are identified by their type, so wrapping them in a
+//! type specific to their use keeps them from being mixed up.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use bastion::prelude::*;
+//! use bastion::local;
+//!
+//! #[derive(Default)]
+//! struct Buffer(Vec<u8>);
+//!
+//! fn handle(data: &[u8]) -> usize {
+//!     // No need to get the buffer from the exec closure.
+//!     local::with(|buffer: &mut Buffer| {
+//!         buffer.0.clear();
+//!         buffer.0.extend_from_slice(data);
+//!         buffer.0.len()
+//!     })
+//! }
+//!
+//! # Bastion::init();
+//! Bastion::children(|children| {
+//!     children.with_exec(|ctx: BastionContext| async move {
+//!         loop {
+//!             msg! { ctx.recv().await?,
+//!                 data: &'static [u8] => {
+//!                     handle(data);
+//!                 };
+//!                 _: _ => ();
+//!             }
+//!         }
+//!     })
+//! }).expect("Couldn't create the children group.");
+//! #
+//! # Bastion::start();
+//! # Bastion::stop();
+//! # Bastion::block_until_stopped();
+//! ```
+
+use fxhash::FxHashMap;
+use std::any::{type_name, Any, TypeId};
+use std::cell::RefCell;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+thread_local! {
+    // The storage of the actor that the thread is polling.
+    static CURRENT: RefCell<Option<Arc<Locals>>> = const { RefCell::new(None) };
+}
+
+// A value is `None` while it is being accessed.
+type Values = FxHashMap<TypeId, Option<Box<dyn Any + Send>>>;
+
+/// The storage of an actor.
+#[derive(Debug, Default)]
+pub(crate) struct Locals(Mutex<Values>);
+
+// Restores the storage the thread was polling once the actor
+// yields.
+pub(crate) struct Entered(Option<Arc<Locals>>);
+
+// Puts the value back into the storage once it was accessed.
+struct Borrowed<'a> {
+    locals: &'a Locals,
+    type_id: TypeId,
+    value: Option<Box<dyn Any + Send>>,
+}
+
+///
+/// Calls `f` with the current actor's value of type `T`, creating
+/// it first if the actor didn't access it yet.
+///
+/// # Panics
+///
+/// Panics if it isn't called by an actor, or if the actor is
+/// already accessing its value of type `T`.
+pub fn with<T, F, R>(f: F) -> R
+where
+    T: Default + Send + 'static,
+    F: FnOnce(&mut T) -> R,
+{
+    try_with(f).expect("actor-local storage accessed outside of an actor")
+}
+
+///
+/// Calls `f` with the current actor's value of type `T`, as [`with`]
+/// does, or returns `None` if it isn't called by an actor.
+///
+/// [`with`]: fn.with.html
+pub fn try_with<T, F, R>(f: F) -> Option<R>
+where
+    T: Default + Send + 'static,
+    F: FnOnce(&mut T) -> R,
+{
+    let locals = CURRENT.with(|current| current.borrow().clone())?;
+    Some(locals.with(f))
+}
+
+impl Locals {
+    pub(crate) fn with<T, F, R>(&self, f: F) -> R
+    where
+        T: Default + Send + 'static,
+        F: FnOnce(&mut T) -> R,
+    {
+        let type_id = TypeId::of::<T>();
+        let value = match self.0.lock().unwrap().get_mut(&type_id) {
+            Some(value) => value.take(),
+            None => None,
+        };
+        let value = match value {
+            Some(value) => value,
+            None => {
+                // Creates the value out of the lock, in case it
+                // accesses other values.
+                let mut values = self.0.lock().unwrap();
+                if values.contains_key(&type_id) {
+                    panic!("the actor-local {} is already accessed", type_name::<T>());
+                }
+                values.insert(type_id, None);
+                drop(values);
+
+                Box::new(T::default())
+            }
+        };
+
+        let mut borrowed = Borrowed {
+            locals: self,
+            type_id,
+            value: Some(value),
+        };
+        // The value is of type `T` since it is stored with its id.
+        let value = borrowed.value.as_mut().unwrap();
+        f(value.downcast_mut().unwrap())
+    }
+
+    /// Sets the storage as the one of the actor that the thread is
+    /// polling, until the returned guard is dropped.
+    pub(crate) fn enter(self: &Arc<Self>) -> Entered {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        Entered(previous)
+    }
+
+    /// Drops the values of the actor.
+    pub(crate) fn clear(&self) {
+        let values = mem::take(&mut *self.0.lock().unwrap());
+        // Dropping a value might access the storage.
+        drop(values);
+    }
+}
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with(|current| current.replace(previous));
+    }
+}
+
+impl Drop for Borrowed<'_> {
+    fn drop(&mut self) {
+        let mut values = self.locals.0.lock().unwrap();
+        values.insert(self.type_id, self.value.take());
+    }
+}
@@ -0,0 +1,178 @@
+//!
+//! Bounded mailboxes, limiting how many messages can wait
+//! to be received by a child of a children group.
+use crate::broadcast::Sender;
+use crate::envelope::Envelope;
+use crate::errors::SendError;
+use crossbeam_queue::SegQueue;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The policy applied when a message is sent to a child whose
+/// mailbox is full (see [`Children::with_mailbox_capacity`]).
+///
+/// Whatever the policy is, asking a child whose mailbox is full
+/// always fails instead of waiting.
+///
+/// [`Children::with_mailbox_capacity`]: ../children/struct.Children.html#method.with_mailbox_capacity
+pub enum MailboxPolicy {
+    /// The message is given back to its sender with
+    /// `SendError::MailboxFull`.
+    #[default]
+    Reject,
+    /// The sender waits until the mailbox has room for the
+    /// message when using [`BastionContext::tell_async`], and
+    /// gets the message back with the other methods.
+    ///
+    /// [`BastionContext::tell_async`]: ../context/struct.BastionContext.html#method.tell_async
+    Backpressure,
+}
+
+#[derive(Debug)]
+pub(crate) struct BoundedMailbox {
+    capacity: usize,
+    policy: MailboxPolicy,
+    // The number of messages that were sent to the child and
+    // that it didn't receive yet.
+    len: AtomicUsize,
+    // Whether the child is dead, in which case no sender
+    // should wait for its mailbox to have room anymore.
+    closed: AtomicBool,
+    // The senders waiting for the mailbox to have room.
+    waiters: SegQueue<Waker>,
+}
+
+struct Reserve<'a> {
+    mailbox: &'a BoundedMailbox,
+}
+
+impl BoundedMailbox {
+    pub(crate) fn new(capacity: usize, policy: MailboxPolicy) -> Self {
+        BoundedMailbox {
+            capacity,
+            policy,
+            len: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            waiters: SegQueue::new(),
+        }
+    }
+
+    fn try_reserve(&self) -> bool {
+        self.len
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |len| {
+                if len < self.capacity {
+                    Some(len + 1)
+                } else {
+                    None
+                }
+            })
+            .is_ok()
+    }
+
+    // Counts a message that was delivered to the child without
+    // reserving room for it first (e.g. a broadcasted message).
+    pub(crate) fn force_reserve(&self) {
+        self.len.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn release(&self) {
+        self.len
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |len| len.checked_sub(1))
+            .ok();
+        self.wake_waiters();
+    }
+
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.wake_waiters();
+    }
+
+    fn wake_waiters(&self) {
+        // Every waiter is woken up because some of them might not
+        // be interested in the room anymore; the others will wait
+        // again if they didn't get any.
+        while let Ok(waker) = self.waiters.pop() {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn try_send(
+        &self,
+        sender: &Sender,
+        env: Envelope,
+    ) -> Result<(), SendError<Envelope>> {
+        if !self.try_reserve() {
+            return Err(SendError::MailboxFull(env));
+        }
+
+        self.send_reserved(sender, env)
+    }
+
+    pub(crate) async fn send(
+        &self,
+        sender: &Sender,
+        env: Envelope,
+    ) -> Result<(), SendError<Envelope>> {
+        if self.policy != MailboxPolicy::Backpressure {
+            return self.try_send(sender, env);
+        }
+
+        self.send_when_room(sender, env).await
+    }
+
+    // Waits for the mailbox to have room for the message whatever
+    // its policy is.
+    pub(crate) async fn send_when_room(
+        &self,
+        sender: &Sender,
+        env: Envelope,
+    ) -> Result<(), SendError<Envelope>> {
+        if !(Reserve { mailbox: self }).await {
+            return Err(SendError::Unreachable(env));
+        }
+
+        self.send_reserved(sender, env)
+    }
+
+    fn send_reserved(&self, sender: &Sender, mut env: Envelope) -> Result<(), SendError<Envelope>> {
+        env.reserved = true;
+        sender.unbounded_send(env).map_err(|err| {
+            self.release();
+            let mut env = err.into_inner();
+            env.reserved = false;
+            SendError::Unreachable(env)
+        })
+    }
+}
+
+impl<'a> Future for Reserve<'a> {
+    // Whether room was reserved, which isn't the case if the
+    // child died while waiting.
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        let mailbox = self.mailbox;
+        if mailbox.closed.load(Ordering::SeqCst) {
+            return Poll::Ready(false);
+        }
+
+        if mailbox.try_reserve() {
+            return Poll::Ready(true);
+        }
+
+        mailbox.waiters.push(ctx.waker().clone());
+
+        // The room might have been released before the waker
+        // was registered.
+        if mailbox.closed.load(Ordering::SeqCst) {
+            Poll::Ready(false)
+        } else if mailbox.try_reserve() {
+            Poll::Ready(true)
+        } else {
+            Poll::Pending
+        }
+    }
+}
@@ -7,10 +7,12 @@
 //! * Messages are not guaranteed to be ordered, all message's order is causal.
 //!
 use crate::callbacks::CallbackType;
+use crate::child::Init;
 use crate::children::Children;
 use crate::context::{BastionId, ContextState};
 use crate::envelope::{RefAddr, SignedMessage};
 use crate::supervisor::{SupervisionStrategy, Supervisor};
+use crate::time::Delay;
 
 use futures::channel::oneshot::{self, Receiver};
 use std::any::{type_name, Any};
@@ -19,6 +21,7 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tracing::{debug, trace};
 
 /// A trait that any message sent needs to implement (it is
@@ -33,6 +36,9 @@ use tracing::{debug, trace};
 pub trait Message: Any + Send + Sync + Debug {}
 impl<T> Message for T where T: Any + Send + Sync + Debug {}
 
+/// What a panicking element panicked with.
+pub(crate) type PanicPayload = Box<dyn Any + Send>;
+
 #[derive(Debug)]
 #[doc(hidden)]
 pub struct AnswerSender(oneshot::Sender<SignedMessage>);
@@ -44,6 +50,10 @@ pub struct AnswerSender(oneshot::Sender<SignedMessage>);
 /// answered by the child (see the [`msg!`] macro for more
 /// information).
 ///
+/// If the children group of the child was given a default ask
+/// timeout with [`Children::with_ask_timeout`], it resolves to
+/// `Err(())` once the timeout elapsed without an answer.
+///
 /// # Example
 ///
 /// ```rust
@@ -113,7 +123,12 @@ pub struct AnswerSender(oneshot::Sender<SignedMessage>);
 /// [`ChildRef::ask`]: ../children/struct.ChildRef.html#method.ask
 /// [`Msg`]: message/struct.Msg.html
 /// [`msg!`]: macro.msg.html
-pub struct Answer(Receiver<SignedMessage>);
+/// [`Children::with_ask_timeout`]: ../children/struct.Children.html#method.with_ask_timeout
+pub struct Answer {
+    recver: Receiver<SignedMessage>,
+    // Elapses once the answer timed out, if it can.
+    timeout: Option<Delay>,
+}
 
 #[derive(Debug)]
 /// A message returned by [`BastionContext::recv`] or
@@ -174,7 +189,12 @@ pub struct Answer(Receiver<SignedMessage>);
 /// [`BastionContext::recv`]: context/struct.BastionContext.html#method.recv
 /// [`BastionContext::try_recv`]: context/struct.BastionContext.html#method.try_recv
 /// [`msg!`]: macro.msg.html
-pub struct Msg(MsgInner);
+pub struct Msg {
+    inner: MsgInner,
+    // The name of the type of the message, kept to be able to
+    // tell which message wasn't handled.
+    type_name: &'static str,
+}
 
 #[derive(Debug)]
 enum MsgInner {
@@ -206,6 +226,8 @@ pub(crate) enum BastionMessage {
     RestartRequired {
         id: BastionId,
         parent_id: BastionId,
+        // What the element panicked with, if it panicked.
+        panic: Option<PanicPayload>,
     },
     FinishedChild {
         id: BastionId,
@@ -215,10 +237,19 @@ pub(crate) enum BastionMessage {
     RestoreChild {
         id: BastionId,
         state: Arc<Pin<Box<ContextState>>>,
+        // Told once the restored child started, when it's restarted
+        // in sequence with other elements.
+        started: Option<oneshot::Sender<()>>,
     },
     DropChild {
         id: BastionId,
     },
+    // Stops the child without stopping it for good, since it's about
+    // to get restored, and tells once it's done.
+    TearDownChild {
+        id: BastionId,
+        done: oneshot::Sender<()>,
+    },
     SetState {
         state: Arc<Pin<Box<ContextState>>>,
     },
@@ -229,6 +260,8 @@ pub(crate) enum BastionMessage {
         id: BastionId,
     },
     Heartbeat,
+    Resume,
+    HotSwap(Init),
 }
 
 #[derive(Debug)]
@@ -243,6 +276,11 @@ impl AnswerSender {
     #[doc(hidden)]
     pub fn send<M: Message>(self, msg: M, sign: RefAddr) -> Result<(), M> {
         debug!("{:?}: Sending answer: {:?}", self, msg);
+        #[cfg(feature = "tracing-spans")]
+        tracing::debug!(
+            message_type = std::any::type_name::<M>(),
+            "message answered"
+        );
         let msg = Msg::tell(msg);
         trace!("{:?}: Sending message: {:?}", self, msg);
         self.0
@@ -252,56 +290,162 @@ impl AnswerSender {
 }
 
 impl Msg {
+    fn new(inner: MsgInner, type_name: &'static str) -> Self {
+        Msg { inner, type_name }
+    }
+
+    /// Returns the name of the type of the message this `Msg`
+    /// contains, as returned by [`std::any::type_name`].
+    ///
+    /// This is mostly useful to log the messages that aren't
+    /// handled in the default case of [`msg!`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             msg! { ctx.recv().await?,
+    ///                 msg: &'static str => {
+    ///                     // Handle the message...
+    ///                 };
+    ///                 msg: _ => {
+    ///                     println!("Unexpected message of type {}", msg.type_name());
+    ///                 };
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`std::any::type_name`]: https://doc.rust-lang.org/std/any/fn.type_name.html
+    /// [`msg!`]: ../macro.msg.html
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
     pub(crate) fn broadcast<M: Message>(msg: M) -> Self {
         let inner = MsgInner::Broadcast(Arc::new(msg));
-        Msg(inner)
+        Msg::new(inner, type_name::<M>())
     }
 
     pub(crate) fn tell<M: Message>(msg: M) -> Self {
         let inner = MsgInner::Tell(Box::new(msg));
-        Msg(inner)
+        Msg::new(inner, type_name::<M>())
     }
 
     pub(crate) fn ask<M: Message>(msg: M) -> (Self, Answer) {
         let msg = Box::new(msg);
         let (sender, recver) = oneshot::channel();
         let sender = AnswerSender(sender);
-        let answer = Answer(recver);
+        let answer = Answer {
+            recver,
+            timeout: None,
+        };
 
         let sender = Some(sender);
         let inner = MsgInner::Ask { msg, sender };
 
-        (Msg(inner), answer)
+        (Msg::new(inner, type_name::<M>()), answer)
     }
 
     #[doc(hidden)]
     pub fn is_broadcast(&self) -> bool {
-        matches!(self.0, MsgInner::Broadcast(_))
+        matches!(self.inner, MsgInner::Broadcast(_))
     }
 
     #[doc(hidden)]
     pub fn is_tell(&self) -> bool {
-        matches!(self.0, MsgInner::Tell(_))
+        matches!(self.inner, MsgInner::Tell(_))
     }
 
     #[doc(hidden)]
     pub fn is_ask(&self) -> bool {
-        matches!(self.0, MsgInner::Ask { .. })
+        matches!(self.inner, MsgInner::Ask { .. })
     }
 
     #[doc(hidden)]
     pub fn take_sender(&mut self) -> Option<AnswerSender> {
         debug!("{:?}: Taking sender.", self);
-        if let MsgInner::Ask { sender, .. } = &mut self.0 {
+        if let MsgInner::Ask { sender, .. } = &mut self.inner {
             sender.take()
         } else {
             None
         }
     }
 
+    #[doc(hidden)]
+    pub fn restore_sender(&mut self, answer_sender: AnswerSender) {
+        if let MsgInner::Ask { sender, .. } = &mut self.inner {
+            *sender = Some(answer_sender);
+        }
+    }
+
+    /// Answers this message with `answer` if it was asked and
+    /// wasn't answered yet, which is mostly useful in the default
+    /// case of [`msg!`] (in the other cases, use `answer!`).
+    ///
+    /// This method returns `()` if it succeeded, or `Err(answer)`
+    /// otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer` - The answer to send.
+    /// * `sign` - The answer's signature, usually [`BastionContext::signature`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             msg! { ctx.recv().await?,
+    ///                 msg: &'static str =!> {
+    ///                     answer!(ctx, "Handled.").expect("Couldn't answer.");
+    ///                 };
+    ///                 msg: _ => {
+    ///                     // Don't let the asker wait for an answer that won't come.
+    ///                     msg.answer("Unexpected message.", ctx.signature()).ok();
+    ///                 };
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`msg!`]: ../macro.msg.html
+    /// [`BastionContext::signature`]: ../context/struct.BastionContext.html#method.signature
+    pub fn answer<M: Message>(&mut self, answer: M, sign: RefAddr) -> Result<(), M> {
+        match self.take_sender() {
+            Some(sender) => sender.send(answer, sign),
+            None => Err(answer),
+        }
+    }
+
     #[doc(hidden)]
     pub fn is<M: Message>(&self) -> bool {
-        match &self.0 {
+        match &self.inner {
             MsgInner::Tell(msg) => msg.is::<M>(),
             MsgInner::Ask { msg, .. } => msg.is::<M>(),
             MsgInner::Broadcast(msg) => msg.is::<M>(),
@@ -311,14 +455,14 @@ impl Msg {
     #[doc(hidden)]
     pub fn downcast<M: Message>(self) -> Result<M, Self> {
         trace!("{:?}: Downcasting to {}.", self, type_name::<M>());
-        match self.0 {
+        match self.inner {
             MsgInner::Tell(msg) => {
                 if msg.is::<M>() {
                     let msg: Box<dyn Any + 'static> = msg;
                     Ok(*msg.downcast().unwrap())
                 } else {
                     let inner = MsgInner::Tell(msg);
-                    Err(Msg(inner))
+                    Err(Msg::new(inner, self.type_name))
                 }
             }
             MsgInner::Ask { msg, sender } => {
@@ -327,17 +471,27 @@ impl Msg {
                     Ok(*msg.downcast().unwrap())
                 } else {
                     let inner = MsgInner::Ask { msg, sender };
-                    Err(Msg(inner))
+                    Err(Msg::new(inner, self.type_name))
                 }
             }
             _ => Err(self),
         }
     }
 
+    // Returns a reference to the message if it was told and is
+    // of the given type.
+    #[cfg(feature = "distributed")]
+    pub(crate) fn tell_ref<M: Message>(&self) -> Option<&M> {
+        match &self.inner {
+            MsgInner::Tell(msg) => msg.downcast_ref(),
+            _ => None,
+        }
+    }
+
     #[doc(hidden)]
     pub fn downcast_ref<M: Message>(&self) -> Option<Arc<M>> {
         trace!("{:?}: Downcasting to ref of {}.", self, type_name::<M>());
-        if let MsgInner::Broadcast(msg) = &self.0 {
+        if let MsgInner::Broadcast(msg) = &self.inner {
             if msg.is::<M>() {
                 return Some(msg.clone().downcast::<M>().unwrap());
             }
@@ -348,9 +502,9 @@ impl Msg {
 
     pub(crate) fn try_clone(&self) -> Option<Self> {
         trace!("{:?}: Trying to clone.", self);
-        if let MsgInner::Broadcast(msg) = &self.0 {
+        if let MsgInner::Broadcast(msg) = &self.inner {
             let inner = MsgInner::Broadcast(msg.clone());
-            Some(Msg(inner))
+            Some(Msg::new(inner, self.type_name))
         } else {
             None
         }
@@ -358,18 +512,18 @@ impl Msg {
 
     pub(crate) fn try_unwrap<M: Message>(self) -> Result<M, Self> {
         debug!("{:?}: Trying to unwrap.", self);
-        if let MsgInner::Broadcast(msg) = self.0 {
+        if let MsgInner::Broadcast(msg) = self.inner {
             match msg.downcast() {
                 Ok(msg) => match Arc::try_unwrap(msg) {
                     Ok(msg) => Ok(msg),
                     Err(msg) => {
                         let inner = MsgInner::Broadcast(msg);
-                        Err(Msg(inner))
+                        Err(Msg::new(inner, self.type_name))
                     }
                 },
                 Err(msg) => {
                     let inner = MsgInner::Broadcast(msg);
-                    Err(Msg(inner))
+                    Err(Msg::new(inner, self.type_name))
                 }
             }
         } else {
@@ -442,8 +596,16 @@ impl BastionMessage {
         (BastionMessage::Message(msg), answer)
     }
 
-    pub(crate) fn restart_required(id: BastionId, parent_id: BastionId) -> Self {
-        BastionMessage::RestartRequired { id, parent_id }
+    pub(crate) fn restart_required(
+        id: BastionId,
+        parent_id: BastionId,
+        panic: Option<PanicPayload>,
+    ) -> Self {
+        BastionMessage::RestartRequired {
+            id,
+            parent_id,
+            panic,
+        }
     }
 
     pub(crate) fn finished_child(id: BastionId, parent_id: BastionId) -> Self {
@@ -455,13 +617,35 @@ impl BastionMessage {
     }
 
     pub(crate) fn restore_child(id: BastionId, state: Arc<Pin<Box<ContextState>>>) -> Self {
-        BastionMessage::RestoreChild { id, state }
+        BastionMessage::RestoreChild {
+            id,
+            state,
+            started: None,
+        }
+    }
+
+    pub(crate) fn restore_child_in_sequence(
+        id: BastionId,
+        state: Arc<Pin<Box<ContextState>>>,
+    ) -> (Self, Receiver<()>) {
+        let (sender, started) = oneshot::channel();
+        let msg = BastionMessage::RestoreChild {
+            id,
+            state,
+            started: Some(sender),
+        };
+        (msg, started)
     }
 
     pub(crate) fn drop_child(id: BastionId) -> Self {
         BastionMessage::DropChild { id }
     }
 
+    pub(crate) fn tear_down_child(id: BastionId) -> (Self, Receiver<()>) {
+        let (done, recver) = oneshot::channel();
+        (BastionMessage::TearDownChild { id, done }, recver)
+    }
+
     pub(crate) fn set_state(state: Arc<Pin<Box<ContextState>>>) -> Self {
         BastionMessage::SetState { state }
     }
@@ -478,6 +662,14 @@ impl BastionMessage {
         BastionMessage::Heartbeat
     }
 
+    pub(crate) fn resume() -> Self {
+        BastionMessage::Resume
+    }
+
+    pub(crate) fn hot_swap(init: Init) -> Self {
+        BastionMessage::HotSwap(init)
+    }
+
     pub(crate) fn try_clone(&self) -> Option<Self> {
         trace!("{:?}: Trying to clone.", self);
         let clone = match self {
@@ -503,21 +695,28 @@ impl BastionMessage {
                 state.clone(),
             ),
             BastionMessage::Message(msg) => BastionMessage::Message(msg.try_clone()?),
-            BastionMessage::RestartRequired { id, parent_id } => {
-                BastionMessage::restart_required(id.clone(), parent_id.clone())
+            // The panic payload can't be cloned.
+            BastionMessage::RestartRequired { id, parent_id, .. } => {
+                BastionMessage::restart_required(id.clone(), parent_id.clone(), None)
             }
             BastionMessage::FinishedChild { id, parent_id } => {
                 BastionMessage::finished_child(id.clone(), parent_id.clone())
             }
             BastionMessage::RestartSubtree => BastionMessage::restart_subtree(),
-            BastionMessage::RestoreChild { id, state } => {
+            // The clone isn't restored in sequence.
+            BastionMessage::RestoreChild { id, state, .. } => {
                 BastionMessage::restore_child(id.clone(), state.clone())
             }
             BastionMessage::DropChild { id } => BastionMessage::drop_child(id.clone()),
+            // The sender can't be cloned.
+            BastionMessage::TearDownChild { .. } => return None,
             BastionMessage::SetState { state } => BastionMessage::set_state(state.clone()),
             BastionMessage::Stopped { id } => BastionMessage::stopped(id.clone()),
             BastionMessage::Faulted { id } => BastionMessage::faulted(id.clone()),
             BastionMessage::Heartbeat => BastionMessage::heartbeat(),
+            BastionMessage::Resume => BastionMessage::resume(),
+            // The closure can't be cloned.
+            BastionMessage::HotSwap(_) => return None,
         };
 
         Some(clone)
@@ -532,12 +731,28 @@ impl BastionMessage {
     }
 }
 
+impl Answer {
+    // Starts the timeout of the answer, if any, from now on.
+    pub(crate) fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout.map(Delay::new);
+        self
+    }
+}
+
 impl Future for Answer {
     type Output = Result<SignedMessage, ()>;
 
     fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
         debug!("{:?}: Polling.", self);
-        Pin::new(&mut self.get_mut().0).poll(ctx).map_err(|_| ())
+        let answer = self.get_mut();
+        if let Poll::Ready(res) = Pin::new(&mut answer.recver).poll(ctx) {
+            return Poll::Ready(res.map_err(|_| ()));
+        }
+
+        match &mut answer.timeout {
+            Some(timeout) => Pin::new(timeout).poll(ctx).map(|_| Err(())),
+            None => Poll::Pending,
+        }
     }
 }
 
@@ -565,7 +780,10 @@ impl Future for Answer {
 ///
 /// A default case is required, which is defined in the same
 /// way as any other case but with its type set as `_` (note
-/// that it doesn't has the optional `ref` or `=!>`).
+/// that it doesn't has the optional `ref` or `=!>`). It gets
+/// the [`Msg`] that didn't match any other case, whose type can
+/// be found with [`Msg::type_name`] (e.g. to log it), and can
+/// still answer it with [`Msg::answer`] if it was asked.
 ///
 /// # Example
 ///
@@ -626,6 +844,8 @@ impl Future for Answer {
 /// ```
 ///
 /// [`Msg`]: children/struct.Msg.html
+/// [`Msg::type_name`]: message/struct.Msg.html#method.type_name
+/// [`Msg::answer`]: message/struct.Msg.html#method.answer
 /// [`BastionContext::recv`]: context/struct.BastionContext.html#method.recv
 /// [`BastionContext::try_recv`]: context/struct.BastionContext.html#method.try_recv
 macro_rules! msg {
@@ -749,6 +969,8 @@ macro_rules! msg {
                 }
             )*
             else {
+                // Lets the default case answer with `Msg::answer`.
+                $var.restore_sender(sender);
                 { $handle }
             }
         } else {
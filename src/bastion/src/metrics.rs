@@ -0,0 +1,224 @@
+//!
+//! Runtime metrics of the executor and of the children groups,
+//! gathered in the Prometheus text format.
+//!
+//! Bastion doesn't serve them itself: they are meant to be
+//! scraped from an HTTP endpoint of the embedding application.
+//! Enabled with the `metrics` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use bastion::prelude::*;
+//! #
+//! # Bastion::init();
+//! # Bastion::start();
+//! #
+//! // Send this as the body of a response to Prometheus...
+//! let text: String = bastion::metrics::gather();
+//! # assert!(text.contains("bastion_executor_workers"));
+//! #
+//! # Bastion::stop();
+//! # Bastion::block_until_stopped();
+//! ```
+use crate::context::BastionId;
+use bastion_executor::metrics as executor;
+use fxhash::FxHashMap;
+use lazy_static::lazy_static;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    // The metrics of the running children groups, with their names.
+    static ref GROUPS: Mutex<FxHashMap<BastionId, (String, Arc<GroupMetrics>)>> =
+        Mutex::new(FxHashMap::default());
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct GroupMetrics {
+    // How many messages the elements of the group received.
+    messages: AtomicU64,
+    // How many times elements of the group were restarted.
+    restarts: AtomicU64,
+}
+
+impl GroupMetrics {
+    pub(crate) fn record_message(&self) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_restart(&self) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn register_group(id: &BastionId, name: String, metrics: Arc<GroupMetrics>) {
+    if let Ok(mut groups) = GROUPS.lock() {
+        groups.insert(id.clone(), (name, metrics));
+    }
+}
+
+pub(crate) fn unregister_group(id: &BastionId) {
+    if let Ok(mut groups) = GROUPS.lock() {
+        groups.remove(id);
+    }
+}
+
+/// Returns the current metrics of the executor and of the running
+/// children groups, in the Prometheus text format.
+///
+/// The following metrics are gathered:
+/// - `bastion_executor_workers`: live worker threads.
+/// - `bastion_executor_parked_workers`: parked worker threads.
+/// - `bastion_executor_queue_depth`: processes waiting to be run, by `priority`.
+/// - `bastion_executor_blocking_threads`: live threads of the blocking pool.
+/// - `bastion_executor_procs_spawned_total`: processes spawned.
+/// - `bastion_executor_procs_completed_total`: processes that ran to completion.
+/// - `bastion_executor_procs_panicked_total`: processes that panicked.
+/// - `bastion_children_messages_total`: messages received by a group, by `id` and `name`.
+/// - `bastion_children_restarts_total`: restarts of a group's elements, by `id` and `name`.
+pub fn gather() -> String {
+    let mut out = String::new();
+
+    write_header(
+        &mut out,
+        "bastion_executor_workers",
+        "Live worker threads.",
+        "gauge",
+    );
+    write_sample(
+        &mut out,
+        "bastion_executor_workers",
+        "",
+        executor::workers() as u64,
+    );
+
+    write_header(
+        &mut out,
+        "bastion_executor_parked_workers",
+        "Parked worker threads.",
+        "gauge",
+    );
+    write_sample(
+        &mut out,
+        "bastion_executor_parked_workers",
+        "",
+        executor::parked_workers() as u64,
+    );
+
+    write_header(
+        &mut out,
+        "bastion_executor_queue_depth",
+        "Processes waiting to be run.",
+        "gauge",
+    );
+    for (priority, depth) in executor::queue_depths().iter() {
+        let priority = format!("{:?}", priority).to_lowercase();
+        let labels = format!("{{priority=\"{}\"}}", priority);
+        write_sample(
+            &mut out,
+            "bastion_executor_queue_depth",
+            &labels,
+            *depth as u64,
+        );
+    }
+
+    write_header(
+        &mut out,
+        "bastion_executor_blocking_threads",
+        "Live threads of the blocking pool.",
+        "gauge",
+    );
+    write_sample(
+        &mut out,
+        "bastion_executor_blocking_threads",
+        "",
+        executor::blocking_threads() as u64,
+    );
+
+    let procs = [
+        (
+            "bastion_executor_procs_spawned_total",
+            "Processes spawned.",
+            executor::spawned_procs(),
+        ),
+        (
+            "bastion_executor_procs_completed_total",
+            "Processes that ran to completion.",
+            executor::completed_procs(),
+        ),
+        (
+            "bastion_executor_procs_panicked_total",
+            "Processes that panicked.",
+            executor::panicked_procs(),
+        ),
+    ];
+    for (name, help, value) in procs.iter() {
+        write_header(&mut out, name, help, "counter");
+        write_sample(&mut out, name, "", *value);
+    }
+
+    let mut groups = match GROUPS.lock() {
+        Ok(groups) => groups
+            .iter()
+            .map(|(id, (name, metrics))| {
+                let labels = format!("{{id=\"{}\",name=\"{}\"}}", id, escape(name));
+                (labels, metrics.clone())
+            })
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+    groups.sort_by(|(labels, _), (other, _)| labels.cmp(other));
+
+    write_header(
+        &mut out,
+        "bastion_children_messages_total",
+        "Messages received by the elements of a children group.",
+        "counter",
+    );
+    for (labels, metrics) in groups.iter() {
+        let messages = metrics.messages.load(Ordering::Relaxed);
+        write_sample(
+            &mut out,
+            "bastion_children_messages_total",
+            labels,
+            messages,
+        );
+    }
+
+    write_header(
+        &mut out,
+        "bastion_children_restarts_total",
+        "Restarts of the elements of a children group.",
+        "counter",
+    );
+    for (labels, metrics) in groups.iter() {
+        let restarts = metrics.restarts.load(Ordering::Relaxed);
+        write_sample(
+            &mut out,
+            "bastion_children_restarts_total",
+            labels,
+            restarts,
+        );
+    }
+
+    out
+}
+
+fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    // Writing to a `String` can't fail.
+    writeln!(out, "# HELP {} {}", name, help).ok();
+    writeln!(out, "# TYPE {} {}", name, kind).ok();
+}
+
+fn write_sample(out: &mut String, name: &str, labels: &str, value: u64) {
+    writeln!(out, "{}{} {}", name, labels, value).ok();
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
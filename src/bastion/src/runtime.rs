@@ -0,0 +1,287 @@
+//!
+//! Runtimes isolated from each other in the same process.
+//!
+//! Each [`Runtime`] has its own system, supervisors, dispatchers and
+//! worker threads, so the actors of a runtime are never run by the
+//! threads of another one, and messages broadcasted to a dispatcher
+//! only reach the actors of the runtime they were broadcasted in.
+//!
+//! [`Bastion`] acts on the default runtime, or on the runtime of the
+//! actor calling it.
+//!
+//! The blocking tasks of all the runtimes are run by the same
+//! threads, as are the dead letters handlers set with
+//! [`Bastion::on_dead_letter`] and the panic hook set by [`Config`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use bastion::prelude::*;
+//!
+//! let runtime = Runtime::builder().build();
+//! runtime.start();
+//!
+//! runtime
+//!     .children(|children| {
+//!         children.with_exec(|ctx: BastionContext| async move {
+//!             // Run by the threads of the runtime.
+//!             ctx.recv().await?;
+//!             Ok(())
+//!         })
+//!     })
+//!     .expect("Couldn't create the children group.");
+//!
+//! runtime.shutdown();
+//! ```
+//!
+//! [`Runtime`]: struct.Runtime.html
+//! [`Bastion`]: ../struct.Bastion.html
+//! [`Bastion::on_dead_letter`]: ../struct.Bastion.html#method.on_dead_letter
+//! [`Config`]: ../struct.Config.html
+
+use crate::children::Children;
+use crate::children_ref::ChildrenRef;
+use crate::context::BastionContext;
+use crate::executor::RecoverableHandle;
+use crate::lifecycle::LifecycleEvents;
+use crate::message::Message;
+use crate::supervisor::{Supervisor, SupervisorRef};
+use crate::system::{self, GlobalSystem, System};
+use crate::{Bastion, StopReport};
+use bastion_executor::pool::{Pool, PoolConfig};
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::time::Duration;
+
+/// A system of supervisors and actors run by threads of its own.
+///
+/// The functions of a runtime are the ones of [`Bastion`], acting on
+/// the runtime instead of the default one.
+///
+/// A runtime is initialized once built, and needs to be started
+/// with [`start`] as the default one does. Once stopped, its
+/// threads are parked until it is shut down with [`shutdown`].
+///
+/// [`Bastion`]: ../struct.Bastion.html
+/// [`start`]: #method.start
+/// [`shutdown`]: #method.shutdown
+#[derive(Clone, Copy)]
+pub struct Runtime {
+    system: &'static GlobalSystem,
+}
+
+/// A builder of [`Runtime`].
+///
+/// [`Runtime`]: struct.Runtime.html
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeBuilder {
+    pool_config: PoolConfig,
+}
+
+// Mirrors the functions of `Bastion`.
+#[allow(clippy::result_unit_err)]
+impl Runtime {
+    /// Returns a builder of runtimes with the default configuration.
+    pub fn builder() -> RuntimeBuilder {
+        RuntimeBuilder::default()
+    }
+
+    /// Returns the default runtime, which [`Bastion`] acts on.
+    ///
+    /// [`Bastion`]: ../struct.Bastion.html
+    pub fn global() -> Self {
+        Runtime {
+            system: system::global(),
+        }
+    }
+
+    /// Returns the runtime of the actor calling it, or the default
+    /// runtime.
+    pub fn current() -> Self {
+        Runtime {
+            system: system::current(),
+        }
+    }
+
+    /// Creates a new supervisor in the runtime, as
+    /// [`Bastion::supervisor`] does.
+    ///
+    /// [`Bastion::supervisor`]: ../struct.Bastion.html#method.supervisor
+    pub fn supervisor<S>(&self, init: S) -> Result<SupervisorRef, ()>
+    where
+        S: FnOnce(Supervisor) -> Supervisor,
+    {
+        self.system.enter(|| Bastion::supervisor(init))
+    }
+
+    /// Creates a new children group in the runtime, as
+    /// [`Bastion::children`] does.
+    ///
+    /// [`Bastion::children`]: ../struct.Bastion.html#method.children
+    pub fn children<C>(&self, init: C) -> Result<ChildrenRef, ()>
+    where
+        C: FnOnce(Children) -> Children,
+    {
+        self.system.enter(|| Bastion::children(init))
+    }
+
+    /// Creates a new children group of a single child in the runtime,
+    /// as [`Bastion::spawn`] does.
+    ///
+    /// [`Bastion::spawn`]: ../struct.Bastion.html#method.spawn
+    pub fn spawn<I, F>(&self, action: I) -> Result<ChildrenRef, ()>
+    where
+        I: Fn(BastionContext) -> F + Send + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        self.system.enter(|| Bastion::spawn(action))
+    }
+
+    /// Spawns a one-shot task onto the runtime's threads, as
+    /// [`Bastion::spawn_task`] does.
+    ///
+    /// [`Bastion::spawn_task`]: ../struct.Bastion.html#method.spawn_task
+    pub fn spawn_task<F, T>(&self, future: F) -> RecoverableHandle<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.system.enter(|| Bastion::spawn_task(future))
+    }
+
+    /// Broadcasts a message to the supervisors and children groups of
+    /// the runtime, as [`Bastion::broadcast`] does.
+    ///
+    /// [`Bastion::broadcast`]: ../struct.Bastion.html#method.broadcast
+    pub fn broadcast<M: Message>(&self, msg: M) -> Result<(), M> {
+        self.system.enter(|| Bastion::broadcast(msg))
+    }
+
+    /// Returns a receiver of the lifecycle events of the runtime, as
+    /// [`Bastion::lifecycle_events`] does.
+    ///
+    /// [`Bastion::lifecycle_events`]: ../struct.Bastion.html#method.lifecycle_events
+    pub fn lifecycle_events(&self) -> LifecycleEvents {
+        self.system.enter(Bastion::lifecycle_events)
+    }
+
+    /// Starts the runtime, as [`Bastion::start`] does.
+    ///
+    /// [`Bastion::start`]: ../struct.Bastion.html#method.start
+    pub fn start(&self) {
+        self.system.enter(Bastion::start)
+    }
+
+    /// Stops the runtime, as [`Bastion::stop`] does.
+    ///
+    /// [`Bastion::stop`]: ../struct.Bastion.html#method.stop
+    pub fn stop(&self) {
+        self.system.enter(Bastion::stop)
+    }
+
+    /// Kills the runtime, as [`Bastion::kill`] does.
+    ///
+    /// [`Bastion::kill`]: ../struct.Bastion.html#method.kill
+    pub fn kill(&self) {
+        self.system.enter(Bastion::kill)
+    }
+
+    /// Stops the runtime gracefully, as [`Bastion::stop_graceful`]
+    /// does.
+    ///
+    /// [`Bastion::stop_graceful`]: ../struct.Bastion.html#method.stop_graceful
+    pub fn stop_graceful(&self, timeout: Duration) -> StopReport {
+        self.system.enter(|| Bastion::stop_graceful(timeout))
+    }
+
+    /// Blocks the current thread until the runtime is stopped, as
+    /// [`Bastion::block_until_stopped`] does.
+    ///
+    /// [`Bastion::block_until_stopped`]: ../struct.Bastion.html#method.block_until_stopped
+    pub fn block_until_stopped(&self) {
+        self.system.enter(Bastion::block_until_stopped)
+    }
+
+    /// Runs the actors of a deterministic runtime until idle, as
+    /// [`Bastion::run_until_idle`] does.
+    ///
+    /// [`Bastion::run_until_idle`]: ../struct.Bastion.html#method.run_until_idle
+    pub fn run_until_idle(&self) -> usize {
+        self.system.enter(Bastion::run_until_idle)
+    }
+
+    /// Makes virtual time pass for a deterministic runtime, as
+    /// [`Bastion::advance_time`] does.
+    ///
+    /// [`Bastion::advance_time`]: ../struct.Bastion.html#method.advance_time
+    pub fn advance_time(&self, duration: Duration) {
+        self.system.enter(|| Bastion::advance_time(duration))
+    }
+
+    /// Stops the runtime and blocks the current thread until it is
+    /// stopped, then lets the runtime's threads exit. The runtime
+    /// can't be started again afterwards.
+    ///
+    /// The threads of the default runtime also run the processes
+    /// spawned with [`bastion_executor::pool::spawn`], so they are
+    /// left running.
+    ///
+    /// [`bastion_executor::pool::spawn`]: ../../bastion_executor/pool/fn.spawn.html
+    pub fn shutdown(&self) {
+        self.stop();
+        self.block_until_stopped();
+        if !std::ptr::eq(self.system, system::global()) {
+            self.system.pool().shutdown();
+        }
+    }
+}
+
+impl Debug for Runtime {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Runtime").finish()
+    }
+}
+
+impl RuntimeBuilder {
+    /// Sets the configuration of the runtime's threads.
+    ///
+    /// Defaults to [`PoolConfig::default`].
+    ///
+    /// [`PoolConfig::default`]: ../../bastion_executor/pool/struct.PoolConfig.html
+    pub fn with_pool_config(mut self, pool_config: PoolConfig) -> Self {
+        self.pool_config = pool_config;
+        self
+    }
+
+    /// Makes the runtime deterministic, as [`Bastion::init_test`]
+    /// does for the default one: its actors are only run by the
+    /// thread calling [`Runtime::run_until_idle`],
+    /// [`Runtime::advance_time`] or [`Runtime::block_until_stopped`],
+    /// in an order picked from `seed`, and its time is virtual.
+    ///
+    /// The order only depends on the seed as long as the actors aren't
+    /// woken up by other threads, like the ones of blocking tasks, of
+    /// dead letters handlers or of timers other than
+    /// [`bastion::time::Delay`]. [`Runtime::stop_graceful`] still
+    /// waits for its timeout in real time, and must thus not be used.
+    ///
+    /// [`Bastion::init_test`]: ../struct.Bastion.html#method.init_test
+    /// [`Runtime::run_until_idle`]: struct.Runtime.html#method.run_until_idle
+    /// [`Runtime::advance_time`]: struct.Runtime.html#method.advance_time
+    /// [`Runtime::block_until_stopped`]: struct.Runtime.html#method.block_until_stopped
+    /// [`Runtime::stop_graceful`]: struct.Runtime.html#method.stop_graceful
+    /// [`bastion::time::Delay`]: ../time/struct.Delay.html
+    pub fn with_deterministic_seed(mut self, seed: u64) -> Self {
+        self.pool_config = self.pool_config.with_deterministic_seed(seed);
+        self
+    }
+
+    /// Builds the runtime, spawning its threads and initializing its
+    /// system.
+    pub fn build(self) -> Runtime {
+        let pool = Pool::start(&self.pool_config);
+        Runtime {
+            system: System::init(pool),
+        }
+    }
+}
@@ -7,23 +7,30 @@ use crate::children::Children;
 use crate::children_ref::ChildrenRef;
 use crate::context::{BastionId, ContextState};
 use crate::envelope::Envelope;
-use crate::message::{BastionMessage, Deployment, Message};
+use crate::lifecycle::{LifecycleEventKind, RestartReason};
+use crate::message::{BastionMessage, Deployment, Message, PanicPayload};
 use crate::path::{BastionPath, BastionPathElement};
+use crate::system;
+use crate::time::{self, Delay};
 
-use bastion_executor::pool;
 use futures::prelude::*;
 use futures::stream::FuturesOrdered;
 use futures::{pending, poll};
-use futures_timer::Delay;
 use fxhash::FxHashMap;
 use lightproc::prelude::*;
+use std::any::Any;
 use std::cmp::{Eq, PartialEq};
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hasher};
+use std::mem;
 use std::ops::Range;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Poll;
-use std::time::Duration;
-use tracing::{debug, trace, warn};
+use std::time::{Duration, Instant};
+use tracing::{debug, info_span, trace, warn, Instrument, Span};
 
 #[derive(Debug)]
 /// A supervisor that can supervise both [`Children`] and other
@@ -85,6 +92,14 @@ pub struct Supervisor {
     killed: FxHashMap<BastionId, Supervised>,
     strategy: SupervisionStrategy,
     restart_strategy: RestartStrategy,
+    // The maximum amount of restarts of a child within a time
+    // window, after which the child isn't restarted anymore.
+    restart_intensity: Option<(usize, Duration)>,
+    // What happens when a supervised element runs out of restarts.
+    escalation_policy: EscalationPolicy,
+    // The restarts of the supervised supervisors that failed
+    // because they escalated, by their current identifier.
+    escalations: FxHashMap<BastionId, EscalatedRestarts>,
     // The callbacks called at the supervisor's different
     // lifecycle events.
     callbacks: Callbacks,
@@ -101,6 +116,9 @@ pub struct Supervisor {
     subtree_restarts: usize,
     // Store the maximum acceptable restarts for the supervisor.
     subtree_restarts_limit: usize,
+    // The span covering the supervisor's lifecycle, which is
+    // only recorded with the `tracing-spans` feature.
+    span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -108,6 +126,18 @@ struct TrackedChildState {
     id: BastionId,
     state: Arc<Pin<Box<ContextState>>>,
     restarts_counts: usize,
+    // How long the supervisor waited before the last restart.
+    last_delay: Option<Duration>,
+    // When the child was restarted, only kept while
+    // relevant for the restart intensity.
+    recent_restarts: VecDeque<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct EscalatedRestarts {
+    restarts_count: usize,
+    last_delay: Option<Duration>,
+    recent_restarts: VecDeque<Instant>,
 }
 
 #[derive(Debug)]
@@ -132,6 +162,8 @@ pub struct SupervisorRef {
     id: BastionId,
     sender: Sender,
     path: Arc<BastionPath>,
+    // The span covering the supervisor's lifecycle.
+    span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -158,9 +190,39 @@ pub enum SupervisionStrategy {
     /// supervisor after it are restarted (even those which
     /// were stopped) in the same order they were added to
     /// the supervisor.
+    ///
+    /// The children restarted alongside the failed one are
+    /// first stopped, from the last one that started to the
+    /// first one. Then each child is only restarted once the
+    /// previous one started, so that the later children can
+    /// rely on the earlier ones being up.
     RestForOne,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// What a supervisor does when one of its supervised children
+/// groups or supervisors ran out of restarts (because of its
+/// [`RestartStrategy`] or restart intensity).
+///
+/// The default policy is `Stop`.
+///
+/// [`RestartStrategy`]: supervisor/struct.RestartStrategy.html
+pub enum EscalationPolicy {
+    /// The element that ran out of restarts is stopped and
+    /// the supervisor keeps supervising the other ones.
+    #[default]
+    Stop,
+    /// The supervisor kills all the elements it supervises and
+    /// fails itself, letting its parent supervisor restart it
+    /// with a fresh state depending on the parent's own
+    /// supervision strategy and restarts. When a supervisor
+    /// created with [`Bastion::supervisor`] fails this way, the
+    /// whole system is stopped instead.
+    ///
+    /// [`Bastion::supervisor`]: struct.Bastion.html#method.supervisor
+    Escalate,
+}
+
 #[derive(Debug)]
 enum Supervised {
     Supervisor(Supervisor),
@@ -189,10 +251,92 @@ pub enum RestartPolicy {
 ///
 /// The default strategy used is `ActorRestartStrategy::Immediate`
 /// with the `RestartPolicy::Always` restart policy.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// A custom strategy can be used instead of an [`ActorRestartStrategy`]
+/// by converting a boxed [`RestartDelay`] into a `RestartStrategy`.
+#[derive(Debug, Clone)]
 pub struct RestartStrategy {
     restart_policy: RestartPolicy,
     strategy: ActorRestartStrategy,
+    // Replaces `strategy` when set.
+    custom: Option<Arc<dyn RestartDelay>>,
+    max_delay: Option<Duration>,
+    jitter: Option<JitterKind>,
+}
+
+/// How a [`RestartStrategy`] randomizes the restart delays, so
+/// that the elements failing at once don't all restart in lockstep.
+///
+/// [`RestartStrategy`]: struct.RestartStrategy.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JitterKind {
+    /// Waits for a random delay between zero and the delay of the
+    /// restart strategy.
+    Full,
+    /// Waits for a random delay between the delay of the restart
+    /// strategy and three times the delay waited for before the
+    /// previous restart, so that each element's delays drift apart
+    /// from the others'.
+    Decorrelated,
+}
+
+/// Decides whether and when a failed element should get
+/// restarted by its supervisor.
+///
+/// It is implemented by [`ActorRestartStrategy`] and [`RestartStrategy`],
+/// and custom implementations can be given to
+/// [`Supervisor::with_restart_strategy`] once boxed.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// # use std::time::Duration;
+/// #
+/// #[derive(Debug)]
+/// struct Transient;
+///
+/// impl RestartDelay for Transient {
+///     fn next_delay(&self, restart_count: usize, last_failure: &FailureInfo) -> Option<Duration> {
+///         match last_failure.panic_message() {
+///             // Restarts right away after a transient error...
+///             Some("connection reset") => Some(Duration::from_secs(0)),
+///             // ...but backs off hard for the others.
+///             _ if restart_count < 5 => Some(Duration::from_secs(10)),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// # Bastion::init();
+/// Bastion::supervisor(|sp| sp.with_restart_strategy(Box::new(Transient)))
+///     .expect("Couldn't create the supervisor");
+/// #
+/// # Bastion::start();
+/// # Bastion::stop();
+/// # Bastion::block_until_stopped();
+/// ```
+///
+/// [`Supervisor::with_restart_strategy`]: struct.Supervisor.html#method.with_restart_strategy
+pub trait RestartDelay: Debug + Send + Sync + 'static {
+    /// Returns how long to wait before restarting an element that
+    /// was already restarted `restart_count` times, or `None` if it
+    /// shouldn't be restarted anymore, in which case the supervisor
+    /// stops tracking it.
+    fn next_delay(&self, restart_count: usize, last_failure: &FailureInfo) -> Option<Duration>;
+}
+
+/// What a supervisor knows about the failure that made it restart
+/// one of its elements.
+///
+/// When the supervision strategy restarts more than the failed
+/// element, the elements restarted alongside it get the failed
+/// element's panic.
+#[derive(Debug)]
+pub struct FailureInfo<'a> {
+    restart_count: usize,
+    panic: Option<&'a (dyn Any + Send)>,
+    last_delay: Option<Duration>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -244,6 +388,47 @@ impl ActorRestartStrategy {
     }
 }
 
+impl RestartDelay for ActorRestartStrategy {
+    fn next_delay(&self, restart_count: usize, _last_failure: &FailureInfo) -> Option<Duration> {
+        Some(self.calculate(restart_count).unwrap_or_default())
+    }
+}
+
+impl FailureInfo<'_> {
+    /// Returns how many times the element was restarted before.
+    pub fn restart_count(&self) -> usize {
+        self.restart_count
+    }
+
+    /// Returns how long the supervisor waited before the element's
+    /// previous restart, if it was restarted before.
+    pub fn last_delay(&self) -> Option<Duration> {
+        self.last_delay
+    }
+
+    /// Returns whether the element panicked, rather than returning
+    /// an error or failing its health check.
+    pub fn panicked(&self) -> bool {
+        self.panic.is_some()
+    }
+
+    /// Returns what the element panicked with, if it panicked with
+    /// a value of type `T`.
+    pub fn panic_payload<T: Any>(&self) -> Option<&T> {
+        self.panic?.downcast_ref()
+    }
+
+    /// Returns the message the element panicked with, if it
+    /// panicked with a string (as [`panic!`] does).
+    ///
+    /// [`panic!`]: https://doc.rust-lang.org/std/macro.panic.html
+    pub fn panic_message(&self) -> Option<&str> {
+        self.panic_payload::<&str>()
+            .copied()
+            .or_else(|| self.panic_payload::<String>().map(String::as_str))
+    }
+}
+
 impl Supervisor {
     pub(crate) fn new(bcast: Broadcast) -> Self {
         debug!("Supervisor({}): Initializing.", bcast.id());
@@ -255,12 +440,24 @@ impl Supervisor {
         let killed = FxHashMap::default();
         let strategy = SupervisionStrategy::default();
         let restart_strategy = RestartStrategy::default();
+        let restart_intensity = None;
+        let escalation_policy = EscalationPolicy::default();
+        let escalations = FxHashMap::default();
         let callbacks = Callbacks::new();
         let is_system_supervisor = false;
         let pre_start_msgs = Vec::new();
         let started = false;
         let subtree_restarts = 0;
         let subtree_restarts_limit = 3;
+        let span = if cfg!(feature = "tracing-spans") {
+            info_span!(
+                parent: bcast.parent().span_id(),
+                "supervisor",
+                path = %bcast.path(),
+            )
+        } else {
+            Span::none()
+        };
 
         Supervisor {
             bcast,
@@ -272,12 +469,16 @@ impl Supervisor {
             killed,
             strategy,
             restart_strategy,
+            restart_intensity,
+            escalation_policy,
+            escalations,
             callbacks,
             is_system_supervisor,
             pre_start_msgs,
             started,
             subtree_restarts,
             subtree_restarts_limit,
+            span,
         }
     }
 
@@ -327,7 +528,7 @@ impl Supervisor {
         self.pre_start_msgs.shrink_to_fit();
 
         let restarted_objects = self.search_restarted_objects(ActorSearchMethod::All);
-        self.restart(restarted_objects).await;
+        self.restart(restarted_objects, None).await;
 
         debug!(
             "Supervisor({}): Removing {} stopped elements.",
@@ -394,7 +595,9 @@ impl Supervisor {
         let sender = self.bcast.sender().clone();
         let path = self.bcast.path().clone();
 
-        SupervisorRef::new(id, sender, path)
+        let span = self.span.clone();
+
+        SupervisorRef::new(id, sender, path, span)
     }
 
     /// Creates a new supervisor, passes it through the specified
@@ -740,6 +943,10 @@ impl Supervisor {
     /// The default strategy is the [`ActorRestartStrategy::Immediate`] and
     /// unlimited amount of retries.
     ///
+    /// It can also be given any boxed [`RestartDelay`] implementation,
+    /// to decide the delay of each restart from the failure that
+    /// caused it.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -764,7 +971,8 @@ impl Supervisor {
     /// # Bastion::stop();
     /// # Bastion::block_until_stopped();
     /// ```
-    pub fn with_restart_strategy(mut self, restart_strategy: RestartStrategy) -> Self {
+    pub fn with_restart_strategy(mut self, restart_strategy: impl Into<RestartStrategy>) -> Self {
+        let restart_strategy = restart_strategy.into();
         trace!(
             "Supervisor({}): Setting actor restart strategy: {:?}",
             self.id(),
@@ -774,6 +982,89 @@ impl Supervisor {
         self
     }
 
+    /// Limits how many times a supervised child can get restarted
+    /// within a time window. When a child fails more than
+    /// `max_restarts` times within `within`, it gets stopped
+    /// permanently instead of being restarted again.
+    ///
+    /// Restarts older than `within` are forgotten, so a child
+    /// that stays alive longer than the window gets its counter
+    /// reset.
+    ///
+    /// By default, there is no restart intensity limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_restarts` - The maximum amount of restarts allowed.
+    /// * `within` - The duration of the time window.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::supervisor(|sp| {
+    ///     sp.with_restart_intensity(3, Duration::from_secs(5))
+    /// }).expect("Couldn't create the supervisor.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    pub fn with_restart_intensity(mut self, max_restarts: usize, within: Duration) -> Self {
+        trace!(
+            "Supervisor({}): Setting restart intensity: {} restarts within {:?}",
+            self.id(),
+            max_restarts,
+            within
+        );
+        self.restart_intensity = Some((max_restarts, within));
+        self
+    }
+
+    /// Sets what the supervisor does when one of its supervised
+    /// children groups or supervisors runs out of restarts.
+    ///
+    /// The default policy is [`EscalationPolicy::Stop`], which stops
+    /// the element. With [`EscalationPolicy::Escalate`], the
+    /// supervisor fails instead and gets restarted by its own
+    /// parent, along with all the elements it supervises.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # Bastion::init();
+    /// #
+    /// Bastion::supervisor(|parent| {
+    ///     parent.supervisor(|sp| {
+    ///         sp.with_restart_intensity(3, Duration::from_secs(5))
+    ///             .with_escalation_policy(EscalationPolicy::Escalate)
+    ///     })
+    /// }).expect("Couldn't create the supervisor.");
+    /// #
+    /// # Bastion::start();
+    /// # Bastion::stop();
+    /// # Bastion::block_until_stopped();
+    /// ```
+    ///
+    /// [`EscalationPolicy::Stop`]: supervisor/enum.EscalationPolicy.html#variant.Stop
+    /// [`EscalationPolicy::Escalate`]: supervisor/enum.EscalationPolicy.html#variant.Escalate
+    pub fn with_escalation_policy(mut self, escalation_policy: EscalationPolicy) -> Self {
+        trace!(
+            "Supervisor({}): Setting escalation policy: {:?}",
+            self.id(),
+            escalation_policy
+        );
+        self.escalation_policy = escalation_policy;
+        self
+    }
+
     /// Sets the callbacks that will get called at this supervisor's
     /// different lifecycle events.
     ///
@@ -816,16 +1107,35 @@ impl Supervisor {
         self
     }
 
-    async fn restart(&mut self, objects: Vec<RestartedElement>) {
+    // `panic` is what the element whose failure caused the restart
+    // panicked with, if it panicked. Returns whether an element ran
+    // out of restarts and the supervisor escalates, in which case
+    // nothing is restarted.
+    async fn restart(
+        &mut self,
+        objects: Vec<RestartedElement>,
+        panic: Option<PanicPayload>,
+    ) -> bool {
         debug!(
             "Supervisor({}): Restarting {:?} elements",
             self.id(),
             objects.len()
         );
+        // The elements that started after the failed one are torn down
+        // in the reverse order they started in, then restarted one after
+        // the other in the order they started in.
+        let sequenced = matches!(self.strategy, SupervisionStrategy::RestForOne);
+        if sequenced {
+            self.tear_down(&objects).await;
+        }
         let mut restart_futures = FuturesOrdered::new();
 
         for object in objects {
             match object {
+                RestartedElement::Supervisor(supervisor_id) if sequenced => {
+                    let msg = BastionMessage::restart_subtree();
+                    restart_futures.push_back(future::ready((supervisor_id, msg, None)).boxed());
+                }
                 RestartedElement::Supervisor(supervisor_id) => {
                     let msg = BastionMessage::restart_subtree();
                     let env =
@@ -847,39 +1157,117 @@ impl Supervisor {
                     };
                     let restarts_count = tracked_state.restarts_count();
 
-                    let restart_required = match self.restart_strategy.restart_policy() {
-                        RestartPolicy::Always => true,
-                        RestartPolicy::Never => false,
-                        RestartPolicy::Tries(max_retries) => restarts_count < max_retries,
+                    let failure = FailureInfo {
+                        restart_count: restarts_count,
+                        panic: panic.as_deref(),
+                        last_delay: tracked_state.last_delay,
                     };
+                    let delay = self.restart_strategy.next_delay(restarts_count, &failure);
+                    let restart_required = delay.is_some()
+                        && match self.restart_intensity {
+                            Some((max_restarts, within)) => {
+                                let allowed = tracked_state.record_restart(max_restarts, within);
+                                if !allowed {
+                                    warn!(
+                                        "Supervisor({}): Child({}) exceeded restart intensity of {} restarts within {:?}, stopping it",
+                                        self.bcast.id(),
+                                        id,
+                                        max_restarts,
+                                        within
+                                    );
+                                }
+
+                                allowed
+                            }
+                            None => true,
+                        };
 
                     let msg = match restart_required {
                         true => {
+                            #[cfg(feature = "tracing-spans")]
+                            tracing::info!(
+                                child = %id,
+                                strategy = ?self.strategy,
+                                restarts = restarts_count + 1,
+                                "restarting child"
+                            );
                             tracked_state.increase_restarts_counter();
+                            tracked_state.last_delay = delay;
+                            let path = self
+                                .bcast
+                                .path()
+                                .as_ref()
+                                .clone()
+                                .append(BastionPathElement::Children(parent_id.clone()))
+                                .and_then(|path| {
+                                    path.append(BastionPathElement::Child(id.clone()))
+                                });
+                            if let Ok(path) = path {
+                                let kind = LifecycleEventKind::Restarted {
+                                    restart_count: restarts_count + 1,
+                                    reason: RestartReason::of(&failure),
+                                };
+                                system::current().lifecycle().emit(&Arc::new(path), kind);
+                            }
                             let state = tracked_state.state();
-                            BastionMessage::restore_child(id, state)
+                            if sequenced {
+                                let (msg, started) =
+                                    BastionMessage::restore_child_in_sequence(id, state);
+                                (msg, Some(started))
+                            } else {
+                                (BastionMessage::restore_child(id, state), None)
+                            }
+                        }
+                        false if self.escalation_policy == EscalationPolicy::Escalate => {
+                            return true;
                         }
                         false => {
                             self.remove_child(&id.clone(), &parent_id.clone());
-                            BastionMessage::drop_child(id)
+                            (BastionMessage::drop_child(id), None)
                         }
                     };
-                    let restart_strategy = self.restart_strategy.clone();
+                    restart_futures.push(
+                        async move {
+                            match delay {
+                                Some(delay) if restart_required && delay > Duration::ZERO => {
+                                    Delay::new(delay).await;
+                                }
+                                _ => (),
+                            }
 
-                    restart_futures.push(async move {
-                        if restart_required {
-                            restart_strategy.apply_strategy(restarts_count).await;
+                            (parent_id, msg.0, msg.1)
                         }
-
-                        (parent_id, msg)
-                    });
+                        .boxed(),
+                    );
                 }
             }
         }
 
-        while let Some((receiver, msg)) = restart_futures.next().await {
+        while let Some((receiver, msg, started)) = restart_futures.next().await {
             let env = Envelope::new(msg, self.bcast.path().clone(), self.bcast.sender().clone());
             self.bcast.send_child(&receiver, env);
+            // The next element is restarted once this one started, or
+            // failed to.
+            if let Some(started) = started {
+                started.await.ok();
+            }
+        }
+
+        false
+    }
+
+    // Tears down the restarted children that are still running, from
+    // the last one that started to the first one.
+    async fn tear_down(&mut self, objects: &[RestartedElement]) {
+        for object in objects.iter().rev() {
+            if let RestartedElement::Child { id, parent_id } = object {
+                let (msg, done) = BastionMessage::tear_down_child(id.clone());
+                let env =
+                    Envelope::new(msg, self.bcast.path().clone(), self.bcast.sender().clone());
+                self.bcast.send_child(parent_id, env);
+                // The sender is dropped if the group is gone.
+                done.await.ok();
+            }
         }
     }
 
@@ -930,7 +1318,7 @@ impl Supervisor {
                         self.id(),
                         supervised.id()
                     );
-                    supervised.callbacks().after_stop();
+                    supervised.callbacks().after_stop().await;
 
                     let id = supervised.id().clone();
                     self.stopped.insert(id, supervised);
@@ -987,36 +1375,57 @@ impl Supervisor {
 
     fn faulted(&mut self) {
         debug!("Supervisor({}): Faulted.", self.id());
+        system::current()
+            .lifecycle()
+            .emit(self.bcast.path(), LifecycleEventKind::Failed);
         self.bcast.faulted();
     }
 
-    async fn recover(&mut self, id: BastionId, parent_id: BastionId) -> Result<(), ()> {
+    async fn recover(
+        &mut self,
+        id: BastionId,
+        parent_id: BastionId,
+        panic: Option<PanicPayload>,
+    ) -> Result<(), ()> {
         debug!(
             "Supervisor({}): Recovering using strategy: {:?}",
             self.id(),
             self.strategy
         );
 
-        match self.strategy {
+        let escalated = match self.strategy {
             SupervisionStrategy::OneForOne => {
                 let search_method = ActorSearchMethod::OneActor { id, parent_id };
                 let objects = self.search_restarted_objects(search_method);
-                self.restart(objects).await;
+                self.restart(objects, panic).await
             }
             SupervisionStrategy::OneForAll => {
                 let search_method = ActorSearchMethod::All;
                 let objects = self.search_restarted_objects(search_method);
-                self.restart(objects).await;
+                let escalated = self.restart(objects, panic).await;
 
                 // TODO: should be empty
                 self.stopped.shrink_to_fit();
                 self.killed.shrink_to_fit();
+
+                escalated
             }
             SupervisionStrategy::RestForOne => {
                 let search_method = ActorSearchMethod::FromActor { id, parent_id };
                 let objects = self.search_restarted_objects(search_method);
-                self.restart(objects).await;
+                self.restart(objects, panic).await
             }
+        };
+
+        if escalated {
+            warn!(
+                "Supervisor({}): An element ran out of restarts, escalating.",
+                self.id()
+            );
+            system::current()
+                .lifecycle()
+                .emit(self.bcast.path(), LifecycleEventKind::Escalated);
+            return Err(());
         }
 
         Ok(())
@@ -1097,7 +1506,7 @@ impl Supervisor {
         if self.subtree_restarts < self.subtree_restarts_limit {
             self.subtree_restarts += 1;
             let restarted_objects = self.search_restarted_objects(ActorSearchMethod::All);
-            self.restart(restarted_objects).await;
+            self.restart(restarted_objects, None).await;
         }
     }
 
@@ -1119,7 +1528,7 @@ impl Supervisor {
                     self.id(),
                     supervisor.id()
                 );
-                supervisor.callbacks().before_start();
+                supervisor.callbacks().before_start().await;
                 Supervised::supervisor(supervisor)
             }
             Deployment::Children(children) => {
@@ -1128,7 +1537,7 @@ impl Supervisor {
                     self.id(),
                     children.id()
                 );
-                children.callbacks().before_start();
+                children.callbacks().before_start().await;
                 Supervised::children(children)
             }
         };
@@ -1159,7 +1568,7 @@ impl Supervisor {
             // TODO: add a "waiting" list an poll from it instead of awaiting
             // FIXME: panics?
             let supervised = launched.await.unwrap();
-            supervised.callbacks().after_stop();
+            supervised.callbacks().after_stop().await;
 
             self.bcast.unregister(&id);
             self.stopped.insert(id.clone(), supervised);
@@ -1170,12 +1579,13 @@ impl Supervisor {
         &mut self,
         id: BastionId,
         parent_id: BastionId,
+        panic: Option<PanicPayload>,
     ) -> Result<(), ()> {
         if self.launched.contains_key(&id) {
             warn!("Supervisor({}): Supervised({}) faulted.", self.id(), id);
         }
 
-        if self.recover(id, parent_id).await.is_err() {
+        if self.recover(id, parent_id, panic).await.is_err() {
             // TODO: stop or kill?
             self.kill(0..self.order.len()).await;
             self.faulted();
@@ -1186,6 +1596,192 @@ impl Supervisor {
         Ok(())
     }
 
+    // Restarts a supervised supervisor that failed because it
+    // escalated, if it can still be restarted, along with the other
+    // elements the supervision strategy restarts.
+    async fn recover_escalated_object(&mut self, id: BastionId) -> Result<(), ()> {
+        let mut supervisor = match self.stopped.remove(&id) {
+            Some(Supervised::Supervisor(supervisor)) => supervisor,
+            Some(supervised) => {
+                self.stopped.insert(id, supervised);
+                return Ok(());
+            }
+            None => return Ok(()),
+        };
+        let index = match self.order.iter().position(|element| element == &id) {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        let mut escalations = self.escalations.remove(&id).unwrap_or_default();
+        let restarts_count = escalations.restarts_count;
+        let delay = self.restart_strategy.next_delay(
+            restarts_count,
+            &FailureInfo {
+                restart_count: restarts_count,
+                panic: None,
+                last_delay: escalations.last_delay,
+            },
+        );
+        let restart_required = delay.is_some()
+            && match self.restart_intensity {
+                Some((max_restarts, within)) => {
+                    record_restart(&mut escalations.recent_restarts, max_restarts, within)
+                }
+                None => true,
+            };
+
+        if !restart_required {
+            warn!(
+                "Supervisor({}): Supervisor({}) ran out of restarts after escalating.",
+                self.id(),
+                id
+            );
+            self.stopped.insert(id, Supervised::supervisor(supervisor));
+            if self.escalation_policy == EscalationPolicy::Escalate {
+                self.kill(0..self.order.len()).await;
+                self.faulted();
+
+                return Err(());
+            }
+
+            return Ok(());
+        }
+
+        if let Some(delay) = delay.filter(|delay| *delay > Duration::ZERO) {
+            Delay::new(delay).await;
+        }
+
+        let kind = LifecycleEventKind::Restarted {
+            restart_count: restarts_count + 1,
+            reason: RestartReason::Faulted,
+        };
+        system::current()
+            .lifecycle()
+            .emit(supervisor.bcast.path(), kind);
+
+        supervisor.callbacks().before_restart().await;
+        let parent = Parent::supervisor(self.as_ref());
+        let bcast = Broadcast::new(parent, BastionPathElement::Supervisor(BastionId::new()));
+        supervisor.revive(bcast);
+        supervisor.callbacks().after_restart().await;
+
+        warn!(
+            "Supervisor({}): Restarting Supervisor({}) as Supervisor({}).",
+            self.id(),
+            id,
+            supervisor.id()
+        );
+        let supervised = Supervised::supervisor(supervisor);
+        let new_id = supervised.id().clone();
+        escalations.restarts_count += 1;
+        escalations.last_delay = delay;
+        self.escalations.insert(new_id.clone(), escalations);
+        self.order[index] = new_id.clone();
+        self.relaunch(index, supervised);
+
+        let range = match self.strategy {
+            SupervisionStrategy::OneForOne => 0..0,
+            SupervisionStrategy::OneForAll => 0..self.order.len(),
+            SupervisionStrategy::RestForOne => index + 1..self.order.len(),
+        };
+        let mut objects = Vec::new();
+        for element_id in &self.order[range] {
+            if element_id == &new_id || !self.launched.contains_key(element_id) {
+                continue;
+            }
+
+            match self.tracked_groups.get(element_id) {
+                Some(childs) => {
+                    for tracked_state in childs {
+                        objects.push(RestartedElement::Child {
+                            id: tracked_state.id(),
+                            parent_id: element_id.clone(),
+                        });
+                    }
+                }
+                None => objects.push(RestartedElement::Supervisor(element_id.clone())),
+            }
+        }
+
+        if self.restart(objects, None).await {
+            self.kill(0..self.order.len()).await;
+            self.faulted();
+
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    // Registers and launches a revived element at the given index
+    // of the supervisor's order.
+    fn relaunch(&mut self, index: usize, supervised: Supervised) {
+        self.bcast.register(supervised.bcast());
+        if self.started {
+            let msg = BastionMessage::start();
+            let env = Envelope::new(msg, self.bcast.path().clone(), self.bcast.sender().clone());
+            self.bcast.send_child(supervised.id(), env);
+        }
+
+        debug!(
+            "Supervisor({}): Launching Supervised({}).",
+            self.id(),
+            supervised.id()
+        );
+        let id = supervised.id().clone();
+        let launched = supervised.launch();
+        self.launched.insert(id, (index, launched));
+    }
+
+    // Gives the supervisor, which failed after escalating, a new
+    // identity and relaunches the elements that it killed with a
+    // fresh state.
+    fn revive(&mut self, bcast: Broadcast) {
+        debug!(
+            "Supervisor({}): Reviving as Supervisor({}).",
+            self.id(),
+            bcast.id()
+        );
+        self.bcast = bcast;
+        let order = mem::take(&mut self.order);
+        let mut killed = mem::take(&mut self.killed);
+
+        self.tracked_groups.clear();
+        self.tracked_groups_order.clear();
+        self.launched.clear();
+        self.stopped.clear();
+        self.escalations.clear();
+        self.pre_start_msgs.clear();
+        self.started = false;
+        self.subtree_restarts = 0;
+
+        for id in order {
+            let supervised = match killed.remove(&id) {
+                Some(supervised) => supervised,
+                None => continue,
+            };
+
+            let parent = Parent::supervisor(self.as_ref());
+            let supervised = match supervised {
+                Supervised::Supervisor(mut supervisor) => {
+                    let element = BastionPathElement::Supervisor(BastionId::new());
+                    supervisor.revive(Broadcast::new(parent, element));
+                    Supervised::supervisor(supervisor)
+                }
+                Supervised::Children(mut children) => {
+                    let element = BastionPathElement::Children(BastionId::new());
+                    children.revive(Broadcast::new(parent, element));
+                    Supervised::children(children)
+                }
+            };
+
+            let index = self.order.len();
+            self.order.push(supervised.id().clone());
+            self.relaunch(index, supervised);
+        }
+    }
+
     async fn handle(&mut self, env: Envelope) -> Result<(), ()> {
         match env {
             Envelope {
@@ -1263,10 +1859,19 @@ impl Supervisor {
                 self.bcast.send_children(env);
             }
             Envelope {
-                msg: BastionMessage::RestartRequired { id, parent_id },
+                msg:
+                    BastionMessage::RestartRequired {
+                        id,
+                        parent_id,
+                        panic,
+                    },
                 ..
             } => {
-                if self.recover_supervised_object(id, parent_id).await.is_err() {
+                if self
+                    .recover_supervised_object(id, parent_id, panic)
+                    .await
+                    .is_err()
+                {
                     return Err(());
                 }
             }
@@ -1286,6 +1891,10 @@ impl Supervisor {
                 msg: BastionMessage::DropChild { .. },
                 ..
             } => unreachable!(),
+            Envelope {
+                msg: BastionMessage::TearDownChild { .. },
+                ..
+            } => unreachable!(),
             Envelope {
                 msg: BastionMessage::SetState { .. },
                 ..
@@ -1297,11 +1906,24 @@ impl Supervisor {
             Envelope {
                 msg: BastionMessage::Faulted { id },
                 ..
-            } => self.cleanup_supervised_object(id).await,
+            } => {
+                self.cleanup_supervised_object(id.clone()).await;
+                if self.recover_escalated_object(id).await.is_err() {
+                    return Err(());
+                }
+            }
             Envelope {
                 msg: BastionMessage::Heartbeat,
                 ..
             } => unreachable!(),
+            Envelope {
+                msg: BastionMessage::Resume,
+                ..
+            } => unreachable!(),
+            Envelope {
+                msg: BastionMessage::HotSwap(_),
+                ..
+            } => unreachable!(),
         }
 
         Ok(())
@@ -1380,13 +2002,23 @@ impl Supervisor {
     pub(crate) fn launch(self) -> RecoverableHandle<Self> {
         debug!("Supervisor({}): Launching.", self.id());
         let stack = self.stack();
-        pool::spawn(self.run(), stack)
+        let span = self.span.clone();
+        system::spawn(self.run().instrument(span), stack)
     }
 }
 
 impl SupervisorRef {
-    pub(crate) fn new(id: BastionId, sender: Sender, path: Arc<BastionPath>) -> Self {
-        SupervisorRef { id, sender, path }
+    pub(crate) fn new(id: BastionId, sender: Sender, path: Arc<BastionPath>, span: Span) -> Self {
+        SupervisorRef {
+            id,
+            sender,
+            path,
+            span,
+        }
+    }
+
+    pub(crate) fn span(&self) -> &Span {
+        &self.span
     }
 
     /// Returns the identifier of the supervisor this `SupervisorRef`
@@ -1755,6 +2387,8 @@ impl TrackedChildState {
             id,
             state,
             restarts_counts: 0,
+            last_delay: None,
+            recent_restarts: VecDeque::new(),
         }
     }
 
@@ -1773,6 +2407,37 @@ impl TrackedChildState {
     fn increase_restarts_counter(&mut self) {
         self.restarts_counts += 1;
     }
+
+    // Records a new restart if the child is still allowed to
+    // restart within the time window.
+    fn record_restart(&mut self, max_restarts: usize, within: Duration) -> bool {
+        record_restart(&mut self.recent_restarts, max_restarts, within)
+    }
+}
+
+// Forgets the restarts that happened before the time window and, if
+// another restart is still allowed within it, records it and returns
+// `true`.
+fn record_restart(
+    recent_restarts: &mut VecDeque<Instant>,
+    max_restarts: usize,
+    within: Duration,
+) -> bool {
+    let now = time::now();
+    while let Some(restarted_at) = recent_restarts.front() {
+        if now.duration_since(*restarted_at) > within {
+            recent_restarts.pop_front();
+        } else {
+            break;
+        }
+    }
+
+    if recent_restarts.len() >= max_restarts {
+        return false;
+    }
+
+    recent_restarts.push_back(now);
+    true
 }
 
 impl Supervised {
@@ -1816,7 +2481,7 @@ impl Supervised {
         let stack = self.stack();
         match self {
             Supervised::Supervisor(supervisor) => {
-                pool::spawn(
+                system::spawn(
                     async {
                         // FIXME: panics?
                         let supervisor = supervisor.launch().await.unwrap();
@@ -1826,7 +2491,7 @@ impl Supervised {
                 )
             }
             Supervised::Children(children) => {
-                pool::spawn(
+                system::spawn(
                     async {
                         // FIXME: panics?
                         let children = children.launch().await.unwrap();
@@ -1884,6 +2549,9 @@ impl RestartStrategy {
         RestartStrategy {
             restart_policy,
             strategy,
+            custom: None,
+            max_delay: None,
+            jitter: None,
         }
     }
 
@@ -1898,6 +2566,18 @@ impl RestartStrategy {
         self.strategy.clone()
     }
 
+    /// Returns the maximum delay before restarting a failed actor,
+    /// if any was set.
+    pub fn max_delay(&self) -> Option<Duration> {
+        self.max_delay
+    }
+
+    /// Returns how the delays before restarting failed actors are
+    /// randomized, if they are.
+    pub fn jitter(&self) -> Option<JitterKind> {
+        self.jitter
+    }
+
     /// Sets the limit of attempts for restoring failed actors.
     pub fn with_restart_policy(mut self, restart_policy: RestartPolicy) -> Self {
         self.restart_policy = restart_policy;
@@ -1912,10 +2592,121 @@ impl RestartStrategy {
         self
     }
 
-    pub(crate) async fn apply_strategy(&self, restarts_count: usize) {
-        if let Some(dur) = self.strategy.calculate(restarts_count) {
-            Delay::new(dur).await;
+    /// Sets the maximum delay the supervisor waits for before
+    /// restoring a failed actor, which the delays given by the
+    /// strategy (randomized or not) never exceed.
+    ///
+    /// Defaults to no maximum.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use bastion::prelude::*;
+    /// #
+    /// let restart_strategy = RestartStrategy::default()
+    ///     .with_actor_restart_strategy(ActorRestartStrategy::ExponentialBackOff {
+    ///         timeout: Duration::from_millis(100),
+    ///         multiplier: 2.0,
+    ///     })
+    ///     .with_max_delay(Duration::from_secs(30))
+    ///     .with_jitter(JitterKind::Full);
+    /// ```
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Sets how the supervisor randomizes the delays it waits for
+    /// before restoring failed actors, for each restart (see
+    /// [`JitterKind`]).
+    ///
+    /// Defaults to waiting for the delays given by the strategy.
+    ///
+    /// [`JitterKind`]: enum.JitterKind.html
+    pub fn with_jitter(mut self, jitter: JitterKind) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+}
+
+impl RestartDelay for RestartStrategy {
+    fn next_delay(&self, restart_count: usize, last_failure: &FailureInfo) -> Option<Duration> {
+        let restart_allowed = match self.restart_policy {
+            RestartPolicy::Always => true,
+            RestartPolicy::Never => false,
+            RestartPolicy::Tries(max_retries) => restart_count < max_retries,
+        };
+        if !restart_allowed {
+            return None;
         }
+
+        let delay = match &self.custom {
+            Some(custom) => custom.next_delay(restart_count, last_failure),
+            None => self.strategy.next_delay(restart_count, last_failure),
+        }?;
+        let cap = |delay: Duration| match self.max_delay {
+            Some(max_delay) => delay.min(max_delay),
+            None => delay,
+        };
+
+        let delay = cap(delay);
+        let delay = match self.jitter {
+            None => delay,
+            Some(JitterKind::Full) => delay.mul_f64(random_fraction()),
+            Some(JitterKind::Decorrelated) => {
+                let upper = last_failure
+                    .last_delay()
+                    .map_or(delay, |last| last.saturating_mul(3))
+                    .max(delay);
+                cap(delay + (upper - delay).mul_f64(random_fraction()))
+            }
+        };
+
+        Some(delay)
+    }
+}
+
+// Returns a random number in `[0, 1)`, drawn from the seed of the
+// current runtime if it is deterministic.
+fn random_fraction() -> f64 {
+    let random = system::current()
+        .pool()
+        .seeded_random()
+        .unwrap_or_else(|| RandomState::new().build_hasher().finish());
+
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl<D: RestartDelay> From<Box<D>> for RestartStrategy {
+    fn from(strategy: Box<D>) -> Self {
+        let strategy: Box<dyn RestartDelay> = strategy;
+        RestartStrategy::from(strategy)
+    }
+}
+
+impl From<Box<dyn RestartDelay>> for RestartStrategy {
+    fn from(strategy: Box<dyn RestartDelay>) -> Self {
+        RestartStrategy {
+            custom: Some(Arc::from(strategy)),
+            ..RestartStrategy::default()
+        }
+    }
+}
+
+impl PartialEq for RestartStrategy {
+    fn eq(&self, other: &Self) -> bool {
+        let same_custom = match (&self.custom, &other.custom) {
+            (Some(custom), Some(other)) => Arc::ptr_eq(custom, other),
+            (None, None) => true,
+            _ => false,
+        };
+
+        same_custom
+            && self.restart_policy == other.restart_policy
+            && self.strategy == other.strategy
+            && self.max_delay == other.max_delay
+            && self.jitter == other.jitter
     }
 }
 
@@ -1930,6 +2721,9 @@ impl Default for RestartStrategy {
         RestartStrategy {
             restart_policy: RestartPolicy::Always,
             strategy: ActorRestartStrategy::default(),
+            custom: None,
+            max_delay: None,
+            jitter: None,
         }
     }
 }
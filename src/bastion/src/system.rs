@@ -1,40 +1,138 @@
 use crate::broadcast::{Broadcast, Parent, Sender};
 use crate::children_ref::ChildrenRef;
-use crate::context::{BastionContext, BastionId, NIL_ID};
+use crate::context::{BastionContext, BastionId, ContextState, NIL_ID};
+use crate::dead_letters::{self, DeadLetterReason};
 use crate::dispatcher::GlobalDispatcher;
 use crate::envelope::Envelope;
+use crate::lifecycle::Lifecycle;
 use crate::message::{BastionMessage, Deployment};
 use crate::path::{BastionPath, BastionPathElement};
 use crate::supervisor::{Supervisor, SupervisorRef};
 use async_mutex::Mutex as AsyncMutex;
-use bastion_executor::pool;
+use bastion_executor::deterministic::VirtualClock;
+use bastion_executor::pool::{self, Pool};
+use futures::future;
 use futures::prelude::*;
 use futures::stream::FuturesUnordered;
 use futures::{pending, poll};
 use fxhash::{FxHashMap, FxHashSet};
 use lazy_static::lazy_static;
 use lightproc::prelude::*;
-use std::sync::{Arc, Condvar, Mutex};
+use once_cell::sync::OnceCell;
+use std::cell::Cell;
+use std::pin::Pin;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
 use std::task::Poll;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, trace, warn};
 
 lazy_static! {
-    pub(crate) static ref SYSTEM: GlobalSystem = System::init();
+    static ref SYSTEM: &'static GlobalSystem = System::init(pool::get());
+}
+
+thread_local! {
+    // The system whose process the thread is polling, or which the
+    // thread entered.
+    static CURRENT: Cell<Option<&'static GlobalSystem>> = const { Cell::new(None) };
+}
+
+/// Returns the system of the default runtime, starting it if needed.
+pub(crate) fn global() -> &'static GlobalSystem {
+    &SYSTEM
+}
+
+/// Returns the system of the runtime that the thread is running,
+/// which is the default runtime's unless it entered another one.
+pub(crate) fn current() -> &'static GlobalSystem {
+    try_current().unwrap_or_else(global)
+}
+
+/// Returns the system that the thread entered, if any.
+pub(crate) fn try_current() -> Option<&'static GlobalSystem> {
+    CURRENT.try_with(Cell::get).ok().flatten()
+}
+
+/// Spawns a process onto the pool of the current system.
+pub(crate) fn spawn<F, T>(future: F, stack: ProcStack) -> RecoverableHandle<T>
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    current().spawn(future, stack)
+}
+
+// Restores the system that the thread entered before.
+struct Entered(Option<&'static GlobalSystem>);
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.0));
+    }
+}
+
+// Counts a process of the system as running until it is dropped.
+struct InFlight(&'static AtomicUsize);
+
+impl InFlight {
+    fn new(count: &'static AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        InFlight(count)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Whether a system accepts the messages sent from outside of its
+/// processes, which it stops doing once it is stopping gracefully.
+#[derive(Debug, Default)]
+pub(crate) struct Intake {
+    closed: AtomicBool,
+}
+
+impl Intake {
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether a message sent by the current thread is
+    /// accepted, which it always is when sent by one of the system's
+    /// processes.
+    pub(crate) fn accepts(&self) -> bool {
+        !self.closed.load(Ordering::SeqCst)
+            || try_current().is_some_and(|system| ptr::eq(&system.intake, self))
+    }
 }
 
 pub(crate) struct GlobalSystem {
+    pool: &'static Pool,
     sender: Sender,
     supervisor: SupervisorRef,
-    dead_letters: ChildrenRef,
+    dead_letters: OnceCell<ChildrenRef>,
     path: Arc<BastionPath>,
     handle: Arc<AsyncMutex<Option<RecoverableHandle<()>>>>,
     running: Mutex<bool>,
     stopping_cvar: Condvar,
     dispatcher: GlobalDispatcher,
+    lifecycle: Lifecycle,
+    intake: Intake,
+    // How many processes spawned onto the pool are still running.
+    in_flight: AtomicUsize,
+    // The states of the children, whose mailboxes are counted when
+    // a graceful stop times out.
+    mailboxes: Mutex<Vec<Weak<Pin<Box<ContextState>>>>>,
+    // When the children have to be done draining their mailbox,
+    // once the system is stopping gracefully.
+    drain_deadline: Mutex<Option<Instant>>,
 }
 
 #[derive(Debug)]
-struct System {
+pub(crate) struct System {
     bcast: Broadcast,
     launched: FxHashMap<BastionId, RecoverableHandle<Supervisor>>,
     // TODO: set limit
@@ -46,20 +144,21 @@ struct System {
 
 #[allow(clippy::mutex_atomic)]
 impl GlobalSystem {
-    fn new(
-        sender: Sender,
-        supervisor: SupervisorRef,
-        dead_letters: ChildrenRef,
-        handle: RecoverableHandle<()>,
-    ) -> Self {
-        let handle = Some(handle);
-        let handle = Arc::new(AsyncMutex::new(handle));
+    fn new(pool: &'static Pool, sender: Sender, supervisor: SupervisorRef) -> Self {
+        let dead_letters = OnceCell::new();
+        let handle = Arc::new(AsyncMutex::new(None));
         let path = Arc::new(BastionPath::root());
         let running = Mutex::new(true);
         let stopping_cvar = Condvar::new();
         let dispatcher = GlobalDispatcher::new();
+        let lifecycle = Lifecycle::default();
+        let intake = Intake::default();
+        let in_flight = AtomicUsize::new(0);
+        let mailboxes = Mutex::default();
+        let drain_deadline = Mutex::new(None);
 
         GlobalSystem {
+            pool,
             sender,
             supervisor,
             dead_letters,
@@ -68,19 +167,62 @@ impl GlobalSystem {
             running,
             stopping_cvar,
             dispatcher,
+            lifecycle,
+            intake,
+            in_flight,
+            mailboxes,
+            drain_deadline,
         }
     }
 
+    /// Calls `f` with the system set as the current one of the thread.
+    pub(crate) fn enter<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let previous = CURRENT.with(|current| current.replace(Some(self)));
+        let _entered = Entered(previous);
+        f()
+    }
+
+    /// Spawns a process onto the system's pool, which polls it with
+    /// the system entered.
+    pub(crate) fn spawn<F, T>(&'static self, future: F, stack: ProcStack) -> RecoverableHandle<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let in_flight = InFlight::new(&self.in_flight);
+        let mut future = Box::pin(future);
+        let future = future::poll_fn(move |cx| {
+            let _in_flight = &in_flight;
+            self.enter(|| future.as_mut().poll(cx))
+        });
+        self.pool.spawn(future, stack)
+    }
+
     pub(crate) fn sender(&self) -> &Sender {
         &self.sender
     }
 
+    pub(crate) fn pool(&self) -> &'static Pool {
+        self.pool
+    }
+
+    /// Returns the virtual clock of the system, if its pool is
+    /// deterministic.
+    pub(crate) fn clock(&self) -> Option<&'static VirtualClock> {
+        self.pool.clock()
+    }
+
     pub(crate) fn supervisor(&self) -> &SupervisorRef {
         &self.supervisor
     }
 
     pub(crate) fn dead_letters(&self) -> &ChildrenRef {
-        &self.dead_letters
+        self.dead_letters
+            .get()
+            .expect("dead letters used before being spawned")
     }
 
     pub(crate) fn handle(&self) -> Arc<AsyncMutex<Option<RecoverableHandle<()>>>> {
@@ -95,6 +237,68 @@ impl GlobalSystem {
         &self.dispatcher
     }
 
+    pub(crate) fn lifecycle(&self) -> &Lifecycle {
+        &self.lifecycle
+    }
+
+    pub(crate) fn intake(&self) -> &Intake {
+        &self.intake
+    }
+
+    /// Returns how many processes spawned onto the pool are still
+    /// running.
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Keeps track of the mailbox of a child, for as long as the child
+    /// runs.
+    pub(crate) fn register_mailbox(&self, state: &Arc<Pin<Box<ContextState>>>) {
+        // FIXME: panics
+        let mut mailboxes = self.mailboxes.lock().unwrap();
+        mailboxes.retain(|state| state.strong_count() > 0);
+        mailboxes.push(Arc::downgrade(state));
+    }
+
+    /// Returns how many messages are waiting in the mailboxes of the
+    /// children that are still running.
+    pub(crate) fn pending_messages(&self) -> usize {
+        // FIXME: panics
+        self.mailboxes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|state| state.mailbox_size() as usize)
+            .sum()
+    }
+
+    /// Stops accepting the messages sent from outside of the system's
+    /// processes and makes the children drain their mailbox until
+    /// `deadline` once they are stopped.
+    pub(crate) fn start_draining(&self, deadline: Instant) {
+        self.intake.close();
+        // FIXME: panics
+        *self.drain_deadline.lock().unwrap() = Some(deadline);
+    }
+
+    /// Returns how long a child drains its mailbox once stopped, given
+    /// how long its group set it to, which is bounded by the time left
+    /// until a graceful stop's deadline.
+    pub(crate) fn drain_timeout(&self, configured: Option<Duration>) -> Option<Duration> {
+        // FIXME: panics
+        let left = self
+            .drain_deadline
+            .lock()
+            .unwrap()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        match (configured, left) {
+            (Some(configured), Some(left)) => Some(configured.min(left)),
+            (configured, None) => configured,
+            (None, left) => left,
+        }
+    }
+
     pub(crate) fn notify_stopped(&self) {
         // FIXME: panics
         *self.running.lock().unwrap() = false;
@@ -102,16 +306,54 @@ impl GlobalSystem {
     }
 
     pub(crate) fn wait_until_stopped(&self) {
+        if self.pool.is_deterministic() {
+            return self.run_until_stopped();
+        }
+
         // FIXME: panics
         let mut running = self.running.lock().unwrap();
         while *running {
             running = self.stopping_cvar.wait(running).unwrap();
         }
     }
+
+    /// Runs the processes of the system's deterministic pool until it
+    /// is stopped, advancing its virtual time whenever they are all
+    /// waiting for a timer.
+    fn run_until_stopped(&self) {
+        let clock = self.clock().expect("deterministic pool without clock");
+        loop {
+            self.pool.run_until_idle();
+            // FIXME: panics
+            if !*self.running.lock().unwrap() {
+                return;
+            }
+
+            match clock.next_timer() {
+                Some(next) => self
+                    .pool
+                    .advance(next.saturating_duration_since(clock.now())),
+                None => panic!("System: Can't stop, as none of its processes can make progress."),
+            }
+        }
+    }
+
+    /// Waits until the system is stopped or `deadline` is reached, and
+    /// returns whether it stopped.
+    pub(crate) fn wait_until_stopped_or(&self, deadline: Instant) -> bool {
+        // FIXME: panics
+        let running = self.running.lock().unwrap();
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let (running, _) = self
+            .stopping_cvar
+            .wait_timeout_while(running, timeout, |running| *running)
+            .unwrap();
+        !*running
+    }
 }
 
 impl System {
-    fn init() -> GlobalSystem {
+    pub(crate) fn init(pool: &'static Pool) -> &'static GlobalSystem {
         info!("System: Initializing.");
         let parent = Parent::none();
         let bcast = Broadcast::new_root(parent);
@@ -147,14 +389,25 @@ impl System {
         );
         system.bcast.send_self(env);
 
+        // The processes of the system refer to it for as long as they
+        // run, which might be after it stopped.
+        let global = GlobalSystem::new(pool, sender, supervisor_ref);
+        let global: &'static GlobalSystem = Box::leak(Box::new(global));
+
+        let dead_letters_ref = global
+            .enter(|| Self::spawn_dead_letters(global.supervisor()))
+            .expect("Can't spawn dead letters");
+        global.dead_letters.set(dead_letters_ref).ok();
+
         debug!("System: Launching.");
         let stack = system.stack();
-        let handle = pool::spawn(system.run(), stack);
-
-        let dead_letters_ref =
-            Self::spawn_dead_letters(&supervisor_ref).expect("Can't spawn dead letters");
+        let handle = global.spawn(system.run(), stack);
+        *global
+            .handle
+            .try_lock()
+            .expect("system handle locked before launching") = Some(handle);
 
-        GlobalSystem::new(sender, supervisor_ref, dead_letters_ref, handle)
+        global
     }
 
     fn stack(&self) -> ProcStack {
@@ -168,6 +421,7 @@ impl System {
                 loop {
                     let smsg = ctx.recv().await?;
                     debug!("Received dead letter: {:?}", smsg);
+                    dead_letters::report(smsg, DeadLetterReason::NoRecipient, None);
                 }
             })
         })
@@ -176,7 +430,7 @@ impl System {
     // TODO: set a limit?
     async fn recover(&mut self, mut supervisor: Supervisor) {
         warn!("System: Recovering Supervisor({}).", supervisor.id());
-        supervisor.callbacks().before_restart();
+        supervisor.callbacks().before_restart().await;
 
         let parent = Parent::system();
         let bcast = if supervisor.id() == &NIL_ID {
@@ -189,7 +443,7 @@ impl System {
         };
 
         supervisor.reset(bcast).await;
-        supervisor.callbacks().after_restart();
+        supervisor.callbacks().after_restart().await;
 
         self.bcast.register(supervisor.bcast());
 
@@ -253,7 +507,7 @@ impl System {
         match *deployment {
             Deployment::Supervisor(supervisor) => {
                 debug!("System: Deploying Supervisor({}).", supervisor.id());
-                supervisor.callbacks().before_start();
+                supervisor.callbacks().before_start().await;
 
                 self.bcast.register(supervisor.bcast());
                 if self.started {
@@ -303,7 +557,7 @@ impl System {
             } => {
                 info!("System: Stopping.");
                 for supervisor in self.stop().await {
-                    supervisor.callbacks().after_stop();
+                    supervisor.callbacks().after_stop().await;
                 }
 
                 return Err(());
@@ -365,6 +619,10 @@ impl System {
                 msg: BastionMessage::DropChild { .. },
                 ..
             } => unreachable!(),
+            Envelope {
+                msg: BastionMessage::TearDownChild { .. },
+                ..
+            } => unreachable!(),
             Envelope {
                 msg: BastionMessage::SetState { .. },
                 ..
@@ -373,14 +631,31 @@ impl System {
                 msg: BastionMessage::Stopped { id, .. },
                 ..
             } => self.restart_supervised_object(id),
+            // A supervisor only faults when it escalates, which stops
+            // the system instead of restarting it over and over.
             Envelope {
                 msg: BastionMessage::Faulted { id, .. },
                 ..
-            } => self.restart_supervised_object(id),
+            } => {
+                error!("System: Supervisor({}) escalated, stopping.", id);
+                for supervisor in self.stop().await {
+                    supervisor.callbacks().after_stop().await;
+                }
+
+                return Err(());
+            }
             Envelope {
                 msg: BastionMessage::Heartbeat,
                 ..
             } => unreachable!(),
+            Envelope {
+                msg: BastionMessage::Resume,
+                ..
+            } => unreachable!(),
+            Envelope {
+                msg: BastionMessage::HotSwap(_),
+                ..
+            } => unreachable!(),
         }
 
         Ok(())
@@ -397,7 +672,7 @@ impl System {
                     if self.restart.remove(&id) {
                         self.recover(supervisor).await;
                     } else {
-                        supervisor.callbacks().after_stop();
+                        supervisor.callbacks().after_stop().await;
                     }
 
                     continue;
@@ -433,11 +708,11 @@ impl System {
                         trace!("System: Replaying message: {:?}", msg);
                         // FIXME: Err(Error)?
                         if self.handle(msg).await.is_err() {
-                            let handle = SYSTEM.handle();
+                            let handle = current().handle();
                             let mut system = handle.lock().await;
                             *system = None;
 
-                            SYSTEM.notify_stopped();
+                            current().notify_stopped();
 
                             return;
                         }
@@ -450,11 +725,11 @@ impl System {
                 Poll::Ready(Some(msg)) => {
                     trace!("System: Received a new message (started=true): {:?}", msg);
                     if self.handle(msg).await.is_err() {
-                        let handle = SYSTEM.handle();
+                        let handle = current().handle();
                         let mut system = handle.lock().await;
                         *system = None;
 
-                        SYSTEM.notify_stopped();
+                        current().notify_stopped();
 
                         return;
                     }
@@ -0,0 +1,96 @@
+//!
+//! Timers following the clock of the runtime they are created in.
+//!
+//! The timers of bastion (like the ones of ask timeouts, restart
+//! backoffs and heartbeats) use the real time, except in the
+//! deterministic runtimes created by [`Bastion::init_test`] or
+//! [`RuntimeBuilder::with_deterministic_seed`], whose time is virtual
+//! and only passes when the runtime is advanced.
+//!
+//! Actors waiting with [`Delay`] and reading the time with [`now`]
+//! instead of using other timers can thus be tested without waiting
+//! for real.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use bastion::prelude::*;
+//! use bastion::time::Delay;
+//! use std::time::Duration;
+//! #
+//! # Bastion::init();
+//!
+//! Bastion::children(|children| {
+//!     children.with_exec(|ctx: BastionContext| async move {
+//!         // Waits for a second of the runtime's time...
+//!         Delay::new(Duration::from_secs(1)).await;
+//!         ctx.recv().await?;
+//!         Ok(())
+//!     })
+//! })
+//! .expect("Couldn't create the children group.");
+//! #
+//! # Bastion::start();
+//! # Bastion::stop();
+//! # Bastion::block_until_stopped();
+//! ```
+//!
+//! [`Bastion::init_test`]: ../struct.Bastion.html#method.init_test
+//! [`RuntimeBuilder::with_deterministic_seed`]: ../runtime/struct.RuntimeBuilder.html#method.with_deterministic_seed
+//! [`Delay`]: struct.Delay.html
+//! [`now`]: fn.now.html
+
+use crate::system;
+use bastion_executor::deterministic::Sleep;
+use futures_timer::Delay as RealDelay;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// A future completing once a duration of the current runtime's
+/// time passed.
+#[derive(Debug)]
+pub struct Delay {
+    inner: Inner,
+}
+
+#[derive(Debug)]
+enum Inner {
+    Real(RealDelay),
+    Virtual(Sleep),
+}
+
+impl Delay {
+    /// Creates a future completing once `duration` passed, following
+    /// the clock of the runtime the caller runs in (or of the default
+    /// runtime).
+    pub fn new(duration: Duration) -> Self {
+        let inner = match system::current().clock() {
+            Some(clock) => Inner::Virtual(clock.sleep(duration)),
+            None => Inner::Real(RealDelay::new(duration)),
+        };
+
+        Delay { inner }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        match &mut self.get_mut().inner {
+            Inner::Real(delay) => Pin::new(delay).poll(cx),
+            Inner::Virtual(sleep) => Pin::new(sleep).poll(cx),
+        }
+    }
+}
+
+/// Returns the current time of the runtime the caller runs in (or of
+/// the default runtime).
+pub fn now() -> Instant {
+    match system::current().clock() {
+        Some(clock) => clock.now(),
+        None => Instant::now(),
+    }
+}
@@ -0,0 +1,108 @@
+use bastion::local;
+use bastion::prelude::*;
+use bastion_executor::pool::{self, PoolConfig};
+use futures_timer::Delay;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+#[derive(Default)]
+struct Counter(usize);
+
+// Sets the flag once dropped, telling that the value holding it was.
+#[derive(Default)]
+struct DropGuard(Option<Arc<AtomicBool>>);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        if let Some(dropped) = &self.0 {
+            dropped.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+fn follows_migrating_actor() {
+    // How many threads each actor ran on, and the count it saw.
+    let runs: Arc<Mutex<Vec<(usize, usize)>>> = Arc::default();
+    let exec_runs = runs.clone();
+    Bastion::children(|children| {
+        children
+            .with_redundancy(4)
+            .with_exec(move |ctx: BastionContext| {
+                let runs = exec_runs.clone();
+                async move {
+                    let mut threads = HashSet::<ThreadId>::new();
+                    for _ in 0..50 {
+                        threads.insert(thread::current().id());
+                        local::with(|counter: &mut Counter| counter.0 += 1);
+                        // Might get woken up on another worker.
+                        Delay::new(Duration::from_millis(1)).await;
+                    }
+
+                    let count = ctx.with_local(|counter: &mut Counter| counter.0);
+                    runs.lock().unwrap().push((threads.len(), count));
+                    Ok(())
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    thread::sleep(Duration::from_secs(1));
+    let runs = runs.lock().unwrap();
+    assert_eq!(runs.len(), 4);
+    assert!(runs.iter().any(|(threads, _)| *threads > 1), "{:?}", runs);
+    assert!(runs.iter().all(|(_, count)| *count == 50), "{:?}", runs);
+}
+
+fn dropped_on_restart() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let runs: Arc<Mutex<Vec<bool>>> = Arc::default();
+    let (exec_dropped, exec_runs) = (dropped.clone(), runs.clone());
+    Bastion::supervisor(|sp| {
+        sp.children(|children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let dropped = exec_dropped.clone();
+                let runs = exec_runs.clone();
+                async move {
+                    let restarted = ctx.with_local(|guard: &mut DropGuard| {
+                        let fresh = guard.0.is_none();
+                        guard.0 = Some(dropped.clone());
+                        !fresh
+                    });
+                    let first_run = runs.lock().unwrap().is_empty();
+                    runs.lock().unwrap().push(restarted);
+                    if first_run {
+                        panic!("restarting");
+                    }
+
+                    loop {
+                        ctx.recv().await?;
+                    }
+                }
+            })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    thread::sleep(Duration::from_millis(300));
+    assert!(dropped.load(Ordering::SeqCst));
+    // The restarted child didn't see the value of the panicked one.
+    assert_eq!(*runs.lock().unwrap(), vec![false, false]);
+}
+
+#[test]
+fn actor_local() {
+    // Lets the actors migrate even on a single core.
+    pool::configure(PoolConfig::default().with_min_threads(4)).unwrap();
+    Bastion::init();
+    Bastion::start();
+
+    assert!(local::try_with(|_: &mut Counter| ()).is_none());
+    follows_migrating_actor();
+    dropped_on_restart();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
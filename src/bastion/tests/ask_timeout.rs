@@ -0,0 +1,112 @@
+use bastion::prelude::*;
+use futures_timer::Delay;
+use std::time::Duration;
+
+fn spawn_one<I, F>(init: I) -> ChildRef
+where
+    I: Fn(BastionContext) -> F + Send + Sync + 'static,
+    F: std::future::Future<Output = Result<(), ()>> + Send + 'static,
+{
+    let children = Bastion::children(|children| children.with_exec(init))
+        .expect("Couldn't create the children group.");
+    children.elems()[0].clone()
+}
+
+// Answers after 200ms, in a group asked with a default timeout.
+fn spawn_slow(ask_timeout: Duration) -> ChildRef {
+    let children = Bastion::children(|children| {
+        children
+            .with_ask_timeout(ask_timeout)
+            .with_exec(|ctx: BastionContext| async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        msg: &'static str =!> {
+                            Delay::new(Duration::from_millis(200)).await;
+                            let _ = answer!(ctx, msg);
+                        };
+                        _: _ => ();
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+    children.elems()[0].clone()
+}
+
+fn ask_through(asker: &ChildRef, target: &ChildRef, timeout: u64) -> Option<AskError> {
+    let answer = run!(asker.ask_anonymously((target.clone(), timeout)).unwrap()).unwrap();
+    msg! { answer,
+        res: Option<AskError> => res;
+        _: _ => panic!("Unexpected answer.");
+    }
+}
+
+#[test]
+fn ask_timeout() {
+    Bastion::init();
+    Bastion::start();
+
+    let asker = spawn_one(|ctx: BastionContext| async move {
+        loop {
+            msg! { ctx.recv().await?,
+                target: (ChildRef, u64) =!> {
+                    let timeout = Duration::from_millis(target.1);
+                    let res = ctx.ask_timeout(&target.0.addr(), "ping", timeout).await;
+                    answer!(ctx, res.err()).unwrap();
+                };
+                _: _ => ();
+            }
+        }
+    });
+
+    // Keeps the messages alive without ever answering them.
+    let silent = spawn_one(|ctx: BastionContext| async move {
+        let mut pending = Vec::new();
+        loop {
+            pending.push(ctx.recv().await?);
+        }
+    });
+
+    let slow = spawn_one(|ctx: BastionContext| async move {
+        loop {
+            msg! { ctx.recv().await?,
+                msg: &'static str =!> {
+                    Delay::new(Duration::from_millis(200)).await;
+                    // The asker may have given up already.
+                    let _ = answer!(ctx, msg);
+                };
+                _: _ => ();
+            }
+        }
+    });
+
+    let dead = spawn_one(|_: BastionContext| async move { Ok(()) });
+
+    assert_eq!(
+        ask_through(&asker, &silent, 50),
+        Some(AskError::Timeout(Duration::from_millis(50)))
+    );
+
+    // The late answer is dropped and the answering child keeps working.
+    assert_eq!(
+        ask_through(&asker, &slow, 50),
+        Some(AskError::Timeout(Duration::from_millis(50)))
+    );
+    assert_eq!(ask_through(&asker, &slow, 1_000), None);
+
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(
+        ask_through(&asker, &dead, 1_000),
+        Some(AskError::Unreachable)
+    );
+
+    // The group's default applies unless the asker overrides it.
+    let defaulted = spawn_slow(Duration::from_millis(50));
+    assert!(run!(defaulted.ask_anonymously("ping").unwrap()).is_err());
+    assert_eq!(ask_through(&asker, &defaulted, 1_000), None);
+    let patient = spawn_slow(Duration::from_secs(1));
+    assert!(run!(patient.ask_anonymously("ping").unwrap()).is_ok());
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
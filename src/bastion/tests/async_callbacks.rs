@@ -0,0 +1,76 @@
+use bastion::prelude::*;
+use futures_timer::Delay;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn async_callbacks() {
+    Bastion::init();
+    Bastion::start();
+
+    let started = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let callbacks_started = started.clone();
+    let callbacks_stopped = stopped.clone();
+    let callbacks = Callbacks::new()
+        .with_before_start_async(move || {
+            let started = callbacks_started.clone();
+            async move {
+                Delay::new(Duration::from_millis(50)).await;
+                started.store(true, Ordering::SeqCst);
+            }
+        })
+        .with_after_stop_async(move || {
+            let stopped = callbacks_stopped.clone();
+            async move {
+                Delay::new(Duration::from_millis(50)).await;
+                stopped.store(true, Ordering::SeqCst);
+            }
+        });
+
+    let exec_started = started.clone();
+    let children = Bastion::children(|children| {
+        children
+            .with_callbacks(callbacks)
+            .with_exec(move |ctx: BastionContext| {
+                let started = exec_started.clone();
+                async move {
+                    // The future only starts once `before_start_async` resolved.
+                    let started = started.load(Ordering::SeqCst);
+                    loop {
+                        msg! { ctx.recv().await?,
+                            _msg: &'static str =!> {
+                                answer!(ctx, started).unwrap();
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+    let child = children.elems()[0].clone();
+
+    let answer = run!(child.ask_anonymously("started?").unwrap()).unwrap();
+    msg! { answer,
+        started: bool => assert!(started);
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    child.stop().unwrap();
+    for _ in 0..100 {
+        if stopped.load(Ordering::SeqCst) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(stopped.load(Ordering::SeqCst));
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
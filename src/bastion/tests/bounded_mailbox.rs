@@ -0,0 +1,116 @@
+use bastion::prelude::*;
+use futures_timer::Delay;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// Spawns a child with a mailbox of two messages, which only starts
+// receiving them once `paused` is set to false, counting them in
+// `received`.
+fn spawn_consumer(
+    policy: MailboxPolicy,
+    paused: Arc<AtomicBool>,
+    received: Arc<AtomicUsize>,
+) -> ChildRef {
+    let children = Bastion::children(|children| {
+        children
+            .with_mailbox_capacity(2)
+            .with_mailbox_policy(policy)
+            .with_exec(move |ctx: BastionContext| {
+                let paused = paused.clone();
+                let received = received.clone();
+                async move {
+                    while paused.load(Ordering::SeqCst) {
+                        Delay::new(Duration::from_millis(10)).await;
+                    }
+
+                    loop {
+                        ctx.recv().await?;
+                        received.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+    children.elems()[0].clone()
+}
+
+fn wait_for(count: &AtomicUsize, expected: usize) {
+    for _ in 0..100 {
+        if count.load(Ordering::SeqCst) == expected {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(count.load(Ordering::SeqCst), expected);
+}
+
+fn rejects_when_full() {
+    let paused = Arc::new(AtomicBool::new(true));
+    let received = Arc::new(AtomicUsize::new(0));
+    let consumer = spawn_consumer(MailboxPolicy::Reject, paused.clone(), received.clone());
+
+    assert!(consumer.tell_anonymously(1u8).is_ok());
+    assert!(consumer.tell_anonymously(2u8).is_ok());
+    assert_eq!(consumer.tell_anonymously(3u8), Err(3u8));
+    // Asking doesn't wait for the mailbox to have room.
+    assert!(consumer.ask_anonymously(4u8).is_err());
+
+    paused.store(false, Ordering::SeqCst);
+    wait_for(&received, 2);
+
+    assert!(consumer.tell_anonymously(5u8).is_ok());
+    wait_for(&received, 3);
+}
+
+fn applies_backpressure() {
+    let paused = Arc::new(AtomicBool::new(true));
+    let received = Arc::new(AtomicUsize::new(0));
+    let consumer = spawn_consumer(
+        MailboxPolicy::Backpressure,
+        paused.clone(),
+        received.clone(),
+    );
+
+    let sent = Arc::new(AtomicUsize::new(0));
+    let producer_sent = sent.clone();
+    let target = consumer.clone();
+    Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let sent = producer_sent.clone();
+            let target = target.addr();
+            async move {
+                for i in 0..10u8 {
+                    ctx.tell_async(&target, i).await.unwrap();
+                    sent.fetch_add(1, Ordering::SeqCst);
+                }
+
+                Ok(())
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    wait_for(&sent, 2);
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(sent.load(Ordering::SeqCst), 2);
+    // Asking doesn't wait for the mailbox to have room either.
+    assert!(consumer.ask_anonymously(0u8).is_err());
+
+    paused.store(false, Ordering::SeqCst);
+    wait_for(&sent, 10);
+    wait_for(&received, 10);
+}
+
+#[test]
+fn bounded_mailbox() {
+    Bastion::init();
+    Bastion::start();
+
+    rejects_when_full();
+    applies_backpressure();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,59 @@
+use bastion::prelude::*;
+
+#[test]
+fn children_request() {
+    Bastion::init();
+    Bastion::start();
+
+    let children = Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            loop {
+                msg! { ctx.recv().await?,
+                    n: u64 =!> {
+                        answer!(ctx, n * 2).expect("couldn't reply");
+                    };
+                    _text: &'static str =!> {
+                        answer!(ctx, "not a number").expect("couldn't reply");
+                    };
+                    _: _ => ();
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let doubled: Result<u64, RequestError> = run!(children.request(21u64));
+    assert_eq!(doubled, Ok(42));
+
+    let mismatch: Result<u64, RequestError> = run!(children.request("hello"));
+    assert_eq!(mismatch, Err(RequestError::TypeMismatch));
+
+    // Nothing answers a `bool`.
+    let dropped: Result<u64, RequestError> = run!(children.request(true));
+    assert_eq!(dropped, Err(RequestError::Dropped));
+
+    // The requests skip the stopped element.
+    let group = Bastion::children(|children| {
+        children
+            .with_redundancy(2)
+            .with_exec(|ctx: BastionContext| async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        n: u64 =!> {
+                            answer!(ctx, n + 1).expect("couldn't reply");
+                        };
+                        _: _ => ();
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+    run!(group.elems()[0].stop_and_wait()).unwrap();
+    for n in 0..4u64 {
+        let next: Result<u64, RequestError> = run!(group.request(n));
+        assert_eq!(next, Ok(n + 1));
+    }
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,68 @@
+use bastion::prelude::*;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::Duration;
+
+type Failures = Arc<Mutex<Vec<(usize, Option<String>)>>>;
+
+// Restarts the children for as long as they panic.
+#[derive(Debug)]
+struct WhilePanicking(Failures);
+
+impl RestartDelay for WhilePanicking {
+    fn next_delay(&self, restart_count: usize, last_failure: &FailureInfo) -> Option<Duration> {
+        assert_eq!(last_failure.restart_count(), restart_count);
+        let message = last_failure.panic_message().map(str::to_string);
+        self.0.lock().unwrap().push((restart_count, message));
+
+        if last_failure.panicked() {
+            Some(Duration::from_millis(10))
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn custom_restart_strategy() {
+    Bastion::init();
+    Bastion::start();
+
+    let failures: Failures = Arc::default();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let child_runs = runs.clone();
+
+    let strategy = WhilePanicking(failures.clone());
+    Bastion::supervisor(|sp| {
+        sp.with_restart_strategy(Box::new(strategy))
+            .children(|children| {
+                children.with_exec(move |_: BastionContext| {
+                    let runs = child_runs.clone();
+                    async move {
+                        if runs.fetch_add(1, Ordering::SeqCst) < 2 {
+                            panic!("transient");
+                        }
+
+                        Err(())
+                    }
+                })
+            })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    thread::sleep(Duration::from_secs(1));
+
+    // The two panicking runs got restarted, but not the failing one.
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+    let transient = Some("transient".to_string());
+    assert_eq!(
+        *failures.lock().unwrap(),
+        vec![(0, transient.clone()), (1, transient), (2, None)]
+    );
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,154 @@
+use bastion::prelude::*;
+use futures_timer::Delay;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+type Letters = Arc<Mutex<Vec<DeadLetter>>>;
+
+// Whether the dead letter was meant to be received by `child`.
+fn is_for(letter: &DeadLetter, child: &ChildRef) -> bool {
+    letter
+        .recipient
+        .as_ref()
+        .is_some_and(|path| path.to_string().ends_with(&child.id().to_string()))
+}
+
+// Waits for `count` dead letters to be reported, and takes them.
+fn take_letters(letters: &Letters, count: usize) -> Vec<DeadLetter> {
+    for _ in 0..100 {
+        if letters.lock().unwrap().len() >= count {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let letters = letters.lock().unwrap().drain(..).collect::<Vec<_>>();
+    assert_eq!(letters.len(), count, "{:?}", letters);
+    letters
+}
+
+fn unhandled(letters: &Letters) {
+    let children = Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            loop {
+                msg! { ctx.recv().await?,
+                    _msg: &'static str => ();
+                    msg: _ => ctx.unhandled(msg, signature!());
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    let child = &children.elems()[0];
+
+    child.tell_anonymously("handled").unwrap();
+    child.tell_anonymously(42u8).unwrap();
+
+    let letter = take_letters(letters, 1).remove(0);
+    assert_eq!(letter.reason, DeadLetterReason::Unhandled);
+    assert!(is_for(&letter, child));
+    msg! { letter.envelope,
+        msg: u8 => assert_eq!(msg, 42);
+        _: _ => panic!("Unexpected dead letter.");
+    }
+}
+
+fn stopped(letters: &Letters) {
+    let paused = Arc::new(AtomicBool::new(true));
+    let exec_paused = paused.clone();
+    let children = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let paused = exec_paused.clone();
+            async move {
+                while paused.load(Ordering::SeqCst) {
+                    Delay::new(Duration::from_millis(10)).await;
+                }
+
+                loop {
+                    ctx.recv().await?;
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    let child = &children.elems()[0];
+
+    child.tell_anonymously(1u8).unwrap();
+    child.tell_anonymously(2u8).unwrap();
+    run!(child.stop_and_wait()).unwrap();
+
+    let letters = take_letters(letters, 2);
+    assert!(letters
+        .iter()
+        .all(|letter| letter.reason == DeadLetterReason::Stopped && is_for(letter, child)));
+}
+
+fn undeliverable(letters: &Letters) {
+    let paused = Arc::new(AtomicBool::new(true));
+    let exec_paused = paused.clone();
+    Bastion::supervisor(|supervisor| {
+        supervisor.children(|children| {
+            children
+                .with_mailbox_capacity(1)
+                .with_dispatcher(Dispatcher::with_type(DispatcherType::Named(
+                    "dead letters".to_string(),
+                )))
+                .with_exec(move |ctx: BastionContext| {
+                    let paused = exec_paused.clone();
+                    async move {
+                        while paused.load(Ordering::SeqCst) {
+                            Delay::new(Duration::from_millis(10)).await;
+                        }
+
+                        loop {
+                            ctx.recv().await?;
+                        }
+                    }
+                })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+    // Lets the child register in the dispatcher.
+    thread::sleep(Duration::from_millis(100));
+
+    Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            let group = BroadcastTarget::Group("dead letters".to_string());
+            ctx.broadcast_message(group.clone(), 1u8);
+            ctx.broadcast_message(group, 2u8);
+
+            let unknown = BroadcastTarget::Group("unknown".to_string());
+            ctx.broadcast_message(unknown, 3u8);
+
+            Ok(())
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let letters = take_letters(letters, 2);
+    assert_eq!(letters[0].reason, DeadLetterReason::MailboxFull);
+    assert!(letters[0].recipient.is_some());
+    assert_eq!(letters[1].reason, DeadLetterReason::NoRecipient);
+    assert!(letters[1].recipient.is_none());
+
+    paused.store(false, Ordering::SeqCst);
+}
+
+#[test]
+fn dead_letters() {
+    Bastion::init();
+    Bastion::start();
+
+    let letters: Letters = Arc::default();
+    let handler_letters = letters.clone();
+    Bastion::on_dead_letter(move |letter| handler_letters.lock().unwrap().push(letter));
+
+    unhandled(&letters);
+    stopped(&letters);
+    undeliverable(&letters);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
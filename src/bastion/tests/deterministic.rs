@@ -0,0 +1,150 @@
+use bastion::prelude::*;
+use bastion::time::{self, Delay};
+use futures::FutureExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Returns the order in which children spawned at once started and
+// handled a broadcasted message.
+fn run_order(seed: u64) -> Vec<(usize, bool)> {
+    let runtime = Runtime::builder().with_deterministic_seed(seed).build();
+    runtime.start();
+
+    let log: Arc<Mutex<Vec<(usize, bool)>>> = Arc::default();
+    for i in 0..10 {
+        let log = log.clone();
+        runtime
+            .children(move |children| {
+                children.with_exec(move |ctx: BastionContext| {
+                    let log = log.clone();
+                    async move {
+                        log.lock().unwrap().push((i, false));
+                        ctx.recv().await?;
+                        log.lock().unwrap().push((i, true));
+                        Ok(())
+                    }
+                })
+            })
+            .expect("Couldn't create the children group.");
+    }
+    runtime.run_until_idle();
+
+    // Nothing runs until the runtime is driven.
+    runtime.broadcast("hello").unwrap();
+    assert_eq!(log.lock().unwrap().len(), 10);
+    runtime.run_until_idle();
+
+    runtime.shutdown();
+    let log = log.lock().unwrap().clone();
+    log
+}
+
+#[test]
+fn seeded_order() {
+    let order = run_order(7);
+    assert_eq!(order.len(), 20);
+    assert_eq!(order, run_order(7));
+    assert_ne!(order, run_order(8));
+}
+
+#[test]
+fn ask_timeout() {
+    let runtime = Runtime::builder().with_deterministic_seed(0).build();
+    runtime.start();
+
+    // Answers after a minute.
+    let slow = runtime
+        .children(|children| {
+            children.with_exec(|ctx: BastionContext| async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        msg: &'static str =!> {
+                            Delay::new(Duration::from_secs(60)).await;
+                            let _ = answer!(ctx, msg);
+                        };
+                        _: _ => ();
+                    }
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+    let slow = slow.elems()[0].clone();
+
+    let asker = runtime
+        .children(|children| {
+            children.with_exec(|ctx: BastionContext| async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        target: ChildRef =!> {
+                            let started = time::now();
+                            let timeout = Duration::from_secs(30);
+                            let res = ctx.ask_timeout(&target.addr(), "ping", timeout).await;
+                            answer!(ctx, (res.err(), time::now() - started)).unwrap();
+                        };
+                        _: _ => ();
+                    }
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+    let asker = asker.elems()[0].clone();
+    runtime.run_until_idle();
+
+    let mut answer = asker.ask_anonymously(slow).unwrap();
+    runtime.run_until_idle();
+    runtime.advance_time(Duration::from_secs(29));
+    assert!((&mut answer).now_or_never().is_none());
+
+    runtime.advance_time(Duration::from_secs(1));
+    msg! { answer.now_or_never().unwrap().unwrap(),
+        res: (Option<AskError>, Duration) => {
+            assert!(matches!(res.0, Some(AskError::Timeout(_))));
+            assert_eq!(res.1, Duration::from_secs(30));
+        };
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    runtime.shutdown();
+}
+
+#[test]
+fn restart_backoff() {
+    let runtime = Runtime::builder().with_deterministic_seed(0).build();
+    runtime.start();
+
+    let starts = Arc::new(AtomicUsize::new(0));
+    let child_starts = starts.clone();
+    runtime
+        .supervisor(move |sp| {
+            sp.with_restart_strategy(RestartStrategy::default().with_actor_restart_strategy(
+                ActorRestartStrategy::LinearBackOff {
+                    timeout: Duration::from_secs(10),
+                },
+            ))
+            .children(move |children| {
+                children.with_exec(move |_: BastionContext| {
+                    let starts = child_starts.clone();
+                    async move {
+                        starts.fetch_add(1, Ordering::SeqCst);
+                        Err(())
+                    }
+                })
+            })
+        })
+        .expect("Couldn't create the supervisor.");
+    runtime.run_until_idle();
+    assert_eq!(starts.load(Ordering::SeqCst), 1);
+
+    // The first restart waits for 10s, the second one for 20s.
+    runtime.advance_time(Duration::from_secs(9));
+    assert_eq!(starts.load(Ordering::SeqCst), 1);
+    runtime.advance_time(Duration::from_secs(1));
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+    runtime.advance_time(Duration::from_secs(19));
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+    runtime.advance_time(Duration::from_secs(1));
+    assert_eq!(starts.load(Ordering::SeqCst), 3);
+
+    runtime.shutdown();
+}
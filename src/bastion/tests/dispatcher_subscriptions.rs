@@ -0,0 +1,112 @@
+use bastion::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const GROUP: &str = "subscriptions";
+
+fn dispatcher() -> DispatcherType {
+    DispatcherType::Named(GROUP.to_string())
+}
+
+fn ping_group(ctx: &BastionContext, count: usize) {
+    for _ in 0..count {
+        ctx.broadcast_message(BroadcastTarget::Group(GROUP.to_string()), "ping");
+    }
+}
+
+// Receives the pings dispatched by the group forever, counting them.
+async fn count_pings(ctx: &BastionContext, pings: &AtomicUsize) -> Result<(), ()> {
+    loop {
+        msg! { ctx.recv().await?,
+            _msg: Arc<SignedMessage> => {
+                pings.fetch_add(1, Ordering::SeqCst);
+            };
+            _: _ => ();
+        }
+    }
+}
+
+#[test]
+fn dispatcher_subscriptions() {
+    Bastion::init();
+    Bastion::start();
+
+    // A member of the group since it was created.
+    let member_pings = Arc::new(AtomicUsize::new(0));
+    let exec_pings = member_pings.clone();
+    Bastion::supervisor(|supervisor| {
+        supervisor.children(|children| {
+            children
+                .with_dispatcher(Dispatcher::with_type(dispatcher()))
+                .with_exec(move |ctx: BastionContext| {
+                    let pings = exec_pings.clone();
+                    async move { count_pings(&ctx, &pings).await }
+                })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+    thread::sleep(Duration::from_millis(100));
+
+    // Joins the group, but stops right away.
+    Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            ctx.subscribe(dispatcher());
+            Ok(())
+        })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(100));
+
+    let results: Arc<Mutex<Vec<bool>>> = Arc::default();
+    let exec_results = results.clone();
+    let subscriber_pings = Arc::new(AtomicUsize::new(0));
+    let exec_pings = subscriber_pings.clone();
+    Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let results = exec_results.clone();
+            let pings = exec_pings.clone();
+            async move {
+                let unknown = DispatcherType::Named("unknown".to_string());
+                let subscribed = vec![
+                    ctx.subscribe(dispatcher()),
+                    ctx.subscribe(dispatcher()),
+                    ctx.subscribe(unknown),
+                ];
+                results.lock().unwrap().extend(subscribed);
+
+                // Half of them are for this child...
+                ping_group(&ctx, 4);
+                while pings.load(Ordering::SeqCst) < 2 {
+                    msg! { ctx.recv().await?,
+                        _msg: Arc<SignedMessage> => {
+                            pings.fetch_add(1, Ordering::SeqCst);
+                        };
+                        _: _ => ();
+                    }
+                }
+
+                let unsubscribed =
+                    vec![ctx.unsubscribe(dispatcher()), ctx.unsubscribe(dispatcher())];
+                results.lock().unwrap().extend(unsubscribed);
+
+                // ...but these are all for the group's member.
+                ping_group(&ctx, 4);
+                count_pings(&ctx, &pings).await
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(300));
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec![true, false, false, true, false]
+    );
+    assert_eq!(subscriber_pings.load(Ordering::SeqCst), 2);
+    assert_eq!(member_pings.load(Ordering::SeqCst), 6);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,58 @@
+use bastion::prelude::*;
+use futures_timer::Delay;
+use std::time::Duration;
+
+fn answering_children(handling_time: Duration, drain_timeout: Duration) -> ChildRef {
+    let children = Bastion::children(|children| {
+        children.with_drain_timeout(drain_timeout).with_exec(
+            move |ctx: BastionContext| async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        n: usize =!> {
+                            Delay::new(handling_time).await;
+                            answer!(ctx, n).expect("couldn't reply");
+                        };
+                        _: _ => ();
+                    }
+                }
+            },
+        )
+    })
+    .expect("Couldn't create the children group.");
+
+    children.elems()[0].clone()
+}
+
+#[test]
+fn drain_on_stop() {
+    Bastion::init();
+    Bastion::start();
+
+    // Those have enough time to answer every message before being stopped.
+    let drained = answering_children(Duration::from_millis(10), Duration::from_secs(5));
+    let answers = (0..5usize)
+        .map(|n| drained.ask_anonymously(n).unwrap())
+        .collect::<Vec<_>>();
+    drained.stop().unwrap();
+
+    for (n, answer) in answers.into_iter().enumerate() {
+        msg! { run!(answer).expect("Couldn't receive the answer."),
+            answered: usize => assert_eq!(answered, n);
+            _: _ => panic!("Unexpected answer.");
+        }
+    }
+
+    // Those don't, so their asks must resolve to an error instead of hanging.
+    let timed_out = answering_children(Duration::from_secs(1), Duration::from_millis(100));
+    let answers = (0..3usize)
+        .map(|n| timed_out.ask_anonymously(n).unwrap())
+        .collect::<Vec<_>>();
+    timed_out.stop().unwrap();
+
+    for answer in answers {
+        assert!(run!(answer).is_err());
+    }
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,83 @@
+use bastion::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// Creates a group whose child fails during its first `failures` runs.
+fn failing_children(children: Children, runs: &Arc<AtomicUsize>, failures: usize) -> Children {
+    let runs = runs.clone();
+    children.with_exec(move |ctx: BastionContext| {
+        let runs = runs.clone();
+        async move {
+            if runs.fetch_add(1, Ordering::SeqCst) < failures {
+                return Err(());
+            }
+
+            loop {
+                ctx.recv().await?;
+            }
+        }
+    })
+}
+
+fn escalates_to_parent() {
+    let (runs, sibling_runs) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let (exec_runs, exec_sibling_runs) = (runs.clone(), sibling_runs.clone());
+    Bastion::supervisor(|parent| {
+        parent.supervisor(|sp| {
+            sp.with_restart_intensity(1, Duration::from_secs(60))
+                .with_escalation_policy(EscalationPolicy::Escalate)
+                .children(|children| failing_children(children, &exec_sibling_runs, 0))
+                .children(|children| failing_children(children, &exec_runs, 3))
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    thread::sleep(Duration::from_secs(1));
+
+    // The first run and its restart, then the runs of the restarted
+    // supervisor, which can restart the child once again.
+    assert_eq!(runs.load(Ordering::SeqCst), 4);
+    // The sibling was restarted along with the failed supervisor.
+    assert_eq!(sibling_runs.load(Ordering::SeqCst), 2);
+}
+
+fn stops_at_root() {
+    let runtime = Runtime::builder().build();
+    runtime.start();
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let exec_runs = runs.clone();
+    runtime
+        .supervisor(|sp| {
+            sp.with_restart_intensity(1, Duration::from_secs(60))
+                .with_escalation_policy(EscalationPolicy::Escalate)
+                .children(|children| failing_children(children, &exec_runs, usize::MAX))
+        })
+        .expect("Couldn't create the supervisor.");
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    let waiter_stopped = stopped.clone();
+    thread::spawn(move || {
+        runtime.block_until_stopped();
+        waiter_stopped.store(true, Ordering::SeqCst);
+    });
+
+    thread::sleep(Duration::from_secs(1));
+    assert!(stopped.load(Ordering::SeqCst));
+    // The escalated failure wasn't restarted.
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn escalation() {
+    Bastion::init();
+    Bastion::start();
+
+    escalates_to_parent();
+    stops_at_root();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
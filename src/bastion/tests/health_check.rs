@@ -0,0 +1,137 @@
+use bastion::prelude::*;
+use futures::future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn wait_for(count: &AtomicUsize, expected: usize) {
+    for _ in 0..100 {
+        if count.load(Ordering::SeqCst) >= expected {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(count.load(Ordering::SeqCst) >= expected);
+}
+
+// Spawns a child answering pings until it gets wedged, and whose
+// health check asks it for a ping.
+fn spawn_wedgeable(started: Arc<AtomicUsize>) -> ChildrenRef {
+    Bastion::children(|children| {
+        children
+            .with_heartbeat_tick(Duration::from_millis(50))
+            .with_health_check(|child: ChildRef| async move {
+                let answer = child.ask_anonymously("ping").map_err(|_| ())?;
+                answer.await.map(|_| ())
+            })
+            .with_exec(move |ctx: BastionContext| {
+                started.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    loop {
+                        msg! { ctx.recv().await?,
+                            _ping: &'static str =!> {
+                                answer!(ctx, "pong").unwrap();
+                            };
+                            _wedge: u8 => {
+                                // Waits for something that never happens.
+                                future::pending::<()>().await;
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.")
+}
+
+fn restarts_stuck_children() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let children = spawn_wedgeable(started.clone());
+    wait_for(&started, 1);
+
+    // Healthy children are left alone.
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(started.load(Ordering::SeqCst), 1);
+
+    children.elems()[0].tell_anonymously(0u8).unwrap();
+    wait_for(&started, 2);
+
+    // The restarted child is healthy again.
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(started.load(Ordering::SeqCst), 2);
+}
+
+fn restarts_unhealthy_children() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let child_started = started.clone();
+    Bastion::children(|children| {
+        children
+            .with_heartbeat_tick(Duration::from_millis(50))
+            .with_health_check(|_child: ChildRef| async { Err(()) })
+            .with_exec(move |ctx: BastionContext| {
+                child_started.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    loop {
+                        ctx.recv().await?;
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    wait_for(&started, 2);
+}
+
+// Counts how many hung health checks were dropped.
+struct Hung(Arc<AtomicUsize>);
+
+impl Drop for Hung {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn cancels_hung_checks() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let (child_started, check_dropped) = (started.clone(), dropped.clone());
+    Bastion::children(|children| {
+        children
+            .with_heartbeat_tick(Duration::from_millis(50))
+            .with_health_check(move |_child: ChildRef| {
+                let hung = Hung(check_dropped.clone());
+                async move {
+                    let _hung = hung;
+                    future::pending::<Result<(), ()>>().await
+                }
+            })
+            .with_exec(move |ctx: BastionContext| {
+                child_started.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    loop {
+                        ctx.recv().await?;
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    // The check that hung is cancelled when the child is restarted.
+    wait_for(&started, 2);
+    wait_for(&dropped, 1);
+}
+
+#[test]
+fn health_check() {
+    Bastion::init();
+    Bastion::start();
+
+    restarts_stuck_children();
+    restarts_unhealthy_children();
+    cancels_hung_checks();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,94 @@
+use bastion::prelude::*;
+use futures::future::BoxFuture;
+use futures_timer::Delay;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Default)]
+struct Shared {
+    // The messages handled, along with the behavior that handled them.
+    log: Mutex<Vec<(&'static str, &'static str)>>,
+    // The child's current incarnation.
+    current: Mutex<Option<ChildRef>>,
+}
+
+impl Shared {
+    fn current(&self) -> ChildRef {
+        self.current.lock().unwrap().clone().unwrap()
+    }
+}
+
+// Returns a behavior logging the messages it handles as `version`,
+// which takes a while to handle "slow" and fails on "fail".
+fn behavior(
+    version: &'static str,
+    shared: &Arc<Shared>,
+) -> impl Fn(BastionContext) -> BoxFuture<'static, Result<(), ()>> {
+    let shared = shared.clone();
+    move |ctx: BastionContext| {
+        let shared = shared.clone();
+        Box::pin(async move {
+            *shared.current.lock().unwrap() = Some(ctx.current().clone());
+            loop {
+                msg! { ctx.recv().await?,
+                    msg: &'static str => {
+                        if msg == "slow" {
+                            Delay::new(Duration::from_millis(50)).await;
+                        }
+                        shared.log.lock().unwrap().push((version, msg));
+                        if msg == "fail" {
+                            return Err(());
+                        }
+                    };
+                    _: _ => ();
+                }
+            }
+        })
+    }
+}
+
+#[test]
+fn hot_swap() {
+    Bastion::init();
+    Bastion::start();
+
+    let shared: Arc<Shared> = Arc::default();
+    let v1 = behavior("v1", &shared);
+    Bastion::children(|children| children.with_exec(v1))
+        .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(100));
+
+    // The message being handled while the swap is requested is
+    // still handled by the previous behavior, and the messages
+    // received meanwhile are kept for the new one.
+    let child = shared.current();
+    child.tell_anonymously("slow").unwrap();
+    thread::sleep(Duration::from_millis(10));
+    assert!(child.hot_swap(behavior("v2", &shared)));
+    child.tell_anonymously("first").unwrap();
+    child.tell_anonymously("fail").unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    // The restarted child keeps its new behavior.
+    let restarted = shared.current();
+    assert_eq!(restarted.id(), child.id());
+    restarted.tell_anonymously("second").unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(
+        *shared.log.lock().unwrap(),
+        vec![
+            ("v1", "slow"),
+            ("v2", "first"),
+            ("v2", "fail"),
+            ("v2", "second"),
+        ]
+    );
+
+    // A stopped child can't get swapped anymore.
+    assert!(!child.hot_swap(behavior("v3", &shared)));
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,138 @@
+use bastion::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Returns the kinds of the received events which happened to the
+// element with the given identifier.
+fn kinds_of(events: &LifecycleEvents, id: &BastionId) -> Vec<LifecycleEventKind> {
+    std::iter::from_fn(|| events.try_recv())
+        .filter(|event| event.path.id() == id)
+        .map(|event| event.kind)
+        .collect()
+}
+
+// Makes the children group's child fail (by panicking on the first
+// time) the first `failures` times it runs, setting `id` to the
+// child's identifier.
+fn failing(children: Children, failures: usize, id: &Arc<Mutex<Option<BastionId>>>) -> Children {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let id = id.clone();
+    children.with_exec(move |ctx: BastionContext| {
+        let runs = runs.clone();
+        *id.lock().unwrap() = Some(ctx.current().id().clone());
+        async move {
+            match runs.fetch_add(1, Ordering::SeqCst) {
+                0 if failures > 0 => panic!("boom"),
+                run if run < failures => Err(()),
+                _ => Ok(()),
+            }
+        }
+    })
+}
+
+#[test]
+fn restarts_and_stops() {
+    let runtime = Runtime::builder().with_deterministic_seed(0).build();
+    let events = runtime.lifecycle_events();
+    runtime.start();
+
+    let id = Arc::default();
+    let child_id = Arc::clone(&id);
+    runtime
+        .supervisor(move |sp| sp.children(|children| failing(children, 2, &child_id)))
+        .expect("Couldn't create the supervisor.");
+    runtime.run_until_idle();
+
+    let restarted = |restart_count, reason| LifecycleEventKind::Restarted {
+        restart_count,
+        reason,
+    };
+    let id = id.lock().unwrap().clone().unwrap();
+    assert_eq!(
+        kinds_of(&events, &id),
+        vec![
+            LifecycleEventKind::Started,
+            restarted(1, RestartReason::Panicked(Some("boom".to_string()))),
+            LifecycleEventKind::Started,
+            restarted(2, RestartReason::Faulted),
+            LifecycleEventKind::Started,
+            LifecycleEventKind::Stopped,
+        ]
+    );
+    assert_eq!(events.dropped(), 0);
+
+    runtime.shutdown();
+}
+
+#[test]
+fn escalates() {
+    let runtime = Runtime::builder().with_deterministic_seed(0).build();
+    let events = runtime.lifecycle_events();
+    runtime.start();
+
+    // The supervisor can't restart its child, so its own supervisor
+    // restarts it instead.
+    let child_id = Arc::default();
+    runtime
+        .supervisor(move |parent| {
+            parent.supervisor(|sp| {
+                sp.with_restart_strategy(
+                    RestartStrategy::default().with_restart_policy(RestartPolicy::Never),
+                )
+                .with_escalation_policy(EscalationPolicy::Escalate)
+                .children(|children| failing(children, 1, &child_id))
+            })
+        })
+        .expect("Couldn't create the supervisor.");
+    runtime.run_until_idle();
+
+    let events: Vec<_> = std::iter::from_fn(|| events.try_recv())
+        .filter(|event| {
+            let supervisor = event.path.elem().as_ref();
+            supervisor.is_some_and(BastionPathElement::is_supervisor)
+        })
+        .map(|event| event.kind)
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            LifecycleEventKind::Escalated,
+            LifecycleEventKind::Failed,
+            LifecycleEventKind::Restarted {
+                restart_count: 1,
+                reason: RestartReason::Faulted,
+            },
+        ]
+    );
+
+    runtime.shutdown();
+}
+
+#[test]
+fn drops_oldest_events() {
+    let runtime = Runtime::builder().with_deterministic_seed(0).build();
+    runtime.start();
+
+    let id = Arc::default();
+    let child_id = Arc::clone(&id);
+    let events = runtime.lifecycle_events();
+    runtime
+        .supervisor(move |sp| sp.children(|children| failing(children, 1000, &child_id)))
+        .expect("Couldn't create the supervisor.");
+    runtime.run_until_idle();
+    let dropped = events.dropped();
+
+    // Each failure is followed by a restart and a start, and the
+    // receiver only kept the last events.
+    let id = id.lock().unwrap().clone().unwrap();
+    let events: Vec<_> = std::iter::from_fn(|| events.try_recv()).collect();
+    assert_eq!(events.len(), 1024);
+    assert!(dropped >= 1 + 2 * 1000 + 1 - 1024);
+    let last = events.last().unwrap();
+    assert_eq!(
+        (last.path.id(), &last.kind),
+        (&id, &LifecycleEventKind::Stopped)
+    );
+
+    runtime.shutdown();
+}
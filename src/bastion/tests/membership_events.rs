@@ -0,0 +1,118 @@
+use bastion::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const WORKERS: &str = "membership_workers";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    Joined(BastionId),
+    Left(BastionId, LeaveReason),
+}
+
+// Asks the watcher to send a message to one of the workers.
+#[derive(Debug)]
+struct Poke(usize, &'static str);
+
+fn spawn_workers() {
+    Bastion::supervisor(|sp| {
+        sp.children(|children| {
+            children
+                .with_redundancy(2)
+                .with_dispatcher(Dispatcher::with_type(DispatcherType::Named(
+                    WORKERS.to_string(),
+                )))
+                .with_exec(|ctx: BastionContext| async move {
+                    loop {
+                        msg! { ctx.recv().await?,
+                            msg: &'static str => {
+                                match msg {
+                                    "fail" => return Err(()),
+                                    "done" => return Ok(()),
+                                    _ => (),
+                                }
+                            };
+                            _: _ => ();
+                        }
+                    }
+                })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+    thread::sleep(Duration::from_millis(100));
+}
+
+fn watch_workers(changes: &Arc<Mutex<Vec<Change>>>) -> ChildRef {
+    let changes = changes.clone();
+    let watcher = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let changes = changes.clone();
+            async move {
+                assert!(ctx.watch_members(DispatcherType::Named(WORKERS.to_string())));
+                let mut members: Vec<RefAddr> = Vec::new();
+                loop {
+                    msg! { ctx.recv().await?,
+                        event: MembershipEvent => {
+                            let change = match event {
+                                MembershipEvent::MemberJoined { member, .. } => {
+                                    let id = member.path().id().clone();
+                                    if members.iter().all(|addr| addr.path().id() != &id) {
+                                        members.push(member);
+                                    }
+                                    Change::Joined(id)
+                                }
+                                MembershipEvent::MemberLeft { member, reason, .. } => {
+                                    Change::Left(member.path().id().clone(), reason)
+                                }
+                            };
+                            changes.lock().unwrap().push(change);
+                        };
+                        poke: Poke => {
+                            ctx.tell(&members[poke.0], poke.1).ok();
+                        };
+                        _: _ => ();
+                    }
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(100));
+
+    watcher.elems()[0].clone()
+}
+
+#[test]
+fn membership_events() {
+    Bastion::init();
+    Bastion::start();
+
+    spawn_workers();
+    let changes: Arc<Mutex<Vec<Change>>> = Arc::default();
+    let watcher = watch_workers(&changes);
+
+    // The current members are told first.
+    let (first, second) = match changes.lock().unwrap().as_slice() {
+        [Change::Joined(first), Change::Joined(second)] => (first.clone(), second.clone()),
+        changes => panic!("unexpected changes: {:?}", changes),
+    };
+
+    // A faulted member leaves before its restarted incarnation joins.
+    watcher.tell_anonymously(Poke(0, "fail")).unwrap();
+    thread::sleep(Duration::from_millis(200));
+    watcher.tell_anonymously(Poke(1, "done")).unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    assert_eq!(
+        changes.lock().unwrap()[2..],
+        [
+            Change::Left(first.clone(), LeaveReason::Faulted),
+            Change::Joined(first),
+            Change::Left(second, LeaveReason::Stopped),
+        ]
+    );
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
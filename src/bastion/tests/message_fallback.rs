@@ -0,0 +1,59 @@
+use bastion::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct Unexpected;
+
+#[test]
+fn fallback_gets_type_name() {
+    Bastion::init();
+    Bastion::start();
+
+    let unhandled = Arc::new(Mutex::new(Vec::new()));
+    let responder_unhandled = unhandled.clone();
+    let responders = Bastion::children(|children: Children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let unhandled = responder_unhandled.clone();
+            async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        msg: &'static str =!> {
+                            answer!(ctx, msg).unwrap();
+                        };
+                        msg: _ => {
+                            let type_name = msg.type_name();
+                            unhandled.lock().unwrap().push(type_name);
+                            // Told messages can't be answered.
+                            msg.answer(type_name, ctx.signature()).ok();
+                        };
+                    }
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    let responder = &responders.elems()[0];
+
+    let answer = run!(responder.ask_anonymously(Unexpected).unwrap()).unwrap();
+    msg! { answer,
+        type_name: &'static str => assert!(type_name.ends_with("Unexpected"));
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    responder.tell_anonymously(42u8).unwrap();
+
+    // The answer to an expected message isn't affected.
+    let answer = run!(responder.ask_anonymously("hello").unwrap()).unwrap();
+    msg! { answer,
+        msg: &'static str => assert_eq!(msg, "hello");
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    let unhandled = unhandled.lock().unwrap();
+    assert_eq!(unhandled.len(), 2);
+    assert!(unhandled[0].ends_with("Unexpected"));
+    assert_eq!(unhandled[1], "u8");
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
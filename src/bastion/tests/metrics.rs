@@ -0,0 +1,64 @@
+#![cfg(feature = "metrics")]
+use bastion::prelude::*;
+use std::thread;
+use std::time::Duration;
+
+fn sample(text: &str, name: &str, labels: &str) -> Option<u64> {
+    let prefix = format!("{}{} ", name, labels);
+    text.lines()
+        .find(|line| line.starts_with(&prefix))
+        .and_then(|line| line[prefix.len()..].parse().ok())
+}
+
+#[test]
+fn metrics() {
+    Bastion::init();
+    Bastion::start();
+
+    let children = Bastion::children(|children| {
+        children
+            .with_name("metrics \"test\"")
+            .with_exec(|ctx: BastionContext| async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        _msg: &'static str =!> {
+                            answer!(ctx, "pong").unwrap();
+                        };
+                        _: _ => return Err(());
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+    let labels = format!("{{id=\"{}\",name=\"metrics \\\"test\\\"\"}}", children.id());
+
+    for _ in 0..3 {
+        let child = &children.elems()[0];
+        run!(child.ask_anonymously("ping").unwrap()).unwrap();
+    }
+    // Makes the element fail and be restarted.
+    children.elems()[0].tell_anonymously(0u8).unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    let text = bastion::metrics::gather();
+    assert!(text.contains("# TYPE bastion_children_messages_total counter"));
+    assert_eq!(
+        sample(&text, "bastion_children_messages_total", &labels),
+        Some(4)
+    );
+    assert_eq!(
+        sample(&text, "bastion_children_restarts_total", &labels),
+        Some(1)
+    );
+    assert!(sample(&text, "bastion_executor_workers", "").unwrap() > 0);
+    assert!(sample(&text, "bastion_executor_procs_spawned_total", "").unwrap() > 0);
+    assert!(sample(
+        &text,
+        "bastion_executor_queue_depth",
+        "{priority=\"normal\"}"
+    )
+    .is_some());
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,76 @@
+use bastion::prelude::*;
+use futures_timer::Delay;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn wait_for(count: &AtomicUsize, expected: usize) {
+    for _ in 0..100 {
+        if count.load(Ordering::SeqCst) == expected {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(count.load(Ordering::SeqCst), expected);
+}
+
+#[test]
+fn pause_resume() {
+    Bastion::init();
+    Bastion::start();
+
+    let started = Arc::new(AtomicUsize::new(0));
+    let received = Arc::new(AtomicUsize::new(0));
+    let crash = Arc::new(AtomicBool::new(false));
+    let (child_started, child_received, child_crash) =
+        (started.clone(), received.clone(), crash.clone());
+    let children = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let received = child_received.clone();
+            let crash = child_crash.clone();
+            child_started.fetch_add(1, Ordering::SeqCst);
+            async move {
+                loop {
+                    if ctx.try_recv().await.is_some() {
+                        received.fetch_add(1, Ordering::SeqCst);
+                    } else if crash.swap(false, Ordering::SeqCst) {
+                        panic!("crashing while paused");
+                    } else {
+                        Delay::new(Duration::from_millis(10)).await;
+                    }
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    wait_for(&started, 1);
+
+    assert_eq!(children.pause(), Ok(()));
+    assert_eq!(children.pause(), Ok(()));
+    assert!(children.is_paused());
+    for i in 0..3u8 {
+        children.broadcast(i).unwrap();
+    }
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(received.load(Ordering::SeqCst), 0);
+
+    assert_eq!(children.resume(), Ok(()));
+    assert_eq!(children.resume(), Ok(()));
+    assert!(!children.is_paused());
+    wait_for(&received, 3);
+
+    // A paused child is still supervised.
+    children.pause().unwrap();
+    crash.store(true, Ordering::SeqCst);
+    wait_for(&started, 2);
+    assert!(children.is_paused());
+
+    children.stop().unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(children.pause(), Err(PauseError::Stopped));
+    assert_eq!(children.resume(), Err(PauseError::Stopped));
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
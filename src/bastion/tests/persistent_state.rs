@@ -0,0 +1,82 @@
+use bastion::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// Sets the flag once dropped, telling that the state holding it was.
+#[derive(Debug)]
+struct DropGuard(Arc<AtomicBool>);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+fn survives_restarts() {
+    let runs: Arc<Mutex<Vec<Option<u32>>>> = Arc::default();
+    let child_runs = runs.clone();
+    Bastion::supervisor(|sp| {
+        sp.children(|children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let runs = child_runs.clone();
+                async move {
+                    let seq = ctx.take_persistent_state::<u32>();
+                    runs.lock().unwrap().push(seq);
+
+                    let seq = seq.unwrap_or(0) + 1;
+                    ctx.set_persistent_state(seq);
+                    if seq < 3 {
+                        panic!("restarting");
+                    }
+
+                    loop {
+                        ctx.recv().await?;
+                    }
+                }
+            })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(*runs.lock().unwrap(), vec![None, Some(1), Some(2)]);
+}
+
+fn dropped_on_stop() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let child_dropped = dropped.clone();
+    let children = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let dropped = child_dropped.clone();
+            async move {
+                ctx.set_persistent_state(DropGuard(dropped));
+
+                loop {
+                    ctx.recv().await?;
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    let child = &children.elems()[0];
+
+    thread::sleep(Duration::from_millis(100));
+    assert!(!dropped.load(Ordering::SeqCst));
+
+    run!(child.stop_and_wait()).unwrap();
+    assert!(dropped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn persistent_state() {
+    Bastion::init();
+    Bastion::start();
+
+    survives_restarts();
+    dropped_on_stop();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
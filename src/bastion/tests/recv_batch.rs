@@ -0,0 +1,75 @@
+use bastion::prelude::*;
+use bastion::time::Delay;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn contents(batch: Vec<SignedMessage>) -> Vec<&'static str> {
+    batch
+        .into_iter()
+        .map(|msg| {
+            msg! { msg,
+                msg: &'static str => msg;
+                _: _ => panic!("unexpected message");
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn recv_batch() {
+    let runtime = Runtime::builder().with_deterministic_seed(0).build();
+    runtime.start();
+
+    let batches: Arc<Mutex<Vec<Vec<&'static str>>>> = Arc::default();
+    let child: Arc<Mutex<Option<ChildRef>>> = Arc::default();
+    let (exec_batches, exec_child) = (batches.clone(), child.clone());
+    runtime
+        .children(move |children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let (batches, child) = (exec_batches.clone(), exec_child.clone());
+                async move {
+                    *child.lock().unwrap() = Some(ctx.current().clone());
+                    let record = |batch| batches.lock().unwrap().push(contents(batch));
+
+                    for msg in &["first", "second", "third"] {
+                        ctx.tell(&ctx.signature(), *msg).unwrap();
+                    }
+                    ctx.tell_priority(&ctx.signature(), "urgent").unwrap();
+                    Delay::new(Duration::from_millis(10)).await;
+
+                    // The batches don't wait to be filled, and priority
+                    // messages come first.
+                    record(ctx.try_recv_batch(2).await);
+                    record(ctx.recv_batch(100).await);
+                    record(ctx.try_recv_batch(100).await);
+                    record(ctx.recv_batch(0).await);
+
+                    // The mailbox is empty, so it waits for a message.
+                    record(ctx.recv_batch(100).await);
+
+                    Ok(())
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+
+    runtime.run_until_idle();
+    runtime.advance_time(Duration::from_millis(10));
+    runtime.run_until_idle();
+    assert_eq!(batches.lock().unwrap().len(), 4);
+
+    let child = child.lock().unwrap().clone().unwrap();
+    child.tell_anonymously("late").unwrap();
+    runtime.run_until_idle();
+
+    assert_eq!(
+        *batches.lock().unwrap(),
+        vec![
+            vec!["urgent", "first"],
+            vec!["second", "third"],
+            vec![],
+            vec![],
+            vec!["late"],
+        ]
+    );
+}
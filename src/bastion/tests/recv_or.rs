@@ -0,0 +1,78 @@
+use bastion::prelude::*;
+use futures::future;
+use futures_timer::Delay;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Message(&'static str),
+    Completed(&'static str),
+}
+
+fn outcome(received: RecvOr<&'static str>) -> Outcome {
+    match received {
+        RecvOr::Message(msg) => msg! { msg,
+            msg: &'static str => Outcome::Message(msg);
+            _: _ => panic!("unexpected message");
+        },
+        RecvOr::Completed(output) => Outcome::Completed(output),
+    }
+}
+
+#[test]
+fn recv_or() {
+    Bastion::init();
+    Bastion::start();
+
+    let outcomes: Arc<Mutex<Vec<Outcome>>> = Arc::default();
+    let exec_outcomes = outcomes.clone();
+    Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let outcomes = exec_outcomes.clone();
+            async move {
+                let record = |received| outcomes.lock().unwrap().push(outcome(received));
+
+                // The future completes while the mailbox is empty.
+                let tick = async {
+                    Delay::new(Duration::from_millis(10)).await;
+                    "tick"
+                };
+                record(ctx.recv_or(tick).await);
+
+                // The future completes while a message is queued,
+                // which is kept for the next receive.
+                ctx.tell(&ctx.signature(), "kept").unwrap();
+                Delay::new(Duration::from_millis(10)).await;
+                record(ctx.recv_or(future::ready("ready")).await);
+                record(ctx.recv_or(future::pending()).await);
+
+                // A message is received before the future completes.
+                ctx.tell(&ctx.signature(), "first").unwrap();
+                let late = async {
+                    Delay::new(Duration::from_secs(10)).await;
+                    "late"
+                };
+                record(ctx.recv_or(late).await);
+
+                Ok(())
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(200));
+
+    assert_eq!(
+        *outcomes.lock().unwrap(),
+        vec![
+            Outcome::Completed("tick"),
+            Outcome::Completed("ready"),
+            Outcome::Message("kept"),
+            Outcome::Message("first"),
+        ]
+    );
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,90 @@
+use bastion::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+// A group of one child which panics once it receives a message, or
+// when it runs for the `panics_on_run`th time.
+fn group(
+    children: Children,
+    name: &'static str,
+    panics_on_run: Option<usize>,
+    log: &Log,
+    current: &Arc<Mutex<Option<ChildRef>>>,
+) -> Children {
+    let (stop_log, start_log) = (log.clone(), log.clone());
+    let callbacks = Callbacks::new()
+        .with_before_restart(move || stop_log.lock().unwrap().push(format!("stop {}", name)))
+        .with_after_restart(move || start_log.lock().unwrap().push(format!("start {}", name)));
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let current = current.clone();
+    children
+        .with_callbacks(callbacks)
+        .with_exec(move |ctx: BastionContext| {
+            let (runs, current) = (runs.clone(), current.clone());
+            async move {
+                *current.lock().unwrap() = Some(ctx.current().clone());
+                let run = runs.fetch_add(1, Ordering::SeqCst) + 1;
+                if Some(run) == panics_on_run {
+                    panic!("{} failed to restart", name);
+                }
+
+                ctx.recv().await?;
+                panic!("{} failed", name);
+            }
+        })
+}
+
+// Starts four groups under a `RestForOne` supervisor, with the third
+// one panicking again when it's restarted if `panics_again`, makes the
+// second one fail and returns the callbacks called meanwhile.
+fn restarts(panics_again: bool) -> Vec<String> {
+    let runtime = Runtime::builder().with_deterministic_seed(0).build();
+    runtime.start();
+
+    let log: Log = Arc::default();
+    let current: Vec<Arc<Mutex<Option<ChildRef>>>> = (0..4).map(|_| Arc::default()).collect();
+    let (sp_log, sp_current) = (log.clone(), current.clone());
+    runtime
+        .supervisor(move |sp| {
+            let third_panics = if panics_again { Some(2) } else { None };
+            sp.with_strategy(SupervisionStrategy::RestForOne)
+                .children(|children| group(children, "A", None, &sp_log, &sp_current[0]))
+                .children(|children| group(children, "B", None, &sp_log, &sp_current[1]))
+                .children(|children| group(children, "C", third_panics, &sp_log, &sp_current[2]))
+                .children(|children| group(children, "D", None, &sp_log, &sp_current[3]))
+        })
+        .expect("Couldn't create the supervisor.");
+    runtime.run_until_idle();
+    assert!(current.iter().all(|child| child.lock().unwrap().is_some()));
+    assert!(log.lock().unwrap().is_empty());
+
+    let failing = current[1].lock().unwrap().clone().unwrap();
+    failing.tell_anonymously("fail").unwrap();
+    runtime.run_until_idle();
+
+    let log = log.lock().unwrap().clone();
+    log
+}
+
+#[test]
+fn restarts_in_start_order() {
+    // The first group started before the failed one, so it's left
+    // running.
+    assert_eq!(
+        restarts(false),
+        vec!["stop D", "stop C", "start B", "start C", "start D"]
+    );
+}
+
+#[test]
+fn restarts_again_on_failed_restart() {
+    // The third group's failure is handled once the supervisor is
+    // done restarting the fourth one.
+    assert_eq!(
+        restarts(true),
+        vec!["stop D", "stop C", "start B", "start C", "start D", "stop D", "start C", "start D",]
+    );
+}
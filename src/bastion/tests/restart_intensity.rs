@@ -0,0 +1,38 @@
+use bastion::prelude::*;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn stops_child_after_exceeding_restart_intensity() {
+    Bastion::init();
+    Bastion::start();
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let child_runs = runs.clone();
+
+    Bastion::supervisor(|sp| {
+        sp.with_restart_intensity(2, Duration::from_secs(60))
+            .children(|children| {
+                children.with_exec(move |_: BastionContext| {
+                    let runs = child_runs.clone();
+                    async move {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        Err(())
+                    }
+                })
+            })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    thread::sleep(Duration::from_secs(1));
+
+    // The first run, followed by the two allowed restarts.
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -1,5 +1,9 @@
-use bastion::supervisor::{ActorRestartStrategy, RestartPolicy, RestartStrategy};
-use std::time::Duration;
+use bastion::prelude::*;
+use bastion::supervisor::{ActorRestartStrategy, JitterKind, RestartPolicy, RestartStrategy};
+use bastion::time;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[test]
 fn check_default_values() {
@@ -7,6 +11,8 @@ fn check_default_values() {
 
     assert_eq!(restart_strategy.restart_policy(), RestartPolicy::Always);
     assert_eq!(restart_strategy.strategy(), ActorRestartStrategy::Immediate);
+    assert_eq!(restart_strategy.max_delay(), None);
+    assert_eq!(restart_strategy.jitter(), None);
 }
 
 #[test]
@@ -101,3 +107,90 @@ fn calculate_exp_strategy_with_multiplier_non_zero() {
         Some(Duration::from_millis(100 + 99 * 5 * 100))
     );
 }
+
+// Returns the delays waited for before each restart of two
+// children always failing, supervised with the same strategy.
+fn restart_delays(restart_strategy: RestartStrategy) -> Vec<Vec<Duration>> {
+    let runtime = Runtime::builder().with_deterministic_seed(3).build();
+    runtime.start();
+
+    let starts: Arc<Mutex<HashMap<BastionId, Vec<Instant>>>> = Arc::default();
+    for _ in 0..2 {
+        let restart_strategy = restart_strategy.clone();
+        let starts = starts.clone();
+        runtime
+            .supervisor(move |sp| {
+                sp.with_restart_strategy(restart_strategy)
+                    .children(move |children| {
+                        children.with_exec(move |ctx: BastionContext| {
+                            let starts = starts.clone();
+                            async move {
+                                let mut starts = starts.lock().unwrap();
+                                let id = ctx.current().id().clone();
+                                starts.entry(id).or_default().push(time::now());
+                                Err(())
+                            }
+                        })
+                    })
+            })
+            .expect("Couldn't create the supervisor.");
+    }
+    runtime.run_until_idle();
+    for _ in 0..40 {
+        runtime.advance_time(Duration::from_secs(1));
+    }
+    runtime.shutdown();
+
+    let starts = starts.lock().unwrap();
+    let mut delays: Vec<Vec<Duration>> = starts
+        .values()
+        .map(|starts| starts.windows(2).map(|w| w[1] - w[0]).collect())
+        .collect();
+    delays.sort();
+    delays
+}
+
+fn backoff() -> RestartStrategy {
+    RestartStrategy::default()
+        .with_actor_restart_strategy(ActorRestartStrategy::ExponentialBackOff {
+            timeout: Duration::from_secs(1),
+            multiplier: 1.0,
+        })
+        .with_max_delay(Duration::from_secs(4))
+}
+
+#[test]
+fn capped_delays() {
+    let secs = |secs: &[u64]| secs.iter().copied().map(Duration::from_secs).collect();
+    let delays = restart_delays(backoff());
+
+    let expected: Vec<Duration> = secs(&[1, 2, 3, 4, 4, 4, 4, 4, 4, 4, 4]);
+    assert_eq!(delays.len(), 2);
+    for delays in delays {
+        assert_eq!(delays[..expected.len()], expected[..]);
+    }
+}
+
+#[test]
+fn jittered_delays() {
+    for jitter in [JitterKind::Full, JitterKind::Decorrelated] {
+        let delays = restart_delays(backoff().with_jitter(jitter));
+
+        // The children restart at different times, without ever
+        // waiting for longer than the cap.
+        assert_eq!(delays.len(), 2);
+        assert_ne!(delays[0], delays[1]);
+        for delay in delays.iter().flatten() {
+            assert!(
+                *delay <= Duration::from_secs(4),
+                "{:?}: {:?}",
+                jitter,
+                delay
+            );
+        }
+        if jitter == JitterKind::Decorrelated {
+            let min = Duration::from_secs(1);
+            assert!(delays.iter().flatten().all(|delay| *delay >= min));
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use bastion::prelude::*;
+use bastion_executor::pool::{self, PoolConfig};
+use futures_timer::Delay;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const MEMBERS: &str = "round_robin_prune";
+const GONE: &str = "round_robin_prune_gone";
+const FULL: &str = "round_robin_prune_full";
+const SENT: usize = 400;
+
+fn dispatcher(name: &str) -> Dispatcher {
+    Dispatcher::with_type(DispatcherType::Named(name.to_string()))
+}
+
+fn hammer_dying_members(letters: &Arc<Mutex<Vec<DeadLetterReason>>>) {
+    let received = Arc::new(AtomicUsize::new(0));
+    let exec_received = received.clone();
+    Bastion::supervisor(|sp| {
+        sp.children(|children| {
+            children
+                .with_redundancy(4)
+                .with_dispatcher(dispatcher(MEMBERS))
+                .with_exec(move |ctx: BastionContext| {
+                    let received = exec_received.clone();
+                    async move {
+                        // Fails after a few messages, to get restarted.
+                        let lifetime = 3 + received.load(Ordering::SeqCst) % 11;
+                        for _ in 0..lifetime {
+                            msg! { ctx.recv().await?,
+                                _msg: Arc<SignedMessage> => {
+                                    received.fetch_add(1, Ordering::SeqCst);
+                                };
+                                _: _ => ();
+                            }
+                        }
+
+                        Err(())
+                    }
+                })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+    thread::sleep(Duration::from_millis(100));
+
+    let failures: Arc<Mutex<Vec<DispatchError>>> = Arc::default();
+    let exec_failures = failures.clone();
+    Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let failures = exec_failures.clone();
+            async move {
+                for i in 0..SENT {
+                    if let Err(err) = ctx.send_to_group(MEMBERS, i) {
+                        failures.lock().unwrap().push(err);
+                    }
+                    Delay::new(Duration::from_millis(1)).await;
+                }
+
+                Ok(())
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_secs(2));
+
+    assert!(failures.lock().unwrap().is_empty());
+    let letters = letters.lock().unwrap();
+    assert!(
+        !letters.contains(&DeadLetterReason::NoRecipient),
+        "{:?}",
+        letters
+    );
+    // Only the messages left in the mailbox of a failing member got lost.
+    let stopped = letters
+        .iter()
+        .filter(|reason| **reason == DeadLetterReason::Stopped)
+        .count();
+    assert_eq!(received.load(Ordering::SeqCst) + stopped, SENT);
+}
+
+fn fails_without_members() {
+    Bastion::children(|children| {
+        children
+            .with_redundancy(2)
+            .with_dispatcher(dispatcher(GONE))
+            .with_exec(|_: BastionContext| async move { Ok(()) })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(100));
+
+    let results: Arc<Mutex<Vec<Result<(), DispatchError>>>> = Arc::default();
+    let exec_results = results.clone();
+    Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let results = exec_results.clone();
+            async move {
+                results
+                    .lock()
+                    .unwrap()
+                    .push(ctx.send_to_group(GONE, "ping"));
+                Ok(())
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec![Err(DispatchError::NoAvailableRecipient)]
+    );
+}
+
+fn skips_full_members() {
+    Bastion::supervisor(|sp| {
+        sp.children(|children| {
+            children
+                .with_redundancy(2)
+                .with_mailbox_capacity(1)
+                .with_dispatcher(dispatcher(FULL))
+                .with_exec(|_: BastionContext| async move {
+                    // Never receives, so that the mailbox stays full.
+                    Delay::new(Duration::from_secs(60)).await;
+                    Ok(())
+                })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+    thread::sleep(Duration::from_millis(100));
+
+    let results: Arc<Mutex<Vec<Result<(), DispatchError>>>> = Arc::default();
+    let exec_results = results.clone();
+    Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let results = exec_results.clone();
+            async move {
+                for _ in 0..3 {
+                    let result = ctx.send_to_group(FULL, "ping");
+                    results.lock().unwrap().push(result);
+                }
+                Ok(())
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(100));
+
+    // Once the first member is full, the second one gets the message.
+    assert_eq!(
+        *results.lock().unwrap(),
+        vec![Ok(()), Ok(()), Err(DispatchError::MailboxFull)]
+    );
+}
+
+#[test]
+fn round_robin_prune() {
+    // Lets the members fail while the group is hammered.
+    pool::configure(PoolConfig::default().with_min_threads(4)).unwrap();
+    Bastion::init();
+    Bastion::start();
+
+    let letters: Arc<Mutex<Vec<DeadLetterReason>>> = Arc::default();
+    let handler_letters = letters.clone();
+    Bastion::on_dead_letter(move |letter| handler_letters.lock().unwrap().push(letter.reason));
+
+    hammer_dying_members(&letters);
+    fails_without_members();
+    skips_full_members();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
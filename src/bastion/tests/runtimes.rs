@@ -0,0 +1,110 @@
+use bastion::prelude::*;
+use bastion_executor::pool::PoolConfig;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+const GROUP: &str = "runtimes";
+
+type Threads = Arc<Mutex<HashSet<ThreadId>>>;
+
+// How many threads that ran children exited.
+static EXITED: AtomicUsize = AtomicUsize::new(0);
+
+struct ExitGuard;
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        EXITED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+thread_local! {
+    static EXIT_GUARD: ExitGuard = const { ExitGuard };
+}
+
+// Creates a group of the runtime which counts the messages dispatched
+// to it and records the threads that run its children.
+fn spawn_members(runtime: &Runtime, threads: &Threads, pings: &Arc<AtomicUsize>) {
+    let (threads, pings) = (threads.clone(), pings.clone());
+    runtime
+        .supervisor(|sp| {
+            sp.children(|children| {
+                children
+                    .with_redundancy(4)
+                    .with_dispatcher(Dispatcher::with_type(DispatcherType::Named(
+                        GROUP.to_string(),
+                    )))
+                    .with_exec(move |ctx: BastionContext| {
+                        let (threads, pings) = (threads.clone(), pings.clone());
+                        async move {
+                            loop {
+                                threads.lock().unwrap().insert(thread::current().id());
+                                EXIT_GUARD.with(|_| ());
+                                msg! { ctx.recv().await?,
+                                    _msg: Arc<SignedMessage> => {
+                                        pings.fetch_add(1, Ordering::SeqCst);
+                                    };
+                                    _: _ => ();
+                                }
+                            }
+                        }
+                    })
+            })
+        })
+        .expect("Couldn't create the supervisor.");
+}
+
+#[test]
+fn runtimes() {
+    let config = PoolConfig::default()
+        .with_min_threads(2)
+        .with_max_threads(2);
+    let runtimes = [
+        Runtime::builder().with_pool_config(config.clone()).build(),
+        Runtime::builder().with_pool_config(config).build(),
+    ];
+
+    let threads: [Threads; 2] = Default::default();
+    let pings: [Arc<AtomicUsize>; 2] = Default::default();
+    for (i, runtime) in runtimes.iter().enumerate() {
+        runtime.start();
+        spawn_members(runtime, &threads[i], &pings[i]);
+    }
+    thread::sleep(Duration::from_millis(100));
+
+    // Dispatched to the group of the first runtime only.
+    runtimes[0]
+        .spawn(|ctx: BastionContext| async move {
+            for _ in 0..8 {
+                ctx.broadcast_message(BroadcastTarget::Group(GROUP.to_string()), "ping");
+            }
+            Ok(())
+        })
+        .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(200));
+
+    assert_eq!(pings[0].load(Ordering::SeqCst), 8);
+    assert_eq!(pings[1].load(Ordering::SeqCst), 0);
+
+    let (first, second) = (threads[0].lock().unwrap(), threads[1].lock().unwrap());
+    assert!(!first.is_empty() && !second.is_empty());
+    assert!(first.is_disjoint(&second), "{:?} {:?}", first, second);
+    let ran = first.len() + second.len();
+    drop((first, second));
+
+    for runtime in runtimes.iter() {
+        runtime.shutdown();
+    }
+
+    // The threads of the runtimes exit once they're shut down.
+    for _ in 0..100 {
+        if EXITED.load(Ordering::SeqCst) == ran {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(EXITED.load(Ordering::SeqCst), ran);
+}
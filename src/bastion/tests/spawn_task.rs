@@ -0,0 +1,54 @@
+use bastion::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn returns_output() {
+    let handle = Bastion::spawn_task(async { 6 * 7 });
+    assert_eq!(run!(handle), Some(42));
+}
+
+fn surfaces_panic() {
+    let handle = Bastion::spawn_task(async {
+        panic!("task failed");
+    });
+    let error: ProcError = run!(handle.join()).unwrap_err();
+    assert!(error.is_panic());
+    assert_eq!(error.panic_message(), Some("task failed"));
+
+    // The panic didn't take down the threads running the tasks.
+    let handle = Bastion::spawn_task(async { "still running" });
+    assert_eq!(run!(handle), Some("still running"));
+}
+
+fn offloaded_by_actor() {
+    let results: Arc<Mutex<Vec<Option<u64>>>> = Arc::default();
+    let exec_results = results.clone();
+    Bastion::children(|children| {
+        children.with_exec(move |_: BastionContext| {
+            let results = exec_results.clone();
+            async move {
+                let sum = Bastion::spawn_task(async { (1..=10).sum::<u64>() }).await;
+                results.lock().unwrap().push(sum);
+                Ok(())
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(200));
+
+    assert_eq!(*results.lock().unwrap(), vec![Some(55)]);
+}
+
+#[test]
+fn spawn_task() {
+    Bastion::init();
+    Bastion::start();
+
+    returns_output();
+    surfaces_panic();
+    offloaded_by_actor();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
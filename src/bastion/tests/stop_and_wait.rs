@@ -0,0 +1,87 @@
+use bastion::prelude::*;
+use futures_timer::Delay;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::Duration;
+
+// Spawns a child whose `after_stop_async` callback takes a while
+// to resolve, and doesn't as long as `stuck` is set, before
+// setting `stopped`.
+fn spawn_child(stuck: Arc<AtomicBool>, stopped: Arc<AtomicBool>) -> ChildrenRef {
+    let callbacks = Callbacks::new().with_after_stop_async(move || {
+        let stuck = stuck.clone();
+        let stopped = stopped.clone();
+        async move {
+            Delay::new(Duration::from_millis(100)).await;
+            while stuck.load(Ordering::SeqCst) {
+                Delay::new(Duration::from_millis(10)).await;
+            }
+            stopped.store(true, Ordering::SeqCst);
+        }
+    });
+
+    Bastion::children(|children| {
+        children
+            .with_callbacks(callbacks)
+            .with_exec(|ctx: BastionContext| async move {
+                loop {
+                    ctx.recv().await?;
+                }
+            })
+    })
+    .expect("Couldn't create the children group.")
+}
+
+fn waits_for_callbacks() {
+    let stuck = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let children = spawn_child(stuck, stopped.clone());
+    let child = children.elems()[0].clone();
+
+    assert_eq!(run!(child.stop_and_wait()), Ok(()));
+    assert!(stopped.load(Ordering::SeqCst));
+
+    // The child is dead now.
+    assert_eq!(run!(child.stop_and_wait()), Err(StopError::Unreachable));
+}
+
+fn kills_on_timeout() {
+    let stuck = Arc::new(AtomicBool::new(true));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let children = spawn_child(stuck.clone(), stopped.clone());
+    let child = children.elems()[0].clone();
+
+    let timeout = Duration::from_millis(100);
+    assert_eq!(
+        run!(child.stop_and_wait_timeout(timeout)),
+        Err(StopError::Timeout(timeout))
+    );
+
+    // The child is killed without waiting for its callback.
+    for _ in 0..100 {
+        if child.kill().is_err() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(child.kill().is_err());
+    assert!(!stopped.load(Ordering::SeqCst));
+
+    // The group itself uses the same callback when stopping.
+    stuck.store(false, Ordering::SeqCst);
+}
+
+#[test]
+fn stop_and_wait() {
+    Bastion::init();
+    Bastion::start();
+
+    waits_for_callbacks();
+    kills_on_timeout();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
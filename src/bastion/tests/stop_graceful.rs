@@ -0,0 +1,84 @@
+use bastion::prelude::*;
+use futures_timer::Delay;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const SENT: usize = 20;
+
+// Creates a child taking `delay` to handle each of the messages,
+// which are sent to it before the runtime is stopped.
+fn spawn_busy_child(runtime: &Runtime, delay: Duration, handled: &Arc<AtomicUsize>) -> ChildRef {
+    let handled = handled.clone();
+    let children = runtime
+        .children(|children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let handled = handled.clone();
+                async move {
+                    loop {
+                        ctx.recv().await?;
+                        Delay::new(delay).await;
+                        handled.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+    thread::sleep(Duration::from_millis(100));
+
+    let child = children.elems()[0].clone();
+    for i in 0..SENT {
+        child
+            .tell_anonymously(i)
+            .expect("Couldn't send the message.");
+    }
+
+    child
+}
+
+fn drains_in_flight_work() {
+    let runtime = Runtime::builder().build();
+    runtime.start();
+
+    let handled = Arc::new(AtomicUsize::new(0));
+    let child = spawn_busy_child(&runtime, Duration::from_millis(5), &handled);
+
+    let finished = Arc::new(AtomicBool::new(false));
+    let task_finished = finished.clone();
+    runtime.spawn_task(async move {
+        Delay::new(Duration::from_millis(300)).await;
+        task_finished.store(true, Ordering::SeqCst);
+    });
+
+    let report = runtime.stop_graceful(Duration::from_secs(5));
+    assert!(report.is_complete(), "{:?}", report);
+    assert_eq!(handled.load(Ordering::SeqCst), SENT);
+    assert!(finished.load(Ordering::SeqCst));
+
+    // Messages aren't accepted from outside of the runtime anymore.
+    assert_eq!(child.tell_anonymously("late"), Err("late"));
+}
+
+fn reports_outstanding_work() {
+    let runtime = Runtime::builder().build();
+    runtime.start();
+
+    let handled = Arc::new(AtomicUsize::new(0));
+    spawn_busy_child(&runtime, Duration::from_millis(100), &handled);
+
+    let report = runtime.stop_graceful(Duration::from_millis(250));
+    assert!(!report.is_complete());
+    assert!(report.outstanding_messages() > 0, "{:?}", report);
+    assert!(report.outstanding_messages() < SENT, "{:?}", report);
+
+    // The runtime was killed once the timeout elapsed.
+    runtime.block_until_stopped();
+    assert!(handled.load(Ordering::SeqCst) < SENT);
+}
+
+#[test]
+fn stop_graceful() {
+    drains_in_flight_work();
+    reports_outstanding_work();
+}
@@ -0,0 +1,139 @@
+use bastion::prelude::*;
+use bastion::time::Delay;
+use futures::FutureExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn group() -> Dispatcher {
+    Dispatcher::with_type(DispatcherType::Named("config".to_string()))
+}
+
+#[derive(Default)]
+struct Member {
+    // The member, once it started.
+    child: Mutex<Option<ChildRef>>,
+    received: AtomicUsize,
+}
+
+impl Member {
+    fn child(&self) -> ChildRef {
+        self.child.lock().unwrap().clone().unwrap()
+    }
+
+    fn received(&self) -> usize {
+        self.received.load(Ordering::SeqCst)
+    }
+}
+
+// Spawns a member of the group with a mailbox of one message, which
+// only starts receiving after `paused_for`.
+fn spawn_member(runtime: &Runtime, paused_for: Duration) -> Arc<Member> {
+    let member: Arc<Member> = Arc::default();
+    let exec_member = member.clone();
+    runtime
+        .supervisor(move |sp| {
+            sp.children(move |children| {
+                children
+                    .with_dispatcher(group())
+                    .with_mailbox_capacity(1)
+                    .with_exec(move |ctx: BastionContext| {
+                        let member = exec_member.clone();
+                        async move {
+                            *member.child.lock().unwrap() = Some(ctx.current().clone());
+                            Delay::new(paused_for).await;
+                            loop {
+                                ctx.recv().await?;
+                                member.received.fetch_add(1, Ordering::SeqCst);
+                            }
+                        }
+                    })
+            })
+        })
+        .expect("Couldn't create the supervisor.");
+    member
+}
+
+#[test]
+fn tell_everyone() {
+    let runtime = Runtime::builder().with_deterministic_seed(0).build();
+    runtime.start();
+
+    let fast = spawn_member(&runtime, Duration::ZERO);
+    let slow = spawn_member(&runtime, Duration::from_secs(60));
+
+    // Tells the group, waiting for the full mailboxes for as many
+    // seconds as asked (or not at all).
+    let sender = runtime
+        .children(|children| {
+            children.with_exec(|ctx: BastionContext| async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        secs: u64 =!> {
+                            let deliveries = match secs {
+                                0 => ctx.tell_everyone("config", "update"),
+                                secs => {
+                                    let timeout = Duration::from_secs(secs);
+                                    ctx.tell_everyone_timeout("config", "update", timeout).await
+                                }
+                            };
+                            let deliveries: Vec<_> = deliveries
+                                .into_iter()
+                                .map(|(member, delivery)| (member.id().clone(), delivery))
+                                .collect();
+                            answer!(ctx, deliveries).unwrap();
+                        };
+                        _: _ => ();
+                    }
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+    let sender = sender.elems()[0].clone();
+    runtime.run_until_idle();
+
+    let delivery_of = |deliveries: &[(BastionId, Delivery)], member: &ChildRef| {
+        let (_, delivery) = deliveries.iter().find(|(id, _)| id == member.id()).unwrap();
+        *delivery
+    };
+    let tell = |secs: u64, wait: Duration| {
+        let mut answer = sender.ask_anonymously(secs).unwrap();
+        runtime.run_until_idle();
+        runtime.advance_time(wait);
+        let answer = (&mut answer).now_or_never().unwrap().unwrap();
+        msg! { answer,
+            deliveries: Vec<(BastionId, Delivery)> => deliveries;
+            _: _ => panic!("Unexpected answer.");
+        }
+    };
+
+    // Fills the mailbox of the paused member.
+    slow.child().tell_anonymously("update").unwrap();
+    runtime.run_until_idle();
+
+    let deliveries = tell(0, Duration::ZERO);
+    assert_eq!(deliveries.len(), 2);
+    assert_eq!(delivery_of(&deliveries, &fast.child()), Delivery::Delivered);
+    assert_eq!(
+        delivery_of(&deliveries, &slow.child()),
+        Delivery::MailboxFull
+    );
+
+    // The paused member doesn't receive for long enough...
+    let deliveries = tell(10, Duration::from_secs(10));
+    assert_eq!(delivery_of(&deliveries, &fast.child()), Delivery::Delivered);
+    assert_eq!(
+        delivery_of(&deliveries, &slow.child()),
+        Delivery::MailboxFull
+    );
+
+    // ...and then starts to.
+    let deliveries = tell(120, Duration::from_secs(50));
+    assert_eq!(delivery_of(&deliveries, &fast.child()), Delivery::Delivered);
+    assert_eq!(delivery_of(&deliveries, &slow.child()), Delivery::Delivered);
+    runtime.run_until_idle();
+    assert_eq!(fast.received(), 3);
+    assert_eq!(slow.received(), 2);
+
+    runtime.shutdown();
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "tokio-runtime")]
+use bastion::prelude::*;
+use std::time::Duration;
+
+#[test]
+fn tokio_runtime() {
+    Bastion::init();
+    Bastion::start();
+
+    let children = Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            loop {
+                msg! { ctx.recv().await?,
+                    delay: u64 =!> {
+                        tokio::time::sleep(Duration::from_millis(delay)).await;
+                        // Tasks spawned onto the runtime can be awaited too.
+                        let task = tokio::spawn(async move { delay * 2 });
+                        answer!(ctx, task.await.unwrap()).unwrap();
+                    };
+                    _: _ => ();
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let child = &children.elems()[0];
+    let answer = run!(async {
+        msg! { child.ask_anonymously(10u64).unwrap().await.unwrap(),
+            doubled: u64 => doubled;
+            _: _ => 0;
+        }
+    });
+    assert_eq!(answer, 20);
+
+    // Blocking tasks and the calling thread enter the runtime as well.
+    let slept = run!(blocking! {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        true
+    });
+    assert_eq!(slept, Some(true));
+    run!(async { tokio::time::sleep(Duration::from_millis(10)).await });
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "tracing-spans")]
+use bastion::prelude::*;
+use std::sync::{Arc, Mutex};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+
+// The message of an event and the names of its spans.
+type Recorded = (String, Vec<String>);
+
+// Records the message of every event along with the names of the
+// spans it happened in, innermost first.
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl<S> Layer<S> for Recorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event, ctx: Context<S>) {
+        let mut message = String::new();
+        event.record(
+            &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                if field.name() == "message" {
+                    message = format!("{:?}", value);
+                }
+            },
+        );
+
+        let mut spans = Vec::new();
+        let mut current = ctx.current_span().id().and_then(|id| ctx.span(id));
+        while let Some(span) = current {
+            spans.push(span.name().to_string());
+            current = span.parent();
+        }
+
+        self.events.lock().unwrap().push((message, spans));
+    }
+}
+
+#[test]
+fn tracing_spans() {
+    let recorder = Recorder::default();
+    let subscriber = Registry::default().with(recorder.clone());
+    tracing::subscriber::set_global_default(subscriber).unwrap();
+
+    Bastion::init();
+    Bastion::start();
+
+    let children = Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            loop {
+                msg! { ctx.recv().await?,
+                    _msg: &'static str =!> {
+                        answer!(ctx, "pong").unwrap();
+                    };
+                    _: _ => ();
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let child = &children.elems()[0];
+    run!(child.ask_anonymously("ping").unwrap()).unwrap();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+
+    let events = recorder.events.lock().unwrap();
+    let scope = |message: &str| {
+        events
+            .iter()
+            .find(|(msg, _)| msg == message)
+            .map(|(_, spans)| spans.clone())
+    };
+    let expected = Some(vec![
+        "child".to_string(),
+        "children".to_string(),
+        "supervisor".to_string(),
+    ]);
+    assert_eq!(scope("child started"), expected);
+    assert_eq!(scope("message received"), expected);
+    assert_eq!(scope("message answered"), expected);
+}
@@ -1,3 +1,4 @@
+use crate::panic_location::{self, LocationSlot};
 use pin_utils::unsafe_pinned;
 use std::any::Any;
 use std::future::Future;
@@ -11,6 +12,7 @@ where
     F: Future,
 {
     future: F,
+    location: LocationSlot,
 }
 
 impl<F> CatchUnwind<F>
@@ -19,8 +21,8 @@ where
 {
     unsafe_pinned!(future: F);
 
-    pub(crate) fn new(future: F) -> CatchUnwind<F> {
-        CatchUnwind { future }
+    pub(crate) fn new(future: F, location: LocationSlot) -> CatchUnwind<F> {
+        CatchUnwind { future, location }
     }
 }
 
@@ -30,7 +32,16 @@ where
 {
     type Output = Result<F::Output, Box<dyn Any + Send>>;
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-        catch_unwind(AssertUnwindSafe(|| self.future().poll(cx)))?.map(Ok)
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let future = self.as_mut().future();
+        match catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => {
+                // Stores where it panicked before the output is given
+                // to the handle.
+                *self.location.lock().unwrap() = panic_location::take();
+                Poll::Ready(Err(payload))
+            }
+        }
     }
 }
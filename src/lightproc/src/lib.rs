@@ -28,6 +28,7 @@ mod raw_proc;
 mod state;
 
 pub mod lightproc;
+pub mod panic_location;
 pub mod proc_handle;
 pub mod proc_stack;
 pub mod proc_state;
@@ -38,6 +39,7 @@ pub mod recoverable_handle;
 /// The prelude re-exports lightproc structs and handles from this crate.
 pub mod prelude {
     pub use crate::lightproc::*;
+    pub use crate::panic_location::PanicLocation;
     pub use crate::proc_handle::*;
     pub use crate::proc_stack::*;
     pub use crate::proc_state::*;
@@ -34,6 +34,7 @@
 //! );
 //! ```
 
+use crate::panic_location::LocationSlot;
 use crate::proc_data::ProcData;
 use crate::proc_ext::ProcFutureExt;
 use crate::proc_handle::ProcHandle;
@@ -97,9 +98,10 @@ impl LightProc {
         R: Send + 'static,
         S: Fn(LightProc) + Send + Sync + 'static,
     {
-        let recovery_future = AssertUnwindSafe(future).catch_unwind();
+        let location = LocationSlot::default();
+        let recovery_future = AssertUnwindSafe(future).catch_unwind(location.clone());
         let (proc, handle) = Self::build(recovery_future, schedule, stack);
-        (proc, RecoverableHandle(handle))
+        (proc, RecoverableHandle::new(handle, location))
     }
 
     ///
@@ -137,7 +139,7 @@ impl LightProc {
         R: Send + 'static,
         S: Fn(LightProc) + Send + Sync + 'static,
     {
-        let raw_proc = RawProc::allocate(stack, future, schedule);
+        let raw_proc = RawProc::allocate(stack.with_next_proc_id(), future, schedule);
         let proc = LightProc { raw_proc };
         let handle = ProcHandle {
             raw_proc,
@@ -0,0 +1,99 @@
+//!
+//! Locations of the panics of the processes.
+//!
+//! The location of a panic is only known by the panic hook, so it is
+//! captured once [`capture`] installed the hook of this module. The
+//! location of a recoverable process' panic is then given back by
+//! [`ProcError::location`] when joining its handle.
+//!
+//! [`capture`]: fn.capture.html
+//! [`ProcError::location`]: ../proc_handle/enum.ProcError.html#method.location
+use std::cell::RefCell;
+use std::fmt::{self, Display, Formatter};
+use std::panic::{self, Location};
+use std::sync::{Arc, Mutex, Once};
+
+thread_local! {
+    // The location of the last panic of the thread, until it is caught.
+    static LAST: RefCell<Option<PanicLocation>> = const { RefCell::new(None) };
+}
+
+/// Where a shared location is stored once the process panicked.
+pub(crate) type LocationSlot = Arc<Mutex<Option<PanicLocation>>>;
+
+/// Location in the source code where a process panicked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PanicLocation {
+    file: String,
+    line: u32,
+    column: u32,
+}
+
+impl PanicLocation {
+    /// Returns the name of the source file.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the line in the source file.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Returns the column in the source file.
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+}
+
+impl From<&Location<'_>> for PanicLocation {
+    fn from(location: &Location) -> Self {
+        PanicLocation {
+            file: location.file().to_string(),
+            line: location.line(),
+            column: location.column(),
+        }
+    }
+}
+
+impl Display for PanicLocation {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+///
+/// Installs a panic hook capturing the locations of the panics, which
+/// then calls the hook that was installed before.
+///
+/// Installing it more than once has no effect, and a hook installed
+/// afterwards replaces it unless it calls it too.
+///
+/// # Example
+///
+/// ```rust
+/// use lightproc::panic_location;
+///
+/// // Before spawning the processes...
+/// panic_location::capture();
+/// ```
+pub fn capture() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(PanicLocation::from);
+            // Ignored if the thread is being torn down.
+            let _ = LAST.try_with(|last| last.replace(location));
+            previous(info);
+        }));
+    });
+}
+
+/// Takes the location of the last panic of the thread.
+pub(crate) fn take() -> Option<PanicLocation> {
+    LAST.try_with(|last| last.borrow_mut().take())
+        .ok()
+        .flatten()
+}
@@ -1,13 +1,14 @@
 use crate::catch_unwind::CatchUnwind;
+use crate::panic_location::LocationSlot;
 use std::future::Future;
 use std::panic::UnwindSafe;
 
 pub(crate) trait ProcFutureExt: Future {
-    fn catch_unwind(self) -> CatchUnwind<Self>
+    fn catch_unwind(self, location: LocationSlot) -> CatchUnwind<Self>
     where
         Self: Sized + UnwindSafe,
     {
-        CatchUnwind::new(self)
+        CatchUnwind::new(self, location)
     }
 }
 
@@ -1,16 +1,20 @@
 //!
 //! Handle for tasks which don't need to unwind panics inside
 //! the given futures.
+use crate::panic_location::{LocationSlot, PanicLocation};
 use crate::proc_data::ProcData;
 use crate::proc_stack::ProcStack;
 use crate::state::*;
-use std::fmt::{self, Debug, Formatter};
+use std::any::Any;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
 use std::future::Future;
 use std::marker::{PhantomData, Unpin};
 use std::pin::Pin;
 use std::ptr::NonNull;
 use std::sync::atomic::Ordering;
 use std::task::{Context, Poll};
+use std::thread;
 
 /// A handle that awaits the result of a proc.
 ///
@@ -172,6 +176,148 @@ impl<R> Future for ProcHandle<R> {
     }
 }
 
+impl<R> ProcHandle<thread::Result<R>> {
+    /// Turns the handle of a proc which catches its panics into a future
+    /// resolving to either its output or the reason it didn't complete.
+    ///
+    /// Unlike awaiting the handle itself, the panic payload is given back
+    /// instead of being swallowed, similarly to [std::thread::JoinHandle::join].
+    ///
+    /// The location of the panic isn't known unless the proc was created
+    /// by [LightProc::recoverable](../lightproc/struct.LightProc.html#method.recoverable).
+    pub fn join(self) -> Join<R> {
+        Join::new(self, None)
+    }
+}
+
+/// Future returned by [ProcHandle::join], which resolves to a [Result] where:
+///
+/// * `Ok(res)` indicates the proc has completed with `res`
+/// * `Err(ProcError::Panicked { payload, .. })` indicates the proc has panicked with `payload`
+/// * `Err(ProcError::Cancelled)` indicates the proc was cancelled
+pub struct Join<R> {
+    handle: ProcHandle<thread::Result<R>>,
+    location: Option<LocationSlot>,
+}
+
+impl<R> Join<R> {
+    pub(crate) fn new(
+        handle: ProcHandle<thread::Result<R>>,
+        location: Option<LocationSlot>,
+    ) -> Self {
+        Join { handle, location }
+    }
+
+    /// Cancels the proc.
+    ///
+    /// If the proc has already completed, calling this method will have no effect.
+    pub fn cancel(&self) {
+        self.handle.cancel()
+    }
+
+    /// Returns a reference to the stack stored inside the proc.
+    pub fn stack(&self) -> &ProcStack {
+        self.handle.stack()
+    }
+}
+
+impl<R> Future for Join<R> {
+    type Output = Result<R, ProcError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        match Pin::new(&mut self.handle).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(None) => Poll::Ready(Err(ProcError::Cancelled)),
+            Poll::Ready(Some(Ok(val))) => Poll::Ready(Ok(val)),
+            Poll::Ready(Some(Err(payload))) => {
+                let location = self
+                    .location
+                    .as_ref()
+                    .and_then(|location| location.lock().unwrap().take());
+                Poll::Ready(Err(ProcError::Panicked { payload, location }))
+            }
+        }
+    }
+}
+
+impl<R> Debug for Join<R> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Join")
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+/// Reason why a joined proc didn't complete.
+#[derive(Debug)]
+pub enum ProcError {
+    /// The proc was cancelled before completing.
+    Cancelled,
+    /// The proc has panicked.
+    Panicked {
+        /// The payload the proc has panicked with.
+        payload: Box<dyn Any + Send + 'static>,
+        /// Where the proc has panicked, if it was captured.
+        location: Option<PanicLocation>,
+    },
+}
+
+impl ProcError {
+    /// Returns `true` if the proc was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, ProcError::Cancelled)
+    }
+
+    /// Returns `true` if the proc has panicked.
+    pub fn is_panic(&self) -> bool {
+        matches!(self, ProcError::Panicked { .. })
+    }
+
+    /// Returns the panic payload, if the proc has panicked.
+    pub fn into_panic(self) -> Option<Box<dyn Any + Send + 'static>> {
+        match self {
+            ProcError::Panicked { payload, .. } => Some(payload),
+            ProcError::Cancelled => None,
+        }
+    }
+
+    /// Returns the panic message, if the proc has panicked with a
+    /// `&str` or a `String` as `panic!` does.
+    pub fn panic_message(&self) -> Option<&str> {
+        match self {
+            ProcError::Panicked { payload, .. } => payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str)),
+            ProcError::Cancelled => None,
+        }
+    }
+
+    /// Returns where the proc has panicked, if it did and the location
+    /// was captured (see the [panic_location](../panic_location/index.html) module).
+    pub fn location(&self) -> Option<&PanicLocation> {
+        match self {
+            ProcError::Panicked { location, .. } => location.as_ref(),
+            ProcError::Cancelled => None,
+        }
+    }
+}
+
+impl Display for ProcError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            ProcError::Cancelled => write!(fmt, "proc was cancelled"),
+            ProcError::Panicked {
+                location: Some(location),
+                ..
+            } => write!(fmt, "proc has panicked at {}", location),
+            ProcError::Panicked { .. } => write!(fmt, "proc has panicked"),
+        }
+    }
+}
+
+impl Error for ProcError {}
+
 impl<R> Debug for ProcHandle<R> {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         let ptr = self.raw_proc.as_ptr();
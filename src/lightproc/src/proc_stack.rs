@@ -12,6 +12,10 @@ use std::fmt::{self, Debug, Formatter};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
+/// The identifier of the next process to be built, `0` being kept for
+/// the stacks which weren't given to a process.
+static NEXT_PROC_ID: AtomicUsize = AtomicUsize::new(1);
+
 /// Stack abstraction for lightweight processes
 ///
 /// # Example
@@ -31,8 +35,16 @@ pub struct ProcStack {
     /// Can be used to identify specific processes during any executor, reactor implementations.
     pub pid: AtomicUsize,
 
+    /// Identifier given to the process when it's built with this stack
+    pub(crate) proc_id: usize,
+
     pub(crate) state: ProcState,
 
+    /// Scheduling priority of the process
+    ///
+    /// Executors can use it to run processes with a higher priority first.
+    pub(crate) priority: Priority,
+
     /// Before start callback
     ///
     /// This callback is called before we start to inner future of the process
@@ -53,6 +65,20 @@ pub struct ProcStack {
     pub(crate) after_panic: Option<Arc<dyn Fn(ProcState) + Send + Sync>>,
 }
 
+/// Scheduling priority of a lightweight process
+///
+/// Processes spawned without an explicit priority are scheduled with [Priority::Normal].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Priority {
+    /// Processes which can wait for others to be run
+    Low,
+    /// Default priority of the processes
+    #[default]
+    Normal,
+    /// Processes which should be run before the others, like supervision signals
+    High,
+}
+
 impl ProcStack {
     /// Adds pid for the process which is going to take this stack
     ///
@@ -146,6 +172,50 @@ impl ProcStack {
         self
     }
 
+    /// Sets the scheduling priority of the process which is going to take this stack
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use lightproc::proc_stack::{Priority, ProcStack};
+    ///
+    /// ProcStack::default()
+    ///     .with_priority(Priority::High);
+    /// ```
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the scheduling priority of the process
+    pub fn get_priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Returns the identifier given to the process when it was built
+    /// with this stack, which no other process of the program gets.
+    ///
+    /// It is `0` for stacks which weren't given to a process yet.
+    ///
+    /// ```rust
+    /// use lightproc::prelude::*;
+    ///
+    /// let (first, _) = LightProc::build(async {}, |_| {}, ProcStack::default());
+    /// let (second, _) = LightProc::build(async {}, |_| {}, ProcStack::default());
+    ///
+    /// assert_eq!(ProcStack::default().get_proc_id(), 0);
+    /// assert_ne!(first.stack().get_proc_id(), second.stack().get_proc_id());
+    /// ```
+    pub fn get_proc_id(&self) -> usize {
+        self.proc_id
+    }
+
+    /// Gives the stack the identifier of the process it's built with.
+    pub(crate) fn with_next_proc_id(mut self) -> Self {
+        self.proc_id = NEXT_PROC_ID.fetch_add(1, Ordering::Relaxed);
+        self
+    }
+
     /// Utility function to get_pid for the implementation of executors.
     ///
     /// ```rust
@@ -210,7 +280,9 @@ impl Default for ProcStack {
     fn default() -> Self {
         ProcStack {
             pid: AtomicUsize::new(0xDEAD_BEEF),
+            proc_id: 0,
             state: Arc::new(Mutex::new(EmptyState)),
+            priority: Priority::default(),
             before_start: None,
             after_complete: None,
             after_panic: None,
@@ -222,7 +294,9 @@ impl Debug for ProcStack {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         fmt.debug_struct("ProcStack")
             .field("pid", &self.pid.load(Ordering::SeqCst))
+            .field("proc_id", &self.proc_id)
             .field("state", &self.state)
+            .field("priority", &self.priority)
             .field("before_start", &self.before_start.is_some())
             .field("after_complete", &self.after_complete.is_some())
             .field("after_panic", &self.after_panic.is_some())
@@ -234,7 +308,9 @@ impl Clone for ProcStack {
     fn clone(&self) -> Self {
         ProcStack {
             pid: AtomicUsize::new(self.pid.load(Ordering::Acquire)),
+            proc_id: self.proc_id,
             state: self.state.clone(),
+            priority: self.priority,
             before_start: self.before_start.clone(),
             after_complete: self.after_complete.clone(),
             after_panic: self.after_panic.clone(),
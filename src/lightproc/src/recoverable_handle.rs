@@ -1,38 +1,72 @@
 //!
 //! Handle for recoverable process
+use crate::panic_location::LocationSlot;
 use crate::proc_data::ProcData;
-use crate::proc_handle::ProcHandle;
+use crate::proc_handle::{Join, ProcHandle};
 use crate::proc_stack::ProcStack;
 use crate::state::State;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::task::{Context, Poll};
 use std::thread;
 
 /// Recoverable handle which encapsulates a standard Proc Handle and contain all panics inside.
 ///
 /// Execution of `after_panic` will be immediate on polling the [RecoverableHandle]'s future.
-pub struct RecoverableHandle<R>(pub(crate) ProcHandle<thread::Result<R>>);
+pub struct RecoverableHandle<R> {
+    pub(crate) inner: ProcHandle<thread::Result<R>>,
+    // Set once the proc was cancelled through this handle,
+    // in which case a panic shouldn't trigger its recovery.
+    cancelled: AtomicBool,
+    // Where the proc panicked, once it did.
+    location: LocationSlot,
+}
 
 impl<R> RecoverableHandle<R> {
+    pub(crate) fn new(inner: ProcHandle<thread::Result<R>>, location: LocationSlot) -> Self {
+        RecoverableHandle {
+            inner,
+            cancelled: AtomicBool::new(false),
+            location,
+        }
+    }
+
     /// Cancels the proc.
     ///
     /// If the proc has already completed, calling this method will have no effect.
     ///
     /// When a proc is cancelled, its future cannot be polled again and will be dropped instead.
+    /// If the proc is currently being polled, it is dropped once the ongoing poll returns.
+    ///
+    /// Once cancelled, the `after_panic` callback won't be executed, even if the proc
+    /// has panicked before being cancelled.
     pub fn cancel(&self) {
-        self.0.cancel()
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.inner.cancel()
     }
 
     /// Returns a reference to the stack stored inside the proc.
     pub fn stack(&self) -> &ProcStack {
-        self.0.stack()
+        self.inner.stack()
     }
 
     /// Returns a state of the ProcHandle.
     pub fn state(&self) -> State {
-        self.0.state()
+        self.inner.state()
+    }
+
+    /// Turns the handle into a future resolving to either the output of
+    /// the proc or the reason it didn't complete, giving back the panic payload.
+    ///
+    /// The `after_panic` callback isn't executed when the proc panics,
+    /// since the panic is handed over to the caller instead, along with
+    /// where it panicked if [`panic_location::capture`] was called.
+    ///
+    /// [`panic_location::capture`]: ../panic_location/fn.capture.html
+    pub fn join(self) -> Join<R> {
+        Join::new(self.inner, Some(self.location))
     }
 }
 
@@ -40,13 +74,14 @@ impl<R> Future for RecoverableHandle<R> {
     type Output = Option<R>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-        match Pin::new(&mut self.0).poll(cx) {
+        match Pin::new(&mut self.inner).poll(cx) {
             Poll::Pending => Poll::Pending,
             Poll::Ready(None) => Poll::Ready(None),
             Poll::Ready(Some(Ok(val))) => Poll::Ready(Some(val)),
+            Poll::Ready(Some(Err(_))) if self.cancelled.load(Ordering::SeqCst) => Poll::Ready(None),
             Poll::Ready(Some(Err(_))) => {
-                if let Some(after_panic_cb) = self.0.stack().after_panic.clone() {
-                    (*after_panic_cb.clone())(self.0.stack().state.clone());
+                if let Some(after_panic_cb) = self.inner.stack().after_panic.clone() {
+                    (*after_panic_cb.clone())(self.inner.stack().state.clone());
                 }
 
                 Poll::Ready(None)
@@ -57,7 +92,7 @@ impl<R> Future for RecoverableHandle<R> {
 
 impl<R> Debug for RecoverableHandle<R> {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
-        let ptr = self.0.raw_proc.as_ptr();
+        let ptr = self.inner.raw_proc.as_ptr();
         let pdata = ptr as *const ProcData;
 
         fmt.debug_struct("ProcHandle")
@@ -0,0 +1,55 @@
+use futures_executor::block_on;
+use lightproc::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+fn schedule(proc: LightProc) {
+    proc.run();
+}
+
+fn panicking_proc(recovered: Arc<AtomicBool>) -> (LightProc, RecoverableHandle<()>) {
+    let stack = ProcStack::default().with_after_panic(move |_s: &mut EmptyProcState| {
+        recovered.store(true, Ordering::SeqCst);
+    });
+
+    LightProc::recoverable(
+        async {
+            panic!("cancel panic");
+        },
+        schedule,
+        stack,
+    )
+}
+
+#[test]
+fn panic_recovers() {
+    let recovered = Arc::new(AtomicBool::new(false));
+    let (proc, handle) = panicking_proc(recovered.clone());
+    proc.schedule();
+
+    assert!(block_on(handle).is_none());
+    assert!(recovered.load(Ordering::SeqCst));
+}
+
+#[test]
+fn cancel_suppresses_recovery() {
+    let recovered = Arc::new(AtomicBool::new(false));
+    let (proc, handle) = panicking_proc(recovered.clone());
+    proc.schedule();
+
+    // The proc has already panicked when getting cancelled.
+    handle.cancel();
+
+    assert!(block_on(handle).is_none());
+    assert!(!recovered.load(Ordering::SeqCst));
+}
+
+#[test]
+fn cancel_completed_is_noop() {
+    let (proc, handle) = LightProc::recoverable(async { 42 }, schedule, ProcStack::default());
+    proc.schedule();
+
+    handle.cancel();
+
+    assert_eq!(block_on(handle), Some(42));
+}
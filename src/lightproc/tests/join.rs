@@ -0,0 +1,88 @@
+use futures_executor::block_on;
+use lightproc::panic_location;
+use lightproc::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+fn schedule(proc: LightProc) {
+    proc.run();
+}
+
+#[test]
+fn join_completed() {
+    let (proc, handle) = LightProc::recoverable(async { 42 }, schedule, ProcStack::default());
+    proc.schedule();
+
+    assert_eq!(block_on(handle.join()).unwrap(), 42);
+}
+
+#[test]
+fn join_panicked() {
+    let (proc, handle) = LightProc::recoverable(
+        async {
+            panic!("join panic");
+        },
+        schedule,
+        ProcStack::default(),
+    );
+    proc.schedule();
+
+    let err = block_on(handle.join()).unwrap_err();
+    assert!(err.is_panic());
+    assert_eq!(
+        err.into_panic().unwrap().downcast_ref::<&str>(),
+        Some(&"join panic")
+    );
+}
+
+#[test]
+fn join_cancelled() {
+    let (proc, handle) = LightProc::recoverable(async { 42 }, schedule, ProcStack::default());
+    handle.cancel();
+    drop(proc);
+
+    let err = block_on(handle.join()).unwrap_err();
+    assert!(err.is_cancelled());
+}
+
+#[test]
+fn join_panic_location() {
+    panic_location::capture();
+
+    let line = line!() + 3;
+    let (proc, handle) = LightProc::recoverable(
+        async {
+            panic!("located panic");
+        },
+        schedule,
+        ProcStack::default(),
+    );
+    proc.schedule();
+
+    let err = block_on(handle.join()).unwrap_err();
+    assert_eq!(err.panic_message(), Some("located panic"));
+    let location = err.location().expect("location wasn't captured");
+    assert!(location.file().ends_with("join.rs"), "{}", location);
+    assert_eq!(location.line(), line);
+}
+
+#[test]
+fn join_skips_after_panic() {
+    let recovered = Arc::new(AtomicBool::new(false));
+    let after_panic = recovered.clone();
+    let stack = ProcStack::default().with_after_panic(move |_: &mut EmptyProcState| {
+        after_panic.store(true, Ordering::SeqCst);
+    });
+    let (proc, handle) = LightProc::recoverable(
+        async {
+            std::panic::panic_any(String::from("owned panic"));
+        },
+        schedule,
+        stack,
+    );
+    proc.schedule();
+
+    let err = block_on(handle.join()).unwrap_err();
+    assert_eq!(err.panic_message(), Some("owned panic"));
+    assert!(!recovered.load(Ordering::SeqCst));
+}
@@ -1,4 +1,4 @@
-use lightproc::proc_stack::ProcStack;
+use lightproc::proc_stack::{Priority, ProcStack};
 use lightproc::proc_state::EmptyProcState;
 
 #[test]
@@ -13,3 +13,12 @@ fn stack_copy() {
 
     assert_eq!(stack2.get_pid(), 12);
 }
+
+#[test]
+fn stack_priority() {
+    let stack = ProcStack::default();
+    assert_eq!(stack.get_priority(), Priority::Normal);
+
+    let stack = stack.with_priority(Priority::High);
+    assert_eq!(stack.clone().get_priority(), Priority::High);
+}